@@ -0,0 +1,24 @@
+#!/bin/bash
+# runs the app against the `mock-upstreams` server, no network access to upstream services required
+
+FIXTURES="$(pwd)/ssr/src/mock_upstreams/fixtures/default.json"
+
+export YRAL_ML_FEED_URL="http://localhost:8790"
+export YRAL_HON_WORKER_URL="http://localhost:8791/"
+export YRAL_PUMP_AND_DUMP_WORKER_URL="http://localhost:8787/"
+export YRAL_ICPUMP_SEARCH_GRPC_URL="http://localhost:8792"
+export YRAL_NSFW_SERVER_URL="http://localhost:8792"
+export YRAL_OFF_CHAIN_AGENT_GRPC_URL="http://localhost:8792"
+export YRAL_OFF_CHAIN_AGENT_URL="http://localhost:8793/"
+export YRAL_QSTASH_URL="http://localhost:8794/v2/"
+export QSTASH_TOKEN="mock-qstash-token"
+# the fixture posts live on canisters that only exist in the fixture file
+export MOCK_UPSTREAMS_FIXTURES="$FIXTURES"
+export LOCAL_FAKE_CANISTERS="$FIXTURES"
+
+cargo run -p mock-upstreams &
+MOCK_PID=$!
+trap "kill $MOCK_PID" EXIT
+
+cargo leptos build --bin-features local-bin,qstash --lib-features local-lib || exit 1
+LEPTOS_SITE_ROOT="target/site" LEPTOS_HASH_FILES=true ./target/debug/hot-or-not-web-leptos-ssr
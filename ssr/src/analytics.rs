@@ -0,0 +1,76 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use http::{header, HeaderValue};
+use leptos::prelude::*;
+use utils::event_streaming::{
+    client_id::ClientId,
+    consent::{ConsentCategory, ConsentState},
+};
+
+use consts::analytics::{CLIENT_ID_COOKIE, CONSENT_COOKIE};
+
+/// Reads the analytics consent and issues (or rotates) the analytics client id
+/// both are available to handlers through request extensions
+/// the client id is only issued with analytics consent, and removed once it is denied
+pub async fn analytics_cookies_layer(mut req: Request, next: Next) -> Response {
+    let jar = CookieJar::from_headers(req.headers());
+    let consent = jar
+        .get(CONSENT_COOKIE)
+        .and_then(|c| c.value().parse::<ConsentState>().ok())
+        .unwrap_or_default();
+    req.extensions_mut().insert(consent);
+
+    let existing = jar
+        .get(CLIENT_ID_COOKIE)
+        .and_then(|c| c.value().parse::<ClientId>().ok())
+        .filter(|client_id| !client_id.is_expired());
+
+    let set_cookie = if !consent.is_granted(ConsentCategory::Analytics) {
+        jar.get(CLIENT_ID_COOKIE)
+            .map(|_| ClientId::removal_cookie())
+    } else if let Some(client_id) = existing {
+        req.extensions_mut().insert(client_id);
+        None
+    } else {
+        let client_id = ClientId::generate();
+        let cookie = client_id.cookie();
+        req.extensions_mut().insert(client_id);
+        Some(cookie)
+    };
+
+    let mut res = next.run(req).await;
+    if let Some(cookie) = set_cookie.as_ref().and_then(set_cookie_header) {
+        res.headers_mut().append(header::SET_COOKIE, cookie);
+    }
+
+    res
+}
+
+fn set_cookie_header(cookie: &Cookie<'static>) -> Option<HeaderValue> {
+    HeaderValue::from_str(&cookie.encoded().to_string()).ok()
+}
+
+/// Analytics state extracted by [`analytics_cookies_layer`]
+#[derive(Clone, Default)]
+pub struct AnalyticsCtx {
+    consent: Option<ConsentState>,
+    client_id: Option<ClientId>,
+}
+
+impl AnalyticsCtx {
+    pub fn from_extensions(extensions: &http::Extensions) -> Self {
+        Self {
+            consent: extensions.get().copied(),
+            client_id: extensions.get().cloned(),
+        }
+    }
+
+    pub fn provide(&self) {
+        if let Some(consent) = self.consent {
+            provide_context(consent);
+        }
+        if let Some(client_id) = self.client_id.clone() {
+            provide_context(client_id);
+        }
+    }
+}
@@ -6,13 +6,14 @@ use page::about_us::AboutUs;
 use page::hon;
 use page::icpump::ai::ICPumpAi;
 use page::icpump::ICPumpLanding;
-use page::post_view::PostDetailsCacheCtx;
+use page::post_view::{engagement::EngagementCtx, PostDetailsCacheCtx};
 use page::pumpdump::{withdrawal, PndProfilePage};
 use state::app_type::AppType;
+use state::canister_backend::mirror_fake_canisters;
 use state::local_storage::LocalStorageSyncContext;
 // use crate::page::wallet::TestIndex;
 use crate::error_template::{AppError, ErrorTemplate};
-use component::{base_route::BaseRoute, nav::NavBar};
+use component::{base_route::BaseRoute, consent_banner::ConsentBanner, nav::NavBar};
 use leptos::prelude::*;
 use leptos_meta::*;
 use leptos_router::hooks::use_location;
@@ -23,12 +24,16 @@ use page::{
     leaderboard::Leaderboard,
     logout::Logout,
     menu::Menu,
-    post_view::{single_post::SinglePost, PostView, PostViewCtx},
+    post_view::{
+        following_feed::FollowingView, hashtag_feed::HashtagView, single_post::SinglePost,
+        PostView, PostViewCtx,
+    },
     privacy::PrivacyPolicy,
     profile::{profile_post::ProfilePost, ProfilePostsContext, ProfileView},
     refer_earn::ReferEarn,
     root::RootPage,
-    settings::Settings,
+    search::SearchPage,
+    settings::{HiddenCreators, Settings},
     terms::TermsOfService,
     token::{
         create::{CreateToken, CreateTokenCtx, CreateTokenSettings},
@@ -166,6 +171,7 @@ pub fn App() -> impl IntoView {
 
     // Existing context providers
     provide_context(Canisters::default());
+    mirror_fake_canisters();
     provide_context(ContentSeedClient::default());
     provide_context(PostViewCtx::default());
     provide_context(ProfilePostsContext::default());
@@ -173,6 +179,7 @@ pub fn App() -> impl IntoView {
     provide_context(AudioState::default());
     provide_context(CreateTokenCtx::default());
     provide_context(PostDetailsCacheCtx::default());
+    provide_context(EngagementCtx::default());
 
     // History Tracking
     let history_ctx = HistoryCtx::default();
@@ -243,6 +250,11 @@ pub fn App() -> impl IntoView {
                 {r#"
                 window.dataLayer = window.dataLayer || [];
                 function gtag(){dataLayer.push(arguments);}
+                var consent = decodeURIComponent((document.cookie.match(/(?:^|; )yral-analytics-consent=([^;]*)/) || [])[1] || '');
+                gtag('consent', 'default', {
+                    analytics_storage: consent.indexOf('analytics:denied') === -1 ? 'granted' : 'denied',
+                    personalization_storage: consent.indexOf('personalization:denied') === -1 ? 'granted' : 'denied'
+                });
                 gtag('js', new Date());
                 gtag('config', 'G-PLNNETMSLM');
                 "#}
@@ -276,6 +288,10 @@ pub fn App() -> impl IntoView {
                         <Route path=path!("/error") view=ServerErrorPage />
                         <Route path=path!("/menu") view=Menu />
                         <Route path=path!("/settings") view=Settings />
+                        <Route path=path!("/settings/hidden-creators") view=HiddenCreators />
+                        <Route path=path!("/tag/:hashtag") view=HashtagView />
+                        <Route path=path!("/following") view=FollowingView />
+                        <Route path=path!("/search") view=SearchPage />
                         <Route path=path!("/refer-earn") view=ReferEarn />
                         <Route path=path!("/profile/:id/:tab") view=ProfileView />
                         <Route path=path!("/profile/:tab") view=ProfileView />
@@ -322,6 +338,7 @@ pub fn App() -> impl IntoView {
             <nav>
                 <NavBar />
             </nav>
+            <ConsentBanner />
         </Router>
     }
 }
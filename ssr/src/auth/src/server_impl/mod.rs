@@ -14,9 +14,12 @@ use k256::elliptic_curve::JwkEcKey;
 use leptos::prelude::*;
 use leptos_axum::{extract_with_state, ResponseOptions};
 use rand_chacha::rand_core::OsRng;
+use web_time::Duration;
 use yral_canisters_common::utils::time::current_epoch;
 
-use consts::auth::{REFRESH_MAX_AGE, REFRESH_TOKEN_COOKIE};
+use consts::auth::{
+    PREVIOUS_IDENTITY_COOKIE, PREVIOUS_IDENTITY_MAX_AGE, REFRESH_MAX_AGE, REFRESH_TOKEN_COOKIE,
+};
 
 use self::store::{KVStore, KVStoreImpl};
 use yral_types::delegated_identity::DelegatedIdentityWire;
@@ -48,6 +51,24 @@ pub fn extract_principal_from_cookie(
     Ok(Some(token.principal))
 }
 
+/// Principal of the identity the current one replaced, see [`update_user_identity`]
+/// the cookie is removed, so the previous principal is only returned once
+pub async fn take_previous_principal() -> Result<Option<Principal>, ServerFnError> {
+    let key: Key = expect_context();
+    let jar: SignedCookieJar = extract_with_state(&key).await?;
+    let Some(cookie) = jar.get(PREVIOUS_IDENTITY_COOKIE) else {
+        return Ok(None);
+    };
+    let previous = Principal::from_text(cookie.value())?;
+
+    let resp: ResponseOptions = expect_context();
+    set_cookies(
+        &resp,
+        jar.remove(Cookie::build(PREVIOUS_IDENTITY_COOKIE).path("/")),
+    );
+    Ok(Some(previous))
+}
+
 async fn fetch_identity_from_kv(
     kv: &KVStoreImpl,
     principal: Principal,
@@ -59,6 +80,155 @@ async fn fetch_identity_from_kv(
     Ok(Some(k256::SecretKey::from_jwk_str(&identity_jwk)?))
 }
 
+/// Read per user data stored by other features, `namespace` keeps it apart from identities
+pub async fn read_user_data(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    principal: Principal,
+) -> Result<Option<String>, ServerFnError> {
+    Ok(kv.read(format!("{namespace}:{principal}")).await?)
+}
+
+pub async fn write_user_data(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    principal: Principal,
+    value: String,
+) -> Result<(), ServerFnError> {
+    Ok(kv.write(format!("{namespace}:{principal}"), value).await?)
+}
+
+/// Read data shared by every user, keyed by `key` within `namespace`
+pub async fn read_shared_data(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+) -> Result<Option<String>, ServerFnError> {
+    Ok(kv.read(format!("{namespace}:{key}")).await?)
+}
+
+pub async fn write_shared_data(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    value: String,
+) -> Result<(), ServerFnError> {
+    Ok(kv.write(format!("{namespace}:{key}"), value).await?)
+}
+
+/// Batched [`read_shared_data`], values are returned in the order of `keys`
+pub async fn read_shared_data_many(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    keys: &[String],
+) -> Result<Vec<Option<String>>, ServerFnError> {
+    let keys = keys
+        .iter()
+        .map(|key| format!("{namespace}:{key}"))
+        .collect();
+    Ok(kv.read_many(keys).await?)
+}
+
+/// Atomically add `delta` to a shared counter, returns the new value
+pub async fn incr_shared_counter(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    delta: i64,
+) -> Result<i64, ServerFnError> {
+    Ok(kv.incr(format!("{namespace}:{key}"), delta).await?)
+}
+
+/// Let the store drop shared data or a shared set after `ttl`
+pub async fn expire_shared_data(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    ttl: Duration,
+) -> Result<(), ServerFnError> {
+    Ok(kv.expire(format!("{namespace}:{key}"), ttl).await?)
+}
+
+/// Add `member` to a shared sorted set, returns true if it was not in the set yet
+pub async fn add_to_shared_set(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    member: String,
+    score: f64,
+) -> Result<bool, ServerFnError> {
+    Ok(kv
+        .sorted_add(format!("{namespace}:{key}"), member, score)
+        .await?)
+}
+
+/// Atomically add `delta` to the score of `member`
+pub async fn incr_in_shared_set(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    member: String,
+    delta: f64,
+) -> Result<f64, ServerFnError> {
+    Ok(kv
+        .sorted_incr(format!("{namespace}:{key}"), member, delta)
+        .await?)
+}
+
+/// Returns true if `member` was in the set
+pub async fn remove_from_shared_set(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    member: String,
+) -> Result<bool, ServerFnError> {
+    Ok(kv
+        .sorted_remove(format!("{namespace}:{key}"), member)
+        .await?)
+}
+
+pub async fn shared_set_score(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    member: String,
+) -> Result<Option<f64>, ServerFnError> {
+    Ok(kv
+        .sorted_score(format!("{namespace}:{key}"), member)
+        .await?)
+}
+
+pub async fn shared_set_len(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+) -> Result<u64, ServerFnError> {
+    Ok(kv.sorted_len(format!("{namespace}:{key}")).await?)
+}
+
+/// Members of a shared sorted set with their scores, highest score first
+pub async fn shared_set_range(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    start: u64,
+    limit: u64,
+) -> Result<Vec<(String, f64)>, ServerFnError> {
+    Ok(kv
+        .sorted_range_rev(format!("{namespace}:{key}"), start, limit)
+        .await?)
+}
+
+/// Keep only the `keep` highest scored members of a shared sorted set
+pub async fn trim_shared_set(
+    kv: &KVStoreImpl,
+    namespace: &str,
+    key: &str,
+    keep: u64,
+) -> Result<(), ServerFnError> {
+    Ok(kv.sorted_trim(format!("{namespace}:{key}"), keep).await?)
+}
+
 pub async fn try_extract_identity(
     jar: &SignedCookieJar,
     kv: &KVStoreImpl,
@@ -95,14 +265,31 @@ fn identity_from_jwk(id: &JwkEcKey) -> Result<Secp256k1Identity, ServerFnError>
     Ok(base_identity)
 }
 
+/// Also remembers the replaced principal in a signed cookie, see [`take_previous_principal`]
 pub fn update_user_identity(
     response_opts: &ResponseOptions,
     mut jar: SignedCookieJar,
     identity: &impl Identity,
 ) -> Result<(), ServerFnError> {
+    let principal = identity.sender().unwrap();
+    let previous = extract_principal_from_cookie(&jar)
+        .ok()
+        .flatten()
+        .filter(|previous| *previous != principal);
+    if let Some(previous) = previous {
+        let previous_cookie = Cookie::build((PREVIOUS_IDENTITY_COOKIE, previous.to_text()))
+            .http_only(true)
+            .secure(true)
+            .path("/")
+            .same_site(SameSite::None)
+            .partitioned(true)
+            .max_age(PREVIOUS_IDENTITY_MAX_AGE.try_into().unwrap());
+        jar = jar.add(previous_cookie);
+    }
+
     let refresh_max_age = REFRESH_MAX_AGE;
     let refresh_token = RefreshToken {
-        principal: identity.sender().unwrap(),
+        principal,
         expiry_epoch_ms: (current_epoch() + refresh_max_age).as_millis(),
     };
     let refresh_token_enc = serde_json::to_string(&refresh_token)?;
@@ -1,6 +1,8 @@
 pub mod redb_kv;
 pub mod redis_kv;
 
+use std::time::Duration;
+
 use enum_dispatch::enum_dispatch;
 use redis::RedisError;
 use thiserror::Error;
@@ -21,6 +23,30 @@ pub enum KVError {
 pub(crate) trait KVStore: Send {
     async fn read(&self, key: String) -> Result<Option<String>, KVError>;
     async fn write(&self, key: String, value: String) -> Result<(), KVError>;
+    /// Values of `keys` in order, read in a single round trip
+    async fn read_many(&self, keys: Vec<String>) -> Result<Vec<Option<String>>, KVError>;
+    /// Atomically add `delta` to the integer at `key`, missing keys count as 0
+    async fn incr(&self, key: String, delta: i64) -> Result<i64, KVError>;
+    /// Remove `key` after `ttl`
+    async fn expire(&self, key: String, ttl: Duration) -> Result<(), KVError>;
+    /// Add `member` to the sorted set at `key` or update its score
+    /// returns true if `member` was not in the set
+    async fn sorted_add(&self, key: String, member: String, score: f64) -> Result<bool, KVError>;
+    /// Atomically add `delta` to the score of `member`, returns the new score
+    async fn sorted_incr(&self, key: String, member: String, delta: f64) -> Result<f64, KVError>;
+    /// Returns true if `member` was in the set
+    async fn sorted_remove(&self, key: String, member: String) -> Result<bool, KVError>;
+    async fn sorted_score(&self, key: String, member: String) -> Result<Option<f64>, KVError>;
+    async fn sorted_len(&self, key: String) -> Result<u64, KVError>;
+    /// Members ranked by descending score, starting at rank `start`
+    async fn sorted_range_rev(
+        &self,
+        key: String,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<(String, f64)>, KVError>;
+    /// Remove every member ranked below the `keep` highest scores
+    async fn sorted_trim(&self, key: String, keep: u64) -> Result<(), KVError>;
 }
 
 #[derive(Clone)]
@@ -1,12 +1,179 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use redb::{Database, TableDefinition};
+use redb::{
+    backends::InMemoryBackend, Database, ReadableTable, ReadableTableMetadata, StorageError,
+    TableDefinition, WriteTransaction,
+};
 use tokio::task::spawn_blocking;
 
 use super::{KVError, KVStore};
 
 const TABLE: TableDefinition<&str, &str> = TableDefinition::new("kv");
 const RAW_METADATA_TABLE: TableDefinition<&str, &str> = TableDefinition::new("kv-meta");
+/// (set key, member) -> score
+const SORTED_TABLE: TableDefinition<(&str, &str), f64> = TableDefinition::new("kv-sorted");
+/// (set key, [`rank_score`], member), the members of a set in rank order
+const SORTED_RANK_TABLE: TableDefinition<(&str, u64, &str), ()> =
+    TableDefinition::new("kv-sorted-rank");
+/// set key -> number of members
+const SORTED_LEN_TABLE: TableDefinition<&str, u64> = TableDefinition::new("kv-sorted-len");
+/// key -> unix time (ms) the key expires at
+const EXPIRY_TABLE: TableDefinition<&str, u64> = TableDefinition::new("kv-expiry");
+/// (unix time (ms), key), the keys with a ttl in expiry order
+const EXPIRY_QUEUE_TABLE: TableDefinition<(u64, &str), ()> =
+    TableDefinition::new("kv-expiry-queue");
+/// Expired keys removed per [`KVStore::expire`] call
+const EXPIRY_SWEEP_LIMIT: usize = 100;
+
+/// Maps a score to an integer with the same order
+fn rank_score(score: f64) -> u64 {
+    let bits = score.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | 1 << 63
+    }
+}
+
+fn score_from_rank(rank: u64) -> f64 {
+    if rank >> 63 == 1 {
+        f64::from_bits(rank & !(1 << 63))
+    } else {
+        f64::from_bits(!rank)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Members of the sorted set at `key` with their scores, lowest rank first
+/// same order as redis' ZRANGE
+fn ranked<'t, T: ReadableTable<(&'static str, u64, &'static str), ()>>(
+    table: &'t T,
+    key: &str,
+) -> Result<impl DoubleEndedIterator<Item = Result<(String, f64), StorageError>> + 't, StorageError>
+{
+    // the smallest string sorting after `key`, so the range ends with the set
+    let next_key = format!("{key}\0");
+    let range = table.range((key, 0, "")..(next_key.as_str(), 0, ""))?;
+    Ok(range.map(|entry| {
+        let (k, _) = entry?;
+        let (_, rank, member) = k.value();
+        Ok((member.to_string(), score_from_rank(rank)))
+    }))
+}
+
+fn is_expired(
+    table: &impl ReadableTable<&'static str, u64>,
+    key: &str,
+    now: u64,
+) -> Result<bool, StorageError> {
+    Ok(table.get(key)?.is_some_and(|at| at.value() <= now))
+}
+
+/// Remove `key` along with its members and ttl
+#[allow(clippy::result_large_err)]
+fn remove_key(txn: &WriteTransaction, key: &str) -> Result<(), redb::Error> {
+    txn.open_table(TABLE)?.remove(key)?;
+
+    let members = {
+        let rank_table = txn.open_table(SORTED_RANK_TABLE)?;
+        let members = ranked(&rank_table, key)?.collect::<Result<Vec<_>, _>>()?;
+        members
+    };
+    {
+        let mut sorted = txn.open_table(SORTED_TABLE)?;
+        let mut rank_table = txn.open_table(SORTED_RANK_TABLE)?;
+        for (member, score) in &members {
+            sorted.remove((key, member.as_str()))?;
+            rank_table.remove((key, rank_score(*score), member.as_str()))?;
+        }
+    }
+    txn.open_table(SORTED_LEN_TABLE)?.remove(key)?;
+
+    persist(txn, key)
+}
+
+/// Clear the ttl of `key`
+#[allow(clippy::result_large_err)]
+fn persist(txn: &WriteTransaction, key: &str) -> Result<(), redb::Error> {
+    let expires_at = txn
+        .open_table(EXPIRY_TABLE)?
+        .remove(key)?
+        .map(|at| at.value());
+    if let Some(expires_at) = expires_at {
+        txn.open_table(EXPIRY_QUEUE_TABLE)?
+            .remove((expires_at, key))?;
+    }
+    Ok(())
+}
+
+/// Expired keys are removed when written to, so the write starts from an empty key like in redis
+#[allow(clippy::result_large_err)]
+fn remove_if_expired(txn: &WriteTransaction, key: &str) -> Result<(), redb::Error> {
+    let expired = is_expired(&txn.open_table(EXPIRY_TABLE)?, key, now_ms())?;
+    if expired {
+        remove_key(txn, key)?;
+    }
+    Ok(())
+}
+
+/// Set the score of `member`, returns its previous score
+#[allow(clippy::result_large_err)]
+fn set_score(
+    txn: &WriteTransaction,
+    key: &str,
+    member: &str,
+    score: f64,
+) -> Result<Option<f64>, redb::Error> {
+    let previous = txn
+        .open_table(SORTED_TABLE)?
+        .insert((key, member), score)?
+        .map(|score| score.value());
+    {
+        let mut rank_table = txn.open_table(SORTED_RANK_TABLE)?;
+        if let Some(previous) = previous {
+            rank_table.remove((key, rank_score(previous), member))?;
+        }
+        rank_table.insert((key, rank_score(score), member), ())?;
+    }
+    if previous.is_none() {
+        let mut lens = txn.open_table(SORTED_LEN_TABLE)?;
+        let len = lens.get(key)?.map(|len| len.value()).unwrap_or_default();
+        lens.insert(key, len + 1)?;
+    }
+    Ok(previous)
+}
+
+/// Remove `member`, returns true if it was in the set
+#[allow(clippy::result_large_err)]
+fn remove_member(txn: &WriteTransaction, key: &str, member: &str) -> Result<bool, redb::Error> {
+    let previous = txn
+        .open_table(SORTED_TABLE)?
+        .remove((key, member))?
+        .map(|score| score.value());
+    let Some(previous) = previous else {
+        return Ok(false);
+    };
+    txn.open_table(SORTED_RANK_TABLE)?
+        .remove((key, rank_score(previous), member))?;
+
+    let mut lens = txn.open_table(SORTED_LEN_TABLE)?;
+    let len = lens.get(key)?.map(|len| len.value()).unwrap_or_default();
+    if len > 1 {
+        lens.insert(key, len - 1)?;
+    } else {
+        lens.remove(key)?;
+    }
+    Ok(true)
+}
 
 #[derive(Clone)]
 pub struct ReDBKV(Arc<Database>);
@@ -15,10 +182,38 @@ impl ReDBKV {
     #[allow(clippy::result_large_err)]
     pub fn new() -> Result<Self, redb::Error> {
         let db = Database::create("./redb-kv.db")?;
+        Self::init(db)
+    }
+
+    /// Non-persistent store, used in tests
+    #[allow(clippy::result_large_err)]
+    pub fn new_in_memory() -> Result<Self, redb::Error> {
+        let db = Database::builder().create_with_backend(InMemoryBackend::new())?;
+        Self::init(db)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn init(db: Database) -> Result<Self, redb::Error> {
         let write_txn = db.begin_write()?;
         {
             write_txn.open_table(TABLE)?;
             write_txn.open_table(RAW_METADATA_TABLE)?;
+            write_txn.open_table(EXPIRY_TABLE)?;
+            write_txn.open_table(EXPIRY_QUEUE_TABLE)?;
+
+            // databases created before the rank index only have the scores
+            let sorted = write_txn.open_table(SORTED_TABLE)?;
+            let mut rank_table = write_txn.open_table(SORTED_RANK_TABLE)?;
+            let mut lens = write_txn.open_table(SORTED_LEN_TABLE)?;
+            if rank_table.is_empty()? {
+                for entry in sorted.iter()? {
+                    let (k, score) = entry?;
+                    let (key, member) = k.value();
+                    rank_table.insert((key, rank_score(score.value()), member), ())?;
+                    let len = lens.get(key)?.map(|len| len.value()).unwrap_or_default();
+                    lens.insert(key, len + 1)?;
+                }
+            }
         }
         write_txn.commit()?;
         Ok(Self(Arc::new(db)))
@@ -38,6 +233,9 @@ impl KVStore for ReDBKV {
     async fn read(&self, key: String) -> Result<Option<String>, KVError> {
         self.spawn_blocking(move |db| {
             let read_txn = db.begin_read()?;
+            if is_expired(&read_txn.open_table(EXPIRY_TABLE)?, &key, now_ms())? {
+                return Ok(None);
+            }
             let value = {
                 let table = read_txn.open_table(TABLE)?;
                 let v = table.get(key.as_str())?;
@@ -49,9 +247,11 @@ impl KVStore for ReDBKV {
         .unwrap()
     }
 
+    /// Like redis' SET, the key's ttl is cleared
     async fn write(&self, key: String, value: String) -> Result<(), KVError> {
         self.spawn_blocking(move |db| {
             let write_txn = db.begin_write()?;
+            persist(&write_txn, &key)?;
             {
                 let mut table = write_txn.open_table(TABLE)?;
                 table.insert(key.as_str(), value.as_str())?;
@@ -62,4 +262,213 @@ impl KVStore for ReDBKV {
         .await
         .unwrap()
     }
+
+    async fn read_many(&self, keys: Vec<String>) -> Result<Vec<Option<String>>, KVError> {
+        self.spawn_blocking(move |db| {
+            let read_txn = db.begin_read()?;
+            let table = read_txn.open_table(TABLE)?;
+            let expiry = read_txn.open_table(EXPIRY_TABLE)?;
+            let now = now_ms();
+            let values: Vec<_> = keys
+                .iter()
+                .map(|key| {
+                    if is_expired(&expiry, key, now)? {
+                        return Ok(None);
+                    }
+                    Ok(table.get(key.as_str())?.map(|v| v.value().to_string()))
+                })
+                .collect::<Result<_, StorageError>>()?;
+            Ok(values)
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn incr(&self, key: String, delta: i64) -> Result<i64, KVError> {
+        self.spawn_blocking(move |db| {
+            // write transactions are serialized, so the read and the write are atomic
+            let write_txn = db.begin_write()?;
+            remove_if_expired(&write_txn, &key)?;
+            let value = {
+                let mut table = write_txn.open_table(TABLE)?;
+                let current = match table.get(key.as_str())? {
+                    Some(v) => v
+                        .value()
+                        .parse::<i64>()
+                        .map_err(|_| redb::Error::Corrupted(format!("{key} is not an integer")))?,
+                    None => 0,
+                };
+                let value = current + delta;
+                table.insert(key.as_str(), value.to_string().as_str())?;
+                value
+            };
+            write_txn.commit()?;
+            Ok(value)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Expired keys are hidden from reads, removed when written to
+    /// and swept a few at a time whenever a ttl is set
+    async fn expire(&self, key: String, ttl: Duration) -> Result<(), KVError> {
+        self.spawn_blocking(move |db| {
+            let now = now_ms();
+            let write_txn = db.begin_write()?;
+            remove_if_expired(&write_txn, &key)?;
+            let exists = write_txn.open_table(TABLE)?.get(key.as_str())?.is_some()
+                || write_txn
+                    .open_table(SORTED_LEN_TABLE)?
+                    .get(key.as_str())?
+                    .is_some();
+            if exists {
+                let expires_at = now + ttl.as_millis() as u64;
+                let previous = write_txn
+                    .open_table(EXPIRY_TABLE)?
+                    .insert(key.as_str(), expires_at)?
+                    .map(|at| at.value());
+                let mut queue = write_txn.open_table(EXPIRY_QUEUE_TABLE)?;
+                if let Some(previous) = previous {
+                    queue.remove((previous, key.as_str()))?;
+                }
+                queue.insert((expires_at, key.as_str()), ())?;
+            }
+
+            let expired = {
+                let queue = write_txn.open_table(EXPIRY_QUEUE_TABLE)?;
+                let expired = queue
+                    .range(..=(now, ""))?
+                    .take(EXPIRY_SWEEP_LIMIT)
+                    .map(|entry| Ok(entry?.0.value().1.to_string()))
+                    .collect::<Result<Vec<_>, StorageError>>()?;
+                expired
+            };
+            for key in expired {
+                remove_key(&write_txn, &key)?;
+            }
+
+            write_txn.commit()?;
+            Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_add(&self, key: String, member: String, score: f64) -> Result<bool, KVError> {
+        self.spawn_blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            remove_if_expired(&write_txn, &key)?;
+            let added = set_score(&write_txn, &key, &member, score)?.is_none();
+            write_txn.commit()?;
+            Ok(added)
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_incr(&self, key: String, member: String, delta: f64) -> Result<f64, KVError> {
+        self.spawn_blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            remove_if_expired(&write_txn, &key)?;
+            let current = write_txn
+                .open_table(SORTED_TABLE)?
+                .get((key.as_str(), member.as_str()))?
+                .map(|v| v.value())
+                .unwrap_or_default();
+            set_score(&write_txn, &key, &member, current + delta)?;
+            write_txn.commit()?;
+            Ok(current + delta)
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_remove(&self, key: String, member: String) -> Result<bool, KVError> {
+        self.spawn_blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            remove_if_expired(&write_txn, &key)?;
+            let removed = remove_member(&write_txn, &key, &member)?;
+            write_txn.commit()?;
+            Ok(removed)
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_score(&self, key: String, member: String) -> Result<Option<f64>, KVError> {
+        self.spawn_blocking(move |db| {
+            let read_txn = db.begin_read()?;
+            if is_expired(&read_txn.open_table(EXPIRY_TABLE)?, &key, now_ms())? {
+                return Ok(None);
+            }
+            let table = read_txn.open_table(SORTED_TABLE)?;
+            let score = table.get((key.as_str(), member.as_str()))?;
+            Ok(score.map(|v| v.value()))
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_len(&self, key: String) -> Result<u64, KVError> {
+        self.spawn_blocking(move |db| {
+            let read_txn = db.begin_read()?;
+            if is_expired(&read_txn.open_table(EXPIRY_TABLE)?, &key, now_ms())? {
+                return Ok(0);
+            }
+            let table = read_txn.open_table(SORTED_LEN_TABLE)?;
+            let len = table.get(key.as_str())?;
+            Ok(len.map(|v| v.value()).unwrap_or_default())
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_range_rev(
+        &self,
+        key: String,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<(String, f64)>, KVError> {
+        self.spawn_blocking(move |db| {
+            let read_txn = db.begin_read()?;
+            if is_expired(&read_txn.open_table(EXPIRY_TABLE)?, &key, now_ms())? {
+                return Ok(vec![]);
+            }
+            let table = read_txn.open_table(SORTED_RANK_TABLE)?;
+            let members = ranked(&table, &key)?
+                .rev()
+                .skip(start as usize)
+                .take(limit as usize)
+                .collect::<Result<_, _>>()?;
+            Ok(members)
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn sorted_trim(&self, key: String, keep: u64) -> Result<(), KVError> {
+        self.spawn_blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            remove_if_expired(&write_txn, &key)?;
+            let len = write_txn
+                .open_table(SORTED_LEN_TABLE)?
+                .get(key.as_str())?
+                .map(|len| len.value())
+                .unwrap_or_default();
+            let dropped = {
+                let table = write_txn.open_table(SORTED_RANK_TABLE)?;
+                let dropped = ranked(&table, &key)?
+                    .take(len.saturating_sub(keep) as usize)
+                    .collect::<Result<Vec<_>, _>>()?;
+                dropped
+            };
+            for (member, _) in dropped {
+                remove_member(&write_txn, &key, &member)?;
+            }
+            write_txn.commit()?;
+            Ok(())
+        })
+        .await
+        .unwrap()
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bb8_redis::RedisConnectionManager;
 use redis::{AsyncCommands, RedisError};
 
@@ -27,4 +29,78 @@ impl KVStore for RedisKV {
         con.hset::<_, _, _, ()>(key, AUTH_FIELD, value).await?;
         Ok(())
     }
+
+    async fn read_many(&self, keys: Vec<String>) -> Result<Vec<Option<String>>, KVError> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.hget(key, AUTH_FIELD);
+        }
+        let mut con = self.0.get().await?;
+        Ok(pipe.query_async(&mut *con).await?)
+    }
+
+    async fn incr(&self, key: String, delta: i64) -> Result<i64, KVError> {
+        let mut con = self.0.get().await?;
+        Ok(con.hincr(key, AUTH_FIELD, delta).await?)
+    }
+
+    async fn expire(&self, key: String, ttl: Duration) -> Result<(), KVError> {
+        let mut con = self.0.get().await?;
+        con.expire::<_, ()>(key, ttl.as_secs() as i64).await?;
+        Ok(())
+    }
+
+    async fn sorted_add(&self, key: String, member: String, score: f64) -> Result<bool, KVError> {
+        let mut con = self.0.get().await?;
+        let added: u64 = con.zadd(key, member, score).await?;
+        Ok(added > 0)
+    }
+
+    async fn sorted_incr(&self, key: String, member: String, delta: f64) -> Result<f64, KVError> {
+        let mut con = self.0.get().await?;
+        Ok(con.zincr(key, member, delta).await?)
+    }
+
+    async fn sorted_remove(&self, key: String, member: String) -> Result<bool, KVError> {
+        let mut con = self.0.get().await?;
+        let removed: u64 = con.zrem(key, member).await?;
+        Ok(removed > 0)
+    }
+
+    async fn sorted_score(&self, key: String, member: String) -> Result<Option<f64>, KVError> {
+        let mut con = self.0.get().await?;
+        Ok(con.zscore(key, member).await?)
+    }
+
+    async fn sorted_len(&self, key: String) -> Result<u64, KVError> {
+        let mut con = self.0.get().await?;
+        Ok(con.zcard(key).await?)
+    }
+
+    async fn sorted_range_rev(
+        &self,
+        key: String,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<(String, f64)>, KVError> {
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+        let mut con = self.0.get().await?;
+        let stop = start + limit - 1;
+        Ok(con
+            .zrevrange_withscores(key, start as isize, stop as isize)
+            .await?)
+    }
+
+    async fn sorted_trim(&self, key: String, keep: u64) -> Result<(), KVError> {
+        let mut con = self.0.get().await?;
+        // ranks are ascending, so the lowest scores come first
+        con.zremrangebyrank::<_, ()>(key, 0, -(keep as isize) - 1)
+            .await?;
+        Ok(())
+    }
 }
@@ -26,6 +26,25 @@ async fn issue_referral_rewards(referee_canister: Principal) -> Result<(), Serve
     issue_referral_rewards_impl(referee_canister).await
 }
 
+/// Merge the mixpanel history of the identity replaced by the login into the logged in user
+#[server]
+async fn merge_mixpanel_identity() -> Result<(), ServerFnError> {
+    use auth::server_impl::{extract_principal_from_cookie, take_previous_principal};
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use leptos_axum::extract_with_state;
+    use utils::mixpanel::proxy::enqueue_identity_merge;
+
+    let key: Key = expect_context();
+    let jar: SignedCookieJar = extract_with_state(&key).await?;
+    let user_principal = extract_principal_from_cookie(&jar)?
+        .ok_or_else(|| ServerFnError::new("User not logged in"))?;
+    if let Some(anon_principal) = take_previous_principal().await? {
+        enqueue_identity_merge(anon_principal, user_principal);
+    }
+
+    Ok(())
+}
+
 #[server]
 async fn mark_user_registered(user_principal: Principal) -> Result<bool, ServerFnError> {
     use self::server_fn_impl::mark_user_registered_impl;
@@ -149,6 +168,9 @@ pub fn LoginProviders(show_modal: RwSignal<bool>, lock_closing: RwSignal<bool>)
             }
 
             let _ = LoginSuccessful.send_event(canisters);
+            if let Err(e) = send_wrap(merge_mixpanel_identity()).await {
+                log::warn!("failed to merge mixpanel identity, err {e}");
+            }
 
             // Update the context signal instead of writing directly
             storage_sync_ctx.account_connected.set(true);
@@ -0,0 +1,122 @@
+use codee::string::FromToStringCodec;
+use leptos::prelude::*;
+use leptos_use::{use_cookie_with_options, UseCookieOptions};
+
+use consts::analytics::{CONSENT_COOKIE, CONSENT_MAX_AGE};
+use utils::event_streaming::consent::{ConsentCategory, ConsentState, ConsentStatus};
+
+fn category_label(category: ConsentCategory) -> &'static str {
+    match category {
+        ConsentCategory::Analytics => "Analytics",
+        ConsentCategory::Personalization => "Personalized feed",
+    }
+}
+
+fn category_description(category: ConsentCategory) -> &'static str {
+    match category {
+        ConsentCategory::Analytics => "Helps us understand how Yral is used",
+        ConsentCategory::Personalization => "Uses your activity to recommend videos",
+    }
+}
+
+/// Asks for analytics consent until the user makes a choice
+/// the choice is stored in the consent cookie, which the server honors
+#[component]
+pub fn ConsentBanner() -> impl IntoView {
+    let (consent, set_consent) = use_cookie_with_options::<ConsentState, FromToStringCodec>(
+        CONSENT_COOKIE,
+        UseCookieOptions::default()
+            .max_age(CONSENT_MAX_AGE.as_millis() as i64)
+            .path("/".to_string()),
+    );
+    let customizing = RwSignal::new(false);
+    let draft = RwSignal::new(ConsentState::default());
+
+    let save = move |state: ConsentState| {
+        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        utils::event_streaming::update_gtag_consent(state);
+        set_consent.set(Some(state));
+    };
+
+    view! {
+        <Show when=move || consent.with(|c| c.is_none())>
+            <div class="fixed inset-x-0 bottom-0 z-[70] p-4 flex justify-center">
+                <div class="w-full max-w-md rounded-lg bg-neutral-900 p-4 flex flex-col gap-4 text-white font-kumbh">
+                    <span class="text-sm text-neutral-300">
+                        "We use cookies to measure how the app is used and to personalize your feed. You can change your choice at any time."
+                    </span>
+                    <Show when=customizing>
+                        <div class="flex flex-col gap-3">
+                            {ConsentCategory::ALL
+                                .into_iter()
+                                .map(|category| {
+                                    let granted = move || draft.with(|d| d.is_granted(category));
+                                    view! {
+                                        <label class="flex items-center justify-between gap-4 cursor-pointer">
+                                            <div class="flex flex-col">
+                                                <span class="font-bold">{category_label(category)}</span>
+                                                <span class="text-xs text-neutral-400">
+                                                    {category_description(category)}
+                                                </span>
+                                            </div>
+                                            <input
+                                                type="checkbox"
+                                                class="accent-primary-600 w-5 h-5"
+                                                prop:checked=granted
+                                                on:change=move |_| {
+                                                    draft
+                                                        .update(|d| {
+                                                            let status = if d.is_granted(category) {
+                                                                ConsentStatus::Denied
+                                                            } else {
+                                                                ConsentStatus::Granted
+                                                            };
+                                                            d.set(category, status);
+                                                        })
+                                                }
+                                            />
+                                        </label>
+                                    }
+                                })
+                                .collect_view()}
+                        </div>
+                    </Show>
+                    <div class="flex flex-row gap-2 text-sm font-bold">
+                        <Show
+                            when=customizing
+                            fallback=move || {
+                                view! {
+                                    <button
+                                        class="flex-1 py-2 rounded-lg bg-neutral-800"
+                                        on:click=move |_| customizing.set(true)
+                                    >
+                                        "Customize"
+                                    </button>
+                                    <button
+                                        class="flex-1 py-2 rounded-lg bg-neutral-800"
+                                        on:click=move |_| save(ConsentState::all(ConsentStatus::Denied))
+                                    >
+                                        "Reject all"
+                                    </button>
+                                }
+                            }
+                        >
+                            <button
+                                class="flex-1 py-2 rounded-lg bg-neutral-800"
+                                on:click=move |_| save(draft.get_untracked())
+                            >
+                                "Save choices"
+                            </button>
+                        </Show>
+                        <button
+                            class="flex-1 py-2 rounded-lg bg-primary-600"
+                            on:click=move |_| save(ConsentState::all(ConsentStatus::Granted))
+                        >
+                            "Accept all"
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -7,6 +7,7 @@ pub mod buttons;
 pub mod canisters_prov;
 pub mod coming_soon;
 pub mod connect;
+pub mod consent_banner;
 pub mod content_upload;
 pub mod dashbox;
 pub mod feed_popup;
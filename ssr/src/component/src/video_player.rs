@@ -8,6 +8,7 @@ pub fn VideoPlayer(
     #[prop(optional)] node_ref: NodeRef<Video>,
     #[prop(into)] view_bg_url: Signal<Option<String>>,
     #[prop(into)] view_video_url: Signal<Option<String>>,
+    #[prop(into, default = "auto".into())] preload: Signal<&'static str>,
 ) -> impl IntoView {
     view! {
         <label class="h-full w-full absolute top-0 left-0 grid grid-cols-1 justify-items-center items-center cursor-pointer z-[3]">
@@ -27,7 +28,7 @@ pub fn VideoPlayer(
                 playsinline
                 disablepictureinpicture
                 disableremoteplayback
-                preload="auto"
+                preload=preload
             ></video>
         </label>
     }
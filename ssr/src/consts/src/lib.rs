@@ -1,3 +1,14 @@
+/// Upstream URL which can be overriden at compile time through an env var
+/// e.g to point the app at `mock-upstreams` for local development
+macro_rules! upstream_url {
+    ($env:literal, $default:expr) => {
+        match option_env!($env) {
+            Some(url) => url,
+            None => $default,
+        }
+    };
+}
+
 #[cfg(any(feature = "local-bin", feature = "local-lib"))]
 mod local;
 use candid::Principal;
@@ -29,17 +40,64 @@ pub const USER_CANISTER_ID_STORE: &str = "user-canister-id";
 pub const USER_PRINCIPAL_STORE: &str = "user-principal";
 pub const USER_ONBOARDING_STORE: &str = "user-onboarding";
 pub const USER_INTERNAL_STORE: &str = "user-internal";
-
-pub static OFF_CHAIN_AGENT_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://icp-off-chain-agent.fly.dev").unwrap());
-pub static OFF_CHAIN_AGENT_GRPC_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://icp-off-chain-agent.fly.dev:443").unwrap()); // pr-91-yral-dapp-off-chain-agent https://icp-off-chain-agent.fly.dev:443
-                                                                                  // G-6W5Q2MRX0E to test locally | G-PLNNETMSLM
+pub const SEEN_POSTS_STORE: &str = "seen-posts";
+pub const HIDDEN_CREATORS_STORE: &str = "hidden-creators";
+pub const FOLLOWING_STORE: &str = "following";
+pub const DATA_SAVER_STORE: &str = "data-saver";
+pub const FEED_SESSION_STORE: &str = "feed-session";
+pub const HON_STAKE_STORE: &str = "hon-stake";
+
+pub static OFF_CHAIN_AGENT_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_OFF_CHAIN_AGENT_URL",
+        "https://icp-off-chain-agent.fly.dev"
+    ))
+    .unwrap()
+});
+pub static OFF_CHAIN_AGENT_GRPC_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_OFF_CHAIN_AGENT_GRPC_URL",
+        "https://icp-off-chain-agent.fly.dev:443"
+    ))
+    .unwrap()
+}); // pr-91-yral-dapp-off-chain-agent https://icp-off-chain-agent.fly.dev:443
+
+// G-6W5Q2MRX0E to test locally | G-PLNNETMSLM
 pub static GTAG_MEASUREMENT_ID: Lazy<&str> = Lazy::new(|| "G-PLNNETMSLM");
+pub const MIXPANEL_PROJECT_TOKEN: &str = "609abef3172b5fc64554f5ac6c77414d";
+pub const MIXPANEL_API_URL: &str =
+    upstream_url!("YRAL_MIXPANEL_API_URL", "https://api.mixpanel.com");
 pub static DOWNLOAD_UPLOAD_SERVICE: Lazy<Url> =
     Lazy::new(|| Url::parse("https://download-upload-service.fly.dev").unwrap());
-pub static ML_FEED_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://yral-ml-feed-server.fly.dev").unwrap());
+pub static ML_FEED_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_ML_FEED_URL",
+        "https://yral-ml-feed-server.fly.dev"
+    ))
+    .unwrap()
+});
+/// Feed providers tried in order until one returns posts
+/// `<provider>[:<timeout ms>]`, see `page::post_view::feed_provider`
+pub const FEED_PROVIDERS: &str = match option_env!("YRAL_FEED_PROVIDERS") {
+    Some(providers) => providers,
+    None => "ml:4000,ml-coldstart:4000,canister:8000",
+};
+
+pub static HON_WORKER_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_HON_WORKER_URL",
+        hon_worker_common::WORKER_URL
+    ))
+    .unwrap()
+});
+
+pub static QSTASH_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_QSTASH_URL",
+        "https://qstash.upstash.io/v2/"
+    ))
+    .unwrap()
+});
 
 pub static FALLBACK_USER_INDEX: Lazy<Principal> =
     Lazy::new(|| Principal::from_text("rimrc-piaaa-aaaao-aaljq-cai").unwrap());
@@ -52,8 +110,14 @@ pub const CDAO_SWAP_PRE_READY_TIME_SECS: u64 = 150;
 
 pub const CDAO_SWAP_TIME_SECS: u64 = CDAO_SWAP_PRE_READY_TIME_SECS + 150;
 
-pub const ICPUMP_SEARCH_GRPC_URL: &str = "https://prod-yral-icpumpsearch.fly.dev:443";
-pub const NSFW_SERVER_URL: &str = "https://prod-yral-nsfw-classification.fly.dev:443";
+pub const ICPUMP_SEARCH_GRPC_URL: &str = upstream_url!(
+    "YRAL_ICPUMP_SEARCH_GRPC_URL",
+    "https://prod-yral-icpumpsearch.fly.dev:443"
+);
+pub const NSFW_SERVER_URL: &str = upstream_url!(
+    "YRAL_NSFW_SERVER_URL",
+    "https://prod-yral-nsfw-classification.fly.dev:443"
+);
 
 pub const CF_KV_ML_CACHE_NAMESPACE_ID: &str = "ea145fc839bd42f9bf2d34b950ddbda5";
 pub const CLOUDFLARE_ACCOUNT_ID: &str = "a209c523d2d9646cc56227dbe6ce3ede";
@@ -78,6 +142,22 @@ pub mod auth {
     /// Refresh expiry, 30 days
     pub const REFRESH_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
     pub const REFRESH_TOKEN_COOKIE: &str = "user-identity";
+    /// Principal replaced by the last identity change, e.g the anonymous one on login
+    pub const PREVIOUS_IDENTITY_COOKIE: &str = "user-identity-previous";
+    /// The previous identity is only kept long enough to merge analytics after login, 1 hour
+    pub const PREVIOUS_IDENTITY_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+}
+
+pub mod analytics {
+    use web_time::Duration;
+
+    /// First party analytics client id, issued by the SSR server
+    pub const CLIENT_ID_COOKIE: &str = "yral-analytics-cid";
+    /// Client ids are rotated after 180 days
+    pub const CLIENT_ID_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 180);
+    pub const CONSENT_COOKIE: &str = "yral-analytics-consent";
+    /// Consent is asked again after a year
+    pub const CONSENT_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 365);
 }
 
 #[cfg(feature = "oauth-ssr")]
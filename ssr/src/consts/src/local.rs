@@ -8,5 +8,10 @@ pub const AGENT_URL: &str = "http://localhost:4943";
 
 pub const YRAL_BACKEND_CONTAINER_TAG: &str = "04b53277579d9370c13312a2833ca0b855cdad72";
 pub const YRAL_METADATA_CONTAINER_TAG: &str = "a4879e2e711c17beeb12ed6987ba315c110be9e5";
-pub static PUMP_AND_DUMP_WORKER_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("http://localhost:8787/").unwrap());
+pub static PUMP_AND_DUMP_WORKER_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_PUMP_AND_DUMP_WORKER_URL",
+        "http://localhost:8787/"
+    ))
+    .unwrap()
+});
@@ -6,5 +6,10 @@ pub static METADATA_API_BASE: Lazy<Url> =
 
 pub const AGENT_URL: &str = "https://ic0.app";
 
-pub static PUMP_AND_DUMP_WORKER_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://yral-pump-n-dump.go-bazzinga.workers.dev/").unwrap());
+pub static PUMP_AND_DUMP_WORKER_URL: Lazy<Url> = Lazy::new(|| {
+    Url::parse(upstream_url!(
+        "YRAL_PUMP_AND_DUMP_WORKER_URL",
+        "https://yral-pump-n-dump.go-bazzinga.workers.dev/"
+    ))
+    .unwrap()
+});
@@ -11,7 +11,10 @@ use auth::server_impl::store::KVStoreImpl;
 use axum_extra::extract::cookie::Key;
 use leptos::prelude::*;
 use leptos_axum::AxumRouteListing;
-use state::server::AppState;
+use state::{
+    canister_backend::{CanisterBackendImpl, FakeCanisters},
+    server::{AppState, ContextKey, ContextRegistry, MissingContexts},
+};
 use utils::token::{icpump::ICPumpSearchGrpcChannel, nsfw::ICPumpNSFWGrpcChannel};
 use yral_canisters_common::Canisters;
 
@@ -150,48 +153,68 @@ async fn init_firestoredb() -> firestore::FirestoreDb {
         .expect("failed to create db")
 }
 
-#[cfg(feature = "ga4")]
-async fn init_grpc_offchain_channel() -> tonic::transport::Channel {
-    use consts::OFF_CHAIN_AGENT_GRPC_URL;
+/// Connect to a gRPC upstream, TLS is only used for `https` urls
+/// (plaintext is allowed for local upstreams, e.g `mock-upstreams`)
+async fn connect_grpc_channel(url: &'static str, name: &str) -> tonic::transport::Channel {
     use tonic::transport::{Channel, ClientTlsConfig};
 
-    let tls_config = ClientTlsConfig::new().with_webpki_roots();
-    let off_chain_agent_url = OFF_CHAIN_AGENT_GRPC_URL.as_ref();
-    Channel::from_static(off_chain_agent_url)
-        .tls_config(tls_config)
-        .expect("Couldn't update TLS config for off-chain agent")
+    let mut endpoint = Channel::from_static(url);
+    if url.starts_with("https://") {
+        let tls_config = ClientTlsConfig::new().with_webpki_roots();
+        endpoint = endpoint
+            .tls_config(tls_config)
+            .unwrap_or_else(|_| panic!("Couldn't update TLS config for {name}"));
+    }
+
+    endpoint
         .connect()
         .await
-        .expect("Couldn't connect to off-chain agent")
+        .unwrap_or_else(|_| panic!("Couldn't connect to {name}"))
+}
+
+#[cfg(feature = "ga4")]
+async fn init_grpc_offchain_channel() -> tonic::transport::Channel {
+    use consts::OFF_CHAIN_AGENT_GRPC_URL;
+
+    connect_grpc_channel(OFF_CHAIN_AGENT_GRPC_URL.as_ref(), "off-chain agent").await
+}
+
+/// `warehouse` is the off-chain agent channel, if connected
+fn init_event_pipeline(
+    warehouse: Option<&tonic::transport::Channel>,
+) -> Result<utils::event_streaming::pipeline::EventPipeline, InitError> {
+    use utils::event_streaming::pipeline::{sinks::parse_sink_specs, EventPipeline, EventSpool};
+
+    #[cfg(feature = "ga4")]
+    const DEFAULT_SINKS: &str = "warehouse,ga4";
+    #[cfg(not(feature = "ga4"))]
+    const DEFAULT_SINKS: &str = "file:./analytics-events.jsonl";
+
+    let sinks = env::var("ANALYTICS_SINKS").unwrap_or_else(|_| DEFAULT_SINKS.into());
+    let sinks = parse_sink_specs(&sinks)?
+        .into_iter()
+        .map(|spec| spec.build(warehouse))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let spool_path =
+        env::var("ANALYTICS_SPOOL_PATH").unwrap_or_else(|_| "./analytics-spool.db".into());
+    let spool = EventSpool::open(&spool_path).map_err(InitError::AnalyticsSpool)?;
+
+    Ok(EventPipeline::start(sinks, spool))
 }
 
 async fn init_grpc_icpump_search_channel() -> ICPumpSearchGrpcChannel {
     use consts::ICPUMP_SEARCH_GRPC_URL;
-    use tonic::transport::{Channel, ClientTlsConfig};
 
-    let tls_config = ClientTlsConfig::new().with_webpki_roots();
-    let off_chain_agent_url = ICPUMP_SEARCH_GRPC_URL;
-    let channel = Channel::from_static(off_chain_agent_url)
-        .tls_config(tls_config)
-        .expect("Couldn't update TLS config for off-chain agent")
-        .connect()
-        .await
-        .expect("Couldn't connect to off-chain agent");
+    let channel = connect_grpc_channel(ICPUMP_SEARCH_GRPC_URL, "icpump search").await;
 
     ICPumpSearchGrpcChannel { channel }
 }
 
 async fn init_grpc_nsfw_channel() -> ICPumpNSFWGrpcChannel {
     use consts::NSFW_SERVER_URL;
-    use tonic::transport::{Channel, ClientTlsConfig};
 
-    let tls_config = ClientTlsConfig::new().with_webpki_roots();
-    let channel = Channel::from_static(NSFW_SERVER_URL)
-        .tls_config(tls_config)
-        .expect("Couldn't update TLS config for nsfw agent")
-        .connect()
-        .await
-        .expect("Couldn't connect to nsfw agent");
+    let channel = connect_grpc_channel(NSFW_SERVER_URL, "nsfw agent").await;
 
     ICPumpNSFWGrpcChannel { channel }
 }
@@ -261,8 +284,63 @@ async fn init_alloydb_client() -> state::alloydb::AlloyDbInstance {
     AlloyDbInstance::new(client, instance, db_name, db_user, db_password)
 }
 
+/// Canisters faked from a `mock-upstreams` fixture file, if `LOCAL_FAKE_CANISTERS` points at one
+#[cfg(feature = "local-bin")]
+fn init_fake_canisters() -> Option<FakeCanisters> {
+    let path = env::var("LOCAL_FAKE_CANISTERS").ok()?;
+    let raw = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read `LOCAL_FAKE_CANISTERS` {path}: {e}"));
+    Some(FakeCanisters::from_fixtures(&raw).expect("Invalid `LOCAL_FAKE_CANISTERS` fixtures"))
+}
+
+/// Invalid server configuration
+#[derive(Debug, thiserror::Error)]
+pub enum InitError {
+    #[error(transparent)]
+    StakeConfig(#[from] page::post_view::bet::stake::StakeConfigError),
+    #[error(transparent)]
+    MissingContexts(#[from] MissingContexts),
+    #[error("invalid `ANALYTICS_SINKS`: {0}")]
+    AnalyticsSinks(#[from] utils::event_streaming::pipeline::sinks::SinkConfigError),
+    #[error("failed to open the analytics spool: {0}")]
+    AnalyticsSpool(redb::Error),
+}
+
+/// Contexts that server functions and SSR expect to be present
+/// for the enabled feature set
+pub fn required_contexts() -> Vec<ContextKey> {
+    vec![
+        ContextKey::of::<Canisters<false>>(),
+        #[cfg(feature = "backend-admin")]
+        ContextKey::of::<state::admin_canisters::AdminCanisters>(),
+        #[cfg(feature = "cloudflare")]
+        ContextKey::of::<gob_cloudflare::CloudflareAuth>(),
+        ContextKey::of::<KVStoreImpl>(),
+        ContextKey::of::<Key>(),
+        #[cfg(feature = "oauth-ssr")]
+        ContextKey::of::<auth::core_clients::CoreClients>(),
+        #[cfg(feature = "ga4")]
+        ContextKey::of::<tonic::transport::Channel>(),
+        ContextKey::of::<utils::event_streaming::pipeline::EventPipeline>(),
+        ContextKey::of::<page::post_view::coldstart::ColdstartCache>(),
+        ContextKey::of::<page::post_view::bet::stake::StakeConfig>(),
+        #[cfg(feature = "firestore")]
+        ContextKey::of::<firestore::FirestoreDb>(),
+        #[cfg(feature = "qstash")]
+        ContextKey::of::<utils::qstash::QStashClient>(),
+        ContextKey::of::<ICPumpSearchGrpcChannel>(),
+        ContextKey::of::<ICPumpNSFWGrpcChannel>(),
+        #[cfg(feature = "alloydb")]
+        ContextKey::of::<state::alloydb::AlloyDbInstance>(),
+        #[cfg(feature = "alloydb")]
+        ContextKey::of::<state::server::HonWorkerJwt>(),
+    ]
+}
+
 pub struct AppStateRes {
     pub app_state: AppState,
+    /// must be shut down after the server stops to flush queued events
+    pub event_pipeline: utils::event_streaming::pipeline::EventPipeline,
     #[cfg(feature = "local-bin")]
     pub containers: containers::TestContainers,
 }
@@ -308,49 +386,91 @@ impl AppStateBuilder {
         }
     }
 
-    pub async fn build(mut self) -> AppStateRes {
+    pub async fn build(mut self) -> Result<AppStateRes, InitError> {
+        let stake_config = page::post_view::bet::stake::StakeConfig::from_env()?;
         let kv = self.init_kv().await;
         #[cfg(feature = "local-bin")]
         {
             self.containers.start_backend().await;
             self.containers.start_metadata().await;
+            page::search::server_impl::index_fixtures(
+                &kv,
+                include_str!("../mock_upstreams/fixtures/default.json"),
+            )
+            .await
+            .expect("Failed to index search fixtures");
         }
 
-        let app_state = AppState {
-            leptos_options: self.leptos_options,
-            canisters: Canisters::default(),
-            routes: self.routes,
-            #[cfg(feature = "backend-admin")]
-            admin_canisters: init_admin_canisters(),
-            #[cfg(feature = "cloudflare")]
-            cloudflare: init_cf(),
-            kv,
-            cookie_key: init_cookie_key(),
-            #[cfg(feature = "oauth-ssr")]
-            google_oauth_clients: init_google_oauth(),
-            #[cfg(feature = "ga4")]
-            grpc_offchain_channel: init_grpc_offchain_channel().await,
-            #[cfg(feature = "firestore")]
-            firestore_db: init_firestoredb().await,
-            #[cfg(feature = "qstash")]
-            qstash: init_qstash_client(),
-            grpc_icpump_search_channel: init_grpc_icpump_search_channel().await,
-            grpc_nsfw_channel: init_grpc_nsfw_channel().await,
-            #[cfg(feature = "alloydb")]
-            alloydb: init_alloydb_client().await,
-            #[cfg(feature = "alloydb")]
-            hon_worker_jwt: {
-                use state::server::HonWorkerJwt;
-                let jwt = env::var("HON_WORKER_JWT").expect("`HON_WORKER_JWT` is required!");
-
-                HonWorkerJwt(std::sync::Arc::new(jwt))
-            },
+        let mut contexts = ContextRegistry::default();
+        contexts
+            .register(Canisters::<false>::default())
+            .register(kv.clone())
+            .register(init_cookie_key())
+            .register(init_grpc_icpump_search_channel().await)
+            .register(init_grpc_nsfw_channel().await);
+        #[cfg(feature = "backend-admin")]
+        contexts.register(init_admin_canisters());
+        #[cfg(feature = "cloudflare")]
+        contexts.register(init_cf());
+        #[cfg(feature = "oauth-ssr")]
+        contexts.register(init_google_oauth());
+        #[cfg(feature = "ga4")]
+        let warehouse = {
+            let channel = init_grpc_offchain_channel().await;
+            contexts.register(channel.clone());
+            Some(channel)
+        };
+        #[cfg(not(feature = "ga4"))]
+        let warehouse = None;
+        let event_pipeline = init_event_pipeline(warehouse.as_ref())?;
+        contexts.register(event_pipeline.clone());
+        let coldstart = page::post_view::coldstart::ColdstartCache::default();
+        coldstart.spawn_warmer();
+        contexts.register(coldstart);
+        contexts.register(stake_config);
+        #[cfg(feature = "local-bin")]
+        let fake_canisters = init_fake_canisters();
+        #[cfg(not(feature = "local-bin"))]
+        let fake_canisters = None::<FakeCanisters>;
+        let backfill_canisters = match fake_canisters.clone() {
+            Some(fake) => CanisterBackendImpl::Fake(fake),
+            None => CanisterBackendImpl::Live(Canisters::default()),
         };
+        page::hashtag::server_impl::spawn_backfill(kv.clone(), backfill_canisters);
+        #[cfg(feature = "local-bin")]
+        {
+            contexts.register_optional(page::comments::store::LocalCommentStore::default());
+            // votes reach the worker only if it is overridden, e.g. by `local-mock-run.sh`
+            if option_env!("YRAL_HON_WORKER_URL").is_some() {
+                contexts
+                    .register_optional(state::server::HonWorkerUrl(consts::HON_WORKER_URL.clone()));
+            }
+            if let Some(fake) = fake_canisters {
+                contexts.register_optional(fake);
+            }
+        }
+        #[cfg(feature = "firestore")]
+        contexts.register(init_firestoredb().await);
+        #[cfg(feature = "qstash")]
+        contexts.register(init_qstash_client());
+        #[cfg(feature = "alloydb")]
+        {
+            use state::server::HonWorkerJwt;
+            let jwt = env::var("HON_WORKER_JWT").expect("`HON_WORKER_JWT` is required!");
+
+            contexts
+                .register(init_alloydb_client().await)
+                .register(HonWorkerJwt(std::sync::Arc::new(jwt)));
+        }
 
-        AppStateRes {
+        contexts.ensure_registered(&required_contexts())?;
+        let app_state = AppState::new(self.leptos_options, self.routes, contexts);
+
+        Ok(AppStateRes {
             app_state,
+            event_pipeline,
             #[cfg(feature = "local-bin")]
             containers: self.containers,
-        }
+        })
     }
 }
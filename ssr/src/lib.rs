@@ -1,5 +1,7 @@
 #![recursion_limit = "256"]
 #![allow(clippy::empty_docs)]
+#[cfg(feature = "ssr")]
+pub mod analytics;
 pub mod app;
 pub mod canister_ids;
 pub mod error_template;
@@ -7,6 +9,10 @@ pub mod error_template;
 pub mod fallback;
 #[cfg(feature = "ssr")]
 pub mod init;
+#[cfg(feature = "ssr")]
+pub mod request_id;
+#[cfg(feature = "ssr")]
+pub mod router;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
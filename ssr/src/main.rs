@@ -1,109 +1,12 @@
 #![recursion_limit = "256"]
-use axum::{
-    body::Body as AxumBody,
-    extract::{Path, State},
-    http::Request,
-    response::{IntoResponse, Response},
-};
-use axum::{routing::get, Router};
-use hot_or_not_web_leptos_ssr::fallback::file_and_error_handler;
+use hot_or_not_web_leptos_ssr::router::app_router;
 use sentry_tower::{NewSentryLayer, SentryHttpLayer};
-use state::server::AppState;
 use tower::ServiceBuilder;
-use tracing::instrument;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use utils::host::is_host_or_origin_from_preview_domain;
 
-use hot_or_not_web_leptos_ssr::app::shell;
 use hot_or_not_web_leptos_ssr::{app::App, init::AppStateBuilder};
-use http::{header, HeaderName, Method};
-use leptos::logging::log;
 use leptos::prelude::*;
-use leptos_axum::handle_server_fns_with_context;
-use leptos_axum::{generate_route_list, LeptosRoutes};
-use tower_http::cors::{AllowOrigin, CorsLayer};
-
-#[instrument(skip(app_state))]
-pub async fn server_fn_handler(
-    State(app_state): State<AppState>,
-    path: Path<String>,
-    request: Request<AxumBody>,
-) -> impl IntoResponse {
-    log!("{:?}", path);
-
-    handle_server_fns_with_context(
-        move || {
-            provide_context(app_state.canisters.clone());
-            #[cfg(feature = "backend-admin")]
-            provide_context(app_state.admin_canisters.clone());
-            #[cfg(feature = "cloudflare")]
-            provide_context(app_state.cloudflare.clone());
-            provide_context(app_state.kv.clone());
-            provide_context(app_state.cookie_key.clone());
-            #[cfg(feature = "oauth-ssr")]
-            provide_context(app_state.google_oauth_clients.clone());
-
-            #[cfg(feature = "ga4")]
-            provide_context(app_state.grpc_offchain_channel.clone());
-
-            #[cfg(feature = "firestore")]
-            provide_context(app_state.firestore_db.clone());
-
-            #[cfg(feature = "qstash")]
-            provide_context(app_state.qstash.clone());
-
-            provide_context(app_state.grpc_icpump_search_channel.clone());
-            provide_context(app_state.grpc_nsfw_channel.clone());
-
-            #[cfg(feature = "alloydb")]
-            {
-                provide_context(app_state.alloydb.clone());
-                provide_context(app_state.hon_worker_jwt.clone());
-            }
-        },
-        request,
-    )
-    .await
-}
-
-#[instrument(skip(state))]
-pub async fn leptos_routes_handler(state: State<AppState>, req: Request<AxumBody>) -> Response {
-    let State(app_state) = state.clone();
-    let handler = leptos_axum::render_route_with_context(
-        app_state.routes.clone(),
-        move || {
-            provide_context(app_state.canisters.clone());
-            #[cfg(feature = "backend-admin")]
-            provide_context(app_state.admin_canisters.clone());
-            #[cfg(feature = "cloudflare")]
-            provide_context(app_state.cloudflare.clone());
-            provide_context(app_state.kv.clone());
-            provide_context(app_state.cookie_key.clone());
-            #[cfg(feature = "oauth-ssr")]
-            provide_context(app_state.google_oauth_clients.clone());
-
-            #[cfg(feature = "ga4")]
-            provide_context(app_state.grpc_offchain_channel.clone());
-
-            #[cfg(feature = "firestore")]
-            provide_context(app_state.firestore_db.clone());
-
-            #[cfg(feature = "qstash")]
-            provide_context(app_state.qstash.clone());
-
-            provide_context(app_state.grpc_icpump_search_channel.clone());
-            provide_context(app_state.grpc_nsfw_channel.clone());
-
-            #[cfg(feature = "alloydb")]
-            {
-                provide_context(app_state.alloydb.clone());
-                provide_context(app_state.hon_worker_jwt.clone());
-            }
-        },
-        move || shell(app_state.leptos_options.clone()),
-    );
-    handler(state, req).await.into_response()
-}
+use leptos_axum::generate_route_list;
 
 async fn main_impl() {
     dotenv::dotenv().ok();
@@ -118,9 +21,14 @@ async fn main_impl() {
     let addr = leptos_options.site_addr;
     let routes = generate_route_list(App);
 
-    let res = AppStateBuilder::new(leptos_options, routes.clone())
-        .build()
-        .await;
+    let res = match AppStateBuilder::new(leptos_options, routes).build().await {
+        Ok(res) => res,
+        Err(e) => {
+            log::error!("invalid configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+    let event_pipeline = res.event_pipeline.clone();
     let terminate = {
         use tokio::signal;
 
@@ -159,34 +67,7 @@ async fn main_impl() {
         .layer(SentryHttpLayer::with_transaction());
 
     // build our application with a route
-    let app = Router::new()
-        .route(
-            "/api/*fn_name",
-            get(server_fn_handler).post(server_fn_handler),
-        )
-        .layer(
-            CorsLayer::new()
-                .allow_credentials(true)
-                .allow_headers([
-                    header::AUTHORIZATION,
-                    header::CONTENT_TYPE,
-                    header::ACCEPT,
-                    HeaderName::from_static("sentry-trace"),
-                    HeaderName::from_static("baggage"),
-                ])
-                .allow_methods([Method::POST, Method::GET, Method::PUT, Method::OPTIONS])
-                .allow_origin(AllowOrigin::predicate(|origin, _| {
-                    if let Ok(host) = origin.to_str() {
-                        is_host_or_origin_from_preview_domain(host) || host == "yral.com"
-                    } else {
-                        false
-                    }
-                })),
-        )
-        .leptos_routes_with_handler(routes, get(leptos_routes_handler))
-        .fallback(file_and_error_handler)
-        .layer(sentry_tower_layer)
-        .with_state(res.app_state);
+    let app = app_router(res.app_state).layer(sentry_tower_layer);
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
@@ -196,6 +77,9 @@ async fn main_impl() {
         .with_graceful_shutdown(terminate)
         .await
         .unwrap();
+
+    // flush analytics events queued by the last requests
+    event_pipeline.shutdown().await;
 }
 
 fn main() {
@@ -0,0 +1,40 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+use yral_types::post::PostItem;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TokenFixture {
+    pub user_id: String,
+    pub token_name: String,
+    pub token_symbol: String,
+    pub logo: String,
+    pub description: String,
+    pub created_at: String,
+    pub link: String,
+    pub is_nsfw: bool,
+}
+
+/// Posts and tokens served by the mocks
+/// the post canisters are the users of the `search` section, the app resolves them through
+/// `FakeCanisters::from_fixtures` (see `local-mock-run.sh`), so no replica needs to host them
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Fixtures {
+    #[serde(default)]
+    pub posts: Vec<PostItem>,
+    #[serde(default)]
+    pub tokens: Vec<TokenFixture>,
+}
+
+impl Fixtures {
+    /// Loads the fixture file, no fixtures if it is missing
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).expect("invalid fixtures file"),
+            Err(e) => {
+                log::warn!("failed to read fixtures from {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+}
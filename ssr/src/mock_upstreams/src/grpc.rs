@@ -0,0 +1,126 @@
+use std::{
+    future::{ready, Ready},
+    sync::Arc,
+};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tonic::{codec::ProstCodec, server::UnaryService, Status};
+use utils::token::{icpump::icpump_search, nsfw::nsfw_detector};
+
+use crate::fixtures::Fixtures;
+
+/// Adapts a plain function into a tonic [`UnaryService`]
+#[derive(Clone)]
+struct UnaryFn<F>(F);
+
+impl<F, Req, Res> UnaryService<Req> for UnaryFn<F>
+where
+    F: FnMut(Req) -> Res,
+{
+    type Response = Res;
+    type Future = Ready<Result<tonic::Response<Res>, Status>>;
+
+    fn call(&mut self, request: tonic::Request<Req>) -> Self::Future {
+        ready(Ok(tonic::Response::new((self.0)(request.into_inner()))))
+    }
+}
+
+async fn unary<Req, Res>(req: Request, f: impl FnMut(Req) -> Res) -> Response
+where
+    Req: prost::Message + Default + Send + 'static,
+    Res: prost::Message + Send + 'static,
+{
+    let mut grpc = tonic::server::Grpc::new(ProstCodec::<Res, Req>::default());
+    grpc.unary(UnaryFn(f), req).await.map(Body::new)
+}
+
+fn search(fixtures: &Fixtures, query: &str) -> icpump_search::SearchResponseV1 {
+    let query = query.to_lowercase();
+    let items = fixtures
+        .tokens
+        .iter()
+        .filter(|t| {
+            t.token_name.to_lowercase().contains(&query)
+                || t.token_symbol.to_lowercase().contains(&query)
+        })
+        .map(|t| icpump_search::SearchItemV1 {
+            user_id: t.user_id.clone(),
+            token_name: t.token_name.clone(),
+            token_symbol: t.token_symbol.clone(),
+            logo: t.logo.clone(),
+            description: t.description.clone(),
+            created_at: t.created_at.clone(),
+            link: t.link.clone(),
+            is_nsfw: t.is_nsfw,
+            ..Default::default()
+        })
+        .collect();
+
+    icpump_search::SearchResponseV1 {
+        items,
+        answer: format!("mock results for \"{query}\""),
+        ..Default::default()
+    }
+}
+
+/// Single gRPC endpoint serving the search, nsfw detector, warehouse events and off chain agent mocks
+/// methods which only need an acknowledgement respond with an empty message
+async fn dispatch(State(fixtures): State<Arc<Fixtures>>, req: Request) -> Response {
+    let method = req.uri().path().to_lowercase();
+    match method.as_str() {
+        "/search.searchservice/searchv1" => {
+            unary(req, |r: icpump_search::SearchRequest| {
+                search(&fixtures, &r.input_query)
+            })
+            .await
+        }
+        "/search.searchservice/contextualsearch" => {
+            unary(req, |_: icpump_search::ContextualSearchRequest| {
+                icpump_search::ContextualSearchResponse {
+                    answer: "mock contextual answer".into(),
+                    ..Default::default()
+                }
+            })
+            .await
+        }
+        "/nsfw_detector.nsfwdetector/detectnsfwimg" => {
+            unary(req, |_: nsfw_detector::NsfwDetectorRequestImg| {
+                nsfw_detector::NsfwDetectorResponse::default()
+            })
+            .await
+        }
+        "/warehouse_events.warehouseevents/sendevent"
+        | "/off_chain.offchain/reportpost"
+        | "/off_chain.offchain/binddevicetoprincipal" => {
+            log::info!("grpc: {method}");
+            unary(req, |_: ()| ()).await
+        }
+        _ => {
+            log::warn!("grpc: unimplemented method {method}");
+            let mut res = StatusCode::OK.into_response();
+            let headers = res.headers_mut();
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/grpc"),
+            );
+            headers.insert(
+                "grpc-status",
+                HeaderValue::from(tonic::Code::Unimplemented as i32),
+            );
+            res
+        }
+    }
+}
+
+/// Mock of all the gRPC upstreams
+/// (`ICPUMP_SEARCH_GRPC_URL`, `NSFW_SERVER_URL` and `OFF_CHAIN_AGENT_GRPC_URL`)
+/// tonic clients connect with HTTP/2 prior knowledge, served as h2c through axum's `http2` feature
+pub fn router(fixtures: Arc<Fixtures>) -> Router {
+    Router::new().fallback(dispatch).with_state(fixtures)
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use candid::Principal;
+use hon_worker_common::{
+    GameResult, HoNGameVoteReq, HoNGameWithdrawReq, HotOrNot, SatsBalanceInfo, VoteRes,
+};
+
+/// Sats balance every new user starts with
+const INITIAL_BALANCE: u128 = 1000;
+
+#[derive(Clone, Default)]
+struct HonWorkerState {
+    balances: Arc<Mutex<HashMap<Principal, u128>>>,
+}
+
+impl HonWorkerState {
+    fn update_balance(
+        &self,
+        user: Principal,
+        f: impl FnOnce(u128) -> Option<u128>,
+    ) -> Option<u128> {
+        let mut balances = self.balances.lock().unwrap();
+        let balance = balances.entry(user).or_insert(INITIAL_BALANCE);
+        let updated = f(*balance)?;
+        *balance = updated;
+        Some(updated)
+    }
+}
+
+async fn balance(
+    State(state): State<HonWorkerState>,
+    Path(user): Path<Principal>,
+) -> Json<SatsBalanceInfo> {
+    let balance = state.update_balance(user, Some).unwrap_or_default();
+    Json(SatsBalanceInfo {
+        balance: balance.into(),
+        airdropped: 0u128.into(),
+    })
+}
+
+async fn vote(
+    State(state): State<HonWorkerState>,
+    Path(sender): Path<Principal>,
+    Json(req): Json<HoNGameVoteReq>,
+) -> Result<Json<VoteRes>, (StatusCode, String)> {
+    let amount = req.request.vote_amount;
+    // the outcome is fully determined by the sentiment sent by the SSR server
+    // so tests can control it through the post they vote on
+    let won = matches!(
+        (&req.request.direction, &req.fetched_sentiment),
+        (HotOrNot::Hot, HotOrNot::Hot) | (HotOrNot::Not, HotOrNot::Not)
+    );
+
+    state
+        .update_balance(sender, |bal| {
+            let bal = bal.checked_sub(amount)?;
+            Some(if won { bal + amount * 2 } else { bal })
+        })
+        .ok_or((StatusCode::BAD_REQUEST, "insufficient balance".into()))?;
+
+    let game_result = if won {
+        GameResult::Win {
+            win_amt: amount.into(),
+        }
+    } else {
+        GameResult::Loss {
+            lose_amt: amount.into(),
+        }
+    };
+
+    Ok(Json(VoteRes { game_result }))
+}
+
+async fn withdraw(
+    State(state): State<HonWorkerState>,
+    Json(req): Json<HoNGameWithdrawReq>,
+) -> Result<(), (StatusCode, String)> {
+    let amount = req.request.amount;
+    state
+        .update_balance(req.request.receiver, |bal| bal.checked_sub(amount))
+        .ok_or((StatusCode::BAD_REQUEST, "insufficient balance".into()))?;
+
+    Ok(())
+}
+
+/// Mock of the hot or not worker (`HON_WORKER_URL`)
+/// signatures and JWTs are not verified
+pub fn router() -> Router {
+    Router::new()
+        .route("/balance/:user", get(balance))
+        .route("/vote/:sender", post(vote))
+        .route("/withdraw", post(withdraw))
+        .with_state(HonWorkerState::default())
+}
@@ -0,0 +1,10 @@
+//! Mock implementations of the upstream services used by the SSR server
+//! for fully offline local development and the app's tests
+pub mod fixtures;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hon_worker;
+pub mod ml_feed;
+pub mod off_chain_agent;
+pub mod pump_n_dump;
+pub mod qstash;
@@ -0,0 +1,66 @@
+//! Serves all the mocks, see `local-mock-run.sh` on how to point the app at these
+use std::{env, net::SocketAddr, path::PathBuf, sync::Arc};
+
+use axum::Router;
+use mock_upstreams::{
+    fixtures::Fixtures, grpc, hon_worker, ml_feed, off_chain_agent, pump_n_dump, qstash,
+};
+
+const DEFAULT_FIXTURES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/default.json");
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+async fn serve(name: &'static str, port: u16, router: Router) -> std::io::Result<()> {
+    let host = env_or("MOCK_UPSTREAMS_HOST", [127, 0, 0, 1].into());
+    let addr = SocketAddr::new(host, port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    log::info!("{name} listening on http://{addr}");
+    axum::serve(listener, router).await
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Info)
+        .init()
+        .expect("failed to init logger");
+
+    let fixtures_path: PathBuf = env_or("MOCK_UPSTREAMS_FIXTURES", DEFAULT_FIXTURES_PATH.into());
+    let fixtures = Arc::new(Fixtures::load(&fixtures_path));
+
+    tokio::try_join!(
+        serve(
+            "ml feed",
+            env_or("MOCK_ML_FEED_PORT", 8790),
+            ml_feed::router(fixtures.clone())
+        ),
+        serve(
+            "hon worker",
+            env_or("MOCK_HON_WORKER_PORT", 8791),
+            hon_worker::router()
+        ),
+        serve(
+            "pump n dump worker",
+            env_or("MOCK_PUMP_N_DUMP_PORT", 8787),
+            pump_n_dump::router()
+        ),
+        serve(
+            "grpc",
+            env_or("MOCK_GRPC_PORT", 8792),
+            grpc::router(fixtures)
+        ),
+        serve(
+            "off chain agent",
+            env_or("MOCK_OFF_CHAIN_AGENT_PORT", 8793),
+            off_chain_agent::router()
+        ),
+        serve("qstash", env_or("MOCK_QSTASH_PORT", 8794), qstash::router()),
+    )?;
+
+    Ok(())
+}
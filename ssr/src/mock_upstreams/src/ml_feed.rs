@@ -0,0 +1,72 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    routing::post,
+    Json, Router,
+};
+use yral_types::post::{FeedRequest, FeedResponse, PostItem};
+
+use crate::fixtures::Fixtures;
+
+/// Posts with a higher probability are considered nsfw by the mock
+const NSFW_THRESHOLD: f32 = 0.4;
+
+fn matches_feed(kind: &str, post: &PostItem) -> bool {
+    match kind {
+        "clean" => post.nsfw_probability <= NSFW_THRESHOLD,
+        "nsfw" => post.nsfw_probability > NSFW_THRESHOLD,
+        _ => true,
+    }
+}
+
+async fn feed(
+    State(fixtures): State<Arc<Fixtures>>,
+    Path(kind): Path<String>,
+    Json(req): Json<FeedRequest>,
+) -> Json<FeedResponse> {
+    let seen: HashSet<_> = req
+        .filter_results
+        .iter()
+        .map(|p| (p.canister_id, p.post_id))
+        .collect();
+
+    let posts = fixtures
+        .posts
+        .iter()
+        .filter(|p| matches_feed(&kind, p))
+        .filter(|p| !seen.contains(&(p.canister_id, p.post_id)))
+        .take(req.num_results as usize)
+        .cloned()
+        .collect();
+
+    Json(FeedResponse { posts })
+}
+
+/// Engagement reports are only logged
+async fn interactions(Json(req): Json<serde_json::Value>) {
+    let count = req["interactions"].as_array().map_or(0, Vec::len);
+    log::info!(
+        "received {count} feed interactions for {}",
+        req["canister_id"]
+    );
+}
+
+/// Negative feedback is only logged
+async fn feedback(Json(req): Json<serde_json::Value>) {
+    log::info!(
+        "received {} feed feedback for {}",
+        req["feedback"]["kind"],
+        req["canister_id"]
+    );
+}
+
+/// Mock of the ML feed server (`ML_FEED_URL`)
+pub fn router(fixtures: Arc<Fixtures>) -> Router {
+    Router::new()
+        .route("/api/v1/feed/interactions", post(interactions))
+        .route("/api/v1/feed/feedback", post(feedback))
+        .route("/api/v1/feed/coldstart/:kind", post(feed))
+        .route("/api/v1/feed/:kind", post(feed))
+        .with_state(fixtures)
+}
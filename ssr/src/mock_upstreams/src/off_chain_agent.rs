@@ -0,0 +1,19 @@
+use axum::{routing::post, Json, Router};
+
+/// Token claims are only logged
+async fn claim_tokens(Json(req): Json<serde_json::Value>) {
+    log::info!("off chain agent: claim tokens {req}");
+}
+
+/// Swap participations are only logged
+async fn participate_in_swap(Json(req): Json<serde_json::Value>) {
+    log::info!("off chain agent: participate in swap {req}");
+}
+
+/// Mock of the off chain agent's HTTP endpoints (`OFF_CHAIN_AGENT_URL`)
+/// called by the QStash mock, the gRPC endpoints are served by the `grpc` mock
+pub fn router() -> Router {
+    Router::new()
+        .route("/qstash/claim_tokens", post(claim_tokens))
+        .route("/qstash/participate_in_swap", post(participate_in_swap))
+}
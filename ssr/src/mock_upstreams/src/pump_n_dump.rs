@@ -0,0 +1,94 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Path, WebSocketUpgrade,
+    },
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use candid::Principal;
+use serde_json::json;
+use yral_pump_n_dump_common::rest::{BalanceInfoResponse, UncommittedGamesRes, UserBetsResponse};
+
+/// Balance (in e8s) every user canister has in the mock
+const MOCK_BALANCE_E8S: u128 = 100 * 100_000_000;
+const MOCK_PLAYER_COUNT: u64 = 7;
+
+async fn balance(Path(_user_canister): Path<Principal>) -> Json<BalanceInfoResponse> {
+    Json(BalanceInfoResponse {
+        net_airdrop_reward: 0u128.into(),
+        balance: MOCK_BALANCE_E8S.into(),
+        withdrawable: MOCK_BALANCE_E8S.into(),
+    })
+}
+
+async fn earnings(Path(_user_canister): Path<Principal>) -> String {
+    "0".into()
+}
+
+async fn game_count(Path(_user_canister): Path<Principal>) -> String {
+    "0".into()
+}
+
+async fn player_count(Path((_owner, _root)): Path<(Principal, Principal)>) -> String {
+    MOCK_PLAYER_COUNT.to_string()
+}
+
+async fn bets(
+    Path((_owner, _root, _user_canister)): Path<(Principal, Principal, Principal)>,
+) -> Json<UserBetsResponse> {
+    Json(UserBetsResponse { pumps: 0, dumps: 0 })
+}
+
+async fn uncommitted_games(Path(_user_canister): Path<Principal>) -> Json<UncommittedGamesRes> {
+    Json(UncommittedGamesRes::default())
+}
+
+async fn claim_gdollr() {}
+
+async fn game_ws(ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(handle_game_ws)
+}
+
+/// Sends the welcome event for a fresh round and then only logs incoming bets
+/// no game results are ever declared by the mock
+async fn handle_game_ws(mut socket: WebSocket) {
+    let welcome = json!({
+        "request_id": uuid::Uuid::new_v4(),
+        "response": {
+            "WelcomeEvent": {
+                "round": 0,
+                "pool": 0,
+                "player_count": MOCK_PLAYER_COUNT,
+                "user_bets": { "pumps": 0, "dumps": 0 },
+            }
+        }
+    });
+    if socket
+        .send(Message::Text(welcome.to_string()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    while let Some(Ok(msg)) = socket.recv().await {
+        if let Message::Text(msg) = msg {
+            log::info!("pump n dump ws: received {msg}");
+        }
+    }
+}
+
+/// Mock of the pump and dump worker (`PUMP_AND_DUMP_WORKER_URL`)
+pub fn router() -> Router {
+    Router::new()
+        .route("/balance/:user_canister", get(balance))
+        .route("/earnings/:user_canister", get(earnings))
+        .route("/game_count/:user_canister", get(game_count))
+        .route("/player_count/:owner/:root", get(player_count))
+        .route("/bets/:owner/:root/:user_canister", get(bets))
+        .route("/uncommitted_games/:user_canister", get(uncommitted_games))
+        .route("/claim_gdollr", post(claim_gdollr))
+        .route("/ws/*rest", get(game_ws))
+}
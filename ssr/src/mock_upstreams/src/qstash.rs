@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::{header, HeaderMap},
+    routing::post,
+    Json, Router,
+};
+use serde_json::json;
+
+/// Delay of a message, e.g. `150s`
+fn delay(headers: &HeaderMap) -> Duration {
+    headers
+        .get("upstash-delay")
+        .and_then(|delay| delay.to_str().ok())
+        .and_then(|delay| delay.strip_suffix('s'))
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default()
+}
+
+/// Accepts the message and delivers it to `destination` once its delay has passed
+async fn publish(
+    Path(destination): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Json<serde_json::Value> {
+    let message_id = format!("msg_{}", uuid::Uuid::new_v4());
+    let delay = delay(&headers);
+    let content_type = headers.get(header::CONTENT_TYPE).cloned();
+    log::info!("qstash: {message_id} to {destination} in {delay:?}");
+
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let mut req = reqwest::Client::new()
+            .post(&destination)
+            .header("upstash-message-id", &message_id)
+            .body(body);
+        if let Some(content_type) = content_type {
+            req = req.header(header::CONTENT_TYPE, content_type);
+        }
+        match req.send().await.and_then(|res| res.error_for_status()) {
+            Ok(_) => log::info!("qstash: delivered {message_id}"),
+            Err(e) => log::warn!("qstash: failed to deliver {message_id}: {e}"),
+        }
+    });
+
+    Json(json!({ "messageId": message_id }))
+}
+
+/// Mock of QStash (`QSTASH_URL`)
+/// messages are delivered once without retries, the auth token is not verified
+pub fn router() -> Router {
+    Router::new().route("/v2/publish/*destination", post(publish))
+}
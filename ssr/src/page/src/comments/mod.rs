@@ -0,0 +1,312 @@
+//! Threaded comments on posts
+//! top level comments can be pinned by the creator, replies are kept one level deep
+mod provider;
+#[cfg(feature = "ssr")]
+pub mod store;
+mod view;
+
+pub use provider::CommentsProvider;
+pub use view::CommentsButton;
+
+use candid::Principal;
+use leptos::{prelude::*, server_fn::codec::Json};
+use serde::{Deserialize, Serialize};
+
+/// Longest comment accepted, in characters
+pub const MAX_COMMENT_LEN: usize = 500;
+/// Comments returned per page at most
+pub const MAX_COMMENTS_PAGE: u64 = 50;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: u64,
+    /// the top level comment this replies to
+    pub parent_id: Option<u64>,
+    pub author: Principal,
+    pub author_name: String,
+    pub author_pic: String,
+    pub text: String,
+    pub created_at_ms: u64,
+    /// replies to a top level comment
+    pub reply_count: u32,
+    pub pinned: bool,
+}
+
+/// Top level comments of a post (pinned first, then newest first) or the replies to
+/// `parent_id` (oldest first)
+#[server(endpoint = "post_comments", input = Json, output = Json)]
+pub async fn post_comments(
+    post_canister: Principal,
+    post_id: u64,
+    parent_id: Option<u64>,
+    start: u64,
+    limit: u64,
+) -> Result<Vec<Comment>, ServerFnError> {
+    server_impl::post_comments(post_canister, post_id, parent_id, start, limit).await
+}
+
+/// Number of comments on a post, including replies
+#[server(endpoint = "comment_count", input = Json, output = Json)]
+pub async fn comment_count(post_canister: Principal, post_id: u64) -> Result<u64, ServerFnError> {
+    server_impl::comment_count(post_canister, post_id).await
+}
+
+/// Comment on a post or reply to a comment, rate limited per user
+#[server(endpoint = "add_comment", input = Json, output = Json)]
+pub async fn add_comment(
+    post_canister: Principal,
+    post_id: u64,
+    parent_id: Option<u64>,
+    text: String,
+) -> Result<Comment, ServerFnError> {
+    server_impl::add_comment(post_canister, post_id, parent_id, text).await
+}
+
+/// Delete a comment and its replies, allowed for its author and the post's creator
+#[server(endpoint = "delete_comment", input = Json, output = Json)]
+pub async fn delete_comment(
+    post_canister: Principal,
+    post_id: u64,
+    comment_id: u64,
+) -> Result<(), ServerFnError> {
+    server_impl::delete_comment(post_canister, post_id, comment_id).await
+}
+
+/// Pin a top level comment or unpin with `None`, only allowed for the post's creator
+#[server(endpoint = "pin_comment", input = Json, output = Json)]
+pub async fn pin_comment(
+    post_canister: Principal,
+    post_id: u64,
+    comment_id: Option<u64>,
+) -> Result<(), ServerFnError> {
+    server_impl::pin_comment(post_canister, post_id, comment_id).await
+}
+
+/// Report a comment with one of the [`utils::report::ReportOption`] reasons
+/// comments reported by enough users are removed
+#[server(endpoint = "report_comment", input = Json, output = Json)]
+pub async fn report_comment(
+    post_canister: Principal,
+    post_id: u64,
+    comment_id: u64,
+    reason: String,
+) -> Result<(), ServerFnError> {
+    server_impl::report_comment(post_canister, post_id, comment_id, reason).await
+}
+
+#[cfg(feature = "ssr")]
+mod server_impl {
+    use std::time::Duration;
+
+    use auth::server_impl::{
+        expire_shared_data, extract_principal_from_cookie, incr_shared_counter, store::KVStoreImpl,
+    };
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use candid::Principal;
+    use leptos::prelude::*;
+    use leptos_axum::extract_with_state;
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+    use utils::report::ReportOption;
+    use yral_canisters_common::utils::{profile::propic_from_principal, time::current_epoch};
+
+    use super::{
+        store::{comment_store, CommentStore},
+        Comment, MAX_COMMENTS_PAGE, MAX_COMMENT_LEN,
+    };
+
+    /// user principal, window and window number -> comments of the user within that window
+    const RATE_LIMIT_NAMESPACE: &str = "comment-rate";
+    /// (window, comments allowed within the window)
+    const RATE_LIMITS: [(Duration, i64); 2] = [
+        (Duration::from_secs(60), 5),
+        (Duration::from_secs(60 * 60), 60),
+    ];
+    /// Distinct reports after which a comment is removed
+    const REPORTS_TO_REMOVE: u64 = 3;
+
+    async fn request_principal() -> Result<Principal, ServerFnError> {
+        let key: Key = expect_context();
+        let jar: SignedCookieJar = extract_with_state(&key).await?;
+        extract_principal_from_cookie(&jar)?.ok_or_else(|| ServerFnError::new("User not logged in"))
+    }
+
+    /// Whether `principal` owns the post's canister
+    async fn is_creator(
+        principal: Principal,
+        post_canister: Principal,
+    ) -> Result<bool, ServerFnError> {
+        let user_canister = unauth_canister_backend()
+            .get_individual_canister_by_user_principal(principal)
+            .await?;
+        Ok(user_canister == Some(post_canister))
+    }
+
+    /// Count a comment by `principal` at `now_ms` in fixed windows, failing if a limit is
+    /// exceeded
+    async fn check_rate_limit(principal: Principal, now_ms: u64) -> Result<(), ServerFnError> {
+        let kv: KVStoreImpl = expect_context();
+        for (window, limit) in RATE_LIMITS {
+            let window_number = now_ms / window.as_millis() as u64;
+            let key = format!("{principal}-{}-{window_number}", window.as_secs());
+            let count = incr_shared_counter(&kv, RATE_LIMIT_NAMESPACE, &key, 1).await?;
+            if count == 1 {
+                expire_shared_data(&kv, RATE_LIMIT_NAMESPACE, &key, window).await?;
+            }
+            if count > limit {
+                return Err(ServerFnError::new("Too many comments, try again later"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Name and picture shown next to the user's comments
+    async fn author_details(principal: Principal) -> (String, String) {
+        let canisters = unauth_canister_backend();
+        let profile = match canisters
+            .get_individual_canister_by_user_principal(principal)
+            .await
+        {
+            Ok(Some(user_canister)) => canisters.get_profile_details(user_canister).await.ok(),
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("failed to fetch canister of {principal}: {e}");
+                None
+            }
+        };
+        match profile {
+            Some(profile) => (
+                profile.display_name_or_fallback(),
+                profile.profile_pic_or_random(),
+            ),
+            None => (principal.to_text(), propic_from_principal(principal)),
+        }
+    }
+
+    pub async fn post_comments(
+        post_canister: Principal,
+        post_id: u64,
+        parent_id: Option<u64>,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Comment>, ServerFnError> {
+        comment_store()
+            .page(
+                post_canister,
+                post_id,
+                parent_id,
+                start,
+                limit.min(MAX_COMMENTS_PAGE),
+            )
+            .await
+    }
+
+    pub async fn comment_count(
+        post_canister: Principal,
+        post_id: u64,
+    ) -> Result<u64, ServerFnError> {
+        comment_store().count(post_canister, post_id).await
+    }
+
+    pub async fn add_comment(
+        post_canister: Principal,
+        post_id: u64,
+        parent_id: Option<u64>,
+        text: String,
+    ) -> Result<Comment, ServerFnError> {
+        let principal = request_principal().await?;
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            return Err(ServerFnError::new("Comment is empty"));
+        }
+        if text.chars().count() > MAX_COMMENT_LEN {
+            return Err(ServerFnError::new(format!(
+                "Comments are limited to {MAX_COMMENT_LEN} characters"
+            )));
+        }
+
+        let now_ms = current_epoch().as_millis() as u64;
+        check_rate_limit(principal, now_ms).await?;
+        let (author_name, author_pic) = author_details(principal).await;
+
+        let comment = Comment {
+            id: 0,
+            parent_id,
+            author: principal,
+            author_name,
+            author_pic,
+            text,
+            created_at_ms: now_ms,
+            reply_count: 0,
+            pinned: false,
+        };
+        comment_store()
+            .insert(post_canister, post_id, comment)
+            .await?
+            .ok_or_else(|| ServerFnError::new("Comment not found"))
+    }
+
+    pub async fn delete_comment(
+        post_canister: Principal,
+        post_id: u64,
+        comment_id: u64,
+    ) -> Result<(), ServerFnError> {
+        let principal = request_principal().await?;
+        let store = comment_store();
+        let Some(comment) = store.get(post_canister, post_id, comment_id).await? else {
+            return Err(ServerFnError::new("Comment not found"));
+        };
+        if comment.author != principal && !is_creator(principal, post_canister).await? {
+            return Err(ServerFnError::new("Not allowed to delete this comment"));
+        }
+
+        store.remove(post_canister, post_id, comment_id).await?;
+        Ok(())
+    }
+
+    pub async fn pin_comment(
+        post_canister: Principal,
+        post_id: u64,
+        comment_id: Option<u64>,
+    ) -> Result<(), ServerFnError> {
+        let principal = request_principal().await?;
+        if !is_creator(principal, post_canister).await? {
+            return Err(ServerFnError::new("Only the creator can pin comments"));
+        }
+
+        let pinned = comment_store()
+            .pin(post_canister, post_id, comment_id)
+            .await?;
+        if !pinned {
+            return Err(ServerFnError::new("Only top level comments can be pinned"));
+        }
+        Ok(())
+    }
+
+    pub async fn report_comment(
+        post_canister: Principal,
+        post_id: u64,
+        comment_id: u64,
+        reason: String,
+    ) -> Result<(), ServerFnError> {
+        let principal = request_principal().await?;
+        if !ReportOption::ALL
+            .iter()
+            .any(|option| option.as_str().to_string() == reason)
+        {
+            return Err(ServerFnError::new("Invalid report reason"));
+        }
+
+        let store = comment_store();
+        let reports = store
+            .report(post_canister, post_id, comment_id, principal)
+            .await?;
+        if reports == 0 {
+            return Err(ServerFnError::new("Comment not found"));
+        }
+        if reports >= REPORTS_TO_REMOVE {
+            store.remove(post_canister, post_id, comment_id).await?;
+        }
+        log::info!("comment {comment_id} on {post_canister}/{post_id} reported for {reason}");
+        Ok(())
+    }
+}
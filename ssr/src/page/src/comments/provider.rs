@@ -0,0 +1,47 @@
+use candid::Principal;
+use leptos::prelude::*;
+use yral_canisters_common::cursored_data::{CursoredDataProvider, KeyedData, PageEntry};
+
+use super::{post_comments, Comment};
+
+impl KeyedData for Comment {
+    type Key = u64;
+
+    fn key(&self) -> Self::Key {
+        self.id
+    }
+}
+
+/// Top level comments of a post, or the replies to `parent_id`
+#[derive(Clone, Copy)]
+pub struct CommentsProvider {
+    pub post_canister: Principal,
+    pub post_id: u64,
+    pub parent_id: Option<u64>,
+}
+
+impl CursoredDataProvider for CommentsProvider {
+    type Data = Comment;
+    type Error = ServerFnError;
+
+    async fn get_by_cursor_inner(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<PageEntry<Comment>, ServerFnError> {
+        let limit = (end - start) as u64;
+        let comments = post_comments(
+            self.post_canister,
+            self.post_id,
+            self.parent_id,
+            start as u64,
+            limit,
+        )
+        .await?;
+        let list_end = (comments.len() as u64) < limit;
+        Ok(PageEntry {
+            data: comments,
+            end: list_end,
+        })
+    }
+}
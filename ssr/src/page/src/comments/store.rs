@@ -0,0 +1,755 @@
+//! Storage of post comments
+//! the KV store keeps each comment under its own key and the threads in sorted sets, so
+//! concurrent writers never overwrite each other
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use auth::server_impl::{
+    add_to_shared_set, incr_shared_counter, read_shared_data, read_shared_data_many,
+    remove_from_shared_set, shared_set_len, shared_set_range, shared_set_score, store::KVStoreImpl,
+    write_shared_data,
+};
+use candid::Principal;
+use futures::future::try_join_all;
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use yral_canisters_common::utils::time::current_epoch;
+
+use super::Comment;
+
+/// Comments of a single post, as kept by [`LocalCommentStore`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PostComments {
+    next_id: u64,
+    comments: BTreeMap<u64, Comment>,
+    pinned: Option<u64>,
+    /// comment id -> users who reported it
+    reports: BTreeMap<u64, BTreeSet<Principal>>,
+}
+
+impl PostComments {
+    pub fn get(&self, id: u64) -> Option<&Comment> {
+        self.comments.get(&id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.comments.len()
+    }
+
+    /// Add a comment, replies to a reply are added to the top level comment's thread
+    /// `None` if `parent_id` does not exist
+    pub fn insert(&mut self, mut comment: Comment) -> Option<Comment> {
+        if let Some(parent_id) = comment.parent_id {
+            let parent = self.comments.get(&parent_id)?;
+            let root_id = parent.parent_id.unwrap_or(parent_id);
+            self.comments.get_mut(&root_id)?.reply_count += 1;
+            comment.parent_id = Some(root_id);
+        }
+
+        comment.id = self.next_id;
+        comment.reply_count = 0;
+        comment.pinned = false;
+        self.next_id += 1;
+        self.comments.insert(comment.id, comment.clone());
+        Some(comment)
+    }
+
+    /// Remove a comment along with its replies, returns the number of comments removed
+    pub fn remove(&mut self, id: u64) -> usize {
+        let Some(comment) = self.comments.remove(&id) else {
+            return 0;
+        };
+        self.reports.remove(&id);
+        if self.pinned == Some(id) {
+            self.pinned = None;
+        }
+
+        if let Some(parent_id) = comment.parent_id {
+            if let Some(parent) = self.comments.get_mut(&parent_id) {
+                parent.reply_count = parent.reply_count.saturating_sub(1);
+            }
+            return 1;
+        }
+
+        let replies: Vec<_> = self
+            .comments
+            .values()
+            .filter(|c| c.parent_id == Some(id))
+            .map(|c| c.id)
+            .collect();
+        for reply in &replies {
+            self.comments.remove(reply);
+            self.reports.remove(reply);
+        }
+        1 + replies.len()
+    }
+
+    /// Pin a top level comment, replacing the pinned one, or unpin with `None`
+    /// returns false if the comment can't be pinned
+    pub fn pin(&mut self, id: Option<u64>) -> bool {
+        if let Some(id) = id {
+            if !self
+                .comments
+                .get(&id)
+                .is_some_and(|c| c.parent_id.is_none())
+            {
+                return false;
+            }
+        }
+        self.pinned = id;
+        true
+    }
+
+    /// Record a report, returns the number of distinct users who reported the comment
+    pub fn report(&mut self, id: u64, reporter: Principal) -> usize {
+        if !self.comments.contains_key(&id) {
+            return 0;
+        }
+        let reporters = self.reports.entry(id).or_default();
+        reporters.insert(reporter);
+        reporters.len()
+    }
+
+    /// Top level comments, the pinned one first and then newest first
+    /// replies to `parent_id`, oldest first
+    pub fn page(&self, parent_id: Option<u64>, start: usize, limit: usize) -> Vec<Comment> {
+        let pinned = self
+            .pinned
+            .filter(|_| parent_id.is_none())
+            .and_then(|id| self.comments.get(&id));
+        let rest = self
+            .comments
+            .values()
+            .filter(|c| c.parent_id == parent_id && Some(c.id) != self.pinned);
+
+        let ordered: Box<dyn Iterator<Item = &Comment>> = if parent_id.is_none() {
+            Box::new(pinned.into_iter().chain(rest.rev()))
+        } else {
+            Box::new(rest)
+        };
+        ordered
+            .skip(start)
+            .take(limit)
+            .map(|c| Comment {
+                pinned: Some(c.id) == self.pinned,
+                ..c.clone()
+            })
+            .collect()
+    }
+}
+
+/// Reads and updates the comments of posts, every update is atomic
+#[allow(async_fn_in_trait)]
+pub trait CommentStore {
+    /// The comment without its reply count and pin state
+    async fn get(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<Option<Comment>, ServerFnError>;
+
+    /// See [`PostComments::page`]
+    async fn page(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        parent_id: Option<u64>,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Comment>, ServerFnError>;
+
+    async fn count(&self, post_canister: Principal, post_id: u64) -> Result<u64, ServerFnError>;
+
+    /// See [`PostComments::insert`]
+    async fn insert(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        comment: Comment,
+    ) -> Result<Option<Comment>, ServerFnError>;
+
+    /// See [`PostComments::remove`]
+    async fn remove(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<u64, ServerFnError>;
+
+    /// See [`PostComments::pin`]
+    async fn pin(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: Option<u64>,
+    ) -> Result<bool, ServerFnError>;
+
+    /// See [`PostComments::report`]
+    async fn report(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+        reporter: Principal,
+    ) -> Result<u64, ServerFnError>;
+}
+
+/// Comments stored in the KV store
+#[derive(Clone)]
+pub struct KVCommentStore(pub KVStoreImpl);
+
+impl KVCommentStore {
+    /// post -> id of the latest comment
+    const IDS_NAMESPACE: &str = "comment-ids";
+    /// post and comment id -> the comment, `null` once removed
+    const COMMENTS_NAMESPACE: &str = "comment";
+    /// post -> ids of all its comments
+    const ALL_NAMESPACE: &str = "comment-all";
+    /// post -> top level comment ids scored by id
+    /// post and comment id -> reply ids scored by negated id, so the oldest reply comes first
+    const THREADS_NAMESPACE: &str = "comment-thread";
+    /// post -> id of the pinned comment, which is kept out of the top level thread
+    const PINNED_NAMESPACE: &str = "comment-pinned";
+    /// post and comment id -> principals who reported the comment
+    const REPORTS_NAMESPACE: &str = "comment-reports";
+    /// Replies removed per read when removing a thread
+    const REMOVE_BATCH: u64 = 100;
+
+    fn post_key(post_canister: Principal, post_id: u64) -> String {
+        format!("{post_canister}-{post_id}")
+    }
+
+    fn comment_key(post_canister: Principal, post_id: u64, id: u64) -> String {
+        format!("{post_canister}-{post_id}-{id}")
+    }
+
+    /// Thread of the top level comments, or of the replies to `parent_id`
+    fn thread_key(post_canister: Principal, post_id: u64, parent_id: Option<u64>) -> String {
+        match parent_id {
+            Some(parent_id) => Self::comment_key(post_canister, post_id, parent_id),
+            None => Self::post_key(post_canister, post_id),
+        }
+    }
+
+    async fn pinned(&self, post: &str) -> Result<Option<u64>, ServerFnError> {
+        let Some(raw) = read_shared_data(&self.0, Self::PINNED_NAMESPACE, post).await? else {
+            return Ok(None);
+        };
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    async fn thread_ids(
+        &self,
+        thread: &str,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<u64>, ServerFnError> {
+        let members =
+            shared_set_range(&self.0, Self::THREADS_NAMESPACE, thread, start, limit).await?;
+        Ok(members
+            .into_iter()
+            .filter_map(|(member, _)| member.parse().ok())
+            .collect())
+    }
+
+    /// Comments with the given ids in order, skipping removed ones
+    async fn read_comments(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        ids: &[u64],
+    ) -> Result<Vec<Comment>, ServerFnError> {
+        let keys: Vec<_> = ids
+            .iter()
+            .map(|id| Self::comment_key(post_canister, post_id, *id))
+            .collect();
+        let values = read_shared_data_many(&self.0, Self::COMMENTS_NAMESPACE, &keys).await?;
+        let mut comments = vec![];
+        for raw in values.into_iter().flatten() {
+            if let Some(comment) = serde_json::from_str::<Option<Comment>>(&raw)? {
+                comments.push(comment);
+            }
+        }
+        Ok(comments)
+    }
+
+    /// Take a comment out of the post and its thread and drop its text
+    /// returns false if it was already removed
+    async fn forget(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+        thread: &str,
+    ) -> Result<bool, ServerFnError> {
+        let post = Self::post_key(post_canister, post_id);
+        let removed =
+            remove_from_shared_set(&self.0, Self::ALL_NAMESPACE, &post, id.to_string()).await?;
+        remove_from_shared_set(&self.0, Self::THREADS_NAMESPACE, thread, id.to_string()).await?;
+        let key = Self::comment_key(post_canister, post_id, id);
+        write_shared_data(&self.0, Self::COMMENTS_NAMESPACE, &key, "null".into()).await?;
+        Ok(removed)
+    }
+}
+
+impl CommentStore for KVCommentStore {
+    async fn get(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<Option<Comment>, ServerFnError> {
+        let key = Self::comment_key(post_canister, post_id, id);
+        let Some(raw) = read_shared_data(&self.0, Self::COMMENTS_NAMESPACE, &key).await? else {
+            return Ok(None);
+        };
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    async fn page(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        parent_id: Option<u64>,
+        mut start: u64,
+        mut limit: u64,
+    ) -> Result<Vec<Comment>, ServerFnError> {
+        let pinned = match parent_id {
+            Some(_) => None,
+            None => self.pinned(&Self::post_key(post_canister, post_id)).await?,
+        };
+        let mut ids = vec![];
+        if let Some(pinned) = pinned.filter(|_| limit > 0) {
+            if start == 0 {
+                ids.push(pinned);
+                limit -= 1;
+            } else {
+                start -= 1;
+            }
+        }
+        let thread = Self::thread_key(post_canister, post_id, parent_id);
+        ids.extend(self.thread_ids(&thread, start, limit).await?);
+
+        let mut comments = self.read_comments(post_canister, post_id, &ids).await?;
+        let reply_counts = try_join_all(comments.iter().map(|comment| async move {
+            if comment.parent_id.is_some() {
+                return Ok(0);
+            }
+            let thread = Self::comment_key(post_canister, post_id, comment.id);
+            shared_set_len(&self.0, Self::THREADS_NAMESPACE, &thread).await
+        }))
+        .await?;
+        for (comment, replies) in comments.iter_mut().zip(reply_counts) {
+            comment.reply_count = replies as u32;
+            comment.pinned = Some(comment.id) == pinned;
+        }
+        Ok(comments)
+    }
+
+    async fn count(&self, post_canister: Principal, post_id: u64) -> Result<u64, ServerFnError> {
+        let post = Self::post_key(post_canister, post_id);
+        shared_set_len(&self.0, Self::ALL_NAMESPACE, &post).await
+    }
+
+    async fn insert(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        mut comment: Comment,
+    ) -> Result<Option<Comment>, ServerFnError> {
+        if let Some(parent_id) = comment.parent_id {
+            let Some(parent) = self.get(post_canister, post_id, parent_id).await? else {
+                return Ok(None);
+            };
+            comment.parent_id = Some(parent.parent_id.unwrap_or(parent_id));
+        }
+
+        let post = Self::post_key(post_canister, post_id);
+        comment.id = incr_shared_counter(&self.0, Self::IDS_NAMESPACE, &post, 1).await? as u64;
+        comment.reply_count = 0;
+        comment.pinned = false;
+        let key = Self::comment_key(post_canister, post_id, comment.id);
+        write_shared_data(
+            &self.0,
+            Self::COMMENTS_NAMESPACE,
+            &key,
+            serde_json::to_string(&comment)?,
+        )
+        .await?;
+
+        let thread = Self::thread_key(post_canister, post_id, comment.parent_id);
+        let score = match comment.parent_id {
+            Some(_) => -(comment.id as f64),
+            None => comment.id as f64,
+        };
+        add_to_shared_set(
+            &self.0,
+            Self::THREADS_NAMESPACE,
+            &thread,
+            comment.id.to_string(),
+            score,
+        )
+        .await?;
+        add_to_shared_set(
+            &self.0,
+            Self::ALL_NAMESPACE,
+            &post,
+            comment.id.to_string(),
+            comment.id as f64,
+        )
+        .await?;
+
+        // the thread is removed before its replies are, so a reply racing with the removal
+        // either sees it here or is seen by `remove`
+        if let Some(root_id) = comment.parent_id {
+            let root =
+                shared_set_score(&self.0, Self::ALL_NAMESPACE, &post, root_id.to_string()).await?;
+            if root.is_none() {
+                self.forget(post_canister, post_id, comment.id, &thread)
+                    .await?;
+                return Ok(None);
+            }
+        }
+        Ok(Some(comment))
+    }
+
+    async fn remove(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<u64, ServerFnError> {
+        let Some(comment) = self.get(post_canister, post_id, id).await? else {
+            return Ok(0);
+        };
+        let thread = Self::thread_key(post_canister, post_id, comment.parent_id);
+        if !self.forget(post_canister, post_id, id, &thread).await? {
+            return Ok(0);
+        }
+        let post = Self::post_key(post_canister, post_id);
+        if self.pinned(&post).await? == Some(id) {
+            write_shared_data(&self.0, Self::PINNED_NAMESPACE, &post, "null".into()).await?;
+        }
+        if comment.parent_id.is_some() {
+            return Ok(1);
+        }
+
+        let replies_thread = Self::comment_key(post_canister, post_id, id);
+        let mut removed = 1;
+        loop {
+            let replies = self
+                .thread_ids(&replies_thread, 0, Self::REMOVE_BATCH)
+                .await?;
+            if replies.is_empty() {
+                return Ok(removed);
+            }
+            for reply in replies {
+                if self
+                    .forget(post_canister, post_id, reply, &replies_thread)
+                    .await?
+                {
+                    removed += 1;
+                }
+            }
+        }
+    }
+
+    async fn pin(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: Option<u64>,
+    ) -> Result<bool, ServerFnError> {
+        if let Some(id) = id {
+            let comment = self.get(post_canister, post_id, id).await?;
+            if !comment.is_some_and(|c| c.parent_id.is_none()) {
+                return Ok(false);
+            }
+        }
+        let post = Self::post_key(post_canister, post_id);
+        let previous = self.pinned(&post).await?;
+        if previous == id {
+            return Ok(true);
+        }
+
+        write_shared_data(
+            &self.0,
+            Self::PINNED_NAMESPACE,
+            &post,
+            serde_json::to_string(&id)?,
+        )
+        .await?;
+        if let Some(id) = id {
+            remove_from_shared_set(&self.0, Self::THREADS_NAMESPACE, &post, id.to_string()).await?;
+        }
+        // the previously pinned comment goes back to the thread, unless it was removed
+        if let Some(previous) = previous {
+            let member = previous.to_string();
+            if shared_set_score(&self.0, Self::ALL_NAMESPACE, &post, member.clone())
+                .await?
+                .is_some()
+            {
+                add_to_shared_set(
+                    &self.0,
+                    Self::THREADS_NAMESPACE,
+                    &post,
+                    member,
+                    previous as f64,
+                )
+                .await?;
+            }
+        }
+        Ok(true)
+    }
+
+    async fn report(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+        reporter: Principal,
+    ) -> Result<u64, ServerFnError> {
+        if self.get(post_canister, post_id, id).await?.is_none() {
+            return Ok(0);
+        }
+        let key = Self::comment_key(post_canister, post_id, id);
+        add_to_shared_set(
+            &self.0,
+            Self::REPORTS_NAMESPACE,
+            &key,
+            reporter.to_text(),
+            current_epoch().as_millis() as f64,
+        )
+        .await?;
+        shared_set_len(&self.0, Self::REPORTS_NAMESPACE, &key).await
+    }
+}
+
+/// In-memory comments for local development, lost on restart
+///
+/// Provide it as a context to use it in place of the KV store
+#[derive(Clone, Default)]
+pub struct LocalCommentStore(Arc<Mutex<HashMap<(Principal, u64), PostComments>>>);
+
+impl LocalCommentStore {
+    fn with_post<R>(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        f: impl FnOnce(&mut PostComments) -> R,
+    ) -> R {
+        let mut posts = self.0.lock().unwrap();
+        f(posts.entry((post_canister, post_id)).or_default())
+    }
+}
+
+impl CommentStore for LocalCommentStore {
+    async fn get(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<Option<Comment>, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| comments.get(id).cloned()))
+    }
+
+    async fn page(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        parent_id: Option<u64>,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Comment>, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| {
+            comments.page(parent_id, start as usize, limit as usize)
+        }))
+    }
+
+    async fn count(&self, post_canister: Principal, post_id: u64) -> Result<u64, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| comments.count() as u64))
+    }
+
+    async fn insert(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        comment: Comment,
+    ) -> Result<Option<Comment>, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| comments.insert(comment)))
+    }
+
+    async fn remove(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<u64, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| {
+            comments.remove(id) as u64
+        }))
+    }
+
+    async fn pin(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: Option<u64>,
+    ) -> Result<bool, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| comments.pin(id)))
+    }
+
+    async fn report(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+        reporter: Principal,
+    ) -> Result<u64, ServerFnError> {
+        Ok(self.with_post(post_canister, post_id, |comments| {
+            comments.report(id, reporter) as u64
+        }))
+    }
+}
+
+/// Either the KV store or the in-memory store
+#[derive(Clone)]
+pub enum CommentStoreImpl {
+    KV(KVCommentStore),
+    Local(LocalCommentStore),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $method:ident($($arg:expr),*)) => {
+        match $self {
+            CommentStoreImpl::KV(store) => store.$method($($arg),*).await,
+            CommentStoreImpl::Local(store) => store.$method($($arg),*).await,
+        }
+    };
+}
+
+impl CommentStore for CommentStoreImpl {
+    async fn get(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<Option<Comment>, ServerFnError> {
+        dispatch!(self, get(post_canister, post_id, id))
+    }
+
+    async fn page(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        parent_id: Option<u64>,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Comment>, ServerFnError> {
+        dispatch!(self, page(post_canister, post_id, parent_id, start, limit))
+    }
+
+    async fn count(&self, post_canister: Principal, post_id: u64) -> Result<u64, ServerFnError> {
+        dispatch!(self, count(post_canister, post_id))
+    }
+
+    async fn insert(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        comment: Comment,
+    ) -> Result<Option<Comment>, ServerFnError> {
+        dispatch!(self, insert(post_canister, post_id, comment))
+    }
+
+    async fn remove(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+    ) -> Result<u64, ServerFnError> {
+        dispatch!(self, remove(post_canister, post_id, id))
+    }
+
+    async fn pin(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: Option<u64>,
+    ) -> Result<bool, ServerFnError> {
+        dispatch!(self, pin(post_canister, post_id, id))
+    }
+
+    async fn report(
+        &self,
+        post_canister: Principal,
+        post_id: u64,
+        id: u64,
+        reporter: Principal,
+    ) -> Result<u64, ServerFnError> {
+        dispatch!(self, report(post_canister, post_id, id, reporter))
+    }
+}
+
+/// Comment store of the server
+/// uses [`LocalCommentStore`] if provided as a context, the KV store otherwise
+pub fn comment_store() -> CommentStoreImpl {
+    if let Some(local) = use_context::<LocalCommentStore>() {
+        return CommentStoreImpl::Local(local);
+    }
+    CommentStoreImpl::KV(KVCommentStore(expect_context()))
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+
+    use super::{Comment, PostComments};
+
+    fn comment(parent_id: Option<u64>) -> Comment {
+        Comment {
+            id: 0,
+            parent_id,
+            author: Principal::anonymous(),
+            author_name: "author".into(),
+            author_pic: String::new(),
+            text: "text".into(),
+            created_at_ms: 0,
+            reply_count: 0,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn threads_and_pins_comments() {
+        let mut comments = PostComments::default();
+        let first = comments.insert(comment(None)).unwrap();
+        let second = comments.insert(comment(None)).unwrap();
+        let reply = comments.insert(comment(Some(first.id))).unwrap();
+        // replies to replies join the top level thread
+        let nested = comments.insert(comment(Some(reply.id))).unwrap();
+        assert_eq!(nested.parent_id, Some(first.id));
+        assert!(comments.insert(comment(Some(42))).is_none());
+
+        let ids = |page: Vec<Comment>| page.into_iter().map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(ids(comments.page(None, 0, 10)), vec![second.id, first.id]);
+        assert_eq!(
+            ids(comments.page(Some(first.id), 0, 10)),
+            vec![reply.id, nested.id]
+        );
+        assert_eq!(comments.get(first.id).unwrap().reply_count, 2);
+
+        assert!(!comments.pin(Some(reply.id)));
+        assert!(comments.pin(Some(first.id)));
+        let page = comments.page(None, 0, 10);
+        assert_eq!(ids(page.clone()), vec![first.id, second.id]);
+        assert!(page[0].pinned);
+
+        assert_eq!(comments.remove(first.id), 3);
+        assert_eq!(comments.count(), 1);
+        assert_eq!(ids(comments.page(None, 0, 10)), vec![second.id]);
+    }
+}
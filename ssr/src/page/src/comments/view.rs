@@ -0,0 +1,376 @@
+use candid::Principal;
+use codee::string::FromToStringCodec;
+use component::{
+    infinite_scroller::InfiniteScroller, login_modal::LoginModal, modal::Modal,
+    option::SelectOption,
+};
+use consts::USER_PRINCIPAL_STORE;
+use leptos::prelude::*;
+use leptos_icons::*;
+use leptos_use::use_cookie;
+use utils::{event_streaming::events::account_connected_reader, report::ReportOption};
+
+use super::{
+    add_comment, comment_count, delete_comment, pin_comment, report_comment, Comment,
+    CommentsProvider, MAX_COMMENT_LEN,
+};
+
+/// Comments fetched per page
+const COMMENTS_FETCH_COUNT: usize = 20;
+
+/// State shared by the comments of an open post
+#[derive(Clone, Copy)]
+struct CommentsCtx {
+    post_canister: Principal,
+    post_id: u64,
+    poster_principal: Principal,
+    viewer: Signal<Option<Principal>>,
+    reply_to: RwSignal<Option<Comment>>,
+    count: RwSignal<u64>,
+    /// bumped to reload the comments
+    version: RwSignal<u64>,
+}
+
+impl CommentsCtx {
+    fn is_creator(&self) -> bool {
+        self.viewer.get() == Some(self.poster_principal)
+    }
+
+    fn provider(&self, parent_id: Option<u64>) -> CommentsProvider {
+        CommentsProvider {
+            post_canister: self.post_canister,
+            post_id: self.post_id,
+            parent_id,
+        }
+    }
+}
+
+#[component]
+fn Replies(comment_id: u64, reply_count: u32) -> impl IntoView {
+    let ctx: CommentsCtx = expect_context();
+    let show = RwSignal::new(false);
+
+    view! {
+        <button
+            class="self-start pl-11 text-xs text-white/60"
+            on:click=move |_| show.update(|s| *s = !*s)
+        >
+            {move || {
+                if show.get() {
+                    "Hide replies".to_string()
+                } else {
+                    format!("View {reply_count} replies")
+                }
+            }}
+        </button>
+        <Show when=show>
+            <div class="flex flex-col gap-3 pl-11">
+                <InfiniteScroller
+                    provider=ctx.provider(Some(comment_id))
+                    fetch_count=COMMENTS_FETCH_COUNT
+                    children=|comment, node_ref| {
+                        view! {
+                            <div node_ref=node_ref.unwrap_or_default()>
+                                <CommentItem comment />
+                            </div>
+                        }
+                    }
+                />
+            </div>
+        </Show>
+    }
+}
+
+/// Top level comments, with their replies collapsed
+#[component]
+fn CommentList() -> impl IntoView {
+    let ctx: CommentsCtx = expect_context();
+
+    move || {
+        ctx.version.track();
+        view! {
+            <InfiniteScroller
+                provider=ctx.provider(None)
+                fetch_count=COMMENTS_FETCH_COUNT
+                children=|comment, node_ref| {
+                    let comment_id = comment.id;
+                    let reply_count = comment.reply_count;
+                    view! {
+                        <div class="flex flex-col gap-2" node_ref=node_ref.unwrap_or_default()>
+                            <CommentItem comment />
+                            {(reply_count > 0)
+                                .then(|| view! { <Replies comment_id reply_count /> })}
+                        </div>
+                    }
+                }
+                empty_content=|| {
+                    view! {
+                        <span class="text-white/50 text-sm text-center">
+                            "No comments yet, be the first!"
+                        </span>
+                    }
+                }
+            />
+        }
+    }
+}
+
+#[component]
+fn ReportComment(comment_id: u64, show: RwSignal<bool>) -> impl IntoView {
+    let ctx: CommentsCtx = expect_context();
+    let reason = RwSignal::new(ReportOption::Nudity.as_str().to_string());
+    let reported = RwSignal::new(false);
+    let report = Action::new_local(move |()| async move {
+        match report_comment(
+            ctx.post_canister,
+            ctx.post_id,
+            comment_id,
+            reason.get_untracked(),
+        )
+        .await
+        {
+            Ok(()) => reported.set(true),
+            Err(e) => log::warn!("failed to report comment: {e}"),
+        }
+    });
+
+    view! {
+        <Modal show>
+            <div class="flex flex-col justify-center items-center gap-4 text-white">
+                <Show
+                    when=reported
+                    fallback=move || {
+                        view! {
+                            <span class="text-lg">Report Comment</span>
+                            <span class="text-lg">Please select a reason:</span>
+                            <div class="max-w-full text-md text-black">
+                                <select
+                                    class="p-2 w-full block rounded-lg text-sm"
+                                    on:change=move |ev| reason.set(event_target_value(&ev))
+                                >
+                                    {ReportOption::ALL
+                                        .iter()
+                                        .map(|option| {
+                                            view! {
+                                                <SelectOption
+                                                    value=reason.read_only()
+                                                    is=option.as_str().to_string()
+                                                />
+                                            }
+                                        })
+                                        .collect_view()}
+                                </select>
+                            </div>
+                            <button
+                                disabled=report.pending()
+                                on:click=move |_| {
+                                    report.dispatch(());
+                                }
+                            >
+                                <div class="rounded-lg bg-pink-500 p-1">Submit</div>
+                            </button>
+                        }
+                    }
+                >
+                    <span class="text-lg">"Thanks, we'll review this comment"</span>
+                </Show>
+            </div>
+        </Modal>
+    }
+}
+
+#[component]
+fn CommentItem(comment: Comment) -> impl IntoView {
+    let ctx: CommentsCtx = expect_context();
+    let show_report = RwSignal::new(false);
+
+    let comment_id = comment.id;
+    let author = comment.author;
+    let is_top_level = comment.parent_id.is_none();
+    let removed = if is_top_level {
+        1 + comment.reply_count as u64
+    } else {
+        1
+    };
+
+    let delete = Action::new_local(move |()| async move {
+        match delete_comment(ctx.post_canister, ctx.post_id, comment_id).await {
+            Ok(()) => {
+                ctx.count.update(|c| *c = c.saturating_sub(removed));
+                ctx.version.update(|v| *v += 1);
+            }
+            Err(e) => log::warn!("failed to delete comment: {e}"),
+        }
+    });
+    let pinned = comment.pinned;
+    let pin = Action::new_local(move |()| async move {
+        let comment_id = (!pinned).then_some(comment_id);
+        match pin_comment(ctx.post_canister, ctx.post_id, comment_id).await {
+            Ok(()) => ctx.version.update(|v| *v += 1),
+            Err(e) => log::warn!("failed to pin comment: {e}"),
+        }
+    });
+
+    let can_delete = move || ctx.viewer.get() == Some(author) || ctx.is_creator();
+    let can_report = move || ctx.viewer.get().is_some_and(|viewer| viewer != author);
+    let reply_comment = comment.clone();
+
+    view! {
+        <div class="flex flex-row gap-3 w-full">
+            <img class="h-8 w-8 shrink-0 rounded-full object-cover" src=comment.author_pic />
+            <div class="flex flex-col gap-1 min-w-0 w-full">
+                <div class="flex flex-row gap-2 items-center text-xs text-white/60">
+                    <span class="truncate">{comment.author_name}</span>
+                    <Show when=move || pinned>
+                        <span class="flex flex-row gap-1 items-center text-primary-600">
+                            <Icon icon=icondata::AiPushpinFilled />
+                            Pinned
+                        </span>
+                    </Show>
+                </div>
+                <p class="text-sm break-words whitespace-pre-wrap">{comment.text}</p>
+                <div class="flex flex-row gap-4 text-xs text-white/60">
+                    <button on:click=move |_| ctx.reply_to.set(Some(reply_comment.clone()))>
+                        Reply
+                    </button>
+                    <Show when=move || is_top_level && ctx.is_creator()>
+                        <button disabled=pin.pending() on:click=move |_| {
+                            pin.dispatch(());
+                        }>{if pinned { "Unpin" } else { "Pin" }}</button>
+                    </Show>
+                    <Show when=can_delete>
+                        <button disabled=delete.pending() on:click=move |_| {
+                            delete.dispatch(());
+                        }>Delete</button>
+                    </Show>
+                    <Show when=can_report>
+                        <button on:click=move |_| show_report.set(true)>Report</button>
+                    </Show>
+                </div>
+            </div>
+        </div>
+        <ReportComment comment_id show=show_report />
+    }
+}
+
+#[component]
+fn CommentInput() -> impl IntoView {
+    let ctx: CommentsCtx = expect_context();
+    let (is_connected, _) = account_connected_reader();
+    let show_login = RwSignal::new(false);
+    let text = RwSignal::new(String::new());
+    let error = RwSignal::new(None::<String>);
+
+    let post = Action::new_local(move |()| {
+        let parent_id = ctx.reply_to.get_untracked().map(|c| c.id);
+        let body = text.get_untracked();
+        async move {
+            match add_comment(ctx.post_canister, ctx.post_id, parent_id, body).await {
+                Ok(_) => {
+                    text.set(String::new());
+                    error.set(None);
+                    ctx.reply_to.set(None);
+                    ctx.count.update(|c| *c += 1);
+                    ctx.version.update(|v| *v += 1);
+                }
+                Err(e) => error.set(Some(e.to_string())),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex flex-col gap-1 w-full">
+            {move || {
+                ctx.reply_to
+                    .get()
+                    .map(|comment| {
+                        view! {
+                            <div class="flex flex-row justify-between text-xs text-white/60">
+                                <span class="truncate">"Replying to " {comment.author_name}</span>
+                                <button on:click=move |_| ctx.reply_to.set(None)>
+                                    <Icon icon=icondata::ChCross />
+                                </button>
+                            </div>
+                        }
+                    })
+            }}
+            <form
+                class="flex flex-row gap-2 w-full"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    if !is_connected.get_untracked() {
+                        show_login.set(true);
+                        return;
+                    }
+                    post.dispatch(());
+                }
+            >
+                <input
+                    class="w-full rounded-full bg-neutral-800 px-4 py-2 text-sm focus:outline focus:outline-1 focus:outline-primary-600"
+                    placeholder="Add a comment"
+                    maxlength=MAX_COMMENT_LEN
+                    prop:value=text
+                    on:input=move |ev| text.set(event_target_value(&ev))
+                />
+                <button
+                    type="submit"
+                    class="text-primary-600 font-semibold disabled:opacity-50"
+                    disabled=move || post.pending().get() || text.with(|t| t.trim().is_empty())
+                >
+                    Post
+                </button>
+            </form>
+            {move || error.get().map(|e| view! { <span class="text-xs text-red-500">{e}</span> })}
+        </div>
+        <LoginModal show=show_login />
+    }
+}
+
+/// Comment count of a post, opening the post's comments when clicked
+#[component]
+pub fn CommentsButton(
+    post_canister: Principal,
+    post_id: u64,
+    poster_principal: Principal,
+) -> impl IntoView {
+    let show = RwSignal::new(false);
+    let count = RwSignal::new(0u64);
+    let count_fetch = Resource::new(|| (), move |_| comment_count(post_canister, post_id));
+    let (viewer, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
+
+    provide_context(CommentsCtx {
+        post_canister,
+        post_id,
+        poster_principal,
+        viewer,
+        reply_to: RwSignal::new(None),
+        count,
+        version: RwSignal::new(0),
+    });
+
+    view! {
+        <div class="flex flex-col gap-1 items-center">
+            <button on:click=move |_| show.set(true)>
+                <Icon attr:class="drop-shadow-lg" icon=icondata::AiMessageOutlined />
+            </button>
+            <span class="text-xs md:text-sm">{count}</span>
+            <Suspense>
+                {move || Suspend::new(async move {
+                    match count_fetch.await {
+                        Ok(c) => count.set(c),
+                        Err(e) => log::warn!("failed to fetch comment count: {e}"),
+                    }
+                })}
+            </Suspense>
+        </div>
+        <Modal show>
+            <div class="flex flex-col gap-4 w-[90vw] max-w-md text-white">
+                <span class="text-lg text-center">{move || format!("{} comments", count.get())}</span>
+                <div class="flex flex-col gap-4 max-h-[60vh] overflow-y-auto">
+                    <CommentList />
+                </div>
+                <CommentInput />
+            </div>
+        </Modal>
+    }
+}
@@ -0,0 +1,307 @@
+//! Creators followed by users, stored as sets per principal in the KV store
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use codee::string::{FromToStringCodec, JsonSerdeCodec};
+use component::login_modal::LoginModal;
+use consts::{FOLLOWING_STORE, USER_PRINCIPAL_STORE};
+use leptos::{prelude::*, server_fn::codec::Json};
+use leptos_use::{storage::use_local_storage, use_cookie};
+use serde::{Deserialize, Serialize};
+use utils::event_streaming::events::account_connected_reader;
+
+/// Max followers or followed creators listed per page
+pub const FOLLOW_LIST_LIMIT: u64 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowedCreator {
+    pub principal: Principal,
+    pub canister_id: Principal,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowStats {
+    pub followers: u64,
+    pub following: u64,
+    /// whether the user making the request follows this user
+    pub followed_by_caller: bool,
+}
+
+/// Returns whether the caller follows the creator
+#[server(endpoint = "follow_creator", input = Json, output = Json)]
+pub async fn follow_creator(creator_principal: Principal) -> Result<bool, ServerFnError> {
+    server_impl::set_following(creator_principal, true).await
+}
+
+/// Returns whether the caller follows the creator
+#[server(endpoint = "unfollow_creator", input = Json, output = Json)]
+pub async fn unfollow_creator(creator_principal: Principal) -> Result<bool, ServerFnError> {
+    server_impl::set_following(creator_principal, false).await
+}
+
+/// Creators followed by the caller, most recently followed first
+#[server(endpoint = "followed_creators", input = Json, output = Json)]
+pub async fn followed_creators(
+    start: u64,
+    limit: u64,
+) -> Result<Vec<FollowedCreator>, ServerFnError> {
+    server_impl::followed_creators(start, limit).await
+}
+
+#[server(endpoint = "follow_stats", input = Json, output = Json)]
+pub async fn follow_stats(user_principal: Principal) -> Result<FollowStats, ServerFnError> {
+    server_impl::follow_stats(user_principal).await
+}
+
+/// Principals following `user_principal`, most recent first
+#[server(endpoint = "followers", input = Json, output = Json)]
+pub async fn followers(
+    user_principal: Principal,
+    start: u64,
+    limit: u64,
+) -> Result<Vec<Principal>, ServerFnError> {
+    server_impl::followers(user_principal, start, limit).await
+}
+
+/// Principals followed by `user_principal`, most recent first
+#[server(endpoint = "following", input = Json, output = Json)]
+pub async fn following(
+    user_principal: Principal,
+    start: u64,
+    limit: u64,
+) -> Result<Vec<Principal>, ServerFnError> {
+    server_impl::following(user_principal, start, limit).await
+}
+
+#[cfg(feature = "ssr")]
+mod server_impl {
+    use auth::server_impl::{
+        add_to_shared_set, extract_principal_from_cookie, read_shared_data_many,
+        remove_from_shared_set, shared_set_len, shared_set_range, shared_set_score,
+        store::KVStoreImpl, write_shared_data,
+    };
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use candid::Principal;
+    use leptos::prelude::*;
+    use leptos_axum::extract_with_state;
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+    use yral_canisters_common::utils::time::current_epoch;
+
+    use super::{FollowStats, FollowedCreator, FOLLOW_LIST_LIMIT};
+
+    /// user principal -> set of the followed creators' principals, scored by follow time
+    const FOLLOWING_NAMESPACE: &str = "following-set";
+    /// user principal -> set of the followers' principals, scored by follow time
+    const FOLLOWERS_NAMESPACE: &str = "followers-set";
+    /// creator principal -> creator canister id
+    const CREATOR_CANISTER_NAMESPACE: &str = "follow-creator-canister";
+
+    async fn request_principal() -> Result<Option<Principal>, ServerFnError> {
+        let key: Key = expect_context();
+        let jar: SignedCookieJar = extract_with_state(&key).await?;
+        extract_principal_from_cookie(&jar)
+    }
+
+    async fn page(
+        kv: &KVStoreImpl,
+        namespace: &str,
+        user_principal: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Principal>, ServerFnError> {
+        let members = shared_set_range(
+            kv,
+            namespace,
+            &user_principal.to_text(),
+            start,
+            limit.min(FOLLOW_LIST_LIMIT),
+        )
+        .await?;
+        Ok(members
+            .into_iter()
+            .filter_map(|(member, _)| Principal::from_text(member).ok())
+            .collect())
+    }
+
+    /// Both sides are updated with atomic set operations, so concurrent (un)follows can't
+    /// overwrite each other
+    pub async fn set_following(
+        creator_principal: Principal,
+        follow: bool,
+    ) -> Result<bool, ServerFnError> {
+        let principal = request_principal()
+            .await?
+            .ok_or_else(|| ServerFnError::new("User not logged in"))?;
+        if principal == creator_principal {
+            return Err(ServerFnError::new("Users can't follow themselves"));
+        }
+        let kv: KVStoreImpl = expect_context();
+        let user = principal.to_text();
+        let creator = creator_principal.to_text();
+
+        if !follow {
+            remove_from_shared_set(&kv, FOLLOWING_NAMESPACE, &user, creator.clone()).await?;
+            remove_from_shared_set(&kv, FOLLOWERS_NAMESPACE, &creator, user).await?;
+            return Ok(false);
+        }
+
+        let canister_id = unauth_canister_backend()
+            .get_individual_canister_by_user_principal(creator_principal)
+            .await?
+            .ok_or_else(|| ServerFnError::new("Creator not found"))?;
+        write_shared_data(
+            &kv,
+            CREATOR_CANISTER_NAMESPACE,
+            &creator,
+            canister_id.to_text(),
+        )
+        .await?;
+        let followed_at = current_epoch().as_millis() as f64;
+        add_to_shared_set(
+            &kv,
+            FOLLOWING_NAMESPACE,
+            &user,
+            creator.clone(),
+            followed_at,
+        )
+        .await?;
+        add_to_shared_set(&kv, FOLLOWERS_NAMESPACE, &creator, user, followed_at).await?;
+
+        Ok(true)
+    }
+
+    pub async fn followed_creators(
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<FollowedCreator>, ServerFnError> {
+        let Some(principal) = request_principal().await? else {
+            return Ok(vec![]);
+        };
+        let kv: KVStoreImpl = expect_context();
+        let creators = page(&kv, FOLLOWING_NAMESPACE, principal, start, limit).await?;
+        let keys: Vec<_> = creators.iter().map(Principal::to_text).collect();
+        let canisters = read_shared_data_many(&kv, CREATOR_CANISTER_NAMESPACE, &keys).await?;
+
+        Ok(creators
+            .into_iter()
+            .zip(canisters)
+            .filter_map(|(principal, canister_id)| {
+                Some(FollowedCreator {
+                    principal,
+                    canister_id: Principal::from_text(canister_id?).ok()?,
+                })
+            })
+            .collect())
+    }
+
+    pub async fn follow_stats(user_principal: Principal) -> Result<FollowStats, ServerFnError> {
+        let kv: KVStoreImpl = expect_context();
+        let user = user_principal.to_text();
+        let followers = shared_set_len(&kv, FOLLOWERS_NAMESPACE, &user).await?;
+        let following = shared_set_len(&kv, FOLLOWING_NAMESPACE, &user).await?;
+        let followed_by_caller = match request_principal().await? {
+            Some(caller) => shared_set_score(&kv, FOLLOWERS_NAMESPACE, &user, caller.to_text())
+                .await?
+                .is_some(),
+            None => false,
+        };
+
+        Ok(FollowStats {
+            followers,
+            following,
+            followed_by_caller,
+        })
+    }
+
+    pub async fn followers(
+        user_principal: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Principal>, ServerFnError> {
+        let kv: KVStoreImpl = expect_context();
+        page(&kv, FOLLOWERS_NAMESPACE, user_principal, start, limit).await
+    }
+
+    pub async fn following(
+        user_principal: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Principal>, ServerFnError> {
+        let kv: KVStoreImpl = expect_context();
+        page(&kv, FOLLOWING_NAMESPACE, user_principal, start, limit).await
+    }
+}
+
+/// Follow or unfollow `creator_principal`
+/// the followed creators are cached in local storage, so the button is free to render in the feed
+#[component]
+pub fn FollowButton(
+    creator_principal: Principal,
+    /// whether the caller follows the creator, overrides the local cache once known
+    #[prop(optional, into)]
+    followed: Option<Signal<Option<bool>>>,
+    /// called with the new state after following or unfollowing
+    #[prop(optional, into)]
+    on_change: Option<Callback<bool>>,
+    #[prop(optional, into)] class: String,
+) -> impl IntoView {
+    let (is_connected, _) = account_connected_reader();
+    let (viewer_principal, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
+    let (following_store, set_following_store, _) =
+        use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(FOLLOWING_STORE);
+    let show_login = RwSignal::new(false);
+
+    let is_following = Signal::derive(move || {
+        followed
+            .and_then(|followed| followed.get())
+            .unwrap_or_else(|| following_store.with(|f| f.contains(&creator_principal)))
+    });
+    let is_self = move || viewer_principal.get() == Some(creator_principal);
+
+    let toggle = Action::new_local(move |&follow: &bool| async move {
+        let res = if follow {
+            follow_creator(creator_principal).await
+        } else {
+            unfollow_creator(creator_principal).await
+        };
+        match res {
+            Ok(following) => {
+                set_following_store.update(|creators| {
+                    if following {
+                        creators.insert(creator_principal);
+                    } else {
+                        creators.remove(&creator_principal);
+                    }
+                });
+                if let Some(on_change) = on_change {
+                    on_change.run(following);
+                }
+            }
+            Err(e) => log::warn!("failed to update following: {e}"),
+        }
+    });
+
+    view! {
+        <Show when=move || !is_self()>
+            <button
+                class=format!(
+                    "rounded-full px-4 py-1 text-sm font-semibold disabled:opacity-50 {class}",
+                )
+                class=("bg-primary-600", move || !is_following())
+                class=("bg-white/20", is_following)
+                disabled=toggle.pending()
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    if !is_connected.get_untracked() {
+                        show_login.set(true);
+                        return;
+                    }
+                    toggle.dispatch(!is_following.get_untracked());
+                }
+            >
+                {move || if is_following() { "Following" } else { "Follow" }}
+            </button>
+        </Show>
+        <LoginModal show=show_login />
+    }
+}
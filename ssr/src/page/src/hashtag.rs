@@ -0,0 +1,393 @@
+//! Index of posts by hashtag, backing the `/tag/:hashtag` feeds and trending hashtags
+use leptos::{prelude::*, server_fn::codec::Json};
+use serde::{Deserialize, Serialize};
+use yral_types::post::PostItem;
+
+/// Hashtags listed by [`trending_hashtags`] by default
+pub const TRENDING_HASHTAGS_LIMIT: u32 = 20;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrendingHashtag {
+    /// normalized, without the `#`
+    pub hashtag: String,
+    /// posts tagged within the window
+    pub posts: u32,
+}
+
+/// Posts tagged with `hashtag`, newest first
+#[server(endpoint = "hashtag_posts", input = Json, output = Json)]
+pub async fn hashtag_posts(
+    hashtag: String,
+    start: u64,
+    limit: u64,
+) -> Result<Vec<PostItem>, ServerFnError> {
+    server_impl::hashtag_posts(hashtag, start, limit).await
+}
+
+/// Most used hashtags of the last `window_hours` hours
+/// windows longer than two days are counted in whole days, windows longer than the retained
+/// history are clamped
+#[server(endpoint = "trending_hashtags", input = Json, output = Json)]
+pub async fn trending_hashtags(
+    window_hours: u64,
+    limit: Option<u32>,
+) -> Result<Vec<TrendingHashtag>, ServerFnError> {
+    server_impl::trending_hashtags(window_hours, limit.unwrap_or(TRENDING_HASHTAGS_LIMIT)).await
+}
+
+#[cfg(feature = "ssr")]
+pub mod server_impl {
+    use std::collections::BTreeMap;
+
+    use auth::server_impl::{
+        add_to_shared_set, expire_shared_data, incr_in_shared_set, incr_shared_counter,
+        read_shared_data_many, shared_set_range, store::KVStoreImpl, trim_shared_set,
+        write_shared_data,
+    };
+    use candid::Principal;
+    use futures::future::try_join_all;
+    use leptos::prelude::*;
+    use state::canister_backend::{CanisterBackend, CanisterBackendImpl};
+    use utils::{
+        hashtags::normalize_hashtag,
+        ml_feed::{get_ml_feed, FeedContent},
+    };
+    use web_time::Duration;
+    use yral_canisters_common::utils::{posts::PostDetails, time::current_epoch};
+    use yral_types::post::PostItem;
+
+    use super::TrendingHashtag;
+    use crate::search::server_impl as search;
+
+    /// `<canister id>-<post id>` -> [`PostItem`]
+    const POST_NAMESPACE: &str = "hashtag-post";
+    /// hashtag -> sorted set of `<canister id>-<post id>`, scored by the post's creation time
+    const POSTS_NAMESPACE: &str = "hashtag-posts";
+    /// hour since the epoch -> sorted set of hashtags, scored by the posts tagged in that hour
+    const TRENDING_NAMESPACE: &str = "hashtag-trending-hourly";
+    /// day since the epoch -> sorted set of hashtags, scored by the posts tagged in that day
+    const TRENDING_DAILY_NAMESPACE: &str = "hashtag-trending-daily";
+    /// backfill version -> number of servers that started it
+    const BACKFILL_NAMESPACE: &str = "hashtag-backfill";
+    /// Bump to index every known creator's posts again
+    const BACKFILL_VERSION: &str = "v1";
+    /// Posts of the global feed whose creators are backfilled
+    const BACKFILL_FEED_POSTS: u32 = 500;
+    const BACKFILL_PAGE_SIZE: u64 = 50;
+    /// Attempts at finding an upload's post, the post is created asynchronously by the upload worker
+    const UPLOAD_INDEX_ATTEMPTS: u32 = 10;
+    const UPLOAD_INDEX_RETRY_DELAY: Duration = Duration::from_secs(3);
+    /// Latest posts of the uploader searched for the uploaded post
+    const RECENT_UPLOADS: u64 = 10;
+    /// Posts kept per hashtag, older ones drop out of the hashtag's feed
+    const POSTS_PER_HASHTAG: u64 = 1000;
+    /// Hourly counts kept for trending hashtags, longer windows are read from the daily counts
+    /// so a window never reads more than a few dozen sets
+    const TRENDING_HOURLY_RETENTION_HOURS: u64 = 48;
+    /// Daily counts kept for trending hashtags
+    const TRENDING_RETENTION_DAYS: u64 = 30;
+    /// Hashtags read from each hour or day of the window, less used ones don't count towards trending
+    const TRENDING_CANDIDATES_PER_BUCKET: u64 = 200;
+    const MAX_PAGE_SIZE: u64 = 50;
+
+    fn post_key(canister_id: Principal, post_id: u64) -> String {
+        format!("{canister_id}-{post_id}")
+    }
+
+    fn current_hour() -> u64 {
+        current_epoch().as_secs() / 3600
+    }
+
+    /// Count `hashtags` in the trending set of `bucket`, the `bucket_secs` long bucket is
+    /// dropped once `retained` newer buckets started
+    async fn count_trending(
+        kv: &KVStoreImpl,
+        namespace: &str,
+        bucket: u64,
+        bucket_secs: u64,
+        retained: u64,
+        hashtags: &[String],
+    ) -> Result<(), ServerFnError> {
+        let key = bucket.to_string();
+        for hashtag in hashtags {
+            incr_in_shared_set(kv, namespace, &key, hashtag.clone(), 1.0).await?;
+        }
+        let expires_at = Duration::from_secs((bucket + retained + 1) * bucket_secs);
+        let ttl = expires_at.saturating_sub(current_epoch());
+        expire_shared_data(kv, namespace, &key, ttl).await
+    }
+
+    /// Index the hashtags of `post`, indexing a post again is a no-op
+    pub async fn index_post(kv: &KVStoreImpl, post: &PostDetails) -> Result<(), ServerFnError> {
+        let mut hashtags: Vec<_> = post
+            .hastags
+            .iter()
+            .filter_map(|hashtag| normalize_hashtag(hashtag))
+            .collect();
+        hashtags.sort();
+        hashtags.dedup();
+        if hashtags.is_empty() {
+            return Ok(());
+        }
+
+        let key = post_key(post.canister_id, post.post_id);
+        let item = PostItem {
+            canister_id: post.canister_id,
+            post_id: post.post_id,
+            video_id: post.uid.clone(),
+            nsfw_probability: post.nsfw_probability,
+        };
+        write_shared_data(kv, POST_NAMESPACE, &key, serde_json::to_string(&item)?).await?;
+
+        // fixtures and older posts may lack a creation time
+        let created_at = if post.created_at.is_zero() {
+            current_epoch()
+        } else {
+            post.created_at
+        };
+        let hour = created_at.as_secs() / 3600;
+        let mut added_hashtags = vec![];
+        for hashtag in hashtags {
+            let added = add_to_shared_set(
+                kv,
+                POSTS_NAMESPACE,
+                &hashtag,
+                key.clone(),
+                created_at.as_secs_f64(),
+            )
+            .await?;
+            if !added {
+                continue;
+            }
+            trim_shared_set(kv, POSTS_NAMESPACE, &hashtag, POSTS_PER_HASHTAG).await?;
+            added_hashtags.push(hashtag);
+        }
+        if added_hashtags.is_empty() {
+            return Ok(());
+        }
+
+        let now = current_hour();
+        if hour + TRENDING_HOURLY_RETENTION_HOURS >= now {
+            count_trending(
+                kv,
+                TRENDING_NAMESPACE,
+                hour,
+                3600,
+                TRENDING_HOURLY_RETENTION_HOURS,
+                &added_hashtags,
+            )
+            .await?;
+        }
+        let day = hour / 24;
+        if day + TRENDING_RETENTION_DAYS >= now / 24 {
+            count_trending(
+                kv,
+                TRENDING_DAILY_NAMESPACE,
+                day,
+                24 * 3600,
+                TRENDING_RETENTION_DAYS,
+                &added_hashtags,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Index the hashtags of the post with `video_uid` and add it and its creator to the search index
+    /// returns false if `user_principal` has no such post yet
+    async fn index_upload(
+        kv: &KVStoreImpl,
+        canisters: &CanisterBackendImpl<false>,
+        user_principal: Principal,
+        video_uid: &str,
+    ) -> Result<bool, ServerFnError> {
+        let Some(user_canister) = canisters
+            .get_individual_canister_by_user_principal(user_principal)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let Some(post) = canisters
+            .get_user_posts(user_canister, 0, RECENT_UPLOADS)
+            .await?
+            .into_iter()
+            .find(|post| post.uid == video_uid)
+        else {
+            return Ok(false);
+        };
+
+        index_post(kv, &post).await?;
+        search::index_post(kv, &post).await?;
+        let profile = canisters.get_profile_details(user_canister).await?;
+        search::index_profile(kv, user_canister, &profile).await?;
+        Ok(true)
+    }
+
+    /// Index the upload `video_uid` of `user_principal` once its post is created
+    /// runs on the server, so the client can leave right after publishing
+    pub fn spawn_upload_indexing(
+        kv: KVStoreImpl,
+        canisters: CanisterBackendImpl<false>,
+        user_principal: Principal,
+        video_uid: String,
+    ) {
+        tokio::spawn(async move {
+            for _ in 0..UPLOAD_INDEX_ATTEMPTS {
+                match index_upload(&kv, &canisters, user_principal, &video_uid).await {
+                    Ok(true) => return,
+                    Ok(false) => tokio::time::sleep(UPLOAD_INDEX_RETRY_DELAY).await,
+                    Err(e) => {
+                        log::warn!("failed to index upload {video_uid}: {e}");
+                        return;
+                    }
+                }
+            }
+            log::warn!("post {video_uid} not found for indexing");
+        });
+    }
+
+    /// Index every post and the profile of the creator with `user_canister`
+    async fn index_creator(
+        kv: &KVStoreImpl,
+        canisters: &CanisterBackendImpl<false>,
+        user_canister: Principal,
+    ) -> Result<(), ServerFnError> {
+        let mut start = 0;
+        loop {
+            let posts = canisters
+                .get_user_posts(user_canister, start, BACKFILL_PAGE_SIZE)
+                .await?;
+            for post in &posts {
+                index_post(kv, post).await?;
+                search::index_post(kv, post).await?;
+            }
+            if (posts.len() as u64) < BACKFILL_PAGE_SIZE {
+                break;
+            }
+            start += BACKFILL_PAGE_SIZE;
+        }
+
+        let profile = canisters.get_profile_details(user_canister).await?;
+        search::index_profile(kv, user_canister, &profile).await
+    }
+
+    /// Index the creators of the global feed, to cover posts uploaded before indexing
+    /// only the first server to start with a new [`BACKFILL_VERSION`] runs it
+    async fn backfill(
+        kv: &KVStoreImpl,
+        canisters: &CanisterBackendImpl<false>,
+    ) -> Result<(), ServerFnError> {
+        if incr_shared_counter(kv, BACKFILL_NAMESPACE, BACKFILL_VERSION, 1).await? > 1 {
+            return Ok(());
+        }
+
+        let feed = get_ml_feed(
+            FeedContent::Mixed,
+            true,
+            Principal::anonymous(),
+            BACKFILL_FEED_POSTS,
+            vec![],
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let mut creators: Vec<_> = feed.into_iter().map(|post| post.canister_id).collect();
+        creators.sort();
+        creators.dedup();
+
+        log::info!("backfilling the posts of {} creators", creators.len());
+        for creator in creators {
+            // indexing is idempotent, a failed creator is picked up by the next version
+            if let Err(e) = index_creator(kv, canisters, creator).await {
+                log::warn!("failed to backfill creator {creator}: {e}");
+            }
+        }
+        log::info!("backfill {BACKFILL_VERSION} completed");
+        Ok(())
+    }
+
+    pub fn spawn_backfill(kv: KVStoreImpl, canisters: CanisterBackendImpl<false>) {
+        tokio::spawn(async move {
+            if let Err(e) = backfill(&kv, &canisters).await {
+                log::error!("backfill {BACKFILL_VERSION} failed: {e}");
+            }
+        });
+    }
+
+    pub async fn hashtag_posts(
+        hashtag: String,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<PostItem>, ServerFnError> {
+        let Some(hashtag) = normalize_hashtag(&hashtag) else {
+            return Ok(vec![]);
+        };
+        let kv: KVStoreImpl = expect_context();
+        let keys: Vec<_> = shared_set_range(
+            &kv,
+            POSTS_NAMESPACE,
+            &hashtag,
+            start,
+            limit.min(MAX_PAGE_SIZE),
+        )
+        .await?
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+
+        let mut posts = vec![];
+        for raw in read_shared_data_many(&kv, POST_NAMESPACE, &keys)
+            .await?
+            .into_iter()
+            .flatten()
+        {
+            posts.push(serde_json::from_str(&raw)?);
+        }
+        Ok(posts)
+    }
+
+    pub async fn trending_hashtags(
+        window_hours: u64,
+        limit: u32,
+    ) -> Result<Vec<TrendingHashtag>, ServerFnError> {
+        let kv: KVStoreImpl = expect_context();
+        let now = current_hour();
+        let since = now.saturating_sub(window_hours.min(TRENDING_RETENTION_DAYS * 24));
+        let (namespace, buckets) = if window_hours <= TRENDING_HOURLY_RETENTION_HOURS {
+            (TRENDING_NAMESPACE, since..=now)
+        } else {
+            (TRENDING_DAILY_NAMESPACE, since / 24..=now / 24)
+        };
+        let counts = try_join_all(buckets.map(|bucket| {
+            let kv = &kv;
+            async move {
+                shared_set_range(
+                    kv,
+                    namespace,
+                    &bucket.to_string(),
+                    0,
+                    TRENDING_CANDIDATES_PER_BUCKET,
+                )
+                .await
+            }
+        }))
+        .await?;
+
+        let mut totals = BTreeMap::<String, u32>::new();
+        for (hashtag, count) in counts.into_iter().flatten() {
+            *totals.entry(hashtag).or_default() += count as u32;
+        }
+
+        let mut top: Vec<_> = totals
+            .into_iter()
+            .map(|(hashtag, posts)| TrendingHashtag { hashtag, posts })
+            .collect();
+        top.sort_by(|a, b| {
+            b.posts
+                .cmp(&a.posts)
+                .then_with(|| a.hashtag.cmp(&b.hashtag))
+        });
+        top.truncate(limit as usize);
+
+        Ok(top)
+    }
+}
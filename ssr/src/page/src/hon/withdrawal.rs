@@ -3,13 +3,14 @@ use component::{
     auth_providers::handle_user_login, back_btn::BackButton,
     icons::notification_icon::NotificationIcon, title::TitleText,
 };
+use consts::HON_WORKER_URL;
 use futures::TryFutureExt;
 use hon_worker_common::{HoNGameWithdrawReq, SatsBalanceInfo};
 use leptos::prelude::*;
 use leptos_router::hooks::use_navigate;
 use log;
 use state::{canisters::authenticated_canisters, server::HonWorkerJwt};
-use utils::{send_wrap, try_or_redirect_opt};
+use utils::{request_id::PropagateRequestId, send_wrap, try_or_redirect_opt};
 use yral_canisters_client::individual_user_template::{Result9, SessionType};
 use yral_canisters_common::{utils::token::balance::TokenBalance, Canisters};
 use yral_identity::Signature;
@@ -26,14 +27,14 @@ macro_rules! format_sats {
 type Details = SatsBalanceInfo;
 
 async fn load_withdrawal_details(user_principal: Principal) -> Result<Details, String> {
-    let url: reqwest::Url = hon_worker_common::WORKER_URL
-        .parse()
-        .expect("Url to be valid");
-    let balance_info = url
+    let balance_info = HON_WORKER_URL
         .join(&format!("/balance/{user_principal}"))
         .expect("Url to be valid");
 
-    let balance_info: SatsBalanceInfo = reqwest::get(balance_info)
+    let balance_info: SatsBalanceInfo = reqwest::Client::new()
+        .get(balance_info)
+        .propagate_request_id()
+        .send()
         .await
         .map_err(|_| "failed to load balance".to_string())?
         .json()
@@ -49,8 +50,6 @@ async fn withdraw_sats_for_ckbtc(
     req: hon_worker_common::WithdrawRequest,
     sig: Signature,
 ) -> Result<(), ServerFnError> {
-    use hon_worker_common::WORKER_URL;
-
     // TODO: yral-auth-v2, we can do this verification with a JWT
     let cans: Canisters<false> = expect_context();
 
@@ -77,13 +76,16 @@ async fn withdraw_sats_for_ckbtc(
         request: req,
         signature: sig,
     };
-    let req_url = format!("{WORKER_URL}withdraw");
+    let req_url = state::server::HonWorkerUrl::current()
+        .join("withdraw")
+        .expect("Url to be valid");
     let client = reqwest::Client::new();
     let jwt = expect_context::<HonWorkerJwt>();
     let res = client
-        .post(&req_url)
+        .post(req_url)
         .json(&worker_req)
         .header("Authorization", format!("Bearer {}", jwt.0))
+        .propagate_request_id()
         .send()
         .await?;
 
@@ -88,13 +88,13 @@ pub async fn process_token_list_item(
     key_principal: Principal,
 ) -> Vec<ProcessedTokenListResponse> {
     use futures::stream::FuturesOrdered;
-    use state::canisters::unauth_canisters;
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
 
     let mut fut = FuturesOrdered::new();
 
     for token in token_list_item {
         fut.push_back(async move {
-            let cans = unauth_canisters();
+            let cans = unauth_canister_backend();
             let root_principal = Principal::from_text(
                 token
                     .link
@@ -1,10 +1,13 @@
 #![recursion_limit = "256"]
 pub mod about_us;
 pub mod airdrop;
+pub mod comments;
 pub mod err;
 pub mod faq;
+pub mod follow;
 #[cfg(any(feature = "oauth-ssr", feature = "oauth-hydrate"))]
 pub mod google_redirect;
+pub mod hashtag;
 pub mod hon;
 pub mod icpump;
 pub mod leaderboard;
@@ -20,6 +23,7 @@ pub mod pumpdump;
 pub mod refer_earn;
 pub mod root;
 pub mod scrolling_post_view;
+pub mod search;
 pub mod settings;
 pub mod terms;
 pub mod terms_ios;
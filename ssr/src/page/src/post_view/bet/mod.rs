@@ -1,78 +1,94 @@
 mod server_impl;
+pub mod stake;
 
 use crate::post_view::BetEligiblePostCtx;
+use candid::{Nat, Principal};
 use codee::string::FromToStringCodec;
 use component::{
     bullet_loader::BulletLoader, canisters_prov::AuthCansProvider, hn_icons::*, spinner::SpinnerFit,
 };
-use hon_worker_common::{sign_vote_request, GameInfo, GameResult, WORKER_URL};
+use consts::{HON_STAKE_STORE, HON_WORKER_URL};
+use hon_worker_common::{sign_vote_request, GameInfo, GameResult, SatsBalanceInfo};
 use ic_agent::Identity;
 use leptos::{either::Either, prelude::*};
 use leptos_icons::*;
 use leptos_use::storage::use_local_storage;
-use server_impl::vote_with_cents_on_post;
+use server_impl::{get_stake_ladder, vote_with_cents_on_post};
+use stake::{StakeError, StakeLadder, MIN_STAKE};
 use state::canisters::authenticated_canisters;
-use utils::try_or_redirect_opt;
 use utils::{mixpanel::mixpanel_events::*, send_wrap};
+use utils::{request_id::PropagateRequestId, try_or_redirect_opt};
 use yral_canisters_common::{
     utils::{posts::PostDetails, token::balance::TokenBalance, vote::VoteKind},
     Canisters,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum CoinState {
-    C50,
-    C100,
-    C200,
-}
-
-impl CoinState {
-    fn wrapping_next(self) -> Self {
-        match self {
-            CoinState::C50 => CoinState::C100,
-            CoinState::C100 => CoinState::C200,
-            CoinState::C200 => CoinState::C50,
-        }
-    }
-
-    fn wrapping_prev(self) -> Self {
-        match self {
-            CoinState::C50 => CoinState::C200,
-            CoinState::C100 => CoinState::C50,
-            CoinState::C200 => CoinState::C100,
-        }
-    }
-}
-
-impl From<CoinState> for u64 {
-    fn from(coin: CoinState) -> u64 {
-        match coin {
-            CoinState::C50 => 50,
-            CoinState::C100 => 100,
-            CoinState::C200 => 200,
-        }
-    }
-}
-
 #[component]
 fn CoinStateView(
-    #[prop(into)] coin: Signal<CoinState>,
+    #[prop(into)] coin: Signal<u64>,
     #[prop(into)] class: String,
     #[prop(optional, into)] disabled: Signal<bool>,
 ) -> impl IntoView {
-    let icon = Signal::derive(move || match coin() {
-        CoinState::C50 => C50Icon,
-        CoinState::C100 => C100Icon,
-        CoinState::C200 => C200Icon,
-    });
+    let icon = move || match coin() {
+        50 => Some(C50Icon),
+        100 => Some(C100Icon),
+        200 => Some(C200Icon),
+        _ => None,
+    };
 
     view! {
         <div class:grayscale=disabled>
-            <Icon attr:class=class icon />
+            {move || match icon() {
+                Some(icon) => Either::Left(view! { <Icon attr:class=class.clone() icon /> }),
+                // stakes without an icon of their own
+                None => Either::Right(view! {
+                    <div class=format!("{class} flex items-center justify-center rounded-full border-4 border-[#FEB635] bg-[#FED056] font-bold text-lg text-[#A35E00]")>
+                        {coin()}
+                    </div>
+                }),
+            }}
         </div>
     }
 }
 
+/// Input for a stake outside the ladder
+#[component]
+fn CustomStakeInput(
+    ladder: StoredValue<StakeLadder>,
+    coin: RwSignal<u64>,
+    stake_error: RwSignal<Option<String>>,
+) -> impl IntoView {
+    let on_input = move |ev| {
+        let res = event_target_value(&ev)
+            .parse::<u64>()
+            .map_err(|_| "Enter a whole number".to_string())
+            .and_then(|stake| {
+                ladder
+                    .with_value(|l| l.check(stake))
+                    .map_err(|e| e.to_string())
+            });
+        match res {
+            Ok(stake) => {
+                coin.set(stake);
+                stake_error.set(None);
+            }
+            Err(e) => stake_error.set(Some(e)),
+        }
+    };
+
+    view! {
+        <input
+            type="number"
+            inputmode="numeric"
+            min=MIN_STAKE
+            max=move || ladder.with_value(|l| l.max)
+            prop:value=coin.get_untracked().to_string()
+            on:input=on_input
+            class="py-1 px-2 w-24 text-center text-white rounded-md border outline-none bg-black/40 border-neutral-500"
+        />
+    }
+}
+
 #[component]
 fn HNButton(
     bet_direction: RwSignal<Option<VoteKind>>,
@@ -101,39 +117,74 @@ fn HNButton(
     }
 }
 
+/// Cents balance of `user` held by the hot or not worker
+async fn load_cents_balance(user: Principal) -> Result<Nat, String> {
+    let url = HON_WORKER_URL
+        .join(&format!("/balance/{user}"))
+        .expect("Url to be valid");
+    let balance_info: SatsBalanceInfo = reqwest::Client::new()
+        .get(url)
+        .propagate_request_id()
+        .send()
+        .await
+        .map_err(|_| "failed to load balance".to_string())?
+        .json()
+        .await
+        .map_err(|_| "failed to read response body".to_string())?;
+
+    Ok(balance_info.balance.into())
+}
+
 #[component]
 fn HNButtonOverlay(
     post: PostDetails,
-    coin: RwSignal<CoinState>,
+    ladder: StakeLadder,
     bet_direction: RwSignal<Option<VoteKind>>,
     refetch_bet: Trigger,
 ) -> impl IntoView {
     let (is_connected, _, _) =
         use_local_storage::<bool, FromToStringCodec>(consts::ACCOUNT_CONNECTED_STORE);
+    let (last_stake, set_last_stake, _) =
+        use_local_storage::<u64, FromToStringCodec>(HON_STAKE_STORE);
+    let last_stake = last_stake.get_untracked();
+    let coin = RwSignal::new(ladder.initial((last_stake > 0).then_some(last_stake)));
+    let ladder = StoredValue::new(ladder);
+    let show_custom = RwSignal::new(false);
+    let stake_error = RwSignal::new(None::<String>);
+
     let place_bet_action = Action::new(
-        move |(canisters, bet_direction, bet_amount): &(Canisters<true>, VoteKind, u64)| {
+        move |(canisters, direction, bet_amount): &(Canisters<true>, VoteKind, u64)| {
             let post_canister = post.canister_id;
             let post_id = post.post_id;
             let cans = canisters.clone();
             let bet_amount = *bet_amount;
-            let bet_direction = *bet_direction;
+            let direction = *direction;
             let req = hon_worker_common::VoteRequest {
                 post_canister,
                 post_id,
                 vote_amount: bet_amount as u128,
-                direction: bet_direction.into(),
+                direction: direction.into(),
             };
 
-            let identity = cans.identity();
-            let sender = identity.sender().unwrap();
-            let sig = sign_vote_request(identity, req.clone());
-
             let post_mix = post.clone();
             send_wrap(async move {
-                let sig = sig.ok()?;
+                let identity = cans.identity();
+                let sender = identity.sender().unwrap();
+                // the worker rejects the vote anyway, but only after it is signed and sent
+                match load_cents_balance(sender).await {
+                    Ok(balance) if balance < Nat::from(bet_amount) => {
+                        stake_error.set(Some(StakeError::InsufficientBalance.to_string()));
+                        bet_direction.set(None);
+                        return None;
+                    }
+                    Ok(_) => (),
+                    Err(e) => log::warn!("{e}, voting without a balance check"),
+                }
+                let sig = sign_vote_request(identity, req.clone()).ok()?;
                 let res = vote_with_cents_on_post(sender, req, sig).await;
                 match res {
                     Ok(_) => {
+                        set_last_stake.set(bet_amount);
                         let is_logged_in = is_connected.get_untracked();
                         let global = MixpanelGlobalProps::try_get(&cans, is_logged_in);
 
@@ -144,7 +195,7 @@ fn HNButtonOverlay(
                             canister_id: global.canister_id,
                             is_nsfw_enabled: global.is_nsfw_enabled,
                             game_type: MixpanelPostGameType::HotOrNot,
-                            option_chosen: bet_direction,
+                            option_chosen: direction,
                             publisher_user_id: post_mix.poster_principal.to_text(),
                             video_id: post_mix.uid.clone(),
                             view_count: post_mix.views,
@@ -183,29 +234,40 @@ fn HNButtonOverlay(
         }
     });
 
+    // stepping through the ladder replaces a custom stake
+    let step = move |up: bool| {
+        coin.update(|c| *c = ladder.with_value(|l| if up { l.next(*c) } else { l.prev(*c) }));
+        show_custom.set(false);
+        stake_error.set(None);
+    };
+
     view! {
         <AuthCansProvider let:canisters>
 
             {
                 Effect::new(move |_| {
-                    let Some(bet_direction) = bet_direction() else {
+                    let Some(direction) = bet_direction() else {
                         return;
                     };
-                    let bet_amount = coin.get_untracked().into();
-                    place_bet_action.dispatch((canisters.clone(), bet_direction, bet_amount));
+                    if stake_error.get_untracked().is_some() {
+                        bet_direction.set(None);
+                        return;
+                    }
+                    let bet_amount = coin.get_untracked();
+                    place_bet_action.dispatch((canisters.clone(), direction, bet_amount));
                 });
             }
 
         </AuthCansProvider>
 
         <div class="flex justify-center w-full touch-manipulation">
-            <button disabled=running on:click=move |_| coin.update(|c| *c = c.wrapping_next())>
+            <button disabled=running on:click=move |_| step(true)>
                 <Icon attr:class="justify-self-end text-2xl text-white" icon=icondata::AiUpOutlined />
             </button>
         </div>
         <div class="flex flex-row gap-6 justify-center items-center w-full touch-manipulation">
             <HNButton disabled=running bet_direction kind=VoteKind::Hot />
-            <button disabled=running on:click=move |_| coin.update(|c| *c = c.wrapping_next())>
+            <button disabled=running on:click=move |_| step(true)>
                 <CoinStateView
                     disabled=running
                     class="w-12 h-12 md:w-14 md:h-14 lg:w-16 lg:h-16 drop-shadow-lg"
@@ -220,12 +282,31 @@ fn HNButtonOverlay(
         <div class="flex gap-6 justify-center items-center pt-2 w-full text-base font-medium text-center md:text-lg lg:text-xl touch-manipulation">
             <p class="w-14 md:w-16 lg:w-18">Hot</p>
             <div class="flex justify-center w-12 md:w-14 lg:w-16">
-                <button disabled=running on:click=move |_| coin.update(|c| *c = c.wrapping_prev())>
+                <button disabled=running on:click=move |_| step(false)>
                     <Icon attr:class="text-2xl text-white" icon=icondata::AiDownOutlined />
                 </button>
             </div>
             <p class="w-14 md:w-16 lg:w-18">Not</p>
         </div>
+        <div class="flex flex-col gap-1 items-center pt-2 w-full text-sm touch-manipulation">
+            <Show
+                when=show_custom
+                fallback=move || {
+                    view! {
+                        <button
+                            disabled=running
+                            class="underline text-neutral-300"
+                            on:click=move |_| show_custom.set(true)
+                        >
+                            "Custom stake"
+                        </button>
+                    }
+                }
+            >
+                <CustomStakeInput ladder coin stake_error />
+            </Show>
+            {move || stake_error.get().map(|e| view! { <p class="text-red-500">{e}</p> })}
+        </div>
         <ShadowBg />
     }
 }
@@ -275,16 +356,7 @@ fn HNWonLost(game_result: GameResult, vote_amount: u64) -> impl IntoView {
             TokenBalance::new(lose_amt.into(), 0).humanize()
         ),
     };
-    let bet_amount = vote_amount;
-    let coin = match bet_amount {
-        50 => CoinState::C50,
-        100 => CoinState::C100,
-        200 => CoinState::C200,
-        amt => {
-            log::warn!("Invalid bet amount: {amt}, using fallback");
-            CoinState::C50
-        }
-    };
+    let coin = vote_amount;
 
     view! {
         <div class="flex gap-6 justify-center items-center p-4 w-full bg-transparent rounded-xl shadow-sm">
@@ -331,7 +403,7 @@ pub fn HNUserParticipation(
     };
     let vote_amount: u64 = vote_amount
         .try_into()
-        .expect("We only allow voting with 200 max, so this is alright");
+        .expect("stakes are capped at `MAX_STAKE_CAP`, so this is alright");
     view! {
         <HNWonLost game_result vote_amount />
         <ShadowBg />
@@ -359,7 +431,7 @@ fn ShadowBg() -> impl IntoView {
 #[component]
 pub fn HNGameOverlay(post: PostDetails) -> impl IntoView {
     let bet_direction = RwSignal::new(None::<VoteKind>);
-    let coin = RwSignal::new(CoinState::C50);
+    let stake_ladder = OnceResource::new(send_wrap(get_stake_ladder()));
 
     let refetch_bet = Trigger::new();
     let post = StoredValue::new(post);
@@ -397,7 +469,7 @@ pub fn HNGameOverlay(post: PostDetails) -> impl IntoView {
                 let post = post.get_value();
                 let game_info = cans
                     .fetch_game_with_sats_info(
-                        reqwest::Url::parse(WORKER_URL).unwrap(),
+                        HON_WORKER_URL.clone(),
                         (post.canister_id, post.post_id).into(),
                     )
                     .await?;
@@ -414,22 +486,24 @@ pub fn HNGameOverlay(post: PostDetails) -> impl IntoView {
                     .and_then(|res| {
                         let participation = try_or_redirect_opt!(res.as_ref());
                         let post = post.get_value();
-                        Some(
-                            if let Some(participation) = participation {
-                                view! {
-                                    <HNUserParticipation post refetch_bet participation=participation.clone() />
-                                }.into_any()
-                            } else {
-                                view! {
-                                    <HNButtonOverlay
-                                        post
-                                        bet_direction
-                                        coin
-                                        refetch_bet
-                                    />
-                                }.into_any()
-                            },
-                        )
+                        if let Some(participation) = participation {
+                            return Some(view! {
+                                <HNUserParticipation post refetch_bet participation=participation.clone() />
+                            }.into_any());
+                        }
+                        // the server checks stakes against its ladder anyway
+                        let ladder = stake_ladder.get()?.unwrap_or_else(|e| {
+                            log::warn!("failed to load stake ladder: {e}");
+                            StakeLadder::default()
+                        });
+                        Some(view! {
+                            <HNButtonOverlay
+                                post
+                                ladder
+                                bet_direction
+                                refetch_bet
+                            />
+                        }.into_any())
                     })
                     .unwrap_or_else(|| view! { <LoaderWithShadowBg /> }.into_any())
                 }
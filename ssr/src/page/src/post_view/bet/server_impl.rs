@@ -1,122 +1,119 @@
 use candid::Principal;
 use hon_worker_common::{VoteRequest, VoteRes};
 use leptos::prelude::*;
+use server_fn::codec::Json;
 use yral_identity::Signature;
 
-#[server(endpoint = "vote", input = server_fn::codec::Json)]
+use super::stake::StakeLadder;
+
+/// Stake ladder of the app being served
+#[server(endpoint = "stake_ladder", input = Json, output = Json)]
+pub async fn get_stake_ladder() -> Result<StakeLadder, ServerFnError> {
+    use super::stake::StakeConfig;
+    use utils::host::get_host;
+
+    let config: StakeConfig = expect_context();
+    Ok(config.ladder(&get_host()))
+}
+
+#[server(endpoint = "vote", input = Json)]
 pub async fn vote_with_cents_on_post(
     sender: Principal,
     req: VoteRequest,
     sig: Signature,
 ) -> Result<VoteRes, ServerFnError> {
-    #[cfg(feature = "alloydb")]
-    use alloydb::vote_with_cents_on_post;
-    #[cfg(not(feature = "alloydb"))]
-    use mock::vote_with_cents_on_post;
+    use hon_worker_common::{GameResult, HoNGameVoteReq};
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+    use utils::request_id::PropagateRequestId;
 
-    vote_with_cents_on_post(sender, req, sig).await
-}
+    let ladder = get_stake_ladder().await?;
+    let stake = u64::try_from(req.vote_amount).unwrap_or(u64::MAX);
+    ladder
+        .check(stake)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some((worker_url, jwt)) = vote_worker() else {
+        return Ok(VoteRes {
+            game_result: GameResult::Win {
+                win_amt: 0u32.into(),
+            },
+        });
+    };
+
+    let Some(post_info) = unauth_canister_backend()
+        .get_post_details(req.post_canister, req.post_id)
+        .await?
+    else {
+        return Err(ServerFnError::new("post not found"));
+    };
+    let sentiment = post_sentiment(&post_info.uid).await?;
+
+    let worker_req = HoNGameVoteReq {
+        request: req,
+        fetched_sentiment: sentiment,
+        signature: sig,
+        post_creator: Some(post_info.poster_principal),
+    };
+
+    let req_url = worker_url
+        .join(&format!("vote/{sender}"))
+        .expect("Url to be valid");
+    let mut worker = reqwest::Client::new().post(req_url).json(&worker_req);
+    if let Some(jwt) = jwt {
+        worker = worker.header("Authorization", format!("Bearer {}", jwt.0));
+    }
+    let res = worker.propagate_request_id().send().await?;
 
-#[cfg(feature = "alloydb")]
-mod alloydb {
-    use super::*;
-    use hon_worker_common::{HoNGameVoteReq, HotOrNot, VoteRequest, VoteRes, WORKER_URL};
-
-    pub async fn vote_with_cents_on_post(
-        sender: Principal,
-        req: VoteRequest,
-        sig: Signature,
-    ) -> Result<VoteRes, ServerFnError> {
-        use state::alloydb::AlloyDbInstance;
-        use state::server::HonWorkerJwt;
-        use yral_canisters_common::Canisters;
-
-        let cans: Canisters<false> = expect_context();
-        let Some(post_info) = cans
-            .get_post_details(req.post_canister, req.post_id)
-            .await?
-        else {
-            return Err(ServerFnError::new("post not found"));
-        };
-        // sanitization is not required here, as get_post_details verifies that the post is valid
-        // and exists on cloudflare
-        let query = format!(
-            "select hot_or_not_evaluator.get_hot_or_not('{}')",
-            post_info.uid
-        );
-
-        let alloydb: AlloyDbInstance = expect_context();
-        let mut res = alloydb.execute_sql_raw(query).await?;
-        let mut res = res
-            .sql_results
-            .pop()
-            .expect("hot_or_not_evaluator.get_hot_or_not MUST return a result");
-        let mut res = res
-            .rows
-            .pop()
-            .expect("hot_or_not_evaluator.get_hot_or_not MUST return a row");
-        let res = res
-            .values
-            .pop()
-            .expect("hot_or_not_evaluator.get_hot_or_not MUST return a value");
-
-        let res = res.value.clone().map(|v| v.to_uppercase());
-        let sentiment = match res.as_deref() {
-            Some("TRUE") => HotOrNot::Hot,
-            Some("FALSE") => HotOrNot::Not,
-            None => HotOrNot::Not,
-            _ => {
-                return Err(ServerFnError::new(
-                    "hot_or_not_evaluator.get_hot_or_not MUST return a boolean",
-                ));
-            }
-        };
-
-        let worker_req = HoNGameVoteReq {
-            request: req,
-            fetched_sentiment: sentiment,
-            signature: sig,
-            post_creator: Some(post_info.poster_principal),
-        };
-
-        let req_url = format!("{WORKER_URL}vote/{sender}");
-        let client = reqwest::Client::new();
-        let jwt = expect_context::<HonWorkerJwt>();
-        let res = client
-            .post(&req_url)
-            .json(&worker_req)
-            .header("Authorization", format!("Bearer {}", jwt.0))
-            .send()
-            .await?;
-
-        if res.status() != reqwest::StatusCode::OK {
-            return Err(ServerFnError::new(format!(
-                "worker error: {}",
-                res.text().await?
-            )));
-        }
-
-        let vote_res: VoteRes = res.json().await?;
-
-        Ok(vote_res)
+    if res.status() != reqwest::StatusCode::OK {
+        return Err(ServerFnError::new(format!(
+            "worker error: {}",
+            res.text().await?
+        )));
     }
+
+    let vote_res: VoteRes = res.json().await?;
+
+    Ok(vote_res)
 }
 
-#[cfg(not(feature = "alloydb"))]
-mod mock {
-    use hon_worker_common::GameResult;
+/// Hot or not worker votes are sent to, along with its jwt
+#[cfg(all(feature = "ssr", feature = "alloydb"))]
+fn vote_worker() -> Option<(reqwest::Url, Option<state::server::HonWorkerJwt>)> {
+    use state::server::HonWorkerUrl;
 
-    use super::*;
+    Some((HonWorkerUrl::current(), Some(expect_context())))
+}
 
-    pub async fn vote_with_cents_on_post(
-        _sender: Principal,
-        _req: VoteRequest,
-        _sig: Signature,
-    ) -> Result<VoteRes, ServerFnError> {
-        Ok(VoteRes {
-            game_result: GameResult::Win {
-                win_amt: 0u32.into(),
-            },
-        })
-    }
+/// Without the sentiment db votes only reach a worker registered as a [`HonWorkerUrl`] (tests
+/// and `mock-upstreams`), the game is mocked otherwise
+///
+/// [`HonWorkerUrl`]: state::server::HonWorkerUrl
+#[cfg(all(feature = "ssr", not(feature = "alloydb")))]
+fn vote_worker() -> Option<(reqwest::Url, Option<state::server::HonWorkerJwt>)> {
+    use state::server::HonWorkerUrl;
+
+    use_context::<HonWorkerUrl>().map(|url| (url.0, None))
+}
+
+/// Sentiment of the video `uid`, which decides the game
+#[cfg(all(feature = "ssr", feature = "alloydb"))]
+async fn post_sentiment(uid: &str) -> Result<hon_worker_common::HotOrNot, ServerFnError> {
+    use hon_worker_common::HotOrNot;
+    use state::alloydb::{queries::HOT_OR_NOT_SENTIMENT, AlloyDbInstance};
+
+    let alloydb: AlloyDbInstance = expect_context();
+    let (is_hot,) = alloydb
+        .query_one(&HOT_OR_NOT_SENTIMENT, &[uid.into()])
+        .await?;
+    Ok(if is_hot.unwrap_or(false) {
+        HotOrNot::Hot
+    } else {
+        HotOrNot::Not
+    })
+}
+
+/// Without the sentiment db every video is hot, only used with a registered mock worker
+#[cfg(all(feature = "ssr", not(feature = "alloydb")))]
+async fn post_sentiment(_uid: &str) -> Result<hon_worker_common::HotOrNot, ServerFnError> {
+    Ok(hon_worker_common::HotOrNot::Hot)
 }
@@ -0,0 +1,236 @@
+//! Stakes of the hot or not game
+//! the ladder the coin cycles through is configured per app, users can also enter a custom
+//! stake within the ladder's bounds
+use serde::{Deserialize, Serialize};
+
+/// Stakes offered when no ladder is configured for an app
+pub const DEFAULT_STAKE_LADDER: [u64; 3] = [50, 100, 200];
+/// Smallest stake allowed
+pub const MIN_STAKE: u64 = 10;
+/// Largest max stake a ladder can be configured with
+pub const MAX_STAKE_CAP: u64 = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StakeError {
+    #[error("Stake must be at least {0}")]
+    BelowMin(u64),
+    #[error("Stake can be at most {0}")]
+    AboveMax(u64),
+    #[error("Not enough balance for this stake")]
+    InsufficientBalance,
+}
+
+/// Invalid stake ladder
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StakeLadderError {
+    #[error("invalid stake {0:?}")]
+    InvalidStake(String),
+    #[error("max stake {0} is above the cap of {MAX_STAKE_CAP}")]
+    AboveCap(u64),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakeLadder {
+    /// stakes the coin cycles through, ascending
+    pub options: Vec<u64>,
+    /// largest stake allowed, custom stakes included
+    pub max: u64,
+}
+
+impl Default for StakeLadder {
+    fn default() -> Self {
+        Self::new(DEFAULT_STAKE_LADDER.to_vec(), None).expect("default ladder is within the cap")
+    }
+}
+
+impl StakeLadder {
+    /// Ladder of `options`, the max defaults to the largest option
+    /// options outside [`MIN_STAKE`]..=max are dropped, the max can't exceed [`MAX_STAKE_CAP`]
+    pub fn new(mut options: Vec<u64>, max: Option<u64>) -> Result<Self, StakeLadderError> {
+        options.sort_unstable();
+        options.dedup();
+        let max = max
+            .or(options.last().copied())
+            .unwrap_or(DEFAULT_STAKE_LADDER[2])
+            .max(MIN_STAKE);
+        if max > MAX_STAKE_CAP {
+            return Err(StakeLadderError::AboveCap(max));
+        }
+        options.retain(|stake| (MIN_STAKE..=max).contains(stake));
+        if options.is_empty() {
+            options.push(MIN_STAKE);
+        }
+        Ok(Self { options, max })
+    }
+
+    /// Parse comma separated stakes, e.g. `50,100,200`
+    pub fn parse(options: &str, max: Option<u64>) -> Result<Self, StakeLadderError> {
+        let options = options
+            .split(',')
+            .map(|stake| {
+                stake
+                    .trim()
+                    .parse()
+                    .map_err(|_| StakeLadderError::InvalidStake(stake.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(options, max)
+    }
+
+    pub fn check(&self, stake: u64) -> Result<u64, StakeError> {
+        if stake < MIN_STAKE {
+            Err(StakeError::BelowMin(MIN_STAKE))
+        } else if stake > self.max {
+            Err(StakeError::AboveMax(self.max))
+        } else {
+            Ok(stake)
+        }
+    }
+
+    /// Stake to start with, the last used one if it is still allowed
+    pub fn initial(&self, last_used: Option<u64>) -> u64 {
+        last_used
+            .and_then(|stake| self.check(stake).ok())
+            .unwrap_or(self.options[0])
+    }
+
+    /// Option after `stake`, wrapping around
+    /// a custom stake moves to the nearest larger option
+    pub fn next(&self, stake: u64) -> u64 {
+        self.options
+            .iter()
+            .copied()
+            .find(|option| *option > stake)
+            .unwrap_or(self.options[0])
+    }
+
+    /// Option before `stake`, wrapping around
+    pub fn prev(&self, stake: u64) -> u64 {
+        self.options
+            .iter()
+            .copied()
+            .rev()
+            .find(|option| *option < stake)
+            .unwrap_or(self.options[self.options.len() - 1])
+    }
+}
+
+#[cfg(feature = "ssr")]
+mod config {
+    use std::{collections::HashMap, env};
+
+    use state::app_type::AppType;
+
+    use super::{StakeLadder, StakeLadderError};
+
+    /// Stake ladder of every app
+    /// read from `HON_STAKE_LADDER_<APP>` (comma separated stakes) and `HON_MAX_STAKE_<APP>`
+    #[derive(Clone, Debug, Default)]
+    pub struct StakeConfig(HashMap<AppType, StakeLadder>);
+
+    /// Malformed stake ladder env var
+    #[derive(Debug, thiserror::Error)]
+    #[error("`{var}`: {error}")]
+    pub struct StakeConfigError {
+        pub var: String,
+        pub error: StakeLadderError,
+    }
+
+    fn env_suffix(app: &AppType) -> &'static str {
+        match app {
+            AppType::YRAL => "YRAL",
+            AppType::HotOrNot => "HOTORNOT",
+            AppType::ICPump => "ICPUMP",
+            AppType::Pumpdump => "PUMPDUMP",
+        }
+    }
+
+    impl StakeConfig {
+        pub fn new(ladders: impl IntoIterator<Item = (AppType, StakeLadder)>) -> Self {
+            Self(ladders.into_iter().collect())
+        }
+
+        pub fn from_env() -> Result<Self, StakeConfigError> {
+            let apps = [
+                AppType::YRAL,
+                AppType::HotOrNot,
+                AppType::ICPump,
+                AppType::Pumpdump,
+            ];
+            let mut ladders = vec![];
+            for app in apps {
+                let suffix = env_suffix(&app);
+                let ladder_var = format!("HON_STAKE_LADDER_{suffix}");
+                let Ok(options) = env::var(&ladder_var) else {
+                    continue;
+                };
+                let max_var = format!("HON_MAX_STAKE_{suffix}");
+                let max = match env::var(&max_var) {
+                    Ok(max) => Some(max.trim().parse().map_err(|_| StakeConfigError {
+                        var: max_var,
+                        error: StakeLadderError::InvalidStake(max),
+                    })?),
+                    Err(_) => None,
+                };
+                let ladder =
+                    StakeLadder::parse(&options, max).map_err(|error| StakeConfigError {
+                        var: ladder_var,
+                        error,
+                    })?;
+                ladders.push((app, ladder));
+            }
+            Ok(Self::new(ladders))
+        }
+
+        /// Ladder of the app served on `host`
+        pub fn ladder(&self, host: &str) -> StakeLadder {
+            self.0
+                .get(&AppType::from_host(host))
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use config::{StakeConfig, StakeConfigError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_ladder() {
+        let ladder = StakeLadder::default();
+        assert_eq!(ladder.initial(None), 50);
+        assert_eq!(ladder.next(50), 100);
+        assert_eq!(ladder.next(200), 50);
+        assert_eq!(ladder.prev(50), 200);
+        // custom stakes move to the nearest option
+        assert_eq!(ladder.next(75), 100);
+        assert_eq!(ladder.prev(75), 50);
+    }
+
+    #[test]
+    fn checks_custom_stakes() {
+        let ladder = StakeLadder::parse("200, 25,100", Some(500)).unwrap();
+        assert_eq!(ladder.options, vec![25, 100, 200]);
+        assert_eq!(ladder.check(300), Ok(300));
+        assert_eq!(ladder.check(501), Err(StakeError::AboveMax(500)));
+        assert_eq!(ladder.check(5), Err(StakeError::BelowMin(MIN_STAKE)));
+        assert_eq!(ladder.initial(Some(300)), 300);
+        assert_eq!(ladder.initial(Some(1000)), 25);
+        assert!(StakeLadder::parse("50,lots", None).is_err());
+    }
+
+    #[test]
+    fn rejects_max_above_cap() {
+        assert_eq!(
+            StakeLadder::new(vec![50], Some(MAX_STAKE_CAP + 1)),
+            Err(StakeLadderError::AboveCap(MAX_STAKE_CAP + 1))
+        );
+        // the max defaults to the largest option
+        assert!(StakeLadder::parse("50,1000000", None).is_err());
+        assert!(StakeLadder::new(vec![50], Some(MAX_STAKE_CAP)).is_ok());
+    }
+}
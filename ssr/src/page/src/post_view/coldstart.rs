@@ -0,0 +1,153 @@
+//! Coldstart feed cached per content filter, used to pick the first post while rendering `/`
+//! a background warmer keeps the cached feeds fresh so requests rarely wait on the ML feed
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use candid::Principal;
+use leptos::prelude::ServerFnError;
+use utils::ml_feed::{get_ml_feed, FeedContent};
+use yral_types::post::PostItem;
+
+use super::feed_provider::NSFW_THRESHOLD;
+
+/// Posts fetched per cached feed
+const COLDSTART_FEED_SIZE: u32 = 20;
+/// Age after which a cached feed is refetched by the request reading it
+const COLDSTART_TTL: Duration = Duration::from_secs(10 * 60);
+/// Interval at which the warmer refetches every cached feed
+const WARM_INTERVAL: Duration = Duration::from_secs(4 * 60);
+
+struct CachedFeed {
+    posts: Vec<PostItem>,
+    fetched_at: Instant,
+}
+
+#[derive(Clone, Default)]
+pub struct ColdstartCache {
+    /// content -> coldstart feed, the ML feed is the same for every host so there is at most one
+    /// feed per [`FeedContent`]
+    feeds: Arc<RwLock<HashMap<FeedContent, CachedFeed>>>,
+    /// content -> lock held while its feed is fetched, so concurrent misses share one fetch
+    fetches: Arc<Mutex<HashMap<FeedContent, Arc<futures::lock::Mutex<()>>>>>,
+    /// posts served instead of the ML feed
+    fixed: Option<Arc<Vec<PostItem>>>,
+}
+
+impl ColdstartCache {
+    /// Cache which never reaches the ML feed and serves `posts`
+    /// e.g. for deterministic renders in tests
+    pub fn fixed(posts: Vec<PostItem>) -> Self {
+        Self {
+            fixed: Some(Arc::new(posts)),
+            ..Default::default()
+        }
+    }
+
+    async fn fetch(content: FeedContent) -> Result<Vec<PostItem>, ServerFnError> {
+        get_ml_feed(
+            content,
+            true,
+            Principal::anonymous(),
+            COLDSTART_FEED_SIZE,
+            vec![],
+        )
+        .await
+        .map_err(|e| ServerFnError::new(format!("failed to fetch {content:?} coldstart feed: {e}")))
+    }
+
+    /// Cached feed of `content` and whether it is older than `max_age`
+    fn cached(&self, content: FeedContent, max_age: Duration) -> Option<(Vec<PostItem>, bool)> {
+        let feeds = self.feeds.read().unwrap();
+        let feed = feeds.get(&content)?;
+        Some((feed.posts.clone(), feed.fetched_at.elapsed() >= max_age))
+    }
+
+    /// Fetch the feed of `content` unless it was fetched within `max_age`, e.g. by a concurrent
+    /// request, a stale feed is returned if the ML feed is unavailable
+    async fn refresh(
+        &self,
+        content: FeedContent,
+        max_age: Duration,
+    ) -> Result<Vec<PostItem>, ServerFnError> {
+        let lock = self
+            .fetches
+            .lock()
+            .unwrap()
+            .entry(content)
+            .or_default()
+            .clone();
+        let _fetching = lock.lock().await;
+
+        let stale = match self.cached(content, max_age) {
+            Some((posts, false)) => return Ok(posts),
+            Some((posts, true)) => Some(posts),
+            None => None,
+        };
+        match Self::fetch(content).await {
+            Ok(posts) => {
+                let feed = CachedFeed {
+                    posts: posts.clone(),
+                    fetched_at: Instant::now(),
+                };
+                self.feeds.write().unwrap().insert(content, feed);
+                Ok(posts)
+            }
+            Err(e) => match stale {
+                Some(posts) if !posts.is_empty() => {
+                    log::warn!("{e}, serving the stale feed");
+                    Ok(posts)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Coldstart feed of `content`, fetched if missing or older than [`COLDSTART_TTL`]
+    /// a stale feed is returned if the ML feed is unavailable
+    pub async fn posts(&self, content: FeedContent) -> Result<Vec<PostItem>, ServerFnError> {
+        if let Some(posts) = &self.fixed {
+            return Ok(posts
+                .iter()
+                .filter(|post| match content {
+                    FeedContent::Clean => post.nsfw_probability <= NSFW_THRESHOLD,
+                    FeedContent::Nsfw => post.nsfw_probability > NSFW_THRESHOLD,
+                    FeedContent::Mixed => true,
+                })
+                .cloned()
+                .collect());
+        }
+
+        match self.cached(content, COLDSTART_TTL) {
+            Some((posts, false)) => Ok(posts),
+            _ => self.refresh(content, COLDSTART_TTL).await,
+        }
+    }
+
+    /// Refetch every cached feed
+    pub async fn warm(&self) {
+        let contents: Vec<_> = self.feeds.read().unwrap().keys().copied().collect();
+        for content in contents {
+            // skipped if a request refetched it since the last tick
+            if let Err(e) = self.refresh(content, WARM_INTERVAL / 2).await {
+                log::warn!("{e}, keeping the cached {content:?} feed");
+            }
+        }
+    }
+
+    /// Warm the cache every [`WARM_INTERVAL`] until the server stops
+    /// feeds are cached once a content filter is first requested
+    pub fn spawn_warmer(&self) {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let start = tokio::time::Instant::now() + WARM_INTERVAL;
+            let mut interval = tokio::time::interval_at(start, WARM_INTERVAL);
+            loop {
+                interval.tick().await;
+                cache.warm().await;
+            }
+        });
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use candid::Principal;
+use codee::string::JsonSerdeCodec;
+use consts::USER_CANISTER_ID_STORE;
+use leptos::prelude::*;
+use leptos_use::storage::use_local_storage;
+use utils::ml_feed::interactions::{
+    send_interactions, send_interactions_keepalive, PostInteraction,
+};
+use yral_canisters_common::utils::{posts::PostDetails, time::current_epoch};
+
+/// Finished interactions sent to the ML feed in a single request
+const BATCH_SIZE: usize = 10;
+/// Watching past this percentage counts as finishing the video
+const FINISHED_PERCENT: u8 = 95;
+
+type PostKey = (Principal, u64);
+
+/// Engagement of the posts being watched, reported to the ML feed in batches
+/// a post's interaction is finished once it stops being the current post
+#[derive(Clone, Copy)]
+pub struct EngagementCtx {
+    watching: StoredValue<HashMap<PostKey, PostInteraction>>,
+    finished: StoredValue<Vec<PostInteraction>>,
+    user_canister: Signal<Option<Principal>>,
+}
+
+impl Default for EngagementCtx {
+    fn default() -> Self {
+        let (user_canister, _, _) =
+            use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
+        Self::new(user_canister)
+    }
+}
+
+impl EngagementCtx {
+    pub fn new(user_canister: Signal<Option<Principal>>) -> Self {
+        Self {
+            watching: StoredValue::new(HashMap::new()),
+            finished: StoredValue::new(vec![]),
+            user_canister,
+        }
+    }
+
+    pub fn update(&self, post: &PostDetails, f: impl FnOnce(&mut PostInteraction)) {
+        self.watching.update_value(|watching| {
+            let interaction = watching
+                .entry((post.canister_id, post.post_id))
+                .or_insert_with(|| PostInteraction {
+                    canister_id: post.canister_id,
+                    post_id: post.post_id,
+                    video_id: post.uid.clone(),
+                    percent_watched: 0,
+                    loops: 0,
+                    skipped_at_secs: None,
+                    muted: true,
+                    liked: false,
+                    shared: false,
+                    timestamp_ms: current_epoch().as_millis() as u64,
+                });
+            f(interaction)
+        });
+    }
+
+    /// Record playback progress, `position` and `duration` are in seconds
+    pub fn record_progress(&self, post: &PostDetails, position: f64, duration: f64, muted: bool) {
+        if !duration.is_finite() || duration <= 0.0 {
+            return;
+        }
+        let percent = ((position / duration) * 100.0).clamp(0.0, 100.0) as u8;
+        self.update(post, |interaction| {
+            interaction.muted = muted;
+            if interaction.loops == 0 {
+                interaction.percent_watched = interaction.percent_watched.max(percent);
+            }
+            interaction.skipped_at_secs = Some(position);
+        });
+    }
+
+    /// The video restarted after reaching its end
+    pub fn record_loop(&self, post: &PostDetails) {
+        self.update(post, |interaction| {
+            interaction.percent_watched = 100;
+            interaction.loops += 1;
+        });
+    }
+
+    /// Move the post's interaction to the next batch
+    pub fn finish(&self, post: &PostDetails) {
+        let Some(mut interaction) = self
+            .watching
+            .try_update_value(|watching| watching.remove(&(post.canister_id, post.post_id)))
+            .flatten()
+        else {
+            return;
+        };
+        if interaction.loops > 0 || interaction.percent_watched >= FINISHED_PERCENT {
+            interaction.skipped_at_secs = None;
+        }
+
+        let batch = self.finished.try_update_value(|finished| {
+            finished.push(interaction);
+            if finished.len() >= BATCH_SIZE {
+                Some(std::mem::take(finished))
+            } else {
+                None
+            }
+        });
+        if let Some(batch) = batch.flatten() {
+            self.send(batch, false);
+        }
+    }
+
+    /// Send the finished interactions without waiting for a full batch
+    /// used when the page is hidden or the feed is left, so the request outlives the page
+    pub fn flush(&self) {
+        let Some(batch) = self.finished.try_update_value(std::mem::take) else {
+            return;
+        };
+        if !batch.is_empty() {
+            self.send(batch, true);
+        }
+    }
+
+    fn send(&self, batch: Vec<PostInteraction>, keepalive: bool) {
+        let Some(user_canister) = self.user_canister.get_untracked() else {
+            log::warn!(
+                "user canister not known, dropping {} interactions",
+                batch.len()
+            );
+            return;
+        };
+        if keepalive {
+            if let Err(e) = send_interactions_keepalive(user_canister, &batch) {
+                log::warn!("failed to send feed interactions: {e:?}");
+            }
+            return;
+        }
+        leptos::task::spawn_local(async move {
+            if let Err(e) = send_interactions(user_canister, &batch).await {
+                log::warn!("failed to send feed interactions: {e:?}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use candid::Principal;
+    use leptos::prelude::*;
+    use yral_canisters_common::utils::posts::PostDetails;
+
+    use super::{EngagementCtx, BATCH_SIZE};
+
+    fn post(post_id: u64) -> PostDetails {
+        PostDetails {
+            canister_id: Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap(),
+            post_id,
+            uid: format!("uid-{post_id}"),
+            description: String::new(),
+            views: 0,
+            likes: 0,
+            display_name: String::new(),
+            propic_url: String::new(),
+            liked_by_user: None,
+            poster_principal: Principal::anonymous(),
+            hastags: vec![],
+            is_nsfw: false,
+            hot_or_not_feed_ranking_score: None,
+            created_at: Duration::from_secs(1_700_000_000),
+            nsfw_probability: 0.0,
+        }
+    }
+
+    fn ctx() -> EngagementCtx {
+        EngagementCtx::new(Signal::stored(None))
+    }
+
+    #[test]
+    fn progress_keeps_the_furthest_point() {
+        let ctx = ctx();
+        let post = post(1);
+
+        ctx.record_progress(&post, 6.0, 10.0, true);
+        ctx.record_progress(&post, 3.0, 10.0, false);
+        ctx.record_progress(&post, 5.0, f64::NAN, true);
+
+        let interaction = ctx
+            .watching
+            .with_value(|watching| watching[&(post.canister_id, post.post_id)].clone());
+        assert_eq!(interaction.percent_watched, 60);
+        assert_eq!(interaction.skipped_at_secs, Some(3.0));
+        assert!(!interaction.muted);
+    }
+
+    #[test]
+    fn loops_count_as_fully_watched() {
+        let ctx = ctx();
+        let post = post(1);
+
+        ctx.record_progress(&post, 9.0, 10.0, true);
+        ctx.record_loop(&post);
+        ctx.record_loop(&post);
+        ctx.record_progress(&post, 2.0, 10.0, true);
+
+        let interaction = ctx
+            .watching
+            .with_value(|watching| watching[&(post.canister_id, post.post_id)].clone());
+        assert_eq!(interaction.percent_watched, 100);
+        assert_eq!(interaction.loops, 2);
+    }
+
+    #[test]
+    fn finish_moves_the_interaction_to_the_batch() {
+        let ctx = ctx();
+        let skipped = post(1);
+        let watched = post(2);
+
+        ctx.record_progress(&skipped, 2.0, 10.0, true);
+        ctx.record_progress(&watched, 9.8, 10.0, true);
+        ctx.finish(&skipped);
+        ctx.finish(&watched);
+        // not being watched
+        ctx.finish(&post(3));
+
+        assert!(ctx.watching.with_value(|watching| watching.is_empty()));
+        let finished = ctx.finished.get_value();
+        assert_eq!(finished.len(), 2);
+        assert_eq!(finished[0].skipped_at_secs, Some(2.0));
+        assert_eq!(finished[1].skipped_at_secs, None);
+    }
+
+    #[test]
+    fn full_batches_are_sent() {
+        let ctx = ctx();
+        for post_id in 0..BATCH_SIZE as u64 {
+            let post = post(post_id);
+            ctx.record_progress(&post, 1.0, 10.0, true);
+            ctx.finish(&post);
+        }
+
+        // without a user canister the batch is dropped instead of sent
+        assert!(ctx.finished.with_value(|finished| finished.is_empty()));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use futures::future::join_all;
+use state::canister_backend::CanisterBackend;
+use yral_canisters_common::Canisters;
+use yral_types::post::PostItem;
+
+use super::{FeedError, FeedProvider, FeedQuery, NSFW_THRESHOLD};
+use crate::post_view::video_iter::FeedResultType;
+
+/// Creators whose latest posts are fetched per batch
+const MAX_CREATORS: usize = 5;
+/// Posts fetched from each creator
+const POSTS_PER_CREATOR: u64 = 10;
+
+/// Latest posts of the creators the user most recently watched, straight from their canisters
+/// only used as a fallback, the posts are not ranked
+pub struct CanisterFeed<'a> {
+    canisters: &'a Canisters<true>,
+}
+
+impl<'a> CanisterFeed<'a> {
+    pub fn new(canisters: &'a Canisters<true>) -> Self {
+        Self { canisters }
+    }
+}
+
+impl FeedProvider for CanisterFeed<'_> {
+    fn result_type(&self, _query: &FeedQuery) -> FeedResultType {
+        FeedResultType::CreatorCanisters
+    }
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError> {
+        let mut creators = vec![];
+        for post in query.seen.iter().rev() {
+            if creators.len() >= MAX_CREATORS {
+                break;
+            }
+            if !creators.contains(&post.canister_id) {
+                creators.push(post.canister_id);
+            }
+        }
+
+        let seen: HashSet<_> = query
+            .seen
+            .iter()
+            .map(|post| (post.canister_id, post.post_id))
+            .collect();
+        let mut posts = vec![];
+        let mut last_err = None;
+        let creator_posts = join_all(creators.into_iter().map(|creator| {
+            CanisterBackend::get_user_posts(self.canisters, creator, 0, POSTS_PER_CREATOR)
+        }))
+        .await;
+        for res in creator_posts {
+            match res {
+                Ok(creator_posts) => posts.extend(creator_posts),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        if posts.is_empty() {
+            if let Some(e) = last_err {
+                return Err(FeedError::Upstream(format!(
+                    "Error fetching creator posts: {e:?}"
+                )));
+            }
+        }
+
+        Ok(posts
+            .into_iter()
+            .filter(|post| !seen.contains(&(post.canister_id, post.post_id)))
+            .filter(|post| query.allow_nsfw || post.nsfw_probability <= NSFW_THRESHOLD)
+            .take(query.limit as usize)
+            .map(|post| PostItem {
+                canister_id: post.canister_id,
+                post_id: post.post_id,
+                video_id: post.uid,
+                nsfw_probability: post.nsfw_probability,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Deserialize;
+use yral_types::post::PostItem;
+
+use super::{FeedError, FeedProvider, FeedQuery, NSFW_THRESHOLD};
+use crate::post_view::video_iter::FeedResultType;
+
+/// Posts served by the mock upstreams, bundled into the app
+const BUNDLED_FIXTURES: &str = include_str!("../../../../mock_upstreams/fixtures/default.json");
+
+#[derive(Deserialize)]
+struct Fixtures {
+    #[serde(default)]
+    posts: Vec<PostItem>,
+}
+
+/// A fixed list of posts, for offline development and as a last resort
+pub struct FixtureFeed {
+    posts: Vec<PostItem>,
+}
+
+impl FixtureFeed {
+    pub fn new(posts: Vec<PostItem>) -> Self {
+        Self { posts }
+    }
+
+    pub fn bundled() -> Self {
+        let fixtures: Fixtures =
+            serde_json::from_str(BUNDLED_FIXTURES).expect("invalid bundled feed fixtures");
+        Self::new(fixtures.posts)
+    }
+}
+
+impl FeedProvider for FixtureFeed {
+    fn result_type(&self, _query: &FeedQuery) -> FeedResultType {
+        FeedResultType::Fixture
+    }
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError> {
+        Ok(self
+            .posts
+            .iter()
+            .filter(|post| {
+                !query.seen.iter().any(|seen| {
+                    seen.canister_id == post.canister_id && seen.post_id == post.post_id
+                })
+            })
+            .filter(|post| query.allow_nsfw || post.nsfw_probability <= NSFW_THRESHOLD)
+            .take(query.limit as usize)
+            .cloned()
+            .collect())
+    }
+}
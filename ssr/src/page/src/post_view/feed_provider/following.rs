@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use futures::future::join_all;
+use state::canister_backend::CanisterBackend;
+use yral_canisters_common::{utils::posts::PostDetails, Canisters};
+use yral_types::post::PostItem;
+
+use super::{FeedError, FeedProvider, FeedQuery, NSFW_THRESHOLD};
+use crate::{follow::followed_creators, post_view::video_iter::FeedResultType};
+
+/// Most recently followed creators queried per batch
+const MAX_CREATORS: u64 = 20;
+/// Latest posts fetched from each creator
+const POSTS_PER_CREATOR: u64 = 10;
+
+/// Latest posts of the creators the user follows, interleaved so no creator dominates a batch
+pub struct FollowingFeed<'a> {
+    canisters: &'a Canisters<true>,
+}
+
+impl<'a> FollowingFeed<'a> {
+    pub fn new(canisters: &'a Canisters<true>) -> Self {
+        Self { canisters }
+    }
+}
+
+impl FeedProvider for FollowingFeed<'_> {
+    fn result_type(&self, _query: &FeedQuery) -> FeedResultType {
+        FeedResultType::Following
+    }
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError> {
+        let creators = followed_creators(0, MAX_CREATORS)
+            .await
+            .map_err(|e| FeedError::Upstream(e.to_string()))?;
+
+        let queued: HashSet<_> = query
+            .seen
+            .iter()
+            .map(|post| (post.canister_id, post.post_id))
+            .collect();
+        let creator_posts = join_all(creators.iter().map(|creator| {
+            CanisterBackend::get_user_posts(
+                self.canisters,
+                creator.canister_id,
+                0,
+                POSTS_PER_CREATOR,
+            )
+        }))
+        .await;
+
+        let mut per_creator: Vec<_> = creator_posts
+            .into_iter()
+            .filter_map(|res| {
+                res.inspect_err(|e| log::warn!("failed to fetch followed creator's posts: {e:?}"))
+                    .ok()
+            })
+            .map(|posts| {
+                posts
+                    .into_iter()
+                    .filter(|post: &PostDetails| {
+                        !queued.contains(&(post.canister_id, post.post_id))
+                            && !query.seen_posts.contains(post.canister_id, post.post_id)
+                            && (query.allow_nsfw || post.nsfw_probability <= NSFW_THRESHOLD)
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            })
+            .collect();
+
+        // round robin over the creators, each creator's posts are newest first
+        let mut posts = vec![];
+        while posts.len() < query.limit as usize {
+            let before = posts.len();
+            for creator in per_creator.iter_mut() {
+                if let Some(post) = creator.next() {
+                    posts.push(PostItem {
+                        canister_id: post.canister_id,
+                        post_id: post.post_id,
+                        video_id: post.uid,
+                        nsfw_probability: post.nsfw_probability,
+                    });
+                }
+            }
+            if posts.len() == before {
+                break;
+            }
+        }
+        posts.truncate(query.limit as usize);
+
+        Ok(posts)
+    }
+}
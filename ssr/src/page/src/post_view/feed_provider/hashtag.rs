@@ -0,0 +1,34 @@
+use yral_types::post::PostItem;
+
+use super::{FeedError, FeedProvider, FeedQuery, NSFW_THRESHOLD};
+use crate::{hashtag::hashtag_posts, post_view::video_iter::FeedResultType};
+
+/// Posts tagged with a hashtag, newest first, from the hashtag index
+pub struct HashtagFeed {
+    hashtag: String,
+    /// offset into the hashtag's posts
+    start: u64,
+}
+
+impl HashtagFeed {
+    pub fn new(hashtag: String, start: u64) -> Self {
+        Self { hashtag, start }
+    }
+}
+
+impl FeedProvider for HashtagFeed {
+    fn result_type(&self, _query: &FeedQuery) -> FeedResultType {
+        FeedResultType::Hashtag
+    }
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError> {
+        let posts = hashtag_posts(self.hashtag.clone(), self.start, query.limit as u64)
+            .await
+            .map_err(|e| FeedError::Upstream(e.to_string()))?;
+
+        Ok(posts
+            .into_iter()
+            .filter(|post| query.allow_nsfw || post.nsfw_probability <= NSFW_THRESHOLD)
+            .collect())
+    }
+}
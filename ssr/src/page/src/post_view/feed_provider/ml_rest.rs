@@ -0,0 +1,76 @@
+use candid::Principal;
+use leptos::{prelude::*, server_fn::codec::Json};
+use utils::ml_feed::{post_details_to_post_item, FeedContent};
+use yral_types::post::PostItem;
+
+use super::{FeedError, FeedProvider, FeedQuery};
+use crate::post_view::video_iter::FeedResultType;
+
+/// The ML feed, proxied so the user's feed feedback is applied before posts reach the client
+#[server(endpoint = "ml_feed", input = Json, output = Json)]
+pub async fn ml_feed(
+    content: FeedContent,
+    coldstart: bool,
+    canister_id: Principal,
+    num_results: u32,
+    filter_results: Vec<PostItem>,
+) -> Result<Vec<PostItem>, ServerFnError> {
+    use crate::post_view::feedback::feedback_for_request;
+    use utils::ml_feed::get_ml_feed;
+
+    let feedback = feedback_for_request().await?;
+    let posts = get_ml_feed(content, coldstart, canister_id, num_results, filter_results)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Error fetching ml feed: {e:?}")))?;
+
+    Ok(posts
+        .into_iter()
+        .filter(|post| feedback.allows(post))
+        .collect())
+}
+
+/// The ML feed server's REST API
+pub struct MlRestFeed {
+    /// always use the global feed
+    coldstart_only: bool,
+}
+
+impl MlRestFeed {
+    pub fn personalized() -> Self {
+        Self {
+            coldstart_only: false,
+        }
+    }
+
+    pub fn coldstart() -> Self {
+        Self {
+            coldstart_only: true,
+        }
+    }
+
+    fn uses_coldstart(&self, query: &FeedQuery) -> bool {
+        self.coldstart_only || query.coldstart
+    }
+}
+
+impl FeedProvider for MlRestFeed {
+    fn result_type(&self, query: &FeedQuery) -> FeedResultType {
+        if self.uses_coldstart(query) {
+            FeedResultType::MLFeedCache
+        } else {
+            FeedResultType::MLFeed
+        }
+    }
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError> {
+        ml_feed(
+            FeedContent::from_nsfw_allowed(query.allow_nsfw),
+            self.uses_coldstart(query),
+            query.user_canister,
+            query.limit,
+            post_details_to_post_item(query.seen.clone()),
+        )
+        .await
+        .map_err(|e| FeedError::Upstream(e.to_string()))
+    }
+}
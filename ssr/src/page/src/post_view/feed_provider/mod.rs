@@ -0,0 +1,349 @@
+//! Sources of feed batches, tried in a configurable fallback chain
+//! the chain is configured with `YRAL_FEED_PROVIDERS` (see [`consts::FEED_PROVIDERS`])
+mod canister;
+mod fixture;
+mod following;
+mod hashtag;
+mod ml_rest;
+
+use std::{collections::BTreeSet, str::FromStr, time::Duration};
+
+use candid::Principal;
+use consts::FEED_PROVIDERS;
+use futures::future::{select, Either};
+use utils::ml_feed::seen_set::SeenSet;
+use web_time::Instant;
+use yral_canisters_common::{utils::posts::PostDetails, Canisters};
+use yral_types::post::PostItem;
+
+pub use canister::CanisterFeed;
+pub use fixture::FixtureFeed;
+pub use following::FollowingFeed;
+pub use hashtag::HashtagFeed;
+pub use ml_rest::MlRestFeed;
+
+use super::video_iter::FeedResultType;
+
+/// Timeout used for providers configured without one
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Posts above this probability are hidden by the fallback providers and search unless NSFW
+/// content is allowed
+pub(crate) const NSFW_THRESHOLD: f32 = 0.4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeedError {
+    #[error("{0}")]
+    Upstream(String),
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("no posts")]
+    Empty,
+    #[error("only seen posts")]
+    AllSeen,
+}
+
+/// Parameters of a single feed batch
+#[derive(Clone, Debug)]
+pub struct FeedQuery {
+    pub user_canister: Principal,
+    pub limit: u32,
+    pub allow_nsfw: bool,
+    /// the user has seen too few posts for a personalized feed
+    pub coldstart: bool,
+    /// the latest posts in the user's queue
+    pub seen: Vec<PostDetails>,
+    /// every post the user has watched recently
+    pub seen_posts: SeenSet,
+    /// canisters of the creators the user hid
+    pub hidden_creators: BTreeSet<Principal>,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait FeedProvider {
+    /// The result type of the batches served for `query`
+    fn result_type(&self, query: &FeedQuery) -> FeedResultType;
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedProviderKind {
+    /// personalized ML feed, global feed during coldstart
+    Ml,
+    /// global ML feed
+    MlColdstart,
+    /// latest posts of the creators in the user's queue
+    Canister,
+    /// posts bundled with the app
+    Fixture,
+}
+
+impl FromStr for FeedProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ml" => Ok(Self::Ml),
+            "ml-coldstart" => Ok(Self::MlColdstart),
+            "canister" => Ok(Self::Canister),
+            "fixture" => Ok(Self::Fixture),
+            _ => Err(format!("unknown feed provider {s}")),
+        }
+    }
+}
+
+/// A provider in the chain, `<provider>[:<timeout ms>]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedProviderSpec {
+    pub kind: FeedProviderKind,
+    pub timeout: Duration,
+}
+
+impl FromStr for FeedProviderSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, timeout) = match s.split_once(':') {
+            Some((kind, timeout)) => {
+                let timeout_ms = timeout
+                    .parse()
+                    .map_err(|_| format!("invalid timeout for feed provider {kind}: {timeout}"))?;
+                (kind, Duration::from_millis(timeout_ms))
+            }
+            None => (s, DEFAULT_TIMEOUT),
+        };
+
+        Ok(Self {
+            kind: kind.parse()?,
+            timeout,
+        })
+    }
+}
+
+/// Parse a comma separated list of [`FeedProviderSpec`], skipping invalid entries
+pub fn parse_provider_specs(specs: &str) -> Vec<FeedProviderSpec> {
+    specs
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .filter_map(|spec| {
+            spec.parse()
+                .inspect_err(|e| leptos::logging::warn!("ignoring feed provider: {e}"))
+                .ok()
+        })
+        .collect()
+}
+
+pub enum FeedProviderImpl<'a> {
+    MlRest(MlRestFeed),
+    Canister(CanisterFeed<'a>),
+    Fixture(FixtureFeed),
+    Hashtag(HashtagFeed),
+    Following(FollowingFeed<'a>),
+}
+
+impl FeedProvider for FeedProviderImpl<'_> {
+    fn result_type(&self, query: &FeedQuery) -> FeedResultType {
+        match self {
+            Self::MlRest(p) => p.result_type(query),
+            Self::Canister(p) => p.result_type(query),
+            Self::Fixture(p) => p.result_type(query),
+            Self::Hashtag(p) => p.result_type(query),
+            Self::Following(p) => p.result_type(query),
+        }
+    }
+
+    async fn fetch(&self, query: &FeedQuery) -> Result<Vec<PostItem>, FeedError> {
+        match self {
+            Self::MlRest(p) => p.fetch(query).await,
+            Self::Canister(p) => p.fetch(query).await,
+            Self::Fixture(p) => p.fetch(query).await,
+            Self::Hashtag(p) => p.fetch(query).await,
+            Self::Following(p) => p.fetch(query).await,
+        }
+    }
+}
+
+/// Outcome of a single provider in the chain
+#[derive(Clone, Debug)]
+struct ProviderAttempt {
+    res_type: FeedResultType,
+    latency: Duration,
+    result: Result<usize, String>,
+    timed_out: bool,
+}
+
+pub struct FeedBatch {
+    pub posts: Vec<PostItem>,
+    pub res_type: FeedResultType,
+}
+
+/// One line per batch, with the provider that served it, the time spent on the whole chain
+/// and the providers that failed or timed out before it
+fn log_batch(served: FeedResultType, attempts: &[ProviderAttempt]) {
+    let latency: Duration = attempts.iter().map(|attempt| attempt.latency).sum();
+    let timed_out = attempts.iter().filter(|attempt| attempt.timed_out).count();
+    let failed = attempts
+        .iter()
+        .filter(|attempt| attempt.res_type != served && attempt.result.is_err())
+        .count();
+    leptos::logging::log!(
+        "feed batch served by {served:?} in {latency:?}, {failed} providers failed, {timed_out} timed out"
+    );
+}
+
+/// Providers tried in order until one returns posts
+pub struct FeedChain<'a> {
+    providers: Vec<(FeedProviderImpl<'a>, Duration)>,
+}
+
+impl<'a> FeedChain<'a> {
+    pub fn new(providers: Vec<(FeedProviderImpl<'a>, Duration)>) -> Self {
+        Self { providers }
+    }
+
+    /// Try `provider` before the rest of the chain
+    pub fn with_first(mut self, provider: FeedProviderImpl<'a>, timeout: Duration) -> Self {
+        self.providers.insert(0, (provider, timeout));
+        self
+    }
+
+    /// The chain configured in [`FEED_PROVIDERS`]
+    pub fn from_config(canisters: &'a Canisters<true>) -> Self {
+        let providers = parse_provider_specs(FEED_PROVIDERS)
+            .into_iter()
+            .map(|spec| {
+                let provider = match spec.kind {
+                    FeedProviderKind::Ml => FeedProviderImpl::MlRest(MlRestFeed::personalized()),
+                    FeedProviderKind::MlColdstart => {
+                        FeedProviderImpl::MlRest(MlRestFeed::coldstart())
+                    }
+                    FeedProviderKind::Canister => {
+                        FeedProviderImpl::Canister(CanisterFeed::new(canisters))
+                    }
+                    FeedProviderKind::Fixture => FeedProviderImpl::Fixture(FixtureFeed::bundled()),
+                };
+                (provider, spec.timeout)
+            })
+            .collect();
+
+        Self::new(providers)
+    }
+
+    /// Fetch a batch from the first provider that returns unseen posts
+    /// providers serving the same result type as an already failed one are skipped
+    /// if every provider only returns seen posts, the first such batch is served as is
+    /// posts of hidden creators are never served
+    pub async fn fetch(&self, query: &FeedQuery) -> Result<FeedBatch, FeedError> {
+        let mut attempts: Vec<ProviderAttempt> = vec![];
+        let mut last_err = FeedError::Empty;
+        let mut fully_seen = None;
+
+        for (provider, timeout) in &self.providers {
+            let res_type = provider.result_type(query);
+            if attempts.iter().any(|a| a.res_type == res_type) {
+                continue;
+            }
+
+            let started = Instant::now();
+            let res = match with_timeout(provider.fetch(query), *timeout)
+                .await
+                .map(|posts| {
+                    posts
+                        .into_iter()
+                        .filter(|post| !query.hidden_creators.contains(&post.canister_id))
+                        .collect::<Vec<_>>()
+                }) {
+                Ok(posts) if posts.is_empty() => Err(FeedError::Empty),
+                Ok(posts) => {
+                    let unseen: Vec<_> = posts
+                        .iter()
+                        .filter(|post| !query.seen_posts.contains(post.canister_id, post.post_id))
+                        .cloned()
+                        .collect();
+                    if unseen.is_empty() {
+                        fully_seen.get_or_insert((posts, res_type));
+                        Err(FeedError::AllSeen)
+                    } else {
+                        Ok(unseen)
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            attempts.push(ProviderAttempt {
+                res_type,
+                latency: started.elapsed(),
+                result: res
+                    .as_ref()
+                    .map(|posts| posts.len())
+                    .map_err(|e| e.to_string()),
+                timed_out: matches!(res, Err(FeedError::Timeout(_))),
+            });
+
+            match res {
+                Ok(posts) => {
+                    log_batch(res_type, &attempts);
+                    return Ok(FeedBatch { posts, res_type });
+                }
+                Err(e) => {
+                    leptos::logging::warn!("feed provider {res_type:?} failed: {e}");
+                    last_err = e;
+                }
+            }
+        }
+
+        match fully_seen {
+            Some((posts, res_type)) => {
+                log_batch(res_type, &attempts);
+                Ok(FeedBatch { posts, res_type })
+            }
+            None => Err(last_err),
+        }
+    }
+}
+
+async fn with_timeout<T>(
+    fut: impl std::future::Future<Output = Result<T, FeedError>>,
+    timeout: Duration,
+) -> Result<T, FeedError> {
+    #[cfg(feature = "hydrate")]
+    let timer = gloo::timers::future::sleep(timeout);
+    #[cfg(all(feature = "ssr", not(feature = "hydrate")))]
+    let timer = tokio::time::sleep(timeout);
+    #[cfg(not(any(feature = "hydrate", feature = "ssr")))]
+    let timer = futures::future::pending::<()>();
+
+    match select(std::pin::pin!(fut), std::pin::pin!(timer)).await {
+        Either::Left((res, _)) => res,
+        Either::Right(_) => Err(FeedError::Timeout(timeout)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_provider_specs, FeedProviderKind, FeedProviderSpec, DEFAULT_TIMEOUT};
+
+    #[test]
+    fn parses_provider_specs() {
+        let specs = parse_provider_specs("ml:1500, canister,unknown,fixture:abc,fixture");
+        assert_eq!(
+            specs,
+            vec![
+                FeedProviderSpec {
+                    kind: FeedProviderKind::Ml,
+                    timeout: Duration::from_millis(1500),
+                },
+                FeedProviderSpec {
+                    kind: FeedProviderKind::Canister,
+                    timeout: DEFAULT_TIMEOUT,
+                },
+                FeedProviderSpec {
+                    kind: FeedProviderKind::Fixture,
+                    timeout: DEFAULT_TIMEOUT,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use leptos::{prelude::*, server_fn::codec::Json};
+use serde::{Deserialize, Serialize};
+use yral_types::post::PostItem;
+
+/// Negative feedback given by a user, applied to their feed
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedFeedback {
+    /// (canister id, post id)
+    #[serde(default)]
+    pub not_interested: BTreeSet<(Principal, u64)>,
+    /// canisters of the hidden creators
+    #[serde(default)]
+    pub hidden_creators: BTreeSet<Principal>,
+}
+
+impl FeedFeedback {
+    pub fn allows(&self, post: &PostItem) -> bool {
+        !self.hidden_creators.contains(&post.canister_id)
+            && !self
+                .not_interested
+                .contains(&(post.canister_id, post.post_id))
+    }
+}
+
+#[server(endpoint = "feed_not_interested", input = Json)]
+pub async fn mark_not_interested(
+    canister_id: Principal,
+    post_id: u64,
+) -> Result<(), ServerFnError> {
+    use utils::ml_feed::interactions::NegativeFeedback;
+
+    server_impl::mark_not_interested(canister_id, post_id).await?;
+    server_impl::forward_to_ml_feed(NegativeFeedback::NotInterested {
+        canister_id,
+        post_id,
+    })
+    .await;
+
+    Ok(())
+}
+
+/// Hide every post of the creator, returns the hidden creators
+#[server(endpoint = "feed_hide_creator", input = Json, output = Json)]
+pub async fn hide_creator(creator_canister: Principal) -> Result<Vec<Principal>, ServerFnError> {
+    use utils::ml_feed::interactions::NegativeFeedback;
+
+    let hidden = server_impl::set_creator_hidden(creator_canister, true).await?;
+    server_impl::forward_to_ml_feed(NegativeFeedback::HideCreator { creator_canister }).await;
+
+    Ok(hidden.into_iter().collect())
+}
+
+/// Returns the remaining hidden creators
+#[server(endpoint = "feed_unhide_creator", input = Json, output = Json)]
+pub async fn unhide_creator(creator_canister: Principal) -> Result<Vec<Principal>, ServerFnError> {
+    let hidden = server_impl::set_creator_hidden(creator_canister, false).await?;
+
+    Ok(hidden.into_iter().collect())
+}
+
+#[server(endpoint = "feed_hidden_creators", input = Json, output = Json)]
+pub async fn hidden_creators() -> Result<Vec<Principal>, ServerFnError> {
+    let feedback = server_impl::feedback_for_request().await?;
+    Ok(feedback.hidden_creators.into_iter().collect())
+}
+
+#[cfg(feature = "ssr")]
+pub use server_impl::feedback_for_request;
+
+#[cfg(feature = "ssr")]
+mod server_impl {
+    use std::collections::BTreeSet;
+
+    use auth::server_impl::{
+        add_to_shared_set, extract_principal_from_cookie, remove_from_shared_set, shared_set_range,
+        store::KVStoreImpl, trim_shared_set,
+    };
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use candid::Principal;
+    use leptos::prelude::*;
+    use leptos_axum::extract_with_state;
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+    use utils::ml_feed::interactions::{send_negative_feedback, NegativeFeedback};
+    use yral_canisters_common::utils::time::current_epoch;
+
+    use super::FeedFeedback;
+
+    /// user principal -> set of `<canister id>-<post id>` marked not interested, scored by time
+    const NOT_INTERESTED_NAMESPACE: &str = "feed-not-interested";
+    /// user principal -> set of the hidden creators' canister ids, scored by time
+    const HIDDEN_CREATORS_NAMESPACE: &str = "feed-hidden-creators";
+    /// Posts marked as not interested that are remembered, oldest are dropped first
+    const NOT_INTERESTED_CAPACITY: u64 = 500;
+    /// Hidden creators that are remembered, oldest are dropped first
+    const HIDDEN_CREATORS_CAPACITY: u64 = 1000;
+
+    async fn request_principal() -> Result<Option<Principal>, ServerFnError> {
+        let key: Key = expect_context();
+        let jar: SignedCookieJar = extract_with_state(&key).await?;
+        extract_principal_from_cookie(&jar)
+    }
+
+    async fn logged_in_user() -> Result<String, ServerFnError> {
+        let principal = request_principal()
+            .await?
+            .ok_or_else(|| ServerFnError::new("User not logged in"))?;
+        Ok(principal.to_text())
+    }
+
+    async fn stored_hidden_creators(
+        kv: &KVStoreImpl,
+        user: &str,
+    ) -> Result<BTreeSet<Principal>, ServerFnError> {
+        let members = shared_set_range(
+            kv,
+            HIDDEN_CREATORS_NAMESPACE,
+            user,
+            0,
+            HIDDEN_CREATORS_CAPACITY,
+        )
+        .await?;
+        Ok(members
+            .into_iter()
+            .filter_map(|(member, _)| Principal::from_text(member).ok())
+            .collect())
+    }
+
+    async fn stored_not_interested(
+        kv: &KVStoreImpl,
+        user: &str,
+    ) -> Result<BTreeSet<(Principal, u64)>, ServerFnError> {
+        let members = shared_set_range(
+            kv,
+            NOT_INTERESTED_NAMESPACE,
+            user,
+            0,
+            NOT_INTERESTED_CAPACITY,
+        )
+        .await?;
+        Ok(members
+            .into_iter()
+            .filter_map(|(member, _)| {
+                // principal text contains dashes, the post id follows the last one
+                let (canister_id, post_id) = member.rsplit_once('-')?;
+                Some((
+                    Principal::from_text(canister_id).ok()?,
+                    post_id.parse().ok()?,
+                ))
+            })
+            .collect())
+    }
+
+    /// Feedback of the user making the request, empty without a user
+    pub async fn feedback_for_request() -> Result<FeedFeedback, ServerFnError> {
+        let Some(principal) = request_principal().await? else {
+            return Ok(FeedFeedback::default());
+        };
+        let kv: KVStoreImpl = expect_context();
+        let user = principal.to_text();
+
+        Ok(FeedFeedback {
+            not_interested: stored_not_interested(&kv, &user).await?,
+            hidden_creators: stored_hidden_creators(&kv, &user).await?,
+        })
+    }
+
+    /// Feedback is kept in sorted sets updated atomically, so concurrent requests from
+    /// several tabs or devices can't overwrite each other
+    pub async fn mark_not_interested(
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<(), ServerFnError> {
+        let user = logged_in_user().await?;
+        let kv: KVStoreImpl = expect_context();
+
+        add_to_shared_set(
+            &kv,
+            NOT_INTERESTED_NAMESPACE,
+            &user,
+            format!("{canister_id}-{post_id}"),
+            current_epoch().as_millis() as f64,
+        )
+        .await?;
+        trim_shared_set(
+            &kv,
+            NOT_INTERESTED_NAMESPACE,
+            &user,
+            NOT_INTERESTED_CAPACITY,
+        )
+        .await
+    }
+
+    /// Returns the hidden creators after the update
+    pub async fn set_creator_hidden(
+        creator_canister: Principal,
+        hidden: bool,
+    ) -> Result<BTreeSet<Principal>, ServerFnError> {
+        let user = logged_in_user().await?;
+        let kv: KVStoreImpl = expect_context();
+        let creator = creator_canister.to_text();
+
+        if hidden {
+            add_to_shared_set(
+                &kv,
+                HIDDEN_CREATORS_NAMESPACE,
+                &user,
+                creator,
+                current_epoch().as_millis() as f64,
+            )
+            .await?;
+            trim_shared_set(
+                &kv,
+                HIDDEN_CREATORS_NAMESPACE,
+                &user,
+                HIDDEN_CREATORS_CAPACITY,
+            )
+            .await?;
+        } else {
+            remove_from_shared_set(&kv, HIDDEN_CREATORS_NAMESPACE, &user, creator).await?;
+        }
+
+        stored_hidden_creators(&kv, &user).await
+    }
+    /// Best effort, the feedback is already applied by our own filters
+    pub async fn forward_to_ml_feed(feedback: NegativeFeedback) {
+        let Ok(Some(principal)) = request_principal().await else {
+            return;
+        };
+        let user_canister = match unauth_canister_backend()
+            .get_individual_canister_by_user_principal(principal)
+            .await
+        {
+            Ok(Some(user_canister)) => user_canister,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("failed to resolve user canister for feed feedback: {e:?}");
+                return;
+            }
+        };
+
+        if let Err(e) = send_negative_feedback(user_canister, &feedback).await {
+            log::warn!("failed to forward feed feedback: {e:?}");
+        }
+    }
+}
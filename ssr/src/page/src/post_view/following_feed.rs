@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use codee::string::{FromToStringCodec, JsonSerdeCodec};
+use consts::{FOLLOWING_STORE, HIDDEN_CREATORS_STORE, NSFW_TOGGLE_STORE, SEEN_POSTS_STORE};
+use futures::StreamExt;
+use leptos::prelude::*;
+use leptos_icons::Icon;
+use leptos_use::storage::use_local_storage;
+use state::canisters::authenticated_canisters;
+use utils::{ml_feed::seen_set::SeenSet, try_or_redirect};
+use yral_canisters_common::Canisters;
+
+use super::{
+    video_iter::{FeedResultType, VideoFetchStream},
+    CommonPostViewWithUpdates, PostViewCtx,
+};
+use crate::follow::{followed_creators, FOLLOW_LIST_LIMIT};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FeedTab {
+    ForYou,
+    Following,
+}
+
+/// Switch between the home feed and the following feed, with a link to search
+#[component]
+pub fn FeedTabs(active: FeedTab) -> impl IntoView {
+    let tab_class = move |tab: FeedTab| {
+        if tab == active {
+            "text-white border-b-2 border-white pb-1"
+        } else {
+            "text-white/60 pb-1"
+        }
+    };
+
+    view! {
+        <div class="fixed top-0 left-1/2 -translate-x-1/2 z-[5] flex flex-row gap-6 pt-1 text-sm font-semibold drop-shadow-lg">
+            <a class=tab_class(FeedTab::ForYou) href="/">
+                For You
+            </a>
+            <a class=tab_class(FeedTab::Following) href="/following">
+                Following
+            </a>
+            <a class="text-white/60 text-base" href="/search">
+                <Icon icon=icondata::AiSearchOutlined />
+            </a>
+        </div>
+    }
+}
+
+#[component]
+fn FollowingFeedWithUpdates() -> impl IntoView {
+    let PostViewCtx {
+        fetch_cursor,
+        video_queue,
+        ..
+    } = expect_context();
+
+    let auth_cans = authenticated_canisters();
+    let fetch_video_action = Action::new_local(move |_| {
+        let (nsfw_enabled, _, _) = use_local_storage::<bool, FromToStringCodec>(NSFW_TOGGLE_STORE);
+        let (seen_posts, _, _) = use_local_storage::<SeenSet, JsonSerdeCodec>(SEEN_POSTS_STORE);
+        let (hidden_creators, _, _) =
+            use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(HIDDEN_CREATORS_STORE);
+        async move {
+            let Some(cursor) = fetch_cursor.try_get_untracked() else {
+                return;
+            };
+            let canisters = auth_cans.await;
+            let cans_true = Canisters::from_wire(canisters.unwrap(), expect_context()).unwrap();
+
+            let mut fetch_stream = VideoFetchStream::new(&cans_true, cursor);
+            let res = try_or_redirect!(
+                fetch_stream
+                    .fetch_following_chunked(
+                        3,
+                        nsfw_enabled.get_untracked(),
+                        video_queue.get_untracked().iter().cloned().collect(),
+                        seen_posts.get_untracked(),
+                        hidden_creators.get_untracked(),
+                    )
+                    .await
+            );
+
+            let mut chunks = res.posts_stream;
+            while let Some(chunk) = chunks.next().await {
+                for post in chunk {
+                    let post = try_or_redirect!(post);
+                    video_queue.update(|vq| {
+                        vq.insert(post);
+                    });
+                }
+            }
+
+            if res.res_type != FeedResultType::MLFeed {
+                fetch_cursor.try_update(|c| c.advance_and_set_limit(50));
+            }
+        }
+    });
+
+    // keep the followed creators of other devices in sync, only the most recent ones are cached
+    let (_, set_following, _) =
+        use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(FOLLOWING_STORE);
+    Effect::new(move || {
+        leptos::task::spawn_local(async move {
+            match followed_creators(0, FOLLOW_LIST_LIMIT).await {
+                Ok(creators) => {
+                    set_following.set(creators.into_iter().map(|c| c.principal).collect())
+                }
+                Err(e) => log::warn!("failed to fetch followed creators: {e}"),
+            }
+        });
+    });
+
+    view! {
+        <CommonPostViewWithUpdates
+            initial_post=None
+            fetch_video_action
+            threshold_trigger_fetch=20
+            keep_url=true
+            overlay=|| view! { <FeedTabs active=FeedTab::Following /> }
+        />
+    }
+}
+
+/// Posts of the followed creators, then the home feed once they run out
+#[component]
+pub fn FollowingView() -> impl IntoView {
+    provide_context(PostViewCtx::default());
+    view! { <FollowingFeedWithUpdates /> }
+}
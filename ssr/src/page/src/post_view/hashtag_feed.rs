@@ -0,0 +1,109 @@
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use codee::string::{FromToStringCodec, JsonSerdeCodec};
+use component::back_btn::BackButton;
+use consts::{HIDDEN_CREATORS_STORE, NSFW_TOGGLE_STORE};
+use futures::StreamExt;
+use leptos::prelude::*;
+use leptos_router::{components::Redirect, hooks::use_params, params::Params};
+use leptos_use::storage::use_local_storage;
+use state::canisters::authenticated_canisters;
+use utils::{hashtags::normalize_hashtag, try_or_redirect};
+use yral_canisters_common::Canisters;
+
+use super::{video_iter::VideoFetchStream, CommonPostViewWithUpdates, PostViewCtx};
+
+#[derive(Params, PartialEq, Clone)]
+struct HashtagParams {
+    hashtag: String,
+}
+
+#[component]
+fn HashtagFeedWithUpdates(hashtag: String) -> impl IntoView {
+    let PostViewCtx {
+        fetch_cursor,
+        video_queue,
+        queue_end,
+        ..
+    } = expect_context();
+
+    let auth_cans = authenticated_canisters();
+    let feed_hashtag = hashtag.clone();
+    let fetch_video_action = Action::new_local(move |_| {
+        let hashtag = feed_hashtag.clone();
+        let (nsfw_enabled, _, _) = use_local_storage::<bool, FromToStringCodec>(NSFW_TOGGLE_STORE);
+        let (hidden_creators, _, _) =
+            use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(HIDDEN_CREATORS_STORE);
+        async move {
+            let Some(cursor) = fetch_cursor.try_get_untracked() else {
+                return;
+            };
+            let canisters = auth_cans.await;
+            let cans_true = Canisters::from_wire(canisters.unwrap(), expect_context()).unwrap();
+
+            let mut fetch_stream = VideoFetchStream::new(&cans_true, cursor);
+            let res = try_or_redirect!(
+                fetch_stream
+                    .fetch_hashtag_chunked(
+                        3,
+                        hashtag,
+                        nsfw_enabled.get_untracked(),
+                        hidden_creators.get_untracked(),
+                    )
+                    .await
+            );
+
+            let mut chunks = res.posts_stream;
+            while let Some(chunk) = chunks.next().await {
+                for post in chunk {
+                    let post = try_or_redirect!(post);
+                    video_queue.update(|vq| {
+                        vq.insert(post);
+                    });
+                }
+            }
+
+            if res.end {
+                queue_end.try_set(true);
+            }
+            fetch_cursor.try_update(|c| c.advance());
+        }
+    });
+
+    let title = format!("#{hashtag}");
+    view! {
+        <CommonPostViewWithUpdates
+            initial_post=None
+            fetch_video_action
+            threshold_trigger_fetch=10
+            keep_url=true
+            overlay=move || {
+                view! {
+                    <div class="fixed top-0 left-0 z-[5] flex flex-row gap-2 items-center p-4 text-white text-lg font-bold drop-shadow-lg">
+                        <BackButton fallback="/".to_string() />
+                        <span>{title.clone()}</span>
+                    </div>
+                }
+            }
+        />
+    }
+}
+
+/// Scrolling feed of the posts tagged with a hashtag
+#[component]
+pub fn HashtagView() -> impl IntoView {
+    let params = use_params::<HashtagParams>();
+    let hashtag = Memo::new(move |_| {
+        params.with(|p| p.as_ref().ok().and_then(|p| normalize_hashtag(&p.hashtag)))
+    });
+
+    move || match hashtag.get() {
+        Some(hashtag) => {
+            // every hashtag gets its own queue, the home feed's is left untouched
+            provide_context(PostViewCtx::default());
+            view! { <HashtagFeedWithUpdates hashtag /> }.into_any()
+        }
+        None => view! { <Redirect path="/" /> }.into_any(),
+    }
+}
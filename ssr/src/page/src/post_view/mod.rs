@@ -1,34 +1,60 @@
-mod bet;
+pub mod bet;
+#[cfg(feature = "ssr")]
+pub mod coldstart;
+pub mod engagement;
 pub mod error;
+pub mod feed_provider;
+pub mod feedback;
+pub mod following_feed;
+pub mod hashtag_feed;
 pub mod overlay;
+pub mod preload;
+pub mod seen_posts;
+pub mod session;
 pub mod single_post;
 pub mod video_iter;
 pub mod video_loader;
 use crate::scrolling_post_view::ScrollingPostView;
 use component::spinner::FullScreenSpinner;
-use consts::NSFW_TOGGLE_STORE;
+use consts::{ACCOUNT_CONNECTED_STORE, HIDDEN_CREATORS_STORE, NSFW_TOGGLE_STORE, SEEN_POSTS_STORE};
 use indexmap::IndexSet;
 use priority_queue::DoublePriorityQueue;
 use state::canisters::{authenticated_canisters, unauth_canisters};
-use std::{cmp::Reverse, collections::HashMap};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+};
 use yral_types::post::PostItem;
 
 use candid::Principal;
-use codee::string::FromToStringCodec;
+use codee::string::{FromToStringCodec, JsonSerdeCodec};
 use futures::StreamExt;
 use leptos::prelude::*;
+use leptos::web_sys::VisibilityState;
 use leptos_router::{
     hooks::{use_navigate, use_params},
     params::Params,
 };
-use leptos_use::{storage::use_local_storage, use_debounce_fn};
+use leptos_use::{storage::use_local_storage, use_debounce_fn, use_document_visibility};
 use utils::{
-    posts::FetchCursor, route::failure_redirect, send_wrap, try_or_redirect, types::PostId,
+    ml_feed::seen_set::SeenSet, posts::FetchCursor, route::failure_redirect, send_wrap,
+    try_or_redirect, types::PostId,
 };
 
+use engagement::EngagementCtx;
+use following_feed::{FeedTab, FeedTabs};
+use seen_posts::sync_seen_posts;
+use session::use_feed_session;
 use video_iter::{FeedResultType, VideoFetchStream};
 use yral_canisters_common::{utils::posts::PostDetails, Canisters};
 
+/// Batches fetched between syncs of the seen posts, for logged in users
+const SEEN_POSTS_SYNC_INTERVAL: usize = 5;
+/// Posts kept in the priority queue, the lowest priority ones are dropped
+const PRIORITY_QUEUE_CAPACITY: usize = 200;
+/// Posts kept in the queue behind the current one while the feed is open
+const POSTS_KEPT_BEHIND: usize = 20;
+
 #[derive(Params, PartialEq, Clone, Copy)]
 struct PostParams {
     canister_id: Principal,
@@ -41,18 +67,18 @@ pub struct BetEligiblePostCtx {
     pub can_place_bet: RwSignal<bool>,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Copy, Default)]
 pub struct PostViewCtx {
     fetch_cursor: RwSignal<FetchCursor>,
-    // TODO: this is a dead simple with no GC
-    // We're using virtual lists for DOM, so this doesn't consume much memory
-    // as uids only occupy 32 bytes each
-    // but ideally this should be cleaned up
+    // GC'd after each batch and when the view is remounted (see `CommonPostViewWithUpdates`)
+    // deduplication across sessions is handled by the seen posts set
     video_queue: RwSignal<IndexSet<PostDetails>>,
     current_idx: RwSignal<usize>,
     queue_end: RwSignal<bool>,
-    priority_q: RwSignal<DoublePriorityQueue<PostDetails, (usize, Reverse<usize>)>>, // we are using DoublePriorityQueue for GC in the future through pop_min
+    priority_q: RwSignal<DoublePriorityQueue<PostDetails, (usize, Reverse<usize>)>>, // lowest priority posts are dropped through pop_min
     batch_cnt: RwSignal<usize>,
+    /// feed type of the latest batch
+    last_result: RwSignal<Option<FeedResultType>>,
 }
 
 #[derive(Clone, Default)]
@@ -60,19 +86,48 @@ pub struct PostDetailsCacheCtx {
     pub post_details: RwSignal<HashMap<PostId, PostItem>>,
 }
 
+/// Drops the posts more than [`POSTS_KEPT_BEHIND`] behind the current one
+/// returns true if any were dropped
+fn drop_passed_posts(
+    video_queue: RwSignal<IndexSet<PostDetails>>,
+    current_idx: RwSignal<usize>,
+) -> bool {
+    let passed = current_idx
+        .get_untracked()
+        .saturating_sub(POSTS_KEPT_BEHIND);
+    if passed == 0 {
+        return false;
+    }
+    video_queue.update(|v| {
+        v.drain(0..passed);
+    });
+    current_idx.update(|c| *c -= passed);
+    true
+}
+
 #[component]
 pub fn CommonPostViewWithUpdates<S: Storage<ArcAction<(), ()>>>(
     initial_post: Option<PostDetails>,
     fetch_video_action: Action<(), (), S>,
     threshold_trigger_fetch: usize,
+    /// shown above the posts
+    #[prop(optional, into)]
+    overlay: Option<ViewFn>,
+    /// stay on the feed's route instead of following the current post
+    #[prop(optional)]
+    keep_url: bool,
+    /// persist the feed to session storage and resume it on reload
+    #[prop(optional)]
+    resumable: bool,
 ) -> impl IntoView {
+    let ctx: PostViewCtx = expect_context();
     let PostViewCtx {
         fetch_cursor,
         video_queue,
         current_idx,
         queue_end,
         ..
-    } = expect_context();
+    } = ctx;
 
     let recovering_state = RwSignal::new(false);
     if let Some(initial_post) = initial_post.clone() {
@@ -97,10 +152,30 @@ pub fn CommonPostViewWithUpdates<S: Storage<ArcAction<(), ()>>>(
         })
     }
 
-    Effect::new(move || {
-        if !recovering_state.get_untracked() {
+    let start = move |resumed: bool| {
+        if resumed {
+            recovering_state.set(true);
+        } else if !recovering_state.get_untracked() {
             fetch_video_action.dispatch(());
         }
+    };
+    if resumable {
+        let initial_post = initial_post
+            .as_ref()
+            .map(|post| (post.canister_id, post.post_id));
+        use_feed_session(ctx, initial_post, start);
+    } else {
+        Effect::new(move || start(false));
+    }
+    // keep the queue bounded in long sessions, the posts are rendered keyed by post
+    // so only the dropped ones are unmounted, the current one is scrolled back into view
+    let batches = fetch_video_action.version();
+    Effect::new(move |prev: Option<usize>| {
+        let version = batches.get();
+        if prev.is_some_and(|prev| prev != version) && drop_passed_posts(video_queue, current_idx) {
+            recovering_state.set(true);
+        }
+        version
     });
     let next_videos = use_debounce_fn(
         move || {
@@ -111,6 +186,17 @@ pub fn CommonPostViewWithUpdates<S: Storage<ArcAction<(), ()>>>(
         200.0,
     );
 
+    // report engagement before the tab is discarded or the feed is left
+    if let Some(engagement) = use_context::<EngagementCtx>() {
+        let visibility = use_document_visibility();
+        Effect::new(move || {
+            if visibility.get() == VisibilityState::Hidden {
+                engagement.flush();
+            }
+        });
+        on_cleanup(move || engagement.flush());
+    }
+
     let current_post_base = Memo::new(move |_| {
         video_queue.with(|q| {
             let cur_idx = current_idx();
@@ -119,10 +205,15 @@ pub fn CommonPostViewWithUpdates<S: Storage<ArcAction<(), ()>>>(
         })
     });
 
+    let (_, set_seen_posts, _) = use_local_storage::<SeenSet, JsonSerdeCodec>(SEEN_POSTS_STORE);
     Effect::new(move || {
         let Some((canister_id, post_id)) = current_post_base() else {
             return;
         };
+        set_seen_posts.update(|seen| seen.insert(canister_id, post_id));
+        if keep_url {
+            return;
+        }
         use_navigate()(
             &format!("/hot-or-not/{canister_id}/{post_id}",),
             Default::default(),
@@ -137,6 +228,7 @@ pub fn CommonPostViewWithUpdates<S: Storage<ArcAction<(), ()>>>(
             fetch_next_videos=next_videos
             queue_end
             threshold_trigger_fetch
+            overlay=overlay.unwrap_or_default()
         />
     }
     .into_any()
@@ -151,6 +243,7 @@ pub fn PostViewWithUpdatesMLFeed(initial_post: Option<PostDetails>) -> impl Into
         priority_q,
         batch_cnt,
         current_idx,
+        last_result,
         ..
     } = expect_context();
 
@@ -159,6 +252,12 @@ pub fn PostViewWithUpdatesMLFeed(initial_post: Option<PostDetails>) -> impl Into
     let fetch_video_action = Action::new_local(move |_| {
         let auth_cans = auth_cans;
         let (nsfw_enabled, _, _) = use_local_storage::<bool, FromToStringCodec>(NSFW_TOGGLE_STORE);
+        let (is_connected, _, _) =
+            use_local_storage::<bool, FromToStringCodec>(ACCOUNT_CONNECTED_STORE);
+        let (seen_posts, set_seen_posts, _) =
+            use_local_storage::<SeenSet, JsonSerdeCodec>(SEEN_POSTS_STORE);
+        let (hidden_creators, _, _) =
+            use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(HIDDEN_CREATORS_STORE);
         async move {
             {
                 let mut prio_q = priority_q.write();
@@ -187,16 +286,30 @@ pub fn PostViewWithUpdatesMLFeed(initial_post: Option<PostDetails>) -> impl Into
                 let Some(batch_cnt_val) = batch_cnt.try_get_untracked() else {
                     return;
                 };
+                let Some(mut seen) = seen_posts.try_get_untracked() else {
+                    return;
+                };
+                if is_connected.get_untracked() && batch_cnt_val % SEEN_POSTS_SYNC_INTERVAL == 0 {
+                    match sync_seen_posts(seen.clone()).await {
+                        Ok(merged) => {
+                            set_seen_posts.set(merged.clone());
+                            seen = merged;
+                        }
+                        Err(e) => log::warn!("failed to sync seen posts: {e}"),
+                    }
+                }
 
                 let canisters = auth_cans.await;
                 let cans_true = Canisters::from_wire(canisters.unwrap(), expect_context()).unwrap();
 
                 let mut fetch_stream = VideoFetchStream::new(&cans_true, cursor);
                 let chunks = fetch_stream
-                    .fetch_post_uids_hybrid(
+                    .fetch_post_uids_chunked(
                         3,
                         nsfw_enabled,
                         video_queue.get_untracked().iter().cloned().collect(),
+                        seen,
+                        hidden_creators.get_untracked(),
                     )
                     .await;
 
@@ -224,6 +337,7 @@ pub fn PostViewWithUpdatesMLFeed(initial_post: Option<PostDetails>) -> impl Into
                 }
 
                 leptos::logging::log!("feed type: {:?} cnt {}", res.res_type, cnt); // For debugging purposes
+                last_result.try_set(Some(res.res_type));
                 if res.res_type != FeedResultType::MLFeed {
                     fetch_cursor.try_update(|c| {
                         c.set_limit(50);
@@ -235,12 +349,27 @@ pub fn PostViewWithUpdatesMLFeed(initial_post: Option<PostDetails>) -> impl Into
                     queue_end.try_set(res.end);
                 }
 
+                priority_q.update(|pq| {
+                    while pq.len() > PRIORITY_QUEUE_CAPACITY {
+                        pq.pop_min();
+                    }
+                });
+
                 batch_cnt.update(|x| *x += 1);
             }
         }
     });
 
-    view! { <CommonPostViewWithUpdates initial_post fetch_video_action threshold_trigger_fetch=50 /> }.into_any()
+    view! {
+        <CommonPostViewWithUpdates
+            initial_post
+            fetch_video_action
+            threshold_trigger_fetch=50
+            overlay=|| view! { <FeedTabs active=FeedTab::ForYou /> }
+            resumable=true
+        />
+    }
+    .into_any()
 }
 
 #[component]
@@ -1,16 +1,21 @@
-use codee::string::FromToStringCodec;
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use codee::string::{FromToStringCodec, JsonSerdeCodec};
 use component::buttons::HighlightedButton;
 use component::{
     canisters_prov::with_cans, hn_icons::HomeFeedShareIcon, modal::Modal, option::SelectOption,
 };
 
-use consts::NSFW_TOGGLE_STORE;
+use consts::{HIDDEN_CREATORS_STORE, NSFW_TOGGLE_STORE};
 use gloo::timers::callback::Timeout;
 use leptos::{prelude::*, task::spawn_local};
 use leptos_icons::*;
 use leptos_use::storage::use_local_storage;
 use leptos_use::use_window;
+use state::canister_backend::{canister_backend, CanisterBackend};
 use utils::event_streaming::events::auth_canisters_store;
+use utils::hashtags::{normalize_hashtag, split_hashtags, DescriptionPart};
 use utils::host::show_nsfw_content;
 use utils::{
     event_streaming::events::{LikeVideo, ShareVideo},
@@ -25,7 +30,15 @@ use yral_canisters_common::{utils::posts::PostDetails, Canisters};
 
 use utils::mixpanel::mixpanel_events::*;
 
-use super::bet::HNGameOverlay;
+use crate::comments::CommentsButton;
+use crate::follow::FollowButton;
+
+use super::{
+    bet::HNGameOverlay,
+    engagement::EngagementCtx,
+    feedback::{hide_creator, mark_not_interested},
+    PostViewCtx,
+};
 
 #[component]
 fn LikeAndAuthCanLoader(post: PostDetails) -> impl IntoView {
@@ -47,6 +60,7 @@ fn LikeAndAuthCanLoader(post: PostDetails) -> impl IntoView {
     let (is_connected, _, _) =
         use_local_storage::<bool, FromToStringCodec>(consts::ACCOUNT_CONNECTED_STORE);
 
+    let engagement = use_context::<EngagementCtx>();
     let like_toggle = Action::new_local(move |&()| {
         let post_details = post.clone();
         let canister_store = canisters;
@@ -64,6 +78,9 @@ fn LikeAndAuthCanLoader(post: PostDetails) -> impl IntoView {
                 *liked_w = Some(!current);
                 !current
             };
+            if let Some(engagement) = engagement {
+                engagement.update(&post_details, |interaction| interaction.liked = should_like);
+            }
 
             if should_like {
                 likes.update(|l| *l += 1);
@@ -109,7 +126,10 @@ fn LikeAndAuthCanLoader(post: PostDetails) -> impl IntoView {
             let result = if let Some(liked) = initial_liked.0 {
                 (liked, initial_liked.1)
             } else {
-                match cans.post_like_info(post_canister, post_id).await {
+                match canister_backend(cans)
+                    .post_like_info(post_canister, post_id)
+                    .await
+                {
                     Ok(liked) => liked,
                     Err(e) => {
                         failure_redirect(e);
@@ -151,6 +171,8 @@ pub fn VideoDetailsOverlay(post: PostDetails) -> impl IntoView {
     let show_share = RwSignal::new(false);
     let show_report = RwSignal::new(false);
     let show_nsfw_permission = RwSignal::new(false);
+    let show_feedback = RwSignal::new(false);
+    let feedback_sent = RwSignal::new(false);
     let report_option = RwSignal::new(ReportOption::Nudity.as_str().to_string());
     let show_copied_popup = RwSignal::new(false);
     let base_url = || {
@@ -173,9 +195,13 @@ pub fn VideoDetailsOverlay(post: PostDetails) -> impl IntoView {
     let share_video_id = post.uid.clone();
     let report_video_id = post.uid.clone();
 
+    let engagement = use_context::<EngagementCtx>();
     let share = move || {
         let video_id = share_video_id.clone();
         let post_details = post_details_share.clone();
+        if let Some(engagement) = engagement {
+            engagement.update(&post_details, |interaction| interaction.shared = true);
+        }
         let url = video_url();
         if share_url(&url).is_some() {
             return;
@@ -291,6 +317,35 @@ pub fn VideoDetailsOverlay(post: PostDetails) -> impl IntoView {
         }
     });
 
+    let post_canister = post.canister_id;
+    let post_id = post.post_id;
+    let click_not_interested = Action::new_local(move |()| async move {
+        feedback_sent.set(true);
+        if let Err(e) = mark_not_interested(post_canister, post_id).await {
+            log::warn!("failed to mark post as not interested: {e}");
+        }
+    });
+
+    let (_, set_hidden_creators, _) =
+        use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(HIDDEN_CREATORS_STORE);
+    let priority_q = use_context::<PostViewCtx>().map(|ctx| ctx.priority_q);
+    let click_hide_creator = Action::new_local(move |()| async move {
+        feedback_sent.set(true);
+        // queued posts of the creator are dropped right away, the rest are filtered by the feed
+        if let Some(priority_q) = priority_q {
+            priority_q.update(|pq| {
+                *pq = std::mem::take(pq)
+                    .into_iter()
+                    .filter(|(post, _)| post.canister_id != post_canister)
+                    .collect();
+            });
+        }
+        match hide_creator(post_canister).await {
+            Ok(hidden) => set_hidden_creators.set(hidden.into_iter().collect()),
+            Err(e) => log::warn!("failed to hide creator: {e}"),
+        }
+    });
+
     let (nsfw_enabled, set_nsfw_enabled, _) =
         use_local_storage::<bool, FromToStringCodec>(NSFW_TOGGLE_STORE);
     let nsfw_enabled_with_host = Signal::derive(move || {
@@ -414,6 +469,10 @@ pub fn VideoDetailsOverlay(post: PostDetails) -> impl IntoView {
                                 />
                                 {post.views}
                             </span>
+                            <FollowButton
+                                creator_principal=post.poster_principal
+                                class="ml-1 px-2 py-0.5 text-xs"
+                            />
                         </div>
                         <ExpandableText clone:post description=post.description />
                     </div>
@@ -440,10 +499,21 @@ pub fn VideoDetailsOverlay(post: PostDetails) -> impl IntoView {
                     <button on:click=move |_| show_report.set(true)>
                         <Icon attr:class="drop-shadow-lg" icon=icondata::TbMessageReport />
                     </button>
+                    <button on:click=move |_| {
+                        feedback_sent.set(false);
+                        show_feedback.set(true);
+                    }>
+                        <Icon attr:class="drop-shadow-lg" icon=icondata::AiEyeInvisibleOutlined />
+                    </button>
                     <a on:click=move|_| mixpanel_track_refer()  href="/refer-earn">
                         <Icon attr:class="drop-shadow-lg" icon=icondata::AiGiftFilled />
                     </a>
                     <LikeAndAuthCanLoader post=post_c.clone() />
+                    <CommentsButton
+                        post_canister
+                        post_id
+                        poster_principal=post.poster_principal
+                    />
                     <button on:click=move |_| share()>
                         <Icon attr:class="drop-shadow-lg" icon=HomeFeedShareIcon />
                     </button>
@@ -516,6 +586,39 @@ pub fn VideoDetailsOverlay(post: PostDetails) -> impl IntoView {
                 </button>
             </div>
         </Modal>
+        <Modal show=show_feedback>
+            <div class="flex flex-col justify-center items-center gap-4 text-white">
+                <Show
+                    when=feedback_sent
+                    fallback=move || {
+                        view! {
+                            <span class="text-lg">Show fewer posts like this?</span>
+                            <button
+                                class="w-full rounded-lg bg-white/10 p-2"
+                                on:click=move |_| {
+                                    click_not_interested.dispatch(());
+                                }
+                            >
+                                Not interested
+                            </button>
+                            <button
+                                class="w-full rounded-lg bg-white/10 p-2"
+                                on:click=move |_| {
+                                    click_hide_creator.dispatch(());
+                                }
+                            >
+                                Hide this creator
+                            </button>
+                        }
+                    }
+                >
+                    <span class="text-lg">"Thanks, we'll use this to improve your feed"</span>
+                    <a class="text-sm text-primary-600" href="/settings/hidden-creators">
+                        Manage hidden creators
+                    </a>
+                </Show>
+            </div>
+        </Modal>
         <Modal show=show_nsfw_permission>
             <div class="flex flex-col justify-center items-center gap-4 text-white">
                 <img class="h-32 w-32 object-contain" src="/img/yral/nsfw/nsfw-modal-logo.svg" />
@@ -557,7 +660,25 @@ fn ExpandableText(description: String) -> impl IntoView {
 
             on:click=move |_| truncated.update(|e| *e = !*e)
         >
-            {description}
+            {split_hashtags(&description)
+                .into_iter()
+                .map(|part| match part {
+                    DescriptionPart::Text(text) => text.to_string().into_any(),
+                    DescriptionPart::Hashtag(hashtag) => {
+                        match normalize_hashtag(hashtag) {
+                            Some(normalized) => {
+                                view! {
+                                    <a class="font-semibold" href=format!("/tag/{normalized}")>
+                                        {hashtag.to_string()}
+                                    </a>
+                                }
+                                    .into_any()
+                            }
+                            None => hashtag.to_string().into_any(),
+                        }
+                    }
+                })
+                .collect_view()}
         </span>
     }
 }
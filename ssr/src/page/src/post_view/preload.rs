@@ -0,0 +1,312 @@
+//! Preloading of the upcoming posts of the feed
+//! thumbnails and the start of each video (an MP4 prefix, or the HLS manifests and first segment)
+//! are fetched into the HTTP cache nearest first, so swipes start instantly
+//! how many posts are preloaded adapts to the bandwidth measured while preloading
+use indexmap::IndexSet;
+use leptos::prelude::*;
+use utils::{types::PostId, video_quality::use_playback_prefs};
+use yral_canisters_common::utils::posts::PostDetails;
+
+/// Posts preloaded ahead until the bandwidth is known
+const DEFAULT_AHEAD: usize = 3;
+const MIN_AHEAD: usize = 1;
+const MAX_AHEAD: usize = 8;
+/// Bandwidth budgeted per preloaded post, in Mbps
+const MBPS_PER_POST: f64 = 1.5;
+/// Bytes of an MP4 preloaded, roughly the first seconds of a video
+const MP4_PREFIX_BYTES: usize = 512 * 1024;
+/// Smallest fetch used as a bandwidth sample, smaller ones mostly measure latency
+const MIN_SAMPLE_BYTES: usize = 64 * 1024;
+/// Weight of the latest sample in the bandwidth estimate
+const BANDWIDTH_EWMA_WEIGHT: f64 = 0.3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PreloadKind {
+    Thumbnail,
+    Video,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreloadTask {
+    pub post: PostId,
+    pub uid: String,
+    pub kind: PreloadKind,
+}
+
+/// Posts to preload after the current one for the estimated bandwidth
+pub fn preload_ahead(bandwidth_mbps: Option<f64>) -> usize {
+    match bandwidth_mbps {
+        Some(mbps) => ((mbps / MBPS_PER_POST) as usize).clamp(MIN_AHEAD, MAX_AHEAD),
+        None => DEFAULT_AHEAD,
+    }
+}
+
+/// Preloads of the `upcoming` posts in priority order
+/// every thumbnail first so no upcoming post shows a blank frame, then the videos nearest first
+pub fn preload_plan(upcoming: &[(PostId, String)]) -> Vec<PreloadTask> {
+    [PreloadKind::Thumbnail, PreloadKind::Video]
+        .into_iter()
+        .flat_map(|kind| {
+            upcoming.iter().map(move |(post, uid)| PreloadTask {
+                post: *post,
+                uid: uid.clone(),
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Moving average of the measured bandwidth
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BandwidthEstimate(Option<f64>);
+
+impl BandwidthEstimate {
+    pub fn mbps(&self) -> Option<f64> {
+        self.0
+    }
+
+    /// Seed the estimate, only used until the first sample
+    pub fn seed(&mut self, mbps: Option<f64>) {
+        self.0 = self.0.or(mbps);
+    }
+
+    pub fn record(&mut self, bytes: usize, elapsed_ms: f64) {
+        if bytes < MIN_SAMPLE_BYTES || elapsed_ms <= 0.0 {
+            return;
+        }
+        let sample = (bytes * 8) as f64 / (elapsed_ms * 1000.0);
+        self.0 = Some(match self.0 {
+            Some(mbps) => mbps + BANDWIDTH_EWMA_WEIGHT * (sample - mbps),
+            None => sample,
+        });
+    }
+}
+
+/// First URI listed in an HLS playlist, resolved against the playlist's URL
+pub fn first_playlist_uri(playlist: &str, playlist_url: &str) -> Option<String> {
+    let uri = playlist
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    let base = reqwest::Url::parse(playlist_url).ok()?;
+    base.join(uri).ok().map(String::from)
+}
+
+#[cfg(feature = "hydrate")]
+mod runner {
+    use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+    use gloo::net::http::Request;
+    use leptos::{prelude::*, task::spawn_local};
+    use utils::{bg_url, video_quality::PlaybackPrefs};
+    use web_sys::{AbortController, AbortSignal};
+
+    use super::{
+        first_playlist_uri, BandwidthEstimate, PreloadKind, PreloadTask, MP4_PREFIX_BYTES,
+    };
+    use utils::types::PostId;
+
+    async fn fetch_text(url: &str, signal: &AbortSignal) -> Result<String, gloo::net::Error> {
+        Request::get(url)
+            .abort_signal(Some(signal))
+            .send()
+            .await?
+            .text()
+            .await
+    }
+
+    /// Fetch `url` into the HTTP cache, at most `max_bytes` of it
+    /// returns the number of bytes received
+    async fn fetch_bytes(
+        url: &str,
+        max_bytes: Option<usize>,
+        signal: &AbortSignal,
+    ) -> Result<usize, gloo::net::Error> {
+        let mut req = Request::get(url).abort_signal(Some(signal));
+        if let Some(max_bytes) = max_bytes {
+            req = req.header("Range", &format!("bytes=0-{}", max_bytes - 1));
+        }
+        Ok(req.send().await?.binary().await?.len())
+    }
+
+    async fn preload(
+        task: &PreloadTask,
+        prefs: PlaybackPrefs,
+        signal: &AbortSignal,
+    ) -> Result<usize, gloo::net::Error> {
+        match task.kind {
+            PreloadKind::Thumbnail => fetch_bytes(&bg_url(&task.uid), None, signal).await,
+            PreloadKind::Video if !prefs.hls => {
+                fetch_bytes(&prefs.video_url(&task.uid), Some(MP4_PREFIX_BYTES), signal).await
+            }
+            PreloadKind::Video => {
+                // master manifest -> first variant -> its first segment
+                let mut url = prefs.video_url(&task.uid);
+                for _ in 0..2 {
+                    let playlist = fetch_text(&url, signal).await?;
+                    let Some(next) = first_playlist_uri(&playlist, &url) else {
+                        return Ok(playlist.len());
+                    };
+                    url = next;
+                }
+                fetch_bytes(&url, None, signal).await
+            }
+        }
+    }
+
+    /// Runs the preloads of the latest plan one at a time
+    #[derive(Default)]
+    pub struct PreloadRunner {
+        done: Rc<RefCell<HashSet<(PostId, PreloadKind)>>>,
+        controller: Option<AbortController>,
+    }
+
+    impl PreloadRunner {
+        /// Abort the preloads in flight
+        pub fn cancel(&mut self) {
+            if let Some(controller) = self.controller.take() {
+                controller.abort();
+            }
+        }
+
+        /// Replace the running preloads with `tasks`, skipping the ones already done
+        pub fn run(
+            &mut self,
+            prefs: PlaybackPrefs,
+            tasks: Vec<PreloadTask>,
+            bandwidth: StoredValue<BandwidthEstimate>,
+        ) {
+            self.cancel();
+            let tasks: Vec<_> = {
+                let done = self.done.borrow();
+                tasks
+                    .into_iter()
+                    .filter(|task| !done.contains(&(task.post, task.kind)))
+                    .collect()
+            };
+            if tasks.is_empty() {
+                return;
+            }
+            let Ok(controller) = AbortController::new() else {
+                return;
+            };
+            let signal = controller.signal();
+            self.controller = Some(controller);
+
+            let done = self.done.clone();
+            spawn_local(async move {
+                for task in tasks {
+                    if signal.aborted() {
+                        return;
+                    }
+                    let start = js_sys::Date::now();
+                    match preload(&task, prefs, &signal).await {
+                        Ok(bytes) => {
+                            let elapsed = js_sys::Date::now() - start;
+                            bandwidth.try_update_value(|b| b.record(bytes, elapsed));
+                            done.borrow_mut().insert((task.post, task.kind));
+                        }
+                        Err(_) if signal.aborted() => return,
+                        Err(e) => {
+                            log::debug!("failed to preload {:?} of {}: {e}", task.kind, task.uid)
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Preload the posts after `current_idx` in `video_queue`
+/// in flight preloads are cancelled once the user scrolls past them, nothing is preloaded in
+/// data saver mode
+pub fn use_preload_manager(
+    video_queue: RwSignal<IndexSet<PostDetails>>,
+    current_idx: RwSignal<usize>,
+) {
+    let playback = use_playback_prefs();
+    let bandwidth = StoredValue::new(BandwidthEstimate::default());
+
+    let plan = Memo::new(move |_| {
+        let prefs = playback.get();
+        if prefs.data_saver {
+            return (prefs, vec![]);
+        }
+        let ahead = preload_ahead(bandwidth.with_value(|b| b.mbps()));
+        let current = current_idx.get();
+        let upcoming: Vec<_> = video_queue.with(|q| {
+            q.iter()
+                .skip(current + 1)
+                .take(ahead)
+                .map(|post| ((post.canister_id, post.post_id), post.uid.clone()))
+                .collect()
+        });
+        (prefs, preload_plan(&upcoming))
+    });
+
+    #[cfg(feature = "hydrate")]
+    {
+        use utils::video_quality::NetworkHints;
+
+        let runner = StoredValue::new_local(runner::PreloadRunner::default());
+        Effect::new(move || {
+            bandwidth.update_value(|b| b.seed(NetworkHints::read().downlink_mbps));
+            let (prefs, tasks) = plan.get();
+            runner.update_value(|r| r.run(prefs, tasks, bandwidth));
+        });
+        on_cleanup(move || {
+            runner.try_update_value(|r| r.cancel());
+        });
+    }
+    #[cfg(not(feature = "hydrate"))]
+    _ = plan;
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+
+    use super::*;
+
+    #[test]
+    fn plans_thumbnails_before_videos() {
+        let post = |id: u64| ((Principal::anonymous(), id), format!("uid-{id}"));
+        let plan = preload_plan(&[post(1), post(2)]);
+        let order: Vec<_> = plan.iter().map(|t| (t.post.1, t.kind)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (1, PreloadKind::Thumbnail),
+                (2, PreloadKind::Thumbnail),
+                (1, PreloadKind::Video),
+                (2, PreloadKind::Video),
+            ]
+        );
+    }
+
+    #[test]
+    fn adapts_to_bandwidth() {
+        let mut bandwidth = BandwidthEstimate::default();
+        assert_eq!(preload_ahead(bandwidth.mbps()), DEFAULT_AHEAD);
+
+        // thumbnails are too small to measure
+        bandwidth.record(10 * 1024, 10.0);
+        assert_eq!(bandwidth.mbps(), None);
+
+        // 1 MB in a second
+        bandwidth.record(1_000_000, 1000.0);
+        assert_eq!(bandwidth.mbps(), Some(8.0));
+        assert_eq!(preload_ahead(bandwidth.mbps()), 5);
+        assert_eq!(preload_ahead(Some(0.2)), MIN_AHEAD);
+        assert_eq!(preload_ahead(Some(100.0)), MAX_AHEAD);
+
+        assert_eq!(
+            first_playlist_uri(
+                "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1\nstream_1.m3u8\n",
+                "https://cf.test/uid/manifest/video.m3u8?clientBandwidthHint=1.0"
+            )
+            .as_deref(),
+            Some("https://cf.test/uid/manifest/stream_1.m3u8")
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use leptos::{prelude::*, server_fn::codec::Json};
+use utils::ml_feed::seen_set::SeenSet;
+
+/// Merge the posts seen on this device with the ones seen on the user's other devices
+/// returns the merged set, to be stored locally
+#[server(endpoint = "sync_seen_posts", input = Json, output = Json)]
+pub async fn sync_seen_posts(local: SeenSet) -> Result<SeenSet, ServerFnError> {
+    server_impl::sync_seen_posts(local).await
+}
+
+#[cfg(feature = "ssr")]
+pub use server_impl::seen_posts_for_request;
+
+#[cfg(feature = "ssr")]
+mod server_impl {
+    use auth::server_impl::{
+        add_to_shared_set, extract_principal_from_cookie, shared_set_range, store::KVStoreImpl,
+        trim_shared_set,
+    };
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use candid::Principal;
+    use leptos::prelude::*;
+    use leptos_axum::extract_with_state;
+    use utils::ml_feed::seen_set::SeenSet;
+    use yral_canisters_common::utils::time::current_epoch;
+
+    /// user principal -> set of the last synced seen sets, scored by sync time
+    const NAMESPACE: &str = "seen-posts-synced";
+    /// Synced sets that are kept, a sync is only lost if more syncs than this race with it
+    const SYNCED_SETS: u64 = 4;
+
+    async fn request_principal() -> Result<Option<Principal>, ServerFnError> {
+        let key: Key = expect_context();
+        let jar: SignedCookieJar = extract_with_state(&key).await?;
+        extract_principal_from_cookie(&jar)
+    }
+
+    /// Union of the last synced sets
+    async fn stored(kv: &KVStoreImpl, user: &str) -> Result<SeenSet, ServerFnError> {
+        let members = shared_set_range(kv, NAMESPACE, user, 0, SYNCED_SETS).await?;
+        let mut seen = SeenSet::default();
+        for (raw, _) in members {
+            if let Ok(synced) = serde_json::from_str::<SeenSet>(&raw) {
+                seen.merge(&synced.validated());
+            }
+        }
+        Ok(seen)
+    }
+
+    /// Each sync adds its merged set as a new member instead of overwriting a single value,
+    /// so concurrent syncs from several devices are all kept until the next read merges them
+    pub async fn sync_seen_posts(local: SeenSet) -> Result<SeenSet, ServerFnError> {
+        let mut merged = local.validated();
+        let Some(principal) = request_principal().await? else {
+            return Ok(merged);
+        };
+        let kv: KVStoreImpl = expect_context();
+        let user = principal.to_text();
+
+        merged.merge(&stored(&kv, &user).await?);
+        add_to_shared_set(
+            &kv,
+            NAMESPACE,
+            &user,
+            serde_json::to_string(&merged)?,
+            current_epoch().as_millis() as f64,
+        )
+        .await?;
+        trim_shared_set(&kv, NAMESPACE, &user, SYNCED_SETS).await?;
+
+        Ok(merged)
+    }
+
+    /// Posts seen by the user making the request, as last synced
+    pub async fn seen_posts_for_request() -> SeenSet {
+        let Ok(Some(principal)) = request_principal().await else {
+            return SeenSet::default();
+        };
+        let kv: KVStoreImpl = expect_context();
+        stored(&kv, &principal.to_text()).await.unwrap_or_else(|e| {
+            log::warn!("failed to read seen posts: {e}");
+            SeenSet::default()
+        })
+    }
+}
@@ -0,0 +1,106 @@
+//! Feed session kept in session storage
+//! a reload of the feed lands on the same post with the same upcoming queue instead of starting
+//! a fresh feed
+use codee::string::JsonSerdeCodec;
+use consts::FEED_SESSION_STORE;
+use indexmap::IndexSet;
+use leptos::prelude::*;
+use leptos_use::storage::use_session_storage;
+use serde::{Deserialize, Serialize};
+use utils::{posts::FetchCursor, types::PostId};
+use yral_canisters_common::utils::posts::PostDetails;
+
+use super::{video_iter::FeedResultType, PostViewCtx};
+
+/// Posts kept before the current one
+const SESSION_POSTS_BEHIND: usize = 5;
+/// Posts kept after the current one
+const SESSION_POSTS_AHEAD: usize = 25;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedSession {
+    /// window of the queue around the current post
+    pub queue: Vec<PostDetails>,
+    pub current_idx: usize,
+    pub cursor: FetchCursor,
+    pub last_result: Option<FeedResultType>,
+    pub batch_cnt: usize,
+    pub queue_end: bool,
+}
+
+impl FeedSession {
+    /// Session of `ctx`, keeping only the posts around the current one
+    pub fn capture(ctx: &PostViewCtx) -> Self {
+        let current_idx = ctx.current_idx.get_untracked();
+        let start = current_idx.saturating_sub(SESSION_POSTS_BEHIND);
+        let queue = ctx.video_queue.with_untracked(|q| {
+            q.iter()
+                .skip(start)
+                .take(current_idx - start + 1 + SESSION_POSTS_AHEAD)
+                .cloned()
+                .collect()
+        });
+
+        Self {
+            queue,
+            current_idx: current_idx - start,
+            cursor: ctx.fetch_cursor.get_untracked(),
+            last_result: ctx.last_result.get_untracked(),
+            batch_cnt: ctx.batch_cnt.get_untracked(),
+            queue_end: ctx.queue_end.get_untracked(),
+        }
+    }
+
+    /// Post the session was on
+    pub fn current_post(&self) -> Option<PostId> {
+        self.queue
+            .get(self.current_idx)
+            .map(|post| (post.canister_id, post.post_id))
+    }
+
+    /// Replace the feed state of `ctx` with the session's
+    pub fn restore(self, ctx: &PostViewCtx) {
+        ctx.video_queue
+            .set(self.queue.into_iter().collect::<IndexSet<_>>());
+        ctx.current_idx.set(self.current_idx);
+        ctx.fetch_cursor.set(self.cursor);
+        ctx.last_result.set(self.last_result);
+        ctx.batch_cnt.set(self.batch_cnt);
+        ctx.queue_end.set(self.queue_end);
+    }
+}
+
+/// Resume the stored session if it was on `initial_post`, then persist the feed as it moves
+/// `on_start` is called with whether the session was resumed
+/// runs after hydration, the server renders `initial_post` alone
+pub fn use_feed_session(
+    ctx: PostViewCtx,
+    initial_post: Option<PostId>,
+    on_start: impl Fn(bool) + 'static,
+) {
+    let (session, set_session, _) =
+        use_session_storage::<FeedSession, JsonSerdeCodec>(FEED_SESSION_STORE);
+
+    Effect::new(move |started: Option<()>| {
+        ctx.current_idx.track();
+        ctx.video_queue.track();
+        ctx.batch_cnt.track();
+        ctx.queue_end.track();
+
+        if started.is_none() {
+            let stored = session.get_untracked();
+            // a queue ahead of the initial post is already the latest session
+            let resume = initial_post.is_some()
+                && stored.current_post() == initial_post
+                && ctx.video_queue.with_untracked(|q| q.len()) <= 1;
+            if resume {
+                stored.restore(&ctx);
+            }
+            on_start(resume);
+            return;
+        }
+        if ctx.video_queue.with_untracked(|q| !q.is_empty()) {
+            set_session.set(FeedSession::capture(&ctx));
+        }
+    });
+}
@@ -6,7 +6,10 @@ use super::{overlay::VideoDetailsOverlay, video_loader::VideoView};
 use crate::scrolling_post_view::MuteIconOverlay;
 use component::{back_btn::go_back_or_fallback, spinner::FullScreenSpinner};
 use leptos_router::{components::Redirect, hooks::use_params, params::Params};
-use state::{audio_state::AudioState, canisters::unauth_canisters};
+use state::{
+    audio_state::AudioState,
+    canister_backend::{canister_backend, unauth_canister_backend, CanisterBackend},
+};
 use utils::event_streaming::events::auth_canisters_store;
 use utils::{bg_url, send_wrap};
 use yral_canisters_common::utils::posts::PostDetails;
@@ -71,11 +74,11 @@ pub fn SinglePost() -> impl IntoView {
         send_wrap(async move {
             let params = params.map_err(|_| PostFetchError::Invalid)?;
             let post_uid = if let Some(canisters) = auth_cans.get_untracked() {
-                canisters
+                canister_backend(canisters)
                     .get_post_details(params.canister_id, params.post_id)
                     .await
             } else {
-                let canisters = unauth_canisters();
+                let canisters = unauth_canister_backend();
                 canisters
                     .get_post_details(params.canister_id, params.post_id)
                     .await
@@ -1,31 +1,37 @@
-use std::pin::Pin;
+use std::{collections::BTreeSet, pin::Pin, time::Duration};
 
 use candid::Principal;
-use codee::string::JsonSerdeCodec;
 use futures::{stream::FuturesOrdered, Stream, StreamExt};
 use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use consts::USER_CANISTER_ID_STORE;
-use leptos_use::storage::use_local_storage;
-use utils::{
-    event_streaming::events::auth_canisters_store,
-    host::show_nsfw_content,
-    ml_feed::{
-        get_ml_feed_clean, get_ml_feed_coldstart_clean, get_ml_feed_coldstart_nsfw,
-        get_ml_feed_nsfw,
-    },
-    posts::FetchCursor,
-};
+use utils::{host::show_nsfw_content, ml_feed::seen_set::SeenSet, posts::FetchCursor};
 use yral_canisters_common::{utils::posts::PostDetails, Canisters, Error as CanistersError};
+use yral_types::post::PostItem;
+
+use super::feed_provider::{
+    FeedChain, FeedError, FeedProviderImpl, FeedQuery, FollowingFeed, HashtagFeed,
+};
+
+/// Followed creators are queried in parallel, a few at a time
+const FOLLOWING_FEED_TIMEOUT: Duration = Duration::from_secs(8);
+/// Hashtag feeds are served by the index alone, without fallbacks
+const HASHTAG_FEED_TIMEOUT: Duration = Duration::from_secs(8);
+/// Latest posts of the queue sent to the feed providers as filters
+const RECENT_POSTS_FILTERED: usize = 50;
 
 type PostsStream<'a> = Pin<Box<dyn Stream<Item = Vec<Result<PostDetails, CanistersError>>> + 'a>>;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum FeedResultType {
     PostCache,
     MLFeedCache,
     MLFeed,
     MLFeedColdstart,
+    CreatorCanisters,
+    Fixture,
+    Hashtag,
+    Following,
 }
 
 pub struct FetchVideosRes<'a> {
@@ -44,56 +50,12 @@ impl<'a, const AUTH: bool> VideoFetchStream<'a, AUTH> {
         Self { canisters, cursor }
     }
 
-    pub async fn fetch_post_uids_ml_feed_chunked(
-        &self,
-        chunks: usize,
-        allow_nsfw: bool,
-        video_queue: Vec<PostDetails>,
-    ) -> Result<FetchVideosRes<'a>, ServerFnError> {
-        let (user_canister_id_local_storage, _, _) =
-            use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
-        let user_canister_id;
-        if let Some(canister_id) = user_canister_id_local_storage.get_untracked() {
-            user_canister_id = canister_id;
-        } else {
-            let cans_store = auth_canisters_store();
-            let mut cans_stream = cans_store.to_stream();
-            let cans;
-            loop {
-                if let Some(cans_val) = cans_stream.next().await.flatten() {
-                    cans = cans_val;
-                    break;
-                } else {
-                    continue;
-                }
-            }
-            user_canister_id = cans.user_canister();
-        }
-
-        let show_nsfw = allow_nsfw || show_nsfw_content();
-        let top_posts = if show_nsfw {
-            get_ml_feed_nsfw(
-                user_canister_id,
-                self.cursor.limit as u32,
-                video_queue.clone(),
-            )
-            .await
-            .map_err(|e| ServerFnError::new(format!("Error fetching ml feed: {e:?}")))?
-        } else {
-            get_ml_feed_clean(
-                user_canister_id,
-                self.cursor.limit as u32,
-                video_queue.clone(),
-            )
-            .await
-            .map_err(|e| ServerFnError::new(format!("Error fetching ml feed: {e:?}")))?
-        };
-
-        let end = false;
-        let chunk_stream = top_posts
+    fn resolve_posts(&self, posts: Vec<PostItem>, chunks: usize) -> PostsStream<'a> {
+        let canisters = self.canisters;
+        let chunk_stream = posts
             .into_iter()
             .map(move |item| {
-                self.canisters.get_post_details_with_nsfw_info(
+                canisters.get_post_details_with_nsfw_info(
                     item.canister_id,
                     item.post_id,
                     item.nsfw_probability,
@@ -103,88 +65,125 @@ impl<'a, const AUTH: bool> VideoFetchStream<'a, AUTH> {
             .filter_map(|res| async { res.transpose() })
             .chunks(chunks);
 
-        Ok(FetchVideosRes {
-            posts_stream: Box::pin(chunk_stream),
-            end,
-            res_type: FeedResultType::MLFeed,
-        })
+        Box::pin(chunk_stream)
     }
 }
 
 impl<'a> VideoFetchStream<'a, true> {
-    pub async fn fetch_post_uids_mlfeed_cache_chunked(
-        &self,
-        chunks: usize,
+    fn feed_query(
+        &mut self,
         allow_nsfw: bool,
         video_queue: Vec<PostDetails>,
-    ) -> Result<FetchVideosRes<'a>, ServerFnError> {
-        let cans_true = self.canisters;
-
-        let user_canister_id = cans_true.user_canister();
+        seen_posts: SeenSet,
+        hidden_creators: BTreeSet<Principal>,
+    ) -> FeedQuery {
+        let coldstart = video_queue.len() < 30;
+        // older posts are covered by `seen_posts`, no need to send the whole queue
+        let recent = video_queue.len().saturating_sub(RECENT_POSTS_FILTERED);
+        if coldstart {
+            self.cursor.set_limit(30);
+        }
+        FeedQuery {
+            user_canister: self.canisters.user_canister(),
+            limit: self.cursor.limit as u32,
+            allow_nsfw: allow_nsfw || show_nsfw_content(),
+            coldstart,
+            seen: video_queue[recent..].to_vec(),
+            seen_posts,
+            hidden_creators,
+        }
+    }
 
-        let show_nsfw = allow_nsfw || show_nsfw_content();
-        let top_posts = if show_nsfw {
-            get_ml_feed_coldstart_nsfw(
-                user_canister_id,
-                self.cursor.limit as u32,
-                video_queue.clone(),
-            )
-            .await
-            .map_err(|e| ServerFnError::new(format!("Error fetching ml feed: {e:?}")))?
-        } else {
-            get_ml_feed_coldstart_clean(
-                user_canister_id,
-                self.cursor.limit as u32,
-                video_queue.clone(),
-            )
+    async fn fetch_chain(
+        &self,
+        chain: FeedChain<'a>,
+        query: &FeedQuery,
+        chunks: usize,
+    ) -> Result<FetchVideosRes<'a>, ServerFnError> {
+        let batch = chain
+            .fetch(query)
             .await
-            .map_err(|e| ServerFnError::new(format!("Error fetching ml feed: {e:?}")))?
-        };
-
-        let end = false;
-        let chunk_stream = top_posts
-            .into_iter()
-            .map(move |item| {
-                self.canisters.get_post_details_with_nsfw_info(
-                    item.canister_id,
-                    item.post_id,
-                    item.nsfw_probability,
-                )
-            })
-            .collect::<FuturesOrdered<_>>()
-            .filter_map(|res| async { res.transpose() })
-            .chunks(chunks);
+            .map_err(|e| ServerFnError::new(format!("Error fetching feed: {e}")))?;
 
         Ok(FetchVideosRes {
-            posts_stream: Box::pin(chunk_stream),
-            end,
-            res_type: FeedResultType::MLFeedCache,
+            posts_stream: self.resolve_posts(batch.posts, chunks),
+            end: false,
+            res_type: batch.res_type,
         })
     }
 
-    pub async fn fetch_post_uids_hybrid(
+    /// Fetch the next batch from the configured [`FeedChain`]
+    pub async fn fetch_post_uids_chunked(
         &mut self,
         chunks: usize,
         allow_nsfw: bool,
         video_queue: Vec<PostDetails>,
+        seen_posts: SeenSet,
+        hidden_creators: BTreeSet<Principal>,
     ) -> Result<FetchVideosRes<'a>, ServerFnError> {
-        if video_queue.len() < 30 {
-            self.cursor.set_limit(30);
-            self.fetch_post_uids_mlfeed_cache_chunked(chunks, allow_nsfw, video_queue)
-                .await
-        } else {
-            let res = self
-                .fetch_post_uids_ml_feed_chunked(chunks, allow_nsfw, video_queue.clone())
-                .await;
-
-            match res {
-                Ok(res) => Ok(res),
-                Err(_) => {
-                    self.cursor.set_limit(50);
-                    self.fetch_post_uids_mlfeed_cache_chunked(chunks, allow_nsfw, video_queue)
-                        .await
-                }
+        let query = self.feed_query(allow_nsfw, video_queue, seen_posts, hidden_creators);
+        self.fetch_chain(FeedChain::from_config(self.canisters), &query, chunks)
+            .await
+    }
+
+    /// Fetch the next batch of posts from the followed creators
+    /// falls back to the configured [`FeedChain`] once they run out of unseen posts
+    pub async fn fetch_following_chunked(
+        &mut self,
+        chunks: usize,
+        allow_nsfw: bool,
+        video_queue: Vec<PostDetails>,
+        seen_posts: SeenSet,
+        hidden_creators: BTreeSet<Principal>,
+    ) -> Result<FetchVideosRes<'a>, ServerFnError> {
+        let query = self.feed_query(allow_nsfw, video_queue, seen_posts, hidden_creators);
+        let chain = FeedChain::from_config(self.canisters).with_first(
+            FeedProviderImpl::Following(FollowingFeed::new(self.canisters)),
+            FOLLOWING_FEED_TIMEOUT,
+        );
+        self.fetch_chain(chain, &query, chunks).await
+    }
+
+    /// Fetch the next page of posts tagged with `hashtag`
+    /// seen posts are not skipped, the feed is a listing of the hashtag
+    /// the feed ends at the first empty page
+    pub async fn fetch_hashtag_chunked(
+        &mut self,
+        chunks: usize,
+        hashtag: String,
+        allow_nsfw: bool,
+        hidden_creators: BTreeSet<Principal>,
+    ) -> Result<FetchVideosRes<'a>, ServerFnError> {
+        let query = FeedQuery {
+            user_canister: self.canisters.user_canister(),
+            limit: self.cursor.limit as u32,
+            allow_nsfw: allow_nsfw || show_nsfw_content(),
+            coldstart: false,
+            seen: vec![],
+            seen_posts: SeenSet::default(),
+            hidden_creators,
+        };
+
+        let chain = FeedChain::new(vec![(
+            FeedProviderImpl::Hashtag(HashtagFeed::new(hashtag, self.cursor.start)),
+            HASHTAG_FEED_TIMEOUT,
+        )]);
+        let batch = match chain.fetch(&query).await {
+            Ok(batch) => batch,
+            Err(FeedError::Empty) => {
+                return Ok(FetchVideosRes {
+                    posts_stream: Box::pin(futures::stream::empty()),
+                    end: true,
+                    res_type: FeedResultType::Hashtag,
+                })
             }
-        }
+            Err(e) => return Err(ServerFnError::new(format!("Error fetching feed: {e}"))),
+        };
+
+        Ok(FetchVideosRes {
+            posts_stream: self.resolve_posts(batch.posts, chunks),
+            end: false,
+            res_type: batch.res_type,
+        })
     }
 }
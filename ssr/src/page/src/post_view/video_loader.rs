@@ -19,17 +19,18 @@ use component::{
 use consts::USER_ONBOARDING_STORE;
 use state::local_storage::use_referrer_store;
 use utils::event_streaming::events::{auth_canisters_store, VideoWatched};
-use utils::{bg_url, event_streaming::events::account_connected_reader, mp4_url};
+use utils::video_quality::use_playback_prefs;
+use utils::{bg_url, event_streaming::events::account_connected_reader};
 
-use super::{overlay::VideoDetailsOverlay, PostDetails};
+use super::{engagement::EngagementCtx, overlay::VideoDetailsOverlay, PostDetails};
 
 #[component]
 pub fn BgView(
     video_queue: RwSignal<IndexSet<PostDetails>>,
-    idx: usize,
+    #[prop(into)] idx: Signal<usize>,
     children: Children,
 ) -> impl IntoView {
-    let post = Memo::new(move |_| video_queue.with(|q| q.get_index(idx).cloned()));
+    let post = Memo::new(move |_| video_queue.with(|q| q.get_index(idx()).cloned()));
     let uid = move || post().as_ref().map(|q| q.uid.clone()).unwrap_or_default();
 
     let (is_connected, _) = account_connected_reader();
@@ -60,7 +61,7 @@ pub fn BgView(
                 style:background-image=move || format!("url({})", bg_url(uid()))
             ></div>
             <ShowAny when=move || {
-                referrer_store.get().is_some() && idx == 0 && !is_connected.get()
+                referrer_store.get().is_some() && idx() == 0 && !is_connected.get()
                     && show_refer_login_popup.get()
             }>
                 <FeedPopUp
@@ -87,12 +88,27 @@ pub fn VideoView(
     #[prop(optional)] _ref: NodeRef<Video>,
     #[prop(optional)] autoplay_at_render: bool,
     muted: RwSignal<bool>,
+    /// overrides the `preload` attribute picked from the playback preferences
+    #[prop(optional, into)]
+    preload: Option<Signal<&'static str>>,
 ) -> impl IntoView {
     let post_for_uid = post;
     let post_for_mixpanel = post;
     let uid = Memo::new(move |_| post_for_uid.with(|p| p.as_ref().map(|p| p.uid.clone())));
     let view_bg_url = move || uid().map(bg_url);
-    let view_video_url = move || uid().map(mp4_url);
+    let playback = use_playback_prefs();
+    // the source of a post is kept once picked, so a video isn't reloaded when the preferences
+    // are read after hydration or changed mid playback
+    let view_video_url = Memo::new(move |prev: Option<&(Option<String>, Option<String>)>| {
+        let uid = uid();
+        if let Some(prev) = prev.filter(|(prev_uid, _)| *prev_uid == uid) {
+            return prev.clone();
+        }
+        let url = uid.as_ref().map(|uid| playback.get().video_url(uid));
+        (uid, url)
+    });
+    let view_video_url = move || view_video_url.with(|(_, url)| url.clone());
+    let preload = preload.unwrap_or_else(|| Signal::derive(move || playback.get().preload()));
     let mixpanel_video_muted = RwSignal::new(muted.get_untracked());
     let (is_connected, _, _) =
         use_local_storage::<bool, FromToStringCodec>(consts::ACCOUNT_CONNECTED_STORE);
@@ -228,13 +244,29 @@ pub fn VideoView(
         })
     });
 
+    let engagement = use_context::<EngagementCtx>();
+    let last_position = StoredValue::new(0.0);
     let _ = use_event_listener(_ref, ev::timeupdate, move |_evt| {
         let Some(video) = _ref.get() else {
             return;
         };
-        // let duration = video.duration();
+        let duration = video.duration();
         let current_time = video.current_time();
 
+        let playing = !video.paused();
+        if let Some((engagement, post)) = engagement
+            .filter(|_| playing)
+            .zip(post_for_view.get_untracked())
+        {
+            let last = last_position.get_value();
+            // `loop` restarts the video without an `ended` event
+            if current_time + 1.0 < last && last >= duration * 0.9 {
+                engagement.record_loop(&post);
+            }
+            engagement.record_progress(&post, current_time, duration, video.muted());
+        }
+        last_position.set_value(current_time);
+
         if current_time >= 3.0 && playing_started() {
             mixpanel_send_view_event.dispatch(());
         }
@@ -242,11 +274,18 @@ pub fn VideoView(
 
     VideoWatched.send_event(post, _ref);
 
+    on_cleanup(move || {
+        if let Some((engagement, post)) = engagement.zip(post_for_view.get_untracked()) {
+            engagement.finish(&post);
+        }
+    });
+
     view! {
         <VideoPlayer
             node_ref=_ref
             view_bg_url=Signal::derive(view_bg_url)
             view_video_url=Signal::derive(view_video_url)
+            preload
         />
     }
     .into_any()
@@ -256,25 +295,37 @@ pub fn VideoView(
 pub fn VideoViewForQueue(
     video_queue: RwSignal<IndexSet<PostDetails>>,
     current_idx: RwSignal<usize>,
-    idx: usize,
+    #[prop(into)] idx: Signal<usize>,
     muted: RwSignal<bool>,
 ) -> impl IntoView {
     let container_ref = NodeRef::<Video>::new();
+    let engagement = use_context::<EngagementCtx>();
+    let post = Signal::derive(move || video_queue.with(|q| q.get_index(idx()).cloned()));
 
     // Handles autoplay
     Effect::new(move |_| {
         let Some(vid) = container_ref.get() else {
             return;
         };
-        if idx != current_idx() {
+        if idx() != current_idx() {
             _ = vid.pause();
+            if let Some((engagement, post)) = engagement.zip(post.get_untracked()) {
+                engagement.finish(&post);
+            }
             return;
         }
         vid.set_autoplay(true);
         _ = vid.play();
     });
 
-    let post = Signal::derive(move || video_queue.with(|q| q.get_index(idx).cloned()));
+    // upcoming posts are fetched by the preload manager, only the current one loads itself
+    let preload = Signal::derive(move || {
+        if idx() == current_idx() {
+            "auto"
+        } else {
+            "none"
+        }
+    });
 
-    view! { <VideoView post _ref=container_ref muted /> }.into_any()
+    view! { <VideoView post _ref=container_ref muted preload /> }.into_any()
 }
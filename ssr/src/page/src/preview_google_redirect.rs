@@ -2,6 +2,7 @@ use ic_agent::identity::DelegatedIdentity;
 use leptos::prelude::*;
 use leptos_router::hooks::use_query;
 use serde::{Deserialize, Serialize};
+use utils::request_id::PropagateRequestId;
 use yral_types::delegated_identity::DelegatedIdentityWire;
 
 use crate::google_redirect::{IdentitySender, OAuthQuery};
@@ -46,7 +47,7 @@ async fn get_google_auth_url(host: String) -> Result<String, ServerFnError> {
         }
     };
 
-    let redirect_url: String = request.send().await?.json().await?;
+    let redirect_url: String = request.propagate_request_id().send().await?.json().await?;
 
     Ok(redirect_url)
 }
@@ -127,7 +128,7 @@ async fn preview_handle_oauth_query(
         }
     };
 
-    let response = request.send().await?;
+    let response = request.propagate_request_id().send().await?;
 
     if response.status().is_success() {
         let identity_wire: DelegatedIdentityWire = response.json().await?;
@@ -0,0 +1,197 @@
+use candid::Principal;
+use leptos::prelude::*;
+use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+use utils::send_wrap;
+use yral_canisters_common::utils::profile::ProfileDetails;
+
+use crate::follow::{
+    follow_stats, followers, following, FollowButton, FollowStats, FOLLOW_LIST_LIMIT,
+};
+
+/// Follower and following counts of the user, with a follow button for other users
+#[component]
+pub fn ProfileFollowStats(user_principal: Principal) -> impl IntoView {
+    let stats = Resource::new(|| (), move |_| follow_stats(user_principal));
+    let followed = Signal::derive(move || {
+        stats
+            .get()
+            .and_then(|stats| stats.ok())
+            .map(|stats| stats.followed_by_caller)
+    });
+    let count = move |f: fn(&FollowStats) -> u64| {
+        move || {
+            stats
+                .get()
+                .and_then(|stats| stats.ok())
+                .map(|stats| f(&stats))
+                .unwrap_or_default()
+        }
+    };
+
+    view! {
+        <Suspense>
+            <div class="flex flex-col items-center gap-3 pt-3">
+                <div class="flex flex-row gap-6 text-sm">
+                    <a href=format!("/profile/{user_principal}/followers")>
+                        <span class="font-bold">{count(|s| s.followers)}</span>
+                        <span class="text-white/60">" Followers"</span>
+                    </a>
+                    <a href=format!("/profile/{user_principal}/following")>
+                        <span class="font-bold">{count(|s| s.following)}</span>
+                        <span class="text-white/60">" Following"</span>
+                    </a>
+                </div>
+                <FollowButton
+                    creator_principal=user_principal
+                    followed
+                    on_change=Callback::new(move |_| stats.refetch())
+                />
+            </div>
+        </Suspense>
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FollowListKind {
+    Followers,
+    Following,
+}
+
+#[component]
+fn FollowListItem(user_principal: Principal) -> impl IntoView {
+    let details = Resource::new(
+        || (),
+        move |_| {
+            send_wrap(async move {
+                let canisters = unauth_canister_backend();
+                let Some(user_canister) = canisters
+                    .get_individual_canister_by_user_principal(user_principal)
+                    .await?
+                else {
+                    return Ok(None);
+                };
+                let details = canisters.get_profile_details(user_canister).await?;
+                Ok::<_, ServerFnError>(Some(details))
+            })
+        },
+    );
+
+    let item = move |details: Option<ProfileDetails>| {
+        let (name, propic) = match details {
+            Some(details) => (
+                details.display_name_or_fallback(),
+                Some(details.profile_pic_or_random()),
+            ),
+            None => (user_principal.to_text(), None),
+        };
+        view! {
+            <div class="flex flex-row items-center justify-between gap-4 w-full">
+                <a
+                    class="flex flex-row items-center gap-4 min-w-0"
+                    href=format!("/profile/{user_principal}/posts")
+                >
+                    {propic
+                        .map(|propic| {
+                            view! {
+                                <img class="h-10 w-10 rounded-full object-cover" src=propic />
+                            }
+                        })}
+                    <span class="truncate">{name}</span>
+                </a>
+                <FollowButton creator_principal=user_principal />
+            </div>
+        }
+    };
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let details = details.await.unwrap_or_else(|e| {
+                    log::warn!("failed to fetch profile of {user_principal}: {e}");
+                    None
+                });
+                item(details)
+            })}
+        </Suspense>
+    }
+}
+
+async fn follow_page(
+    user_principal: Principal,
+    kind: FollowListKind,
+    start: u64,
+) -> Result<Vec<Principal>, ServerFnError> {
+    match kind {
+        FollowListKind::Followers => followers(user_principal, start, FOLLOW_LIST_LIMIT).await,
+        FollowListKind::Following => following(user_principal, start, FOLLOW_LIST_LIMIT).await,
+    }
+}
+
+/// Followers of the user or the users they follow, a page at a time
+#[component]
+pub fn FollowList(user_principal: Principal, kind: FollowListKind) -> impl IntoView {
+    let users = Resource::new(|| (), move |_| follow_page(user_principal, kind, 0));
+    // pages loaded after the first one
+    let more = RwSignal::new(Vec::<Principal>::new());
+    let exhausted = RwSignal::new(false);
+    let load_more = Action::new_local(move |_: &()| async move {
+        let start = FOLLOW_LIST_LIMIT + more.with_untracked(|more| more.len() as u64);
+        match follow_page(user_principal, kind, start).await {
+            Ok(page) => {
+                exhausted.set((page.len() as u64) < FOLLOW_LIST_LIMIT);
+                more.update(|more| more.extend(page));
+            }
+            Err(e) => log::warn!("failed to fetch follow list: {e}"),
+        }
+    });
+    let empty_text = match kind {
+        FollowListKind::Followers => "No followers yet",
+        FollowListKind::Following => "Not following anyone yet",
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 w-full">
+            <Suspense>
+                {move || Suspend::new(async move {
+                    match users.await {
+                        Ok(users) if users.is_empty() => {
+                            view! { <span class="text-white/50 text-center">{empty_text}</span> }
+                                .into_any()
+                        }
+                        Ok(users) => {
+                            let has_more = users.len() as u64 == FOLLOW_LIST_LIMIT;
+                            view! {
+                                {users
+                                    .into_iter()
+                                    .map(|user_principal| view! { <FollowListItem user_principal /> })
+                                    .collect_view()}
+                                <For each=move || more.get() key=|user| *user let:user_principal>
+                                    <FollowListItem user_principal />
+                                </For>
+                                <Show when=move || has_more && !exhausted.get()>
+                                    <button
+                                        class="self-center rounded-full px-4 py-1 text-sm bg-white/20 disabled:opacity-50"
+                                        disabled=load_more.pending()
+                                        on:click=move |_| {
+                                            load_more.dispatch(());
+                                        }
+                                    >
+                                        "Load more"
+                                    </button>
+                                </Show>
+                            }
+                                .into_any()
+                        }
+                        Err(e) => {
+                            log::warn!("failed to fetch follow list: {e}");
+                            view! {
+                                <span class="text-white/50 text-center">Failed to load users</span>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </div>
+    }
+}
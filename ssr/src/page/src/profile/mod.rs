@@ -1,3 +1,4 @@
+mod follows;
 mod ic;
 pub mod overlay;
 mod posts;
@@ -10,6 +11,7 @@ use candid::Principal;
 use codee::string::FromToStringCodec;
 use component::connect::ConnectLogin;
 use consts::USER_PRINCIPAL_STORE;
+use follows::{FollowList, FollowListKind, ProfileFollowStats};
 use indexmap::IndexSet;
 use leptos::prelude::*;
 use leptos_icons::*;
@@ -20,7 +22,8 @@ use posts::ProfilePosts;
 use speculation::ProfileSpeculations;
 use state::{
     app_state::AppState,
-    canisters::{authenticated_canisters, unauth_canisters},
+    canister_backend::{unauth_canister_backend, CanisterBackend},
+    canisters::authenticated_canisters,
 };
 use tokens::ProfileTokens;
 use utils::{event_streaming::events::account_connected_reader, send_wrap};
@@ -58,6 +61,8 @@ fn ListSwitcher1(user_canister: Principal, user_principal: Principal) -> impl In
                 "posts" => 0,
                 "stakes" => 1,
                 "tokens" => 2,
+                "followers" => 3,
+                "following" => 4,
                 _ => 0,
             }
         })
@@ -99,6 +104,12 @@ fn ListSwitcher1(user_canister: Principal, user_principal: Principal) -> impl In
             <Show when=move || current_tab() == 2>
                 <ProfileTokens user_canister user_principal />
             </Show>
+            <Show when=move || current_tab() == 3>
+                <FollowList user_principal kind=FollowListKind::Followers />
+            </Show>
+            <Show when=move || current_tab() == 4>
+                <FollowList user_principal kind=FollowListKind::Following />
+            </Show>
         </div>
     }
 }
@@ -131,6 +142,7 @@ fn ProfileViewInner(user: ProfileDetails, user_canister: Principal) -> impl Into
                             >
                                 {display_name}
                             </span>
+                            <ProfileFollowStats user_principal=user.principal />
                             <Show when=move || !is_connected() && viewer_principal.get().map(|v| v.to_text() == username_or_principal).unwrap_or(false)>
                                 <div class="md:w-4/12 w-6/12 pt-5">
                                     <ConnectLogin cta_location="profile" />
@@ -175,16 +187,15 @@ pub fn ProfileView() -> impl IntoView {
                 let user_canister = canisters.user_canister();
                 return Ok((Some((details, user_canister)), None));
             }
-            let canisters = unauth_canisters();
+            let canisters = unauth_canister_backend();
             let Some(user_canister) = canisters
                 .get_individual_canister_by_user_principal(principal)
                 .await?
             else {
                 return Err(ServerFnError::new("Failed to get user canister"));
             };
-            let user = canisters.individual_user(user_canister).await;
-            let user_details = user.get_profile_details().await?;
-            Ok((Some((user_details.into(), user_canister)), None))
+            let user_details = canisters.get_profile_details(user_canister).await?;
+            Ok((Some((user_details, user_canister)), None))
         })
     });
 
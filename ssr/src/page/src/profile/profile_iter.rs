@@ -1,6 +1,6 @@
 use candid::Principal;
 use futures::stream::{FuturesOrdered, StreamExt, TryStreamExt};
-use yral_canisters_client::individual_user_template::{GetPostsOfUserProfileError, Result8};
+use state::canister_backend::{canister_backend, CanisterBackend};
 
 use yral_canisters_common::{utils::posts::PostDetails, Canisters, Error as CanistersError};
 
@@ -62,26 +62,10 @@ impl<const LIMIT: u64> ProfVideoStream<LIMIT> for ProfileVideoStream<LIMIT> {
         canisters: &Canisters<AUTH>,
         user_canister: Principal,
     ) -> Result<PostsRes, CanistersError> {
-        let user = canisters.individual_user(user_canister).await;
-        let posts = user
-            .get_posts_of_this_user_profile_with_pagination_cursor(cursor.start, cursor.limit)
+        let posts = canister_backend(canisters.clone())
+            .get_user_posts(user_canister, cursor.start, cursor.limit)
             .await?;
-        match posts {
-            Result8::Ok(v) => {
-                let end = v.len() < LIMIT as usize;
-                let posts = v
-                    .into_iter()
-                    .map(|details| PostDetails::from_canister_post(AUTH, user_canister, details))
-                    .collect::<Vec<_>>();
-                Ok(PostsRes { posts, end })
-            }
-            Result8::Err(GetPostsOfUserProfileError::ReachedEndOfItemsList) => Ok(PostsRes {
-                posts: vec![],
-                end: true,
-            }),
-            _ => Err(CanistersError::YralCanister(
-                "user canister refused to send posts".into(),
-            )),
-        }
+        let end = posts.len() < LIMIT as usize;
+        Ok(PostsRes { posts, end })
     }
 }
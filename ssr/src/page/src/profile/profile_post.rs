@@ -9,6 +9,7 @@ use leptos_router::{
     *,
 };
 use leptos_use::use_debounce_fn;
+use state::canister_backend::{unauth_canister_backend, CanisterBackend};
 use state::canisters::unauth_canisters;
 use utils::{
     event_streaming::events::auth_canisters_store, route::failure_redirect, send_wrap,
@@ -149,7 +150,7 @@ fn ProfilePostBase<
     } = expect_context();
 
     let intial_post = Resource::new(canister_and_post, move |params| {
-        let canisters = unauth_canisters();
+        let canisters = unauth_canister_backend();
         send_wrap(async move {
             let Some((canister_id, post_id)) = params else {
                 failure_redirect("Invalid profile post");
@@ -1,15 +1,13 @@
 use candid::Principal;
 use futures::{stream::FuturesOrdered, TryStreamExt};
 use leptos::prelude::*;
-use yral_canisters_client::individual_user_template::DeployedCdaoCanisters;
-use yral_canisters_client::individual_user_template::IndividualUserTemplate;
 
 use crate::wallet::tokens::WalletCard;
 use component::{bullet_loader::BulletLoader, token_confetti_symbol::TokenConfettiSymbol};
+use state::canister_backend::{canister_backend, CanisterBackend};
 use state::canisters::authenticated_canisters;
 use utils::send_wrap;
-use utils::token::icpump::IcpumpTokenInfo;
-use yral_canisters_common::{utils::token::TokenMetadata, Canisters, Error as CanistersError};
+use yral_canisters_common::{utils::token::TokenMetadata, Canisters};
 
 #[component]
 fn CreateYourToken(header_text: &'static str) -> impl IntoView {
@@ -23,51 +21,29 @@ fn CreateYourToken(header_text: &'static str) -> impl IntoView {
     }
 }
 
-async fn token_metadata(
-    cans: &Canisters<true>,
-    user_principal: Principal,
-    deployed_cans: DeployedCdaoCanisters,
-) -> Result<TokenMetadata, CanistersError> {
-    let governance = deployed_cans.governance;
-    let ledger = deployed_cans.ledger;
-    let index = deployed_cans.index;
-    cans.get_token_metadata(
-        &IcpumpTokenInfo,
-        Some(user_principal),
-        deployed_cans.root,
-        governance,
-        ledger,
-        index,
-    )
-    .await
-}
-
-async fn process_profile_tokens(
-    user: IndividualUserTemplate<'_>,
-    cans: Canisters<true>,
+/// Tokens created by the user owning `user_canister`, with whether their airdrop is claimed
+async fn profile_tokens(
+    backend: &impl CanisterBackend,
+    user_canister: Principal,
     user_principal: Principal,
 ) -> Result<Vec<(TokenMetadata, Option<bool>)>, ServerFnError> {
-    let tokens: Vec<_> = user
-        .deployed_cdao_canisters()
+    let tokens = backend
+        .get_user_tokens(user_canister, user_principal)
         .await?
         .into_iter()
-        .map(|deployed_cans| {
-            let cans = cans.clone();
-            async move {
-                let token = token_metadata(&cans, user_principal, deployed_cans).await?;
-                let is_airdrop_claimed = if let (Some(token_owner), Some(root)) =
-                    (token.token_owner.clone(), token.root)
-                {
+        .map(|token| async move {
+            let is_airdrop_claimed =
+                if let (Some(token_owner), Some(root)) = (token.token_owner.clone(), token.root) {
                     Some(
-                        cans.get_airdrop_status(token_owner.canister_id, root, user_principal)
+                        backend
+                            .get_airdrop_status(token_owner.canister_id, root, user_principal)
                             .await?,
                     )
                 } else {
                     None
                 };
 
-                Ok::<_, ServerFnError>((token, is_airdrop_claimed))
-            }
+            Ok::<_, ServerFnError>((token, is_airdrop_claimed))
         })
         .collect::<FuturesOrdered<_>>()
         .try_collect()
@@ -85,9 +61,9 @@ pub fn ProfileTokens(user_canister: Principal, user_principal: Principal) -> imp
             send_wrap(async move {
                 let auth_cans = auth_cans_res.await?;
                 let cans = Canisters::from_wire(auth_cans, expect_context())?;
-                let user = cans.individual_user(user_canister).await;
+                let backend = canister_backend(cans.clone());
 
-                let tokens = process_profile_tokens(user, cans.clone(), user_principal).await?;
+                let tokens = profile_tokens(&backend, user_canister, user_principal).await?;
                 Ok::<_, ServerFnError>((tokens, cans.user_principal() == user_principal))
             })
         },
@@ -4,7 +4,7 @@ use crate::pumpdump::ProcessedTokenListResponse;
 use crate::pumpdump::{PlayerDataRes, RunningGameRes};
 use leptos::{html::Audio, prelude::*, tachys::dom::window};
 use particles::{FireBubbles, SkullBubbles};
-use utils::event_streaming::events::TokenPumpedDumped;
+use utils::event_streaming::{events::TokenPumpedDumped, schema::PumpOrDump};
 use yral_pump_n_dump_common::GameDirection;
 fn non_visual_feedback(audio_ref: NodeRef<Audio>) {
     #[cfg(not(feature = "hydrate"))]
@@ -66,7 +66,7 @@ pub fn DumpButton(audio_ref: NodeRef<Audio>) -> impl IntoView {
                 TokenPumpedDumped.send_event(
                     token_details.token_name,
                     token_root,
-                    "dump".to_string(),
+                    PumpOrDump::Dump,
                     press_count_value,
                 );
 
@@ -183,7 +183,7 @@ pub fn PumpButton(audio_ref: NodeRef<Audio>) -> impl IntoView {
                 TokenPumpedDumped.send_event(
                     token_details.token_name,
                     token_root,
-                    "pump".to_string(),
+                    PumpOrDump::Pump,
                     press_count_value,
                 );
 
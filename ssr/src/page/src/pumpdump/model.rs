@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use yral_pump_n_dump_common::rest::{BalanceInfoResponse, UserBetsResponse};
 
 use consts::PUMP_AND_DUMP_WORKER_URL;
+use utils::request_id::PropagateRequestId;
 
 /// utility macro to quickly format cents
 #[macro_export]
@@ -116,14 +117,20 @@ impl GameRunningData {
             .join(&format!("/player_count/{owner}/{token_root}"))
             .expect("url to be valid");
 
-        let bets: UserBetsResponse = reqwest::get(bets_url)
+        let bets: UserBetsResponse = reqwest::Client::new()
+            .get(bets_url)
+            .propagate_request_id()
+            .send()
             .await
             .map_err(|err| format!("Coulnd't load bets: {err}"))?
             .json()
             .await
             .map_err(|err| format!("Couldn't parse bets out of repsonse: {err}"))?;
 
-        let player_count: u64 = reqwest::get(player_count_url)
+        let player_count: u64 = reqwest::Client::new()
+            .get(player_count_url)
+            .propagate_request_id()
+            .send()
             .await
             .map_err(|err| format!("Coulnd't load player count: {err}"))?
             .text()
@@ -161,9 +168,23 @@ impl PlayerData {
             .join(&format!("/game_count/{user_canister}"))
             .expect("Url to be valid");
 
-        let games_count: u64 = reqwest::get(games_count_url).await?.text().await?.parse()?;
-
-        let wallet_balance: BalanceInfoResponse = reqwest::get(balance_url).await?.json().await?;
+        let client = reqwest::Client::new();
+        let games_count: u64 = client
+            .get(games_count_url)
+            .propagate_request_id()
+            .send()
+            .await?
+            .text()
+            .await?
+            .parse()?;
+
+        let wallet_balance: BalanceInfoResponse = client
+            .get(balance_url)
+            .propagate_request_id()
+            .send()
+            .await?
+            .json()
+            .await?;
         let wallet_balance = wallet_balance.balance;
 
         let wallet_balance = convert_e8s_to_cents(wallet_balance);
@@ -9,15 +9,16 @@ use consts::PUMP_AND_DUMP_WORKER_URL;
 use futures::{stream::FuturesOrdered, StreamExt};
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
+use state::canister_backend::{canister_backend, CanisterBackend};
 use state::canisters::authenticated_canisters;
-use utils::send_wrap;
-use yral_canisters_client::{
-    individual_user_template::IndividualUserTemplate, sns_ledger::MetadataValue,
-    sns_root::ListSnsCanistersArg,
-};
+use utils::{request_id::PropagateRequestId, send_wrap};
+use yral_canisters_client::individual_user_template::IndividualUserTemplate;
 use yral_canisters_common::{
     cursored_data::{CursoredDataProvider, KeyedData, PageEntry},
-    utils::profile::{propic_from_principal, ProfileDetails},
+    utils::{
+        profile::{propic_from_principal, ProfileDetails},
+        token::RootType,
+    },
     Canisters,
 };
 use yral_pump_n_dump_common::rest::{CompletedGameInfo, UncommittedGameInfo, UncommittedGamesRes};
@@ -103,26 +104,23 @@ async fn load_history(
         (items, request_more)
     };
 
+    let backend = canister_backend(cans.clone());
     let token_infos = items
         .into_iter()
         .map(async |item| {
             let token_root = item.token_root();
 
             let owner_and_pfp_fut = async {
-                let token_owner = cans.get_token_owner(token_root).await.ok().flatten();
+                let token_owner = backend.get_token_owner(token_root).await.ok().flatten();
                 let (token_owner_principal, token_owner_canister) = token_owner
                     .map(|o| (o.principal_id, Some(o.canister_id)))
                     .unwrap_or_else(|| (Principal::anonymous(), None));
 
                 let pfp = if let Some(canister) = token_owner_canister {
-                    cans.individual_user(canister)
-                        .await
-                        .get_profile_details()
+                    backend
+                        .get_profile_details(canister)
                         .await
-                        .map(|details| {
-                            let details = ProfileDetails::from(details);
-                            details.profile_pic_or_random()
-                        })
+                        .map(|details| details.profile_pic_or_random())
                         .ok()
                 } else {
                     None
@@ -132,32 +130,12 @@ async fn load_history(
             };
 
             let token_logo_fut = async {
-                let ledger = cans
-                    .sns_root(token_root)
-                    .await
-                    .list_sns_canisters(ListSnsCanistersArg {})
+                backend
+                    .token_metadata_by_root_type(None, RootType::Other(token_root))
                     .await
                     .ok()
-                    .and_then(|l| l.ledger);
-                let Some(ledger) = ledger else {
-                    return propic_from_principal(token_root);
-                };
-
-                cans.sns_ledger(ledger)
-                    .await
-                    .icrc_1_metadata()
-                    .await
-                    .unwrap_or_default()
-                    .into_iter()
-                    .find_map(|(k, v)| {
-                        if k != "icrc1:logo" {
-                            return None;
-                        }
-                        let MetadataValue::Text(logo) = v else {
-                            return None;
-                        };
-                        Some(logo)
-                    })
+                    .flatten()
+                    .map(|meta| meta.logo_b64)
                     .unwrap_or_else(|| propic_from_principal(token_root))
             };
 
@@ -188,7 +166,10 @@ async fn load_uncommitted_games(cans: &Canisters<true>) -> Result<UncommittedGam
         .join(&format!("/uncommitted_games/{}", cans.user_canister()))
         .expect("url to be valid");
 
-    let uncommitted_games: UncommittedGamesRes = reqwest::get(uncommitted_games)
+    let uncommitted_games: UncommittedGamesRes = reqwest::Client::new()
+        .get(uncommitted_games)
+        .propagate_request_id()
+        .send()
         .await
         .map_err(|err| format!("Coulnd't load bets: {err}"))?
         .json()
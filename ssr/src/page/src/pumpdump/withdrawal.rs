@@ -16,7 +16,9 @@ use leptos_router::hooks::use_navigate;
 use leptos_use::storage::use_local_storage;
 use log;
 use state::canisters::authenticated_canisters;
-use utils::{mixpanel::mixpanel_events::*, send_wrap, try_or_redirect_opt};
+use utils::{
+    mixpanel::mixpanel_events::*, request_id::PropagateRequestId, send_wrap, try_or_redirect_opt,
+};
 use yral_canisters_common::{utils::token::balance::TokenBalance, Canisters};
 use yral_pump_n_dump_common::rest::{BalanceInfoResponse, ClaimReq};
 
@@ -36,14 +38,20 @@ async fn load_withdrawal_details(user_canister: Principal) -> Result<Details, St
         .join(&format!("/earnings/{user_canister}"))
         .expect("Url to be valid");
 
-    let balance_info: BalanceInfoResponse = reqwest::get(balance_info)
+    let balance_info: BalanceInfoResponse = reqwest::Client::new()
+        .get(balance_info)
+        .propagate_request_id()
+        .send()
         .await
         .map_err(|_| "failed to load balance".to_string())?
         .json()
         .await
         .map_err(|_| "failed to read response body".to_string())?;
 
-    let net_earnings: Nat = reqwest::get(net_earnings)
+    let net_earnings: Nat = reqwest::Client::new()
+        .get(net_earnings)
+        .propagate_request_id()
+        .send()
         .await
         .map_err(|err| format!("Coulnd't load net earnings: {err}"))?
         .text()
@@ -161,6 +169,7 @@ pub fn PndWithdrawal() -> impl IntoView {
             let res = client
                 .post(claim_url)
                 .json(&req)
+                .propagate_request_id()
                 .send()
                 .await
                 .map_err(ServerFnError::new)?;
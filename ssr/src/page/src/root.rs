@@ -1,6 +1,5 @@
-use crate::post_view::PostDetailsCacheCtx;
+use crate::post_view::{PostDetailsCacheCtx, PostViewWithUpdatesMLFeed};
 use crate::pumpdump::PumpNDump;
-use candid::Principal;
 use codee::string::FromToStringCodec;
 use component::spinner::FullScreenSpinner;
 use consts::USER_INTERNAL_STORE;
@@ -13,39 +12,75 @@ use utils::event_streaming::events::auth_canisters_store;
 use utils::host::show_nsfw_content;
 use utils::mixpanel::mixpanel_events::*;
 use utils::{
+    bg_url,
     host::{show_cdao_page, show_pnd_page},
-    ml_feed::{get_ml_feed_coldstart_clean, get_ml_feed_coldstart_nsfw},
+    ml_feed::FeedContent,
 };
+use yral_canisters_common::utils::posts::PostDetails;
 use yral_types::post::PostItem;
 
-#[server]
-async fn get_top_post_id_global_clean_feed() -> Result<Option<PostItem>, ServerFnError> {
-    let posts = get_ml_feed_coldstart_clean(Principal::anonymous(), 1, vec![])
-        .await
-        .map_err(|e| {
-            log::error!("Error getting top post id global clean feed: {e:?}");
-            ServerFnError::new(e.to_string())
-        })?;
-    if !posts.is_empty() {
-        return Ok(Some(posts[0].clone()));
+/// Posts of the cached coldstart feed tried before giving up on the first post
+#[cfg(feature = "ssr")]
+const INITIAL_POST_ATTEMPTS: usize = 3;
+
+/// First post of the feed, resolved while rendering so the video starts loading before hydration
+/// picked from the cached coldstart feed, skipping posts the user has already seen
+/// posts the user gave negative feedback on are never picked
+#[cfg(feature = "ssr")]
+async fn get_initial_post_global_feed(
+    content: FeedContent,
+) -> Result<Option<PostDetails>, ServerFnError> {
+    use crate::post_view::{
+        coldstart::ColdstartCache, feedback::feedback_for_request,
+        seen_posts::seen_posts_for_request,
+    };
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+
+    let cache: ColdstartCache = expect_context();
+    let posts = cache.posts(content).await.inspect_err(|e| {
+        log::error!("Error getting initial post of global {content:?} feed: {e}");
+    })?;
+    let feedback = feedback_for_request().await.unwrap_or_else(|e| {
+        log::warn!("failed to read feed feedback: {e}");
+        Default::default()
+    });
+    let posts: Vec<_> = posts
+        .into_iter()
+        .filter(|post| feedback.allows(post))
+        .collect();
+    let seen = seen_posts_for_request().await;
+    let unseen = posts
+        .iter()
+        .filter(|post| !seen.contains(post.canister_id, post.post_id));
+    let candidates = unseen.chain(posts.first()).take(INITIAL_POST_ATTEMPTS);
+
+    let canisters = unauth_canister_backend();
+    for post in candidates {
+        match canisters
+            .get_post_details_with_nsfw_info(post.canister_id, post.post_id, post.nsfw_probability)
+            .await
+        {
+            Ok(Some(details)) => return Ok(Some(details)),
+            Ok(None) => {}
+            Err(e) => log::warn!(
+                "failed to fetch details of post {}/{}: {e}",
+                post.canister_id,
+                post.post_id
+            ),
+        }
     }
 
     Ok(None)
 }
 
 #[server]
-async fn get_top_post_id_global_nsfw_feed() -> Result<Option<PostItem>, ServerFnError> {
-    let posts = get_ml_feed_coldstart_nsfw(Principal::anonymous(), 1, vec![])
-        .await
-        .map_err(|e| {
-            log::error!("Error getting top post id global nsfw feed: {e:?}");
-            ServerFnError::new(e.to_string())
-        })?;
-    if !posts.is_empty() {
-        return Ok(Some(posts[0].clone()));
-    }
+async fn get_initial_post_global_clean_feed() -> Result<Option<PostDetails>, ServerFnError> {
+    get_initial_post_global_feed(FeedContent::Clean).await
+}
 
-    Ok(None)
+#[server]
+async fn get_initial_post_global_nsfw_feed() -> Result<Option<PostDetails>, ServerFnError> {
+    get_initial_post_global_feed(FeedContent::Nsfw).await
 }
 
 #[component]
@@ -74,12 +109,13 @@ pub fn YralRootPage() -> impl IntoView {
         }
     });
 
-    let target_post = Resource::new(params, move |params_map| async move {
+    // blocking so the post and its poster preload are part of the initial HTML
+    let initial_post = Resource::new_blocking(params, move |params_map| async move {
         let nsfw_enabled = params_map.get("nsfw").map(|s| s == "true").unwrap_or(false);
         if nsfw_enabled || show_nsfw_content() {
-            get_top_post_id_global_nsfw_feed().await
+            get_initial_post_global_nsfw_feed().await
         } else {
-            get_top_post_id_global_clean_feed().await
+            get_initial_post_global_clean_feed().await
         }
     });
     let post_details_cache: PostDetailsCacheCtx = expect_context();
@@ -89,33 +125,36 @@ pub fn YralRootPage() -> impl IntoView {
     view! {
         <Title text="YRAL - Home" />
         <Suspense fallback=FullScreenSpinner>
-            {move || {
-                target_post
-                    .get()
-                    .map(|u| {
-                        let url = match u {
-                            Ok(Some(post_item)) => {
-                                let canister_id = post_item.canister_id;
-                                let post_id = post_item.post_id;
-                                post_details_cache.post_details.update(|post_details| {
-                                    post_details.insert((canister_id, post_id), post_item.clone());
-                                });
-
-                                if let Some(cans) = auth_canisters_store().get_untracked() {
-                                    let is_logged_in = is_connected.get_untracked();
-                                    let global = MixpanelGlobalProps::try_get(&cans, is_logged_in);
-                                    MixPanelEvent::track_home_page_viewed(MixpanelHomePageViewedProps { user_id:  global.user_id, visitor_id: global.visitor_id, is_logged_in: global.is_logged_in, canister_id: global.canister_id, is_nsfw_enabled: global.is_nsfw_enabled });
-                                }
-
-                                format!("/hot-or-not/{canister_id}/{post_id}")
-                            }
-                            Ok(None) => "/error?err=No Posts Found".to_string(),
-                            Err(e) => format!("/error?err={e}"),
+            {move || Suspend::new(async move {
+                match initial_post.await {
+                    Ok(Some(post)) => {
+                        let poster = bg_url(&post.uid);
+                        let item = PostItem {
+                            canister_id: post.canister_id,
+                            post_id: post.post_id,
+                            video_id: post.uid.clone(),
+                            nsfw_probability: post.nsfw_probability,
                         };
-                        view! { <Redirect path=url /> }
-                    })
-            }}
+                        post_details_cache.post_details.update(|post_details| {
+                            post_details.insert((item.canister_id, item.post_id), item);
+                        });
+
+                        if let Some(cans) = auth_canisters_store().get_untracked() {
+                            let is_logged_in = is_connected.get_untracked();
+                            let global = MixpanelGlobalProps::try_get(&cans, is_logged_in);
+                            MixPanelEvent::track_home_page_viewed(MixpanelHomePageViewedProps { user_id:  global.user_id, visitor_id: global.visitor_id, is_logged_in: global.is_logged_in, canister_id: global.canister_id, is_nsfw_enabled: global.is_nsfw_enabled });
+                        }
 
+                        view! {
+                            <Link rel="preload" as_="image" href=poster />
+                            <PostViewWithUpdatesMLFeed initial_post=Some(post) />
+                        }
+                            .into_any()
+                    }
+                    Ok(None) => view! { <Redirect path="/error?err=No Posts Found" /> }.into_any(),
+                    Err(e) => view! { <Redirect path=format!("/error?err={e}") /> }.into_any(),
+                }
+            })}
         </Suspense>
     }
     .into_any()
@@ -1,4 +1,7 @@
-use crate::post_view::video_loader::{BgView, VideoViewForQueue};
+use crate::post_view::{
+    preload::use_preload_manager,
+    video_loader::{BgView, VideoViewForQueue},
+};
 use indexmap::IndexSet;
 use leptos::html;
 use leptos::prelude::*;
@@ -8,6 +11,10 @@ use leptos_use::{use_intersection_observer_with_options, UseIntersectionObserver
 use state::audio_state::AudioState;
 use yral_canisters_common::utils::posts::PostDetails;
 
+/// Posts rendered on each side of the current one
+/// posts further ahead are fetched by the preload manager instead
+const RENDER_WINDOW: usize = 3;
+
 #[component]
 pub fn MuteIconOverlay(show_mute_icon: RwSignal<bool>) -> impl IntoView {
     view! {
@@ -42,6 +49,7 @@ pub fn ScrollingPostView<F: Fn() -> V + Clone + 'static + Send + Sync, V>(
     } = AudioState::get();
 
     let scroll_root: NodeRef<html::Div> = NodeRef::new();
+    use_preload_manager(video_queue, current_idx);
 
     let var_name = view! {
         <div class="h-full w-full overflow-hidden overflow-y-auto">
@@ -54,9 +62,15 @@ pub fn ScrollingPostView<F: Fn() -> V + Clone + 'static + Send + Sync, V>(
                 {overlay.map(|o| o.run())}
 
                 <For
-                    each=move || video_queue.get().into_iter().enumerate()
-                    key=move |(_, details)| (details.canister_id, details.post_id)
-                    children=move |(queue_idx, _details)| {
+                    each=move || video_queue.get()
+                    key=move |details| (details.canister_id, details.post_id)
+                    children=move |details| {
+                        // posts behind the current one are dropped while the feed is open
+                        let queue_idx = Memo::new(move |_| {
+                            video_queue
+                                .with(|q| q.get_index_of(&details))
+                                .unwrap_or(usize::MAX)
+                        });
                         let container_ref = NodeRef::<html::Div>::new();
                         let next_videos = fetch_next_videos.clone();
                         use_intersection_observer_with_options(
@@ -67,6 +81,7 @@ pub fn ScrollingPostView<F: Fn() -> V + Clone + 'static + Send + Sync, V>(
                                     return;
                                 };
                                 let rect = visible.bounding_client_rect();
+                                let queue_idx = queue_idx.get_untracked();
                                 if rect.y() == rect.height()
                                     || queue_idx == current_idx.get_untracked()
                                 {
@@ -89,13 +104,13 @@ pub fn ScrollingPostView<F: Fn() -> V + Clone + 'static + Send + Sync, V>(
                             let Some(container) = container_ref.get() else {
                                 return;
                             };
-                            if current_idx() == queue_idx && recovering_state.get_untracked() {
+                            if current_idx() == queue_idx() && recovering_state.get_untracked() {
                                 container.scroll_into_view();
                                 recovering_state.set(false);
                             }
                         });
                         let show_video = Memo::new(move |_| {
-                            queue_idx.abs_diff(current_idx()) <= 20
+                            queue_idx().abs_diff(current_idx()) <= RENDER_WINDOW
                         });
                         view! {
                             <div node_ref=container_ref class="snap-always snap-end w-full h-full">
@@ -0,0 +1,443 @@
+//! Search over users (username, principal) and posts (description, hashtags)
+//! the index is kept in the KV store by the SSR server, see [`server_impl`]
+//! uploads are indexed once published and older posts by the backfill of [`crate::hashtag`]
+mod page;
+
+pub use page::SearchPage;
+
+use candid::Principal;
+use leptos::{prelude::*, server_fn::codec::Json};
+use serde::{Deserialize, Serialize};
+use yral_types::post::PostItem;
+
+/// Users and posts returned by [`search`], each
+pub const SEARCH_RESULTS_LIMIT: usize = 20;
+/// Users and hashtags returned by [`search_suggestions`], each
+pub const SEARCH_SUGGESTIONS_LIMIT: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserResult {
+    pub principal: Principal,
+    pub canister_id: Principal,
+    pub username: String,
+    pub display_name: String,
+    pub profile_pic: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PostResult {
+    pub post: PostItem,
+    pub poster_principal: Principal,
+    pub description: String,
+    /// normalized, without the `#`
+    pub hashtags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub users: Vec<UserResult>,
+    /// newest first
+    pub posts: Vec<PostResult>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchSuggestion {
+    User(UserResult),
+    /// normalized, without the `#`
+    Hashtag(String),
+}
+
+/// Users matching every word of `query` by username, display name or principal,
+/// and posts matching every word by description or hashtag
+/// a principal is also looked up directly, so users show up before they are indexed
+#[server(endpoint = "search", input = Json, output = Json)]
+pub async fn search(query: String, allow_nsfw: bool) -> Result<SearchResults, ServerFnError> {
+    server_impl::search(query, allow_nsfw).await
+}
+
+/// Autocomplete for a partially typed query
+/// the last word is matched as a prefix, `@` only suggests users and `#` only hashtags
+#[server(endpoint = "search_suggestions", input = Json, output = Json)]
+pub async fn search_suggestions(prefix: String) -> Result<Vec<SearchSuggestion>, ServerFnError> {
+    server_impl::search_suggestions(prefix).await
+}
+
+#[cfg(feature = "ssr")]
+pub mod server_impl {
+    use std::collections::BTreeSet;
+
+    use auth::server_impl::{
+        add_to_shared_set, incr_shared_counter, read_shared_data_many, shared_set_range,
+        store::KVStoreImpl, trim_shared_set, write_shared_data,
+    };
+    use candid::Principal;
+    use leptos::prelude::*;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+    use utils::{
+        hashtags::normalize_hashtag,
+        search::{lookup_prefix, matches_terms, search_terms, term_prefixes},
+    };
+    use yral_canisters_common::utils::{
+        posts::PostDetails,
+        profile::{propic_from_principal, ProfileDetails},
+    };
+    use yral_types::post::PostItem;
+
+    use super::{
+        PostResult, SearchResults, SearchSuggestion, UserResult, SEARCH_RESULTS_LIMIT,
+        SEARCH_SUGGESTIONS_LIMIT,
+    };
+    use crate::post_view::feed_provider::NSFW_THRESHOLD;
+
+    /// user principal -> [`UserResult`]
+    const USER_NAMESPACE: &str = "search-user";
+    /// term prefix -> set of the matching users' principals, most recently indexed first
+    const USER_PREFIX_NAMESPACE: &str = "search-user-prefixes";
+    /// `<canister id>-<post id>` -> [`PostResult`]
+    const POST_NAMESPACE: &str = "search-post";
+    /// term prefix -> set of the matching posts' `<canister id>-<post id>`, newest first
+    const POST_PREFIX_NAMESPACE: &str = "search-post-prefixes";
+    /// prefix -> set of the hashtags starting with it, most recently used first
+    const HASHTAG_PREFIX_NAMESPACE: &str = "search-hashtag-prefixes";
+    /// Counter ordering the prefix sets, incremented for each indexed entry
+    const SEQUENCE_NAMESPACE: &str = "search-sequence";
+    const SEQUENCE_KEY: &str = "entries";
+    /// Entries kept per prefix, older ones can only be found by a longer prefix
+    const ENTRIES_PER_PREFIX: u64 = 200;
+    /// Words of a description that are indexed, the rest can't be searched
+    const MAX_POST_TERMS: usize = 32;
+
+    /// Values of `keys` read in a single round trip, missing ones are skipped
+    async fn read_many<T: DeserializeOwned>(
+        kv: &KVStoreImpl,
+        namespace: &str,
+        keys: &[String],
+    ) -> Result<Vec<T>, ServerFnError> {
+        let mut values = vec![];
+        for raw in read_shared_data_many(kv, namespace, keys)
+            .await?
+            .into_iter()
+            .flatten()
+        {
+            values.push(serde_json::from_str(&raw)?);
+        }
+        Ok(values)
+    }
+
+    async fn write<T: Serialize>(
+        kv: &KVStoreImpl,
+        namespace: &str,
+        key: &str,
+        value: &T,
+    ) -> Result<(), ServerFnError> {
+        write_shared_data(kv, namespace, key, serde_json::to_string(value)?).await
+    }
+
+    fn post_key(canister_id: Principal, post_id: u64) -> String {
+        format!("{canister_id}-{post_id}")
+    }
+
+    fn user_terms(user: &UserResult) -> Vec<String> {
+        search_terms(&format!(
+            "{} {} {}",
+            user.username, user.display_name, user.principal
+        ))
+    }
+
+    fn post_terms(post: &PostResult) -> Vec<String> {
+        let mut terms = search_terms(&post.description);
+        terms.truncate(MAX_POST_TERMS);
+        for hashtag in &post.hashtags {
+            if !terms.contains(hashtag) {
+                terms.push(hashtag.clone());
+            }
+        }
+        terms
+    }
+
+    /// Add `entry` to the prefix sets of `terms`, re-indexed entries move to the front
+    /// the sets are updated atomically, so concurrent indexing can't drop entries
+    async fn add_to_prefixes(
+        kv: &KVStoreImpl,
+        namespace: &str,
+        terms: &[String],
+        entry: String,
+    ) -> Result<(), ServerFnError> {
+        let sequence = incr_shared_counter(kv, SEQUENCE_NAMESPACE, SEQUENCE_KEY, 1).await?;
+        let prefixes: BTreeSet<_> = terms.iter().flat_map(|term| term_prefixes(term)).collect();
+        for prefix in prefixes {
+            add_to_shared_set(kv, namespace, prefix, entry.clone(), sequence as f64).await?;
+            trim_shared_set(kv, namespace, prefix, ENTRIES_PER_PREFIX).await?;
+        }
+        Ok(())
+    }
+
+    /// Entries listed under the most selective prefix of `terms`
+    async fn candidates(
+        kv: &KVStoreImpl,
+        namespace: &str,
+        terms: &[String],
+    ) -> Result<Vec<String>, ServerFnError> {
+        let Some(term) = terms.iter().max_by_key(|term| term.chars().count()) else {
+            return Ok(vec![]);
+        };
+        let entries =
+            shared_set_range(kv, namespace, lookup_prefix(term), 0, ENTRIES_PER_PREFIX).await?;
+        Ok(entries.into_iter().map(|(entry, _)| entry).collect())
+    }
+
+    pub async fn index_user(kv: &KVStoreImpl, user: UserResult) -> Result<(), ServerFnError> {
+        write(kv, USER_NAMESPACE, &user.principal.to_text(), &user).await?;
+        add_to_prefixes(
+            kv,
+            USER_PREFIX_NAMESPACE,
+            &user_terms(&user),
+            user.principal.to_text(),
+        )
+        .await
+    }
+
+    fn user_result(canister_id: Principal, profile: &ProfileDetails) -> UserResult {
+        UserResult {
+            principal: profile.principal,
+            canister_id,
+            username: profile.username_or_principal(),
+            display_name: profile.display_name_or_fallback(),
+            profile_pic: profile.profile_pic_or_random(),
+        }
+    }
+
+    pub async fn index_profile(
+        kv: &KVStoreImpl,
+        canister_id: Principal,
+        profile: &ProfileDetails,
+    ) -> Result<(), ServerFnError> {
+        index_user(kv, user_result(canister_id, profile)).await
+    }
+
+    async fn index_post_result(kv: &KVStoreImpl, post: PostResult) -> Result<(), ServerFnError> {
+        let key = post_key(post.post.canister_id, post.post.post_id);
+        write(kv, POST_NAMESPACE, &key, &post).await?;
+        add_to_prefixes(kv, POST_PREFIX_NAMESPACE, &post_terms(&post), key).await?;
+        for hashtag in &post.hashtags {
+            add_to_prefixes(
+                kv,
+                HASHTAG_PREFIX_NAMESPACE,
+                std::slice::from_ref(hashtag),
+                hashtag.clone(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn index_post(kv: &KVStoreImpl, post: &PostDetails) -> Result<(), ServerFnError> {
+        let mut hashtags: Vec<_> = post
+            .hastags
+            .iter()
+            .filter_map(|hashtag| normalize_hashtag(hashtag))
+            .collect();
+        hashtags.sort();
+        hashtags.dedup();
+
+        let post = PostResult {
+            post: PostItem {
+                canister_id: post.canister_id,
+                post_id: post.post_id,
+                video_id: post.uid.clone(),
+                nsfw_probability: post.nsfw_probability,
+            },
+            poster_principal: post.poster_principal,
+            description: post.description.clone(),
+            hashtags,
+        };
+        index_post_result(kv, post).await
+    }
+
+    #[derive(Deserialize)]
+    struct FixtureUser {
+        principal: Principal,
+        canister_id: Principal,
+        username: String,
+        display_name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct FixturePost {
+        canister_id: Principal,
+        post_id: u64,
+        description: String,
+        #[serde(default)]
+        hashtags: Vec<String>,
+    }
+
+    #[derive(Default, Deserialize)]
+    struct SearchFixtures {
+        #[serde(default)]
+        users: Vec<FixtureUser>,
+        #[serde(default)]
+        posts: Vec<FixturePost>,
+    }
+
+    #[derive(Deserialize)]
+    struct Fixtures {
+        #[serde(default)]
+        posts: Vec<PostItem>,
+        #[serde(default)]
+        search: SearchFixtures,
+    }
+
+    /// Index the users and posts of `mock-upstreams` fixtures, for local development
+    /// re-indexing only reorders the results, so this can run on every start
+    pub async fn index_fixtures(kv: &KVStoreImpl, fixtures: &str) -> Result<(), ServerFnError> {
+        let fixtures: Fixtures = serde_json::from_str(fixtures)?;
+
+        for user in &fixtures.search.users {
+            index_user(
+                kv,
+                UserResult {
+                    principal: user.principal,
+                    canister_id: user.canister_id,
+                    username: user.username.clone(),
+                    display_name: user.display_name.clone(),
+                    profile_pic: propic_from_principal(user.principal),
+                },
+            )
+            .await?;
+        }
+
+        for post in fixtures.search.posts {
+            let Some(item) = fixtures
+                .posts
+                .iter()
+                .find(|p| p.canister_id == post.canister_id && p.post_id == post.post_id)
+            else {
+                log::warn!(
+                    "search fixture {} has no matching post",
+                    post_key(post.canister_id, post.post_id)
+                );
+                continue;
+            };
+            let Some(poster) = fixtures
+                .search
+                .users
+                .iter()
+                .find(|user| user.canister_id == post.canister_id)
+            else {
+                log::warn!("search fixture post of {} has no user", post.canister_id);
+                continue;
+            };
+            index_post_result(
+                kv,
+                PostResult {
+                    post: item.clone(),
+                    poster_principal: poster.principal,
+                    description: post.description,
+                    hashtags: post
+                        .hashtags
+                        .iter()
+                        .filter_map(|hashtag| normalize_hashtag(hashtag))
+                        .collect(),
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn matching_users(
+        kv: &KVStoreImpl,
+        terms: &[String],
+        limit: usize,
+    ) -> Result<Vec<UserResult>, ServerFnError> {
+        let principals = candidates(kv, USER_PREFIX_NAMESPACE, terms).await?;
+        let users: Vec<UserResult> = read_many(kv, USER_NAMESPACE, &principals).await?;
+        Ok(users
+            .into_iter()
+            // the prefix sets are not cleaned up on renames
+            .filter(|user| matches_terms(&user_terms(user), terms))
+            .take(limit)
+            .collect())
+    }
+
+    /// Look up a user by principal, straight from their canister
+    async fn user_by_principal(principal: Principal) -> Result<Option<UserResult>, ServerFnError> {
+        let canisters = unauth_canister_backend();
+        let Some(canister_id) = canisters
+            .get_individual_canister_by_user_principal(principal)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let profile = canisters.get_profile_details(canister_id).await?;
+        Ok(Some(user_result(canister_id, &profile)))
+    }
+
+    pub async fn search(query: String, allow_nsfw: bool) -> Result<SearchResults, ServerFnError> {
+        let terms = search_terms(&query);
+        if terms.is_empty() {
+            return Ok(SearchResults::default());
+        }
+        let kv: KVStoreImpl = expect_context();
+
+        let mut users = vec![];
+        if let Ok(principal) = Principal::from_text(query.trim()) {
+            match user_by_principal(principal).await {
+                Ok(user) => users.extend(user),
+                Err(e) => log::warn!("failed to look up user {principal}: {e}"),
+            }
+        }
+        for user in matching_users(&kv, &terms, SEARCH_RESULTS_LIMIT).await? {
+            if users.len() < SEARCH_RESULTS_LIMIT && !users.contains(&user) {
+                users.push(user);
+            }
+        }
+
+        let keys = candidates(&kv, POST_PREFIX_NAMESPACE, &terms).await?;
+        let posts: Vec<PostResult> = read_many(&kv, POST_NAMESPACE, &keys).await?;
+        let posts = posts
+            .into_iter()
+            .filter(|post| allow_nsfw || post.post.nsfw_probability <= NSFW_THRESHOLD)
+            .filter(|post| matches_terms(&post_terms(post), &terms))
+            .take(SEARCH_RESULTS_LIMIT)
+            .collect();
+
+        Ok(SearchResults { users, posts })
+    }
+
+    pub async fn search_suggestions(
+        prefix: String,
+    ) -> Result<Vec<SearchSuggestion>, ServerFnError> {
+        let terms = search_terms(&prefix);
+        let Some(last) = terms.last() else {
+            return Ok(vec![]);
+        };
+        let kv: KVStoreImpl = expect_context();
+        let prefix = prefix.trim_start();
+
+        let mut suggestions = vec![];
+        if !prefix.starts_with('#') {
+            suggestions.extend(
+                matching_users(&kv, &terms, SEARCH_SUGGESTIONS_LIMIT)
+                    .await?
+                    .into_iter()
+                    .map(SearchSuggestion::User),
+            );
+        }
+        if !prefix.starts_with('@') {
+            let hashtags =
+                candidates(&kv, HASHTAG_PREFIX_NAMESPACE, std::slice::from_ref(last)).await?;
+            suggestions.extend(
+                hashtags
+                    .into_iter()
+                    .filter(|hashtag| hashtag.starts_with(last.as_str()))
+                    .take(SEARCH_SUGGESTIONS_LIMIT)
+                    .map(SearchSuggestion::Hashtag),
+            );
+        }
+
+        Ok(suggestions)
+    }
+}
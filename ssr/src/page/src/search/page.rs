@@ -0,0 +1,198 @@
+use codee::string::FromToStringCodec;
+use component::{back_btn::BackButton, title::TitleText};
+use consts::NSFW_TOGGLE_STORE;
+use leptos::prelude::*;
+use leptos_router::hooks::{use_navigate, use_query_map};
+use leptos_use::{signal_debounced, storage::use_local_storage};
+use utils::bg_url;
+
+use super::{search, search_suggestions, PostResult, SearchSuggestion, UserResult};
+
+/// Delay before suggestions are fetched for the typed query, in ms
+const SUGGESTIONS_DEBOUNCE_MS: f64 = 250.0;
+
+fn search_url(query: &str) -> String {
+    format!("/search?q={}", urlencoding::encode(query))
+}
+
+#[component]
+fn UserResultItem(user: UserResult) -> impl IntoView {
+    view! {
+        <a
+            class="flex flex-row items-center gap-4 min-w-0"
+            href=format!("/profile/{}/posts", user.principal)
+        >
+            <img class="h-10 w-10 rounded-full object-cover" src=user.profile_pic />
+            <div class="flex flex-col min-w-0">
+                <span class="truncate">{user.display_name}</span>
+                <span class="truncate text-sm text-white/50">"@"{user.username}</span>
+            </div>
+        </a>
+    }
+}
+
+#[component]
+fn PostResultItem(post: PostResult) -> impl IntoView {
+    view! {
+        <a
+            class="flex flex-col gap-1"
+            href=format!("/hot-or-not/{}/{}", post.post.canister_id, post.post.post_id)
+        >
+            <img
+                class="aspect-[9/16] w-full rounded-md object-cover bg-white/10"
+                src=bg_url(&post.post.video_id)
+            />
+            <span class="line-clamp-2 text-sm">{post.description}</span>
+        </a>
+    }
+}
+
+#[component]
+fn Suggestions(query: RwSignal<String>, show: RwSignal<bool>) -> impl IntoView {
+    let debounced = signal_debounced(query, SUGGESTIONS_DEBOUNCE_MS);
+    let suggestions = Resource::new(debounced, |prefix| async move {
+        if prefix.trim().is_empty() {
+            return Ok(vec![]);
+        }
+        search_suggestions(prefix).await
+    });
+
+    let suggestion = move |suggestion: SearchSuggestion| match suggestion {
+        SearchSuggestion::User(user) => view! { <UserResultItem user /> }.into_any(),
+        SearchSuggestion::Hashtag(hashtag) => view! {
+            <a class="py-1" href=format!("/tag/{hashtag}")>
+                "#"
+                {hashtag}
+            </a>
+        }
+        .into_any(),
+    };
+
+    view! {
+        <Show when=show>
+            <Suspense>
+                {move || Suspend::new(async move {
+                    let suggestions = suggestions
+                        .await
+                        .inspect_err(|e| log::warn!("failed to fetch search suggestions: {e}"))
+                        .unwrap_or_default();
+                    (!suggestions.is_empty())
+                        .then(|| {
+                            view! {
+                                <div class="absolute top-full left-0 right-0 z-10 mt-1 flex flex-col gap-3 p-4 rounded-md bg-neutral-900">
+                                    {suggestions.into_iter().map(suggestion).collect_view()}
+                                </div>
+                            }
+                        })
+                })}
+            </Suspense>
+        </Show>
+    }
+}
+
+#[component]
+fn Results(query: String) -> impl IntoView {
+    let (nsfw_enabled, _, _) = use_local_storage::<bool, FromToStringCodec>(NSFW_TOGGLE_STORE);
+    let results = Resource::new(
+        move || nsfw_enabled.get(),
+        move |allow_nsfw| search(query.clone(), allow_nsfw),
+    );
+
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                match results.await {
+                    Ok(results) if results.users.is_empty() && results.posts.is_empty() => {
+                        view! { <span class="text-white/50 text-center">No results</span> }
+                            .into_any()
+                    }
+                    Ok(results) => {
+                        let users = (!results.users.is_empty())
+                            .then(|| {
+                                view! {
+                                    <span class="font-bold">Users</span>
+                                    <div class="flex flex-col gap-4">
+                                        {results
+                                            .users
+                                            .into_iter()
+                                            .map(|user| view! { <UserResultItem user /> })
+                                            .collect_view()}
+                                    </div>
+                                }
+                            });
+                        let posts = (!results.posts.is_empty())
+                            .then(|| {
+                                view! {
+                                    <span class="font-bold">Videos</span>
+                                    <div class="grid grid-cols-3 gap-2">
+                                        {results
+                                            .posts
+                                            .into_iter()
+                                            .map(|post| view! { <PostResultItem post /> })
+                                            .collect_view()}
+                                    </div>
+                                }
+                            });
+                        view! {
+                            {users}
+                            {posts}
+                        }
+                            .into_any()
+                    }
+                    Err(e) => {
+                        log::warn!("search failed: {e}");
+                        view! { <span class="text-white/50 text-center">Search failed</span> }
+                            .into_any()
+                    }
+                }
+            })}
+        </Suspense>
+    }
+}
+
+/// Search for users and videos, `q` holds the submitted query
+#[component]
+pub fn SearchPage() -> impl IntoView {
+    let params = use_query_map();
+    let submitted = Memo::new(move |_| params.with(|p| p.get("q").unwrap_or_default()));
+    let query = RwSignal::new(submitted.get_untracked());
+    let show_suggestions = RwSignal::new(false);
+
+    let navigate = use_navigate();
+    let on_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        show_suggestions.set(false);
+        navigate(&search_url(&query.get_untracked()), Default::default());
+    };
+
+    view! {
+        <div class="min-h-screen w-full flex flex-col text-white pt-2 pb-12 bg-black items-center">
+            <TitleText justify_center=false>
+                <div class="flex flex-row justify-between">
+                    <BackButton fallback="/".to_string() />
+                    <span class="font-bold text-2xl">Search</span>
+                    <div></div>
+                </div>
+            </TitleText>
+            <div class="flex flex-col py-6 px-6 gap-6 w-full">
+                <form class="relative w-full" on:submit=on_submit>
+                    <input
+                        type="search"
+                        placeholder="Search users, videos or #hashtags"
+                        class="w-full rounded-md bg-neutral-800 px-4 py-2 focus:outline focus:outline-1 focus:outline-primary-600"
+                        prop:value=query
+                        on:input=move |ev| {
+                            query.set(event_target_value(&ev));
+                            show_suggestions.set(true);
+                        }
+                    />
+                    <Suggestions query show=show_suggestions />
+                </form>
+                {move || {
+                    let query = submitted.get();
+                    (!query.trim().is_empty()).then(|| view! { <Results query /> })
+                }}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeSet;
+
+use candid::Principal;
+use codee::string::JsonSerdeCodec;
+use component::back_btn::BackButton;
+use component::title::TitleText;
+use consts::HIDDEN_CREATORS_STORE;
+use leptos::prelude::*;
+use leptos_use::storage::use_local_storage;
+use state::canister_backend::{unauth_canister_backend, CanisterBackend};
+use utils::send_wrap;
+use yral_canisters_common::utils::profile::ProfileDetails;
+
+use crate::post_view::feedback::{hidden_creators, unhide_creator};
+
+#[component]
+fn HiddenCreator(
+    creator_canister: Principal,
+    details: Option<ProfileDetails>,
+    unhidden: RwSignal<BTreeSet<Principal>>,
+) -> impl IntoView {
+    let (_, set_hidden_creators, _) =
+        use_local_storage::<BTreeSet<Principal>, JsonSerdeCodec>(HIDDEN_CREATORS_STORE);
+    let unhide = Action::new_local(move |()| async move {
+        match unhide_creator(creator_canister).await {
+            Ok(hidden) => {
+                set_hidden_creators.set(hidden.into_iter().collect());
+                unhidden.update(|u| {
+                    u.insert(creator_canister);
+                });
+            }
+            Err(e) => log::warn!("failed to unhide creator: {e}"),
+        }
+    });
+
+    let (name, propic) = match details {
+        Some(details) => (
+            details.display_name_or_fallback(),
+            Some(details.profile_pic_or_random()),
+        ),
+        None => (creator_canister.to_text(), None),
+    };
+
+    view! {
+        <div class="grid grid-cols-4 items-center w-full gap-4">
+            <div class="flex flex-row gap-4 items-center col-span-3 min-w-0">
+                {propic
+                    .map(|propic| {
+                        view! {
+                            <img class="h-10 w-10 rounded-full object-cover" src=propic />
+                        }
+                    })}
+                <span class="truncate">{name}</span>
+            </div>
+            <button
+                class="justify-self-end rounded-full bg-white/10 px-4 py-1 text-sm disabled:opacity-50"
+                disabled=unhide.pending()
+                on:click=move |_| {
+                    unhide.dispatch(());
+                }
+            >
+                Unhide
+            </button>
+        </div>
+    }
+}
+
+/// Creators hidden from the feed, with a way to unhide them
+#[component]
+pub fn HiddenCreators() -> impl IntoView {
+    let unhidden = RwSignal::new(BTreeSet::<Principal>::new());
+    let creators = Resource::new(
+        || (),
+        |_| {
+            send_wrap(async move {
+                let hidden = hidden_creators().await?;
+                let canisters = unauth_canister_backend();
+                let mut creators = Vec::with_capacity(hidden.len());
+                for creator_canister in hidden {
+                    let details = canisters
+                        .get_profile_details(creator_canister)
+                        .await
+                        .inspect_err(|e| log::warn!("failed to fetch creator profile: {e:?}"))
+                        .ok();
+                    creators.push((creator_canister, details));
+                }
+                Ok::<_, ServerFnError>(creators)
+            })
+        },
+    );
+
+    view! {
+        <div class="min-h-screen w-full flex flex-col text-white pt-2 pb-12 bg-black items-center">
+            <TitleText justify_center=false>
+                <div class="flex flex-row justify-between">
+                    <BackButton fallback="/settings".to_string() />
+                    <span class="font-bold text-2xl">Hidden Creators</span>
+                    <div></div>
+                </div>
+            </TitleText>
+            <div class="flex flex-col py-12 px-8 gap-8 w-full text-lg">
+                <Suspense>
+                    {move || Suspend::new(async move {
+                        match creators.await {
+                            Ok(creators) if creators.is_empty() => {
+                                view! {
+                                    <span class="text-white/50 text-center">
+                                        "You haven't hidden any creators"
+                                    </span>
+                                }
+                                    .into_any()
+                            }
+                            Ok(creators) => {
+                                creators
+                                    .into_iter()
+                                    .map(|(creator_canister, details)| {
+                                        view! {
+                                            <Show when=move || {
+                                                !unhidden.with(|u| u.contains(&creator_canister))
+                                            }>
+                                                <HiddenCreator
+                                                    creator_canister
+                                                    details=details.clone()
+                                                    unhidden
+                                                />
+                                            </Show>
+                                        }
+                                    })
+                                    .collect_view()
+                                    .into_any()
+                            }
+                            Err(e) => {
+                                log::warn!("failed to fetch hidden creators: {e}");
+                                view! {
+                                    <span class="text-white/50 text-center">
+                                        Failed to load hidden creators
+                                    </span>
+                                }
+                                    .into_any()
+                            }
+                        }
+                    })}
+                </Suspense>
+            </div>
+        </div>
+    }
+}
@@ -1,3 +1,5 @@
+mod hidden_creators;
+
 use codee::string::FromToStringCodec;
 use component::back_btn::BackButton;
 use component::canisters_prov::AuthCansProvider;
@@ -12,10 +14,12 @@ use leptos_use::use_event_listener;
 use utils::event_streaming::events::account_connected_reader;
 use utils::host::{show_cdao_page, show_pnd_page};
 use utils::notifications::get_token_for_principal;
+use utils::video_quality::{use_data_saver_pref, use_playback_prefs, DataSaverPref};
 use yral_canisters_common::utils::profile::ProfileDetails;
 
+pub use hidden_creators::HiddenCreators;
+
 #[component]
-#[allow(dead_code)]
 fn MenuItem(
     #[prop(into)] text: String,
     #[prop(into)] href: String,
@@ -146,6 +150,43 @@ fn EnableNotifications(user_details: ProfileDetails) -> impl IntoView {
     }
 }
 
+#[component]
+fn DataSaver() -> impl IntoView {
+    let (_, set_pref) = use_data_saver_pref();
+    let playback = use_playback_prefs();
+    let enabled = Signal::derive(move || playback.get().data_saver);
+    let toggle_ref = NodeRef::<Input>::new();
+
+    _ = use_event_listener(toggle_ref, ev::change, move |_| {
+        let Some(toggle) = toggle_ref.get_untracked() else {
+            return;
+        };
+        set_pref(if toggle.checked() {
+            DataSaverPref::On
+        } else {
+            DataSaverPref::Off
+        })
+    });
+
+    view! {
+        <div class="grid grid-cols-2 items-center w-full">
+            <div class="flex flex-col">
+                <div class="flex flex-row gap-4 items-center">
+                    <Icon attr:class="text-2xl" icon=icondata::AiThunderboltOutlined />
+                    <span>Data Saver</span>
+                </div>
+                <span class="text-sm text-white/50">No preloading</span>
+                <span class="text-sm text-white/50">
+                    Lower video quality only in browsers that play HLS natively, like Safari
+                </span>
+            </div>
+            <div class="justify-self-end">
+                <Toggle checked=enabled node_ref=toggle_ref />
+            </div>
+        </div>
+    }
+}
+
 #[component]
 pub fn Settings() -> impl IntoView {
     view! {
@@ -163,6 +204,12 @@ pub fn Settings() -> impl IntoView {
                 <AuthCansProvider let:canisters>
                     <EnableNotifications user_details=canisters.profile_details() />
                 </AuthCansProvider>
+                <DataSaver />
+                <MenuItem
+                    href="/settings/hidden-creators"
+                    text="Hidden Creators"
+                    icon=icondata::AiEyeInvisibleOutlined
+                />
             </div>
             <MenuFooter />
         </div>
@@ -9,9 +9,9 @@ use leptos_router::components::Redirect;
 use leptos_router::hooks::use_params;
 use leptos_router::hooks::use_query;
 use leptos_router::params::Params;
+use state::canister_backend::{canister_backend, CanisterBackend};
 use state::canisters::authenticated_canisters;
 use utils::send_wrap;
-use utils::token::icpump::IcpumpTokenInfo;
 use utils::web::copy_to_clipboard;
 
 use crate::wallet::transactions::Transactions;
@@ -243,12 +243,9 @@ pub fn TokenInfo() -> impl IntoView {
                 let cans_wire = cans_wire.await?;
                 let cans = Canisters::from_wire(cans_wire, expect_context())?;
 
-                let meta = cans
-                    .token_metadata_by_root_type(
-                        &IcpumpTokenInfo,
-                        key_principal,
-                        params.token_root.clone(),
-                    )
+                let backend = canister_backend(cans.clone());
+                let meta = backend
+                    .token_metadata_by_root_type(key_principal, params.token_root.clone())
                     .await
                     .ok()
                     .flatten();
@@ -265,7 +262,7 @@ pub fn TokenInfo() -> impl IntoView {
                                 is_token_viewer_airdrop_claimed: true,
                             }));
                         };
-                        let is_airdrop_claimed = cans
+                        let is_airdrop_claimed = backend
                             .get_airdrop_status(
                                 token_owner.canister_id,
                                 *root,
@@ -10,12 +10,12 @@ use leptos::{
     ev::durationchange,
     html::{Input, Video},
     prelude::*,
+    server_fn::codec::Json,
 };
 use leptos_icons::*;
 use leptos_use::storage::use_local_storage;
 use leptos_use::use_event_listener;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use state::canisters::authenticated_canisters;
 use utils::mixpanel::mixpanel_events::*;
 use utils::{
@@ -27,6 +27,7 @@ use utils::{
     web::FileWithUrl,
 };
 use yral_canisters_common::Canisters;
+use yral_types::delegated_identity::DelegatedIdentityWire;
 
 #[component]
 pub fn DropBox() -> impl IntoView {
@@ -187,14 +188,14 @@ pub struct Watermark {
     pub uid: Option<String>,
 }
 #[allow(dead_code)]
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VideoMetadata {
     pub title: String,
     pub description: String,
     pub tags: String,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SerializablePostDetailsFromFrontend {
     pub is_nsfw: bool,
     pub hashtags: Vec<String>,
@@ -203,6 +204,48 @@ pub struct SerializablePostDetailsFromFrontend {
     pub creator_consent_for_inclusion_in_hot_or_not: bool,
 }
 
+/// Publish an upload through the upload worker
+/// the created post is indexed by the server, so the client can leave right after
+#[server(endpoint = "publish_video", input = Json, output = Json)]
+pub async fn publish_video(
+    delegated_identity: DelegatedIdentityWire,
+    meta: VideoMetadata,
+    post_details: SerializablePostDetailsFromFrontend,
+) -> Result<(), ServerFnError> {
+    use auth::server_impl::extract_principal_from_cookie;
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use leptos_axum::extract_with_state;
+    use serde_json::json;
+    use state::canister_backend::unauth_canister_backend;
+    use utils::request_id::PropagateRequestId;
+
+    let key: Key = expect_context();
+    let jar: SignedCookieJar = extract_with_state(&key).await?;
+    let principal = extract_principal_from_cookie(&jar)?
+        .ok_or_else(|| ServerFnError::new("User not logged in"))?;
+
+    let video_uid = post_details.video_uid.clone();
+    reqwest::Client::new()
+        .post(format!("{UPLOAD_URL}/update_metadata"))
+        .json(&json!({
+            "video_uid": video_uid,
+            "delegated_identity_wire": delegated_identity,
+            "meta": meta,
+            "post_details": post_details,
+        }))
+        .propagate_request_id()
+        .send()
+        .await?;
+
+    crate::hashtag::server_impl::spawn_upload_indexing(
+        expect_context(),
+        unauth_canister_backend(),
+        principal,
+        video_uid,
+    );
+    Ok(())
+}
+
 async fn upload_video_part(
     upload_base_url: &str,
     form_field_name: &str,
@@ -274,32 +317,22 @@ pub fn VideoUploader(
             async move {
                 let id = canisters.identity();
                 let delegated_identity = delegate_short_lived_identity(id);
-                let res: std::result::Result<reqwest::Response, ServerFnError> = {
-                    let client = reqwest::Client::new();
-
-                    let req = client
-                        .post(format!("{UPLOAD_URL}/update_metadata"))
-                        .json(&json!({
-                            "video_uid": uid,
-                            "delegated_identity_wire": delegated_identity,
-                            "meta": VideoMetadata{
-                                title: description.clone(),
-                                description: description.clone(),
-                                tags: hashtags.join(",")
-                            },
-                            "post_details": SerializablePostDetailsFromFrontend{
-                                is_nsfw,
-                                hashtags,
-                                description,
-                                video_uid: uid.clone(),
-                                creator_consent_for_inclusion_in_hot_or_not: enable_hot_or_not,
-                            }
-                        }));
-
-                    req.send()
-                        .await
-                        .map_err(|e| ServerFnError::new(e.to_string()))
-                };
+                let res = publish_video(
+                    delegated_identity,
+                    VideoMetadata {
+                        title: description.clone(),
+                        description: description.clone(),
+                        tags: hashtags.join(","),
+                    },
+                    SerializablePostDetailsFromFrontend {
+                        is_nsfw,
+                        hashtags,
+                        description,
+                        video_uid: uid.clone(),
+                        creator_consent_for_inclusion_in_hot_or_not: enable_hot_or_not,
+                    },
+                )
+                .await;
 
                 match res {
                     Ok(_) => {
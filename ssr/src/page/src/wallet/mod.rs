@@ -12,7 +12,7 @@ use leptos_meta::*;
 use leptos_router::components::Redirect;
 use leptos_router::hooks::use_params;
 use leptos_router::params::Params;
-use state::canisters::unauth_canisters;
+use state::canister_backend::{canister_backend, unauth_canister_backend, CanisterBackend};
 use state::{app_state::AppState, canisters::authenticated_canisters};
 use tokens::TokenList;
 use utils::event_streaming::events::account_connected_reader;
@@ -176,7 +176,8 @@ pub fn WalletImpl(principal: Principal) -> impl IntoView {
             send_wrap(async move {
                 let cans_wire = auth_cans.await;
                 let cans_wire = cans_wire?;
-                let canisters = Canisters::from_wire(cans_wire, expect_context())?;
+                let canisters =
+                    canister_backend(Canisters::from_wire(cans_wire, expect_context())?);
 
                 let Some(user_canister) = canisters
                     .get_individual_canister_by_user_principal(principal)
@@ -184,9 +185,8 @@ pub fn WalletImpl(principal: Principal) -> impl IntoView {
                 else {
                     return Err(ServerFnError::new("Failed to get user canister"));
                 };
-                let user = canisters.individual_user(user_canister).await;
-                let user_details = user.get_profile_details().await?;
-                Ok::<ProfileDetails, ServerFnError>(user_details.into())
+                let user_details = canisters.get_profile_details(user_canister).await?;
+                Ok::<ProfileDetails, ServerFnError>(user_details)
             })
         },
     );
@@ -204,7 +204,7 @@ pub fn WalletImpl(principal: Principal) -> impl IntoView {
         move || principal,
         move |principal| {
             send_wrap(async move {
-                let canisters = unauth_canisters();
+                let canisters = unauth_canister_backend();
                 let Some(user_canister) = canisters
                     .get_individual_canister_by_user_principal(principal)
                     .await?
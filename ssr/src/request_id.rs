@@ -0,0 +1,61 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    middleware::Next,
+    response::Response,
+};
+use http::header;
+use leptos::prelude::*;
+use leptos::server_fn::error::{NoCustomError, ServerFnErrorSerde};
+use tracing::Instrument;
+use utils::request_id::{attach_to_server_fn_error, RequestId, REQUEST_ID_HEADER};
+
+/// Max size of a server function error body we are willing to rewrite
+const MAX_ERROR_BODY_SIZE: usize = 64 * 1024;
+
+/// Generates (or accepts from the caller) an `x-request-id` for every request
+/// The id is available to handlers through request extensions
+/// and is tagged on the sentry scope + echoed back in the response headers
+pub async fn request_id_layer(mut req: Request, next: Next) -> Response {
+    let req_id = RequestId::from_headers(req.headers());
+
+    sentry::configure_scope(|scope| {
+        scope.set_tag("request_id", &req_id);
+        scope.set_tag("trace_id", req_id.trace_id());
+    });
+    req.extensions_mut().insert(req_id.clone());
+
+    let span = tracing::info_span!("request", request_id = %req_id);
+    let mut res = next.run(req).instrument(span).await;
+    res.headers_mut()
+        .insert(REQUEST_ID_HEADER, req_id.header_value());
+
+    res
+}
+
+/// Appends the request id to the message of a failed server function
+pub async fn attach_request_id_to_server_fn_error(res: Response, req_id: &RequestId) -> Response {
+    if !res.status().is_server_error() {
+        return res;
+    }
+
+    let (mut parts, body) = res.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_ERROR_BODY_SIZE).await else {
+        log::warn!("failed to read server fn error body, request_id: {req_id}");
+        return Response::from_parts(parts, Body::empty());
+    };
+    let Ok(raw_err) = std::str::from_utf8(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let err = ServerFnError::<NoCustomError>::de(raw_err);
+    if !matches!(err, ServerFnError::ServerError(_)) {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    let Ok(body) = attach_to_server_fn_error(err, req_id).ser() else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
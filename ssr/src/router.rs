@@ -0,0 +1,112 @@
+use axum::{
+    body::Body as AxumBody,
+    extract::{Path, State},
+    http::Request,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use http::{header, HeaderName, Method};
+use leptos::logging::log;
+use leptos::prelude::*;
+use leptos_axum::{handle_server_fns_with_context, LeptosRoutes};
+use state::server::AppState;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::instrument;
+use utils::host::is_host_or_origin_from_preview_domain;
+use utils::request_id::{RequestId, REQUEST_ID_HEADER, TRACEPARENT_HEADER};
+
+use crate::analytics::{analytics_cookies_layer, AnalyticsCtx};
+use crate::app::shell;
+use crate::fallback::file_and_error_handler;
+use crate::request_id::{attach_request_id_to_server_fn_error, request_id_layer};
+
+#[instrument(skip(app_state))]
+pub async fn server_fn_handler(
+    State(app_state): State<AppState>,
+    path: Path<String>,
+    request: Request<AxumBody>,
+) -> Response {
+    log!("{:?}", path);
+
+    let req_id = request
+        .extensions()
+        .get::<RequestId>()
+        .cloned()
+        .unwrap_or_else(RequestId::generate);
+    let req_id_ctx = req_id.clone();
+    let analytics = AnalyticsCtx::from_extensions(request.extensions());
+
+    let res = handle_server_fns_with_context(
+        move || {
+            provide_context(req_id_ctx.clone());
+            analytics.provide();
+            app_state.contexts.provide_all();
+        },
+        request,
+    )
+    .await;
+
+    attach_request_id_to_server_fn_error(res.into_response(), &req_id).await
+}
+
+#[instrument(skip(state))]
+pub async fn leptos_routes_handler(state: State<AppState>, req: Request<AxumBody>) -> Response {
+    let State(app_state) = state.clone();
+    let req_id = req.extensions().get::<RequestId>().cloned();
+    let analytics = AnalyticsCtx::from_extensions(req.extensions());
+    let handler = leptos_axum::render_route_with_context(
+        app_state.routes.clone(),
+        move || {
+            if let Some(req_id) = req_id.clone() {
+                provide_context(req_id);
+            }
+            analytics.provide();
+            app_state.contexts.provide_all();
+        },
+        move || shell(app_state.leptos_options.clone()),
+    );
+    handler(state, req).await.into_response()
+}
+
+fn cors_layer() -> CorsLayer {
+    CorsLayer::new()
+        .allow_credentials(true)
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            HeaderName::from_static("sentry-trace"),
+            HeaderName::from_static("baggage"),
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderName::from_static(TRACEPARENT_HEADER),
+        ])
+        .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
+        .allow_methods([Method::POST, Method::GET, Method::PUT, Method::OPTIONS])
+        .allow_origin(AllowOrigin::predicate(|origin, _| {
+            if let Ok(host) = origin.to_str() {
+                is_host_or_origin_from_preview_domain(host) || host == "yral.com"
+            } else {
+                false
+            }
+        }))
+}
+
+/// The application router (server functions + SSR routes)
+/// shared by the server binary and the in-process integration tests
+pub fn app_router(app_state: AppState) -> Router {
+    let routes = app_state.routes.clone();
+
+    Router::new()
+        .route(
+            "/api/*fn_name",
+            get(server_fn_handler).post(server_fn_handler),
+        )
+        .layer(cors_layer())
+        .leptos_routes_with_handler(routes, get(leptos_routes_handler))
+        // static files are served without analytics cookies
+        .route_layer(axum::middleware::from_fn(analytics_cookies_layer))
+        .fallback(file_and_error_handler)
+        .layer(axum::middleware::from_fn(request_id_layer))
+        .with_state(app_state)
+}
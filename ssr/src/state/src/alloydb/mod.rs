@@ -0,0 +1,108 @@
+pub mod queries;
+pub mod query;
+
+use std::sync::Arc;
+
+use google_cloud_alloydb_v1::{
+    builder::alloy_db_admin::ExecuteSql, client::AlloyDBAdmin, model::ExecuteSqlResponse,
+};
+use query::{FromRow, Query, QueryError, SqlParam};
+
+#[derive(Clone)]
+pub struct AlloyDbInstance {
+    pub client: AlloyDBAdmin,
+    execute_sql: Arc<ExecuteSql>,
+}
+
+impl AlloyDbInstance {
+    pub fn new(
+        client: AlloyDBAdmin,
+        instance: String,
+        db_name: String,
+        db_user: String,
+        db_password: String,
+    ) -> Self {
+        let execute_sql = client
+            .execute_sql(instance)
+            .set_database(db_name)
+            .set_user(db_user)
+            .set_password(db_password);
+
+        Self {
+            client,
+            execute_sql: Arc::new(execute_sql),
+        }
+    }
+
+    async fn execute_sql(
+        &self,
+        statement: String,
+    ) -> Result<ExecuteSqlResponse, google_cloud_alloydb_v1::Error> {
+        self.execute_sql
+            .as_ref()
+            .clone()
+            .set_sql_statement(statement)
+            .send()
+            .await
+    }
+
+    /// Rows returned by `query` with `params` bound
+    pub async fn query<R: FromRow>(
+        &self,
+        query: &Query<R>,
+        params: &[SqlParam],
+    ) -> Result<Vec<R>, QueryError> {
+        let statement = query.bind(params)?;
+        let res = self
+            .execute_sql(statement)
+            .await
+            .map_err(|source| QueryError::Db {
+                query: query.name,
+                source,
+            })?;
+        let result_sets: Vec<Vec<Vec<Option<String>>>> = res
+            .sql_results
+            .iter()
+            .map(|result| {
+                result
+                    .rows
+                    .iter()
+                    .map(|row| row.values.iter().map(|v| v.value.clone()).collect())
+                    .collect()
+            })
+            .collect();
+        query.decode(&result_sets)
+    }
+
+    /// Only row returned by `query`, if any
+    pub async fn query_opt<R: FromRow>(
+        &self,
+        query: &Query<R>,
+        params: &[SqlParam],
+    ) -> Result<Option<R>, QueryError> {
+        let mut rows = self.query(query, params).await?;
+        if rows.len() > 1 {
+            return Err(QueryError::RowCount {
+                query: query.name,
+                expected: "at most 1",
+                got: rows.len(),
+            });
+        }
+        Ok(rows.pop())
+    }
+
+    /// Only row returned by `query`
+    pub async fn query_one<R: FromRow>(
+        &self,
+        query: &Query<R>,
+        params: &[SqlParam],
+    ) -> Result<R, QueryError> {
+        self.query_opt(query, params)
+            .await?
+            .ok_or(QueryError::RowCount {
+                query: query.name,
+                expected: "1",
+                got: 0,
+            })
+    }
+}
@@ -0,0 +1,10 @@
+//! Named queries run against AlloyDB
+//! every statement of the server is defined here, parameters are bound by [`super::query`]
+use super::query::Query;
+
+/// Sentiment of the post with the cloudflare uid `$1`, `true` for hot
+/// NULL if no sentiment has been evaluated for the post yet
+pub const HOT_OR_NOT_SENTIMENT: Query<(Option<bool>,)> = Query::new(
+    "hot_or_not_sentiment",
+    "select hot_or_not_evaluator.get_hot_or_not($1)",
+);
@@ -0,0 +1,436 @@
+//! Typed queries over AlloyDB's `ExecuteSql`
+//! `ExecuteSql` has no bound parameters, it takes a single statement as a string, so `$1`, `$2`,
+//! ... are replaced here, on the client, by escaped literals. Rows are decoded into typed values
+//! instead of raw strings
+use std::marker::PhantomData;
+
+/// Errors of a query, tagged with the query's name
+#[derive(Debug, thiserror::Error)]
+pub enum QueryError {
+    #[error("query {query} failed: {source}")]
+    Db {
+        query: &'static str,
+        source: google_cloud_alloydb_v1::Error,
+    },
+    #[error("query {query}: parameter ${index} is not bound")]
+    MissingParam { query: &'static str, index: usize },
+    #[error("query {query}: parameter ${index} is not used")]
+    UnusedParam { query: &'static str, index: usize },
+    #[error("query {query}: parameter ${index} contains a NUL byte")]
+    InvalidParam { query: &'static str, index: usize },
+    #[error("query {query}: expected a single result set, got {got}")]
+    ResultSets { query: &'static str, got: usize },
+    #[error("query {query}: expected {expected} rows, got {got}")]
+    RowCount {
+        query: &'static str,
+        expected: &'static str,
+        got: usize,
+    },
+    #[error("query {query}: expected {expected} columns, got {got}")]
+    ColumnCount {
+        query: &'static str,
+        expected: usize,
+        got: usize,
+    },
+    #[error("query {query}: column {column}: {reason}")]
+    Decode {
+        query: &'static str,
+        column: usize,
+        reason: String,
+    },
+}
+
+/// Parameter bound to a query
+#[derive(Clone, Debug, PartialEq)]
+pub enum SqlParam {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+impl SqlParam {
+    /// SQL literal of the parameter, `None` if it can't be represented
+    fn literal(&self) -> Option<String> {
+        Some(match self {
+            Self::Null => "NULL".into(),
+            Self::Bool(b) => if *b { "TRUE" } else { "FALSE" }.into(),
+            // parenthesized, so a negative value can't form a `--` comment with a preceding `-`
+            Self::Int(i) => format!("({i})"),
+            // escape string syntax, so the literal is the same whatever
+            // `standard_conforming_strings` is set to
+            Self::Text(s) if s.contains('\0') => return None,
+            Self::Text(s) => format!("E'{}'", s.replace('\\', "\\\\").replace('\'', "''")),
+        })
+    }
+}
+
+impl From<bool> for SqlParam {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<i64> for SqlParam {
+    fn from(i: i64) -> Self {
+        Self::Int(i)
+    }
+}
+
+impl From<i32> for SqlParam {
+    fn from(i: i32) -> Self {
+        Self::Int(i.into())
+    }
+}
+
+impl From<u32> for SqlParam {
+    fn from(i: u32) -> Self {
+        Self::Int(i.into())
+    }
+}
+
+impl From<&str> for SqlParam {
+    fn from(s: &str) -> Self {
+        Self::Text(s.into())
+    }
+}
+
+impl From<String> for SqlParam {
+    fn from(s: String) -> Self {
+        Self::Text(s)
+    }
+}
+
+impl<T: Into<SqlParam>> From<Option<T>> for SqlParam {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Null, Into::into)
+    }
+}
+
+/// Value of a column, decoded from the text AlloyDB returns
+pub trait FromSqlValue: Sized {
+    /// `value` is `None` for NULL
+    fn from_sql(value: Option<&str>) -> Result<Self, String>;
+}
+
+fn non_null(value: Option<&str>) -> Result<&str, String> {
+    value.ok_or_else(|| "unexpected NULL".to_string())
+}
+
+impl FromSqlValue for String {
+    fn from_sql(value: Option<&str>) -> Result<Self, String> {
+        non_null(value).map(String::from)
+    }
+}
+
+impl FromSqlValue for bool {
+    fn from_sql(value: Option<&str>) -> Result<Self, String> {
+        let value = non_null(value)?;
+        match value.to_lowercase().as_str() {
+            "true" | "t" => Ok(true),
+            "false" | "f" => Ok(false),
+            _ => Err(format!("{value:?} is not a boolean")),
+        }
+    }
+}
+
+macro_rules! from_sql_parse {
+    ($($ty:ty),*) => {
+        $(impl FromSqlValue for $ty {
+            fn from_sql(value: Option<&str>) -> Result<Self, String> {
+                let value = non_null(value)?;
+                value
+                    .parse()
+                    .map_err(|e| format!("{value:?} is not a {}: {e}", stringify!($ty)))
+            }
+        })*
+    };
+}
+
+from_sql_parse!(i32, i64, u32, u64, f64);
+
+impl<T: FromSqlValue> FromSqlValue for Option<T> {
+    fn from_sql(value: Option<&str>) -> Result<Self, String> {
+        value.map(|v| T::from_sql(Some(v))).transpose()
+    }
+}
+
+/// Row of a query's result
+pub struct Row<'a> {
+    query: &'static str,
+    values: &'a [Option<String>],
+}
+
+impl Row<'_> {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Value of the column at `idx`
+    pub fn get<T: FromSqlValue>(&self, idx: usize) -> Result<T, QueryError> {
+        let decode_err = |reason| QueryError::Decode {
+            query: self.query,
+            column: idx,
+            reason,
+        };
+        let value = self
+            .values
+            .get(idx)
+            .ok_or_else(|| decode_err("missing column".into()))?;
+        T::from_sql(value.as_deref()).map_err(decode_err)
+    }
+}
+
+/// Row type of a query
+pub trait FromRow: Sized {
+    fn from_row(row: &Row) -> Result<Self, QueryError>;
+}
+
+macro_rules! from_row_tuple {
+    ($len:literal: $($ty:ident $idx:tt),+) => {
+        impl<$($ty: FromSqlValue),+> FromRow for ($($ty,)+) {
+            fn from_row(row: &Row) -> Result<Self, QueryError> {
+                if row.len() != $len {
+                    return Err(QueryError::ColumnCount {
+                        query: row.query,
+                        expected: $len,
+                        got: row.len(),
+                    });
+                }
+                Ok(($(row.get::<$ty>($idx)?,)+))
+            }
+        }
+    };
+}
+
+from_row_tuple!(1: A 0);
+from_row_tuple!(2: A 0, B 1);
+from_row_tuple!(3: A 0, B 1, C 2);
+from_row_tuple!(4: A 0, B 1, C 2, D 3);
+from_row_tuple!(5: A 0, B 1, C 2, D 3, E 4);
+
+/// Part of a query's SQL being scanned for parameters
+#[derive(Clone, Copy)]
+enum Scan {
+    Code,
+    /// within a quoted literal or identifier
+    Quoted(char),
+    /// within an escape string literal (`E'...'`), where `\'` doesn't end the literal
+    EscapeString,
+    LineComment,
+    /// within nested block comments
+    BlockComment(usize),
+}
+
+/// Whether `sql` ends with the `E` prefix of an escape string literal, rather than with an
+/// identifier ending in `E`
+fn ends_with_escape_prefix(sql: &str) -> bool {
+    let mut tail = sql.chars().rev();
+    matches!(tail.next(), Some('E' | 'e'))
+        && !tail
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Named query returning rows of `R`
+/// the SQL must be a single statement, parameters are referenced as `$1`, `$2`, ... outside of
+/// quoted literals, identifiers and comments (dollar quoted strings are not supported)
+pub struct Query<R> {
+    pub name: &'static str,
+    pub sql: &'static str,
+    _row: PhantomData<fn() -> R>,
+}
+
+impl<R> Query<R> {
+    pub const fn new(name: &'static str, sql: &'static str) -> Self {
+        Self {
+            name,
+            sql,
+            _row: PhantomData,
+        }
+    }
+
+    /// Statement with `params` bound, every parameter must be used
+    pub fn bind(&self, params: &[SqlParam]) -> Result<String, QueryError> {
+        let mut used = vec![false; params.len()];
+        let mut sql = String::with_capacity(self.sql.len());
+        let mut scan = Scan::Code;
+        let mut chars = self.sql.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (c, scan) {
+                ('\'', Scan::Code) if ends_with_escape_prefix(&sql) => scan = Scan::EscapeString,
+                ('\'' | '"', Scan::Code) => scan = Scan::Quoted(c),
+                ('\\', Scan::EscapeString) => {
+                    sql.push(c);
+                    sql.extend(chars.next());
+                    continue;
+                }
+                ('\'', Scan::EscapeString) if chars.peek() == Some(&'\'') => {
+                    sql.push(c);
+                    sql.extend(chars.next());
+                    continue;
+                }
+                ('\'', Scan::EscapeString) => scan = Scan::Code,
+                (c, Scan::Quoted(q)) if c == q => scan = Scan::Code,
+                ('-', Scan::Code) if chars.peek() == Some(&'-') => scan = Scan::LineComment,
+                ('\n', Scan::LineComment) => scan = Scan::Code,
+                ('/', Scan::Code | Scan::BlockComment(_)) if chars.peek() == Some(&'*') => {
+                    sql.push(c);
+                    sql.extend(chars.next());
+                    scan = match scan {
+                        Scan::BlockComment(depth) => Scan::BlockComment(depth + 1),
+                        _ => Scan::BlockComment(1),
+                    };
+                    continue;
+                }
+                ('*', Scan::BlockComment(depth)) if chars.peek() == Some(&'/') => {
+                    sql.push(c);
+                    sql.extend(chars.next());
+                    scan = match depth {
+                        1 => Scan::Code,
+                        depth => Scan::BlockComment(depth - 1),
+                    };
+                    continue;
+                }
+                ('$', Scan::Code) if chars.peek().is_some_and(char::is_ascii_digit) => {
+                    let mut index = 0usize;
+                    while let Some(d) = chars.peek().and_then(|d| d.to_digit(10)) {
+                        index = index.saturating_mul(10).saturating_add(d as usize);
+                        chars.next();
+                    }
+                    let param = index.checked_sub(1).and_then(|i| params.get(i)).ok_or(
+                        QueryError::MissingParam {
+                            query: self.name,
+                            index,
+                        },
+                    )?;
+                    let literal = param.literal().ok_or(QueryError::InvalidParam {
+                        query: self.name,
+                        index,
+                    })?;
+                    used[index - 1] = true;
+                    sql.push_str(&literal);
+                    continue;
+                }
+                _ => (),
+            }
+            sql.push(c);
+        }
+
+        if let Some(unused) = used.iter().position(|used| !used) {
+            return Err(QueryError::UnusedParam {
+                query: self.name,
+                index: unused + 1,
+            });
+        }
+        Ok(sql)
+    }
+}
+
+impl<R: FromRow> Query<R> {
+    /// Rows of the query's only result set
+    pub fn decode(&self, result_sets: &[Vec<Vec<Option<String>>>]) -> Result<Vec<R>, QueryError> {
+        let [rows] = result_sets else {
+            return Err(QueryError::ResultSets {
+                query: self.name,
+                got: result_sets.len(),
+            });
+        };
+        rows.iter()
+            .map(|values| {
+                R::from_row(&Row {
+                    query: self.name,
+                    values,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOOKUP: Query<(String, Option<bool>)> = Query::new(
+        "lookup",
+        "select name, flag from items where name = $1 and note <> '$2' and size > $2",
+    );
+
+    #[test]
+    fn binds_escaped_literals() {
+        let sql = LOOKUP
+            .bind(&["it's \\'; drop table items; --".into(), 3i64.into()])
+            .unwrap();
+        assert_eq!(
+            sql,
+            "select name, flag from items where name = E'it''s \\\\''; drop table items; --' \
+             and note <> '$2' and size > (3)"
+        );
+
+        assert!(matches!(
+            LOOKUP.bind(&["a".into()]),
+            Err(QueryError::MissingParam { index: 2, .. })
+        ));
+        assert!(matches!(
+            LOOKUP.bind(&["a".into(), 1i64.into(), SqlParam::Null]),
+            Err(QueryError::UnusedParam { index: 3, .. })
+        ));
+        assert!(matches!(
+            LOOKUP.bind(&["a\0".into(), 1i64.into()]),
+            Err(QueryError::InvalidParam { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn skips_comments() {
+        const COMMENTED: Query<(i64,)> = Query::new(
+            "commented",
+            "select $1-$2 -- don't bind $3\n/* nor /* nested */ $3 */ + $2",
+        );
+        let sql = COMMENTED.bind(&[5i64.into(), (-1i64).into()]).unwrap();
+        assert_eq!(
+            sql,
+            "select (5)-(-1) -- don't bind $3\n/* nor /* nested */ $3 */ + (-1)"
+        );
+    }
+
+    #[test]
+    fn skips_escape_strings() {
+        const ESCAPED: Query<(String,)> = Query::new(
+            "escaped",
+            "select e'it\\'s $1\\\\' || E'a''$1' || note || $1 from items where name = 'x\\' and type = $2",
+        );
+        let sql = ESCAPED.bind(&["b".into(), 2i64.into()]).unwrap();
+        assert_eq!(
+            sql,
+            "select e'it\\'s $1\\\\' || E'a''$1' || note || E'b' from items where name = 'x\\' and type = (2)"
+        );
+    }
+
+    #[test]
+    fn decodes_typed_rows() {
+        let row = |name: &str, flag: Option<&str>| vec![Some(name.into()), flag.map(Into::into)];
+        let rows = LOOKUP
+            .decode(&[vec![row("a", Some("TRUE")), row("b", None)]])
+            .unwrap();
+        assert_eq!(rows, vec![("a".into(), Some(true)), ("b".into(), None)]);
+
+        assert!(matches!(
+            LOOKUP.decode(&[vec![row("a", Some("maybe"))]]),
+            Err(QueryError::Decode { column: 1, .. })
+        ));
+        assert!(matches!(
+            LOOKUP.decode(&[vec![vec![None]]]),
+            Err(QueryError::ColumnCount { expected: 2, .. })
+        ));
+        assert!(matches!(
+            LOOKUP.decode(&[]),
+            Err(QueryError::ResultSets { got: 0, .. })
+        ));
+    }
+}
@@ -2,7 +2,7 @@ use leptos::prelude::window;
 
 use utils::host::show_pnd_condition;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AppType {
     YRAL,
     HotOrNot,
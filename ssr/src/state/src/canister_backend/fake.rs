@@ -0,0 +1,473 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use candid::Principal;
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+use yral_canisters_common::{
+    utils::{
+        posts::PostDetails,
+        profile::{propic_from_principal, ProfileDetails},
+        token::{RootType, TokenMetadata, TokenOwner},
+    },
+    Error as CanistersError,
+};
+use yral_types::post::PostItem;
+
+use super::CanisterBackend;
+
+#[derive(Default)]
+struct FakeState {
+    /// user principal -> user canister
+    user_canisters: HashMap<Principal, Principal>,
+    /// user canister -> profile
+    profiles: HashMap<Principal, ProfileDetails>,
+    /// (canister, post id) -> post
+    posts: BTreeMap<(Principal, u64), PostDetails>,
+    /// token root -> token
+    tokens: HashMap<Principal, TokenMetadata>,
+    /// user canister -> roots of the tokens created by the user
+    user_tokens: HashMap<Principal, Vec<Principal>>,
+    /// (token root, user principal)
+    claimed_airdrops: HashSet<(Principal, Principal)>,
+}
+
+/// In-memory canister backend holding fake users, posts and tokens
+/// the token balances are the ones set in the provided [`TokenMetadata`]
+///
+/// Provide it as a context to use it in place of the real canisters
+#[derive(Clone, Default)]
+pub struct FakeCanisters(Arc<RwLock<FakeState>>);
+
+/// Contents of [`FakeCanisters`], serialized to mirror the server's fakes on the client
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct FakeCanistersSnapshot {
+    /// (user canister, profile)
+    users: Vec<(Principal, ProfileDetails)>,
+    posts: Vec<PostDetails>,
+    /// (creator canister, token root, token)
+    tokens: Vec<(Principal, Principal, TokenMetadata)>,
+    /// (token root, user principal)
+    claimed_airdrops: Vec<(Principal, Principal)>,
+}
+
+impl From<FakeCanistersSnapshot> for FakeCanisters {
+    fn from(snapshot: FakeCanistersSnapshot) -> Self {
+        let mut fake = Self::default();
+        for (user_canister, profile) in snapshot.users {
+            fake = fake.with_user(user_canister, profile);
+        }
+        for post in snapshot.posts {
+            fake = fake.with_post(post);
+        }
+        for (creator_canister, root, token) in snapshot.tokens {
+            fake = fake.with_token(creator_canister, root, token);
+        }
+        for (token_root, user_principal) in snapshot.claimed_airdrops {
+            fake.claim_airdrop(token_root, user_principal);
+        }
+        fake
+    }
+}
+
+/// Fixture posts with a higher probability are nsfw, as in the app's feed providers
+const NSFW_THRESHOLD: f32 = 0.4;
+
+/// Fixture file shared with `mock-upstreams`, see `mock_upstreams/fixtures/default.json`
+#[derive(Deserialize)]
+struct Fixtures {
+    #[serde(default)]
+    posts: Vec<PostItem>,
+    #[serde(default)]
+    search: SearchFixtures,
+}
+
+#[derive(Default, Deserialize)]
+struct SearchFixtures {
+    #[serde(default)]
+    users: Vec<UserFixture>,
+    #[serde(default)]
+    posts: Vec<PostFixture>,
+}
+
+#[derive(Deserialize)]
+struct UserFixture {
+    principal: Principal,
+    canister_id: Principal,
+    username: String,
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct PostFixture {
+    canister_id: Principal,
+    post_id: u64,
+    description: String,
+    hashtags: Vec<String>,
+}
+
+impl FakeCanisters {
+    /// Users and posts of a `mock-upstreams` fixture file
+    /// so the posts served by the mock feed resolve without the canisters being deployed
+    /// posts of canisters without a fixture user are skipped
+    pub fn from_fixtures(raw: &str) -> serde_json::Result<Self> {
+        let fixtures: Fixtures = serde_json::from_str(raw)?;
+        let mut fake = Self::default();
+
+        for user in &fixtures.search.users {
+            fake = fake.with_user(
+                user.canister_id,
+                ProfileDetails {
+                    username: Some(user.username.clone()),
+                    lifetime_earnings: 0,
+                    followers_cnt: 0,
+                    following_cnt: 0,
+                    hots: 0,
+                    nots: 0,
+                    principal: user.principal,
+                    profile_pic: Some(propic_from_principal(user.principal)),
+                    display_name: Some(user.display_name.clone()),
+                },
+            );
+        }
+
+        for item in fixtures.posts {
+            let Some(user) = fixtures
+                .search
+                .users
+                .iter()
+                .find(|user| user.canister_id == item.canister_id)
+            else {
+                log::warn!("fixture post of {} has no user", item.canister_id);
+                continue;
+            };
+            let details =
+                fixtures.search.posts.iter().find(|post| {
+                    post.canister_id == item.canister_id && post.post_id == item.post_id
+                });
+            fake = fake.with_post(PostDetails {
+                canister_id: item.canister_id,
+                post_id: item.post_id,
+                uid: item.video_id,
+                description: details.map(|d| d.description.clone()).unwrap_or_default(),
+                views: 0,
+                likes: 0,
+                display_name: user.display_name.clone(),
+                propic_url: propic_from_principal(user.principal),
+                liked_by_user: None,
+                poster_principal: user.principal,
+                hastags: details.map(|d| d.hashtags.clone()).unwrap_or_default(),
+                is_nsfw: item.nsfw_probability > NSFW_THRESHOLD,
+                hot_or_not_feed_ranking_score: None,
+                created_at: Duration::default(),
+                nsfw_probability: item.nsfw_probability,
+            });
+        }
+
+        Ok(fake)
+    }
+
+    pub fn with_user(self, user_canister: Principal, profile: ProfileDetails) -> Self {
+        {
+            let mut state = self.0.write().unwrap();
+            state
+                .user_canisters
+                .insert(profile.principal, user_canister);
+            state.profiles.insert(user_canister, profile);
+        }
+        self
+    }
+
+    pub fn with_post(self, post: PostDetails) -> Self {
+        self.0
+            .write()
+            .unwrap()
+            .posts
+            .insert((post.canister_id, post.post_id), post);
+        self
+    }
+
+    /// Add a token created by the user owning `creator_canister`
+    pub fn with_token(
+        self,
+        creator_canister: Principal,
+        root: Principal,
+        token: TokenMetadata,
+    ) -> Self {
+        {
+            let mut state = self.0.write().unwrap();
+            state
+                .user_tokens
+                .entry(creator_canister)
+                .or_default()
+                .push(root);
+            state.tokens.insert(root, token);
+        }
+        self
+    }
+
+    pub fn snapshot(&self) -> FakeCanistersSnapshot {
+        let state = self.0.read().unwrap();
+        FakeCanistersSnapshot {
+            users: state
+                .profiles
+                .iter()
+                .map(|(user_canister, profile)| (*user_canister, profile.clone()))
+                .collect(),
+            posts: state.posts.values().cloned().collect(),
+            tokens: state
+                .user_tokens
+                .iter()
+                .flat_map(|(creator_canister, roots)| {
+                    roots.iter().filter_map(|root| {
+                        let token = state.tokens.get(root)?.clone();
+                        Some((*creator_canister, *root, token))
+                    })
+                })
+                .collect(),
+            claimed_airdrops: state.claimed_airdrops.iter().copied().collect(),
+        }
+    }
+
+    pub fn claim_airdrop(&self, token_root: Principal, user_principal: Principal) {
+        self.0
+            .write()
+            .unwrap()
+            .claimed_airdrops
+            .insert((token_root, user_principal));
+    }
+}
+
+impl CanisterBackend for FakeCanisters {
+    async fn get_post_details(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<Option<PostDetails>, CanistersError> {
+        let state = self.0.read().unwrap();
+        Ok(state.posts.get(&(canister_id, post_id)).cloned())
+    }
+
+    async fn get_post_details_with_nsfw_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+        nsfw_probability: f32,
+    ) -> Result<Option<PostDetails>, CanistersError> {
+        let post = self.get_post_details(canister_id, post_id).await?;
+        Ok(post.map(|mut post| {
+            post.nsfw_probability = nsfw_probability;
+            post
+        }))
+    }
+
+    async fn get_user_posts(
+        &self,
+        user_canister: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<PostDetails>, CanistersError> {
+        let state = self.0.read().unwrap();
+        Ok(state
+            .posts
+            .range((user_canister, 0)..=(user_canister, u64::MAX))
+            .rev()
+            .skip(start as usize)
+            .take(limit as usize)
+            .map(|(_, post)| post.clone())
+            .collect())
+    }
+
+    async fn post_like_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<(bool, u64), CanistersError> {
+        let state = self.0.read().unwrap();
+        Ok(state
+            .posts
+            .get(&(canister_id, post_id))
+            .map(|post| (post.liked_by_user.unwrap_or_default(), post.likes))
+            .unwrap_or_default())
+    }
+
+    async fn get_individual_canister_by_user_principal(
+        &self,
+        user_principal: Principal,
+    ) -> Result<Option<Principal>, CanistersError> {
+        let state = self.0.read().unwrap();
+        Ok(state.user_canisters.get(&user_principal).copied())
+    }
+
+    async fn get_profile_details(
+        &self,
+        user_canister: Principal,
+    ) -> Result<ProfileDetails, CanistersError> {
+        let state = self.0.read().unwrap();
+        state
+            .profiles
+            .get(&user_canister)
+            .cloned()
+            .ok_or_else(|| CanistersError::YralCanister(format!("unknown user {user_canister}")))
+    }
+
+    async fn get_user_tokens(
+        &self,
+        user_canister: Principal,
+        _user_principal: Principal,
+    ) -> Result<Vec<TokenMetadata>, CanistersError> {
+        let state = self.0.read().unwrap();
+        let Some(roots) = state.user_tokens.get(&user_canister) else {
+            return Ok(vec![]);
+        };
+        Ok(roots
+            .iter()
+            .filter_map(|root| state.tokens.get(root).cloned())
+            .collect())
+    }
+
+    async fn token_metadata_by_root_type(
+        &self,
+        _key_principal: Option<Principal>,
+        root: RootType,
+    ) -> Result<Option<TokenMetadata>, CanistersError> {
+        let RootType::Other(root) = root else {
+            return Ok(None);
+        };
+        let state = self.0.read().unwrap();
+        Ok(state.tokens.get(&root).cloned())
+    }
+
+    async fn get_token_owner(
+        &self,
+        token_root: Principal,
+    ) -> Result<Option<TokenOwner>, CanistersError> {
+        let state = self.0.read().unwrap();
+        Ok(state
+            .tokens
+            .get(&token_root)
+            .and_then(|token| token.token_owner.clone()))
+    }
+
+    async fn get_airdrop_status(
+        &self,
+        _token_owner_canister: Principal,
+        token_root: Principal,
+        user_principal: Principal,
+    ) -> Result<bool, CanistersError> {
+        let state = self.0.read().unwrap();
+        Ok(state
+            .claimed_airdrops
+            .contains(&(token_root, user_principal)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    const FIXTURES: &str = include_str!("../../../mock_upstreams/fixtures/default.json");
+
+    fn principal(text: &str) -> Principal {
+        Principal::from_text(text).unwrap()
+    }
+
+    #[test]
+    fn fixture_posts_and_users_resolve() {
+        let fake = FakeCanisters::from_fixtures(FIXTURES).unwrap();
+        let canister = principal("zfbzf-gaaaa-aaaac-aaaia-cai");
+        let user = principal("3tuwa-t777e-uswqq-bmf55-ytjqk-p6oaj-zuczv-gqpl5-qwfh6-x2zwb-zqe");
+
+        let post = block_on(fake.get_post_details(canister, 1))
+            .unwrap()
+            .expect("fixture post");
+        assert_eq!(post.uid, "93b885adfe0da089cdf634904fd59f71");
+        assert_eq!(post.description, "First mock post #hello");
+        assert_eq!(post.poster_principal, user);
+        assert!(!post.is_nsfw);
+
+        assert_eq!(
+            block_on(fake.get_individual_canister_by_user_principal(user)).unwrap(),
+            Some(canister)
+        );
+        let profile = block_on(fake.get_profile_details(canister)).unwrap();
+        assert_eq!(profile.username.as_deref(), Some("mock_maker"));
+        assert!(block_on(fake.get_profile_details(Principal::anonymous())).is_err());
+    }
+
+    #[test]
+    fn user_posts_are_paginated_newest_first() {
+        let canister = principal("zfbzf-gaaaa-aaaac-aaaia-cai");
+        let base = block_on(
+            FakeCanisters::from_fixtures(FIXTURES)
+                .unwrap()
+                .get_post_details(canister, 1),
+        )
+        .unwrap()
+        .unwrap();
+        let fake = (0..5).fold(FakeCanisters::default(), |fake, post_id| {
+            fake.with_post(PostDetails {
+                post_id,
+                ..base.clone()
+            })
+        });
+
+        let ids = |start, limit| {
+            block_on(fake.get_user_posts(canister, start, limit))
+                .unwrap()
+                .into_iter()
+                .map(|post| post.post_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(0, 2), vec![4, 3]);
+        assert_eq!(ids(2, 2), vec![2, 1]);
+        assert_eq!(ids(4, 2), vec![0]);
+        assert!(ids(6, 2).is_empty());
+    }
+
+    #[test]
+    fn snapshot_round_trips() {
+        let fake = FakeCanisters::from_fixtures(FIXTURES).unwrap();
+        let root = principal("6xq5c-hyaaa-aaaac-baaiq-cai");
+        let user = Principal::anonymous();
+        fake.claim_airdrop(root, user);
+
+        let raw = serde_json::to_string(&fake.snapshot()).unwrap();
+        let mirrored =
+            FakeCanisters::from(serde_json::from_str::<FakeCanistersSnapshot>(&raw).unwrap());
+
+        let canister = principal("zfbzf-gaaaa-aaaac-aaaia-cai");
+        assert_eq!(
+            block_on(mirrored.get_post_details(canister, 1))
+                .unwrap()
+                .map(|post| post.uid),
+            block_on(fake.get_post_details(canister, 1))
+                .unwrap()
+                .map(|post| post.uid),
+        );
+        assert!(block_on(mirrored.get_airdrop_status(canister, root, user)).unwrap());
+    }
+
+    #[test]
+    fn unknown_tokens_are_missing() {
+        let fake = FakeCanisters::from_fixtures(FIXTURES).unwrap();
+        let root = principal("6xq5c-hyaaa-aaaac-baaiq-cai");
+
+        assert!(
+            block_on(fake.token_metadata_by_root_type(None, RootType::Other(root)))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            block_on(fake.token_metadata_by_root_type(None, RootType::COYNS))
+                .unwrap()
+                .is_none()
+        );
+        assert!(block_on(fake.get_token_owner(root)).unwrap().is_none());
+        assert!(!block_on(fake.get_airdrop_status(root, root, Principal::anonymous())).unwrap());
+    }
+}
@@ -0,0 +1,343 @@
+mod fake;
+
+pub use fake::{FakeCanisters, FakeCanistersSnapshot};
+
+use candid::Principal;
+use futures::{stream::FuturesOrdered, TryStreamExt};
+use leptos::prelude::*;
+use utils::token::icpump::IcpumpTokenInfo;
+use yral_canisters_client::individual_user_template::{GetPostsOfUserProfileError, Result8};
+use yral_canisters_common::{
+    utils::{
+        posts::PostDetails,
+        profile::ProfileDetails,
+        token::{RootType, TokenMetadata, TokenOwner},
+    },
+    Canisters, Error as CanistersError,
+};
+
+use crate::canisters::unauth_canisters;
+
+/// Canister operations used by the app
+/// implemented by the real [`Canisters`] and by [`FakeCanisters`] for local development and tests
+#[allow(async_fn_in_trait)]
+pub trait CanisterBackend {
+    async fn get_post_details(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<Option<PostDetails>, CanistersError>;
+
+    async fn get_post_details_with_nsfw_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+        nsfw_probability: f32,
+    ) -> Result<Option<PostDetails>, CanistersError>;
+
+    /// Posts of a user, `start` and `limit` are offsets into the user's posts
+    async fn get_user_posts(
+        &self,
+        user_canister: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<PostDetails>, CanistersError>;
+
+    /// (is liked by the current user, like count)
+    async fn post_like_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<(bool, u64), CanistersError>;
+
+    async fn get_individual_canister_by_user_principal(
+        &self,
+        user_principal: Principal,
+    ) -> Result<Option<Principal>, CanistersError>;
+
+    async fn get_profile_details(
+        &self,
+        user_canister: Principal,
+    ) -> Result<ProfileDetails, CanistersError>;
+
+    /// Tokens created by a user, including `user_principal`'s balance of each
+    async fn get_user_tokens(
+        &self,
+        user_canister: Principal,
+        user_principal: Principal,
+    ) -> Result<Vec<TokenMetadata>, CanistersError>;
+
+    /// Metadata of the token at `root`, including `key_principal`'s balance
+    async fn token_metadata_by_root_type(
+        &self,
+        key_principal: Option<Principal>,
+        root: RootType,
+    ) -> Result<Option<TokenMetadata>, CanistersError>;
+
+    async fn get_token_owner(
+        &self,
+        token_root: Principal,
+    ) -> Result<Option<TokenOwner>, CanistersError>;
+
+    /// Whether `user_principal` has claimed the airdrop for `token_root`
+    async fn get_airdrop_status(
+        &self,
+        token_owner_canister: Principal,
+        token_root: Principal,
+        user_principal: Principal,
+    ) -> Result<bool, CanistersError>;
+}
+
+impl<const AUTH: bool> CanisterBackend for Canisters<AUTH> {
+    async fn get_post_details(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<Option<PostDetails>, CanistersError> {
+        Canisters::get_post_details(self, canister_id, post_id).await
+    }
+
+    async fn get_post_details_with_nsfw_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+        nsfw_probability: f32,
+    ) -> Result<Option<PostDetails>, CanistersError> {
+        Canisters::get_post_details_with_nsfw_info(self, canister_id, post_id, nsfw_probability)
+            .await
+    }
+
+    async fn get_user_posts(
+        &self,
+        user_canister: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<PostDetails>, CanistersError> {
+        let user = self.individual_user(user_canister).await;
+        let posts = user
+            .get_posts_of_this_user_profile_with_pagination_cursor(start, limit)
+            .await?;
+        match posts {
+            Result8::Ok(v) => Ok(v
+                .into_iter()
+                .map(|details| PostDetails::from_canister_post(AUTH, user_canister, details))
+                .collect()),
+            Result8::Err(GetPostsOfUserProfileError::ReachedEndOfItemsList) => Ok(vec![]),
+            _ => Err(CanistersError::YralCanister(
+                "user canister refused to send posts".into(),
+            )),
+        }
+    }
+
+    async fn post_like_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<(bool, u64), CanistersError> {
+        Canisters::post_like_info(self, canister_id, post_id).await
+    }
+
+    async fn get_individual_canister_by_user_principal(
+        &self,
+        user_principal: Principal,
+    ) -> Result<Option<Principal>, CanistersError> {
+        Canisters::get_individual_canister_by_user_principal(self, user_principal).await
+    }
+
+    async fn get_profile_details(
+        &self,
+        user_canister: Principal,
+    ) -> Result<ProfileDetails, CanistersError> {
+        let user = self.individual_user(user_canister).await;
+        Ok(user.get_profile_details().await?.into())
+    }
+
+    async fn get_user_tokens(
+        &self,
+        user_canister: Principal,
+        user_principal: Principal,
+    ) -> Result<Vec<TokenMetadata>, CanistersError> {
+        let user = self.individual_user(user_canister).await;
+        user.deployed_cdao_canisters()
+            .await?
+            .into_iter()
+            .map(|deployed| {
+                self.get_token_metadata(
+                    &IcpumpTokenInfo,
+                    Some(user_principal),
+                    deployed.root,
+                    deployed.governance,
+                    deployed.ledger,
+                    deployed.index,
+                )
+            })
+            .collect::<FuturesOrdered<_>>()
+            .try_collect()
+            .await
+    }
+
+    async fn token_metadata_by_root_type(
+        &self,
+        key_principal: Option<Principal>,
+        root: RootType,
+    ) -> Result<Option<TokenMetadata>, CanistersError> {
+        Canisters::token_metadata_by_root_type(self, &IcpumpTokenInfo, key_principal, root).await
+    }
+
+    async fn get_token_owner(
+        &self,
+        token_root: Principal,
+    ) -> Result<Option<TokenOwner>, CanistersError> {
+        Canisters::get_token_owner(self, token_root).await
+    }
+
+    async fn get_airdrop_status(
+        &self,
+        token_owner_canister: Principal,
+        token_root: Principal,
+        user_principal: Principal,
+    ) -> Result<bool, CanistersError> {
+        Canisters::get_airdrop_status(self, token_owner_canister, token_root, user_principal).await
+    }
+}
+
+/// Either the real canisters or the in-memory fake
+#[derive(Clone)]
+pub enum CanisterBackendImpl<const AUTH: bool> {
+    Live(Canisters<AUTH>),
+    Fake(FakeCanisters),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $method:ident($($arg:expr),*)) => {
+        match $self {
+            CanisterBackendImpl::Live(cans) => CanisterBackend::$method(cans, $($arg),*).await,
+            CanisterBackendImpl::Fake(fake) => fake.$method($($arg),*).await,
+        }
+    };
+}
+
+impl<const AUTH: bool> CanisterBackend for CanisterBackendImpl<AUTH> {
+    async fn get_post_details(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<Option<PostDetails>, CanistersError> {
+        dispatch!(self, get_post_details(canister_id, post_id))
+    }
+
+    async fn get_post_details_with_nsfw_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+        nsfw_probability: f32,
+    ) -> Result<Option<PostDetails>, CanistersError> {
+        dispatch!(
+            self,
+            get_post_details_with_nsfw_info(canister_id, post_id, nsfw_probability)
+        )
+    }
+
+    async fn get_user_posts(
+        &self,
+        user_canister: Principal,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<PostDetails>, CanistersError> {
+        dispatch!(self, get_user_posts(user_canister, start, limit))
+    }
+
+    async fn post_like_info(
+        &self,
+        canister_id: Principal,
+        post_id: u64,
+    ) -> Result<(bool, u64), CanistersError> {
+        dispatch!(self, post_like_info(canister_id, post_id))
+    }
+
+    async fn get_individual_canister_by_user_principal(
+        &self,
+        user_principal: Principal,
+    ) -> Result<Option<Principal>, CanistersError> {
+        dispatch!(
+            self,
+            get_individual_canister_by_user_principal(user_principal)
+        )
+    }
+
+    async fn get_profile_details(
+        &self,
+        user_canister: Principal,
+    ) -> Result<ProfileDetails, CanistersError> {
+        dispatch!(self, get_profile_details(user_canister))
+    }
+
+    async fn get_user_tokens(
+        &self,
+        user_canister: Principal,
+        user_principal: Principal,
+    ) -> Result<Vec<TokenMetadata>, CanistersError> {
+        dispatch!(self, get_user_tokens(user_canister, user_principal))
+    }
+
+    async fn token_metadata_by_root_type(
+        &self,
+        key_principal: Option<Principal>,
+        root: RootType,
+    ) -> Result<Option<TokenMetadata>, CanistersError> {
+        dispatch!(self, token_metadata_by_root_type(key_principal, root))
+    }
+
+    async fn get_token_owner(
+        &self,
+        token_root: Principal,
+    ) -> Result<Option<TokenOwner>, CanistersError> {
+        dispatch!(self, get_token_owner(token_root))
+    }
+
+    async fn get_airdrop_status(
+        &self,
+        token_owner_canister: Principal,
+        token_root: Principal,
+        user_principal: Principal,
+    ) -> Result<bool, CanistersError> {
+        dispatch!(
+            self,
+            get_airdrop_status(token_owner_canister, token_root, user_principal)
+        )
+    }
+}
+
+/// Unauthenticated canister backend
+/// uses [`FakeCanisters`] if provided as a context, the real canisters otherwise
+pub fn unauth_canister_backend() -> CanisterBackendImpl<false> {
+    if let Some(fake) = use_context::<FakeCanisters>() {
+        return CanisterBackendImpl::Fake(fake);
+    }
+    CanisterBackendImpl::Live(unauth_canisters())
+}
+
+/// Canister backend of `cans`, e.g. the authenticated canisters
+/// uses [`FakeCanisters`] if provided as a context
+pub fn canister_backend<const AUTH: bool>(cans: Canisters<AUTH>) -> CanisterBackendImpl<AUTH> {
+    if let Some(fake) = use_context::<FakeCanisters>() {
+        return CanisterBackendImpl::Fake(fake);
+    }
+    CanisterBackendImpl::Live(cans)
+}
+
+/// Provide the server's [`FakeCanisters`] on the client as well, so hydrated pages are faked
+/// too, must be called by the root component
+/// only local builds can fake canisters, elsewhere this is a no-op and nothing is serialized
+pub fn mirror_fake_canisters() {
+    #[cfg(any(feature = "local-bin", feature = "local-lib"))]
+    {
+        let snapshot =
+            SharedValue::new(|| use_context::<FakeCanisters>().map(|fake| fake.snapshot()));
+        if cfg!(feature = "hydrate") {
+            if let Some(snapshot) = snapshot.into_inner() {
+                provide_context(FakeCanisters::from(snapshot));
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use serde::Serialize;
 use std::error::Error;
 
 use consts::DOWNLOAD_UPLOAD_SERVICE;
+use utils::request_id::PropagateRequestId;
 use yral_types::delegated_identity::DelegatedIdentityWire;
 
 #[derive(Deserialize)]
@@ -54,7 +55,12 @@ impl ContentSeedClient {
             .expect("url error")
             .join(&principal.to_string())
             .expect("url error");
-        let res = self.client.get(api_url).send().await?;
+        let res = self
+            .client
+            .get(api_url)
+            .propagate_request_id()
+            .send()
+            .await?;
         let res_json: AllowPrincpalRes = res.json().await?;
         Ok(res_json.allowed)
     }
@@ -72,7 +78,13 @@ impl ContentSeedClient {
             url,
             payload: identity,
         };
-        let res = self.client.post(api_url).json(&req_body).send().await?;
+        let res = self
+            .client
+            .post(api_url)
+            .json(&req_body)
+            .propagate_request_id()
+            .send()
+            .await?;
         if res.status().is_success() {
             return Ok(());
         }
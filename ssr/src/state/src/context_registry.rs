@@ -0,0 +1,178 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use leptos::prelude::provide_context;
+use thiserror::Error;
+
+/// Identifies a context type provided to every request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextKey {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl ContextKey {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("missing contexts: {}", .0.join(", "))]
+pub struct MissingContexts(pub Vec<&'static str>);
+
+#[derive(Clone)]
+struct Entry {
+    name: &'static str,
+    value: Arc<dyn Any + Send + Sync>,
+    provide: fn(&(dyn Any + Send + Sync)),
+    /// server functions and SSR expect the context, instead of checking for it
+    required: bool,
+}
+
+fn provide_erased<T: Clone + Send + Sync + 'static>(value: &(dyn Any + Send + Sync)) {
+    let value = value
+        .downcast_ref::<T>()
+        .expect("context registry entry must match its type id");
+    provide_context(value.clone());
+}
+
+/// Registry of server-side services that are provided as leptos contexts
+/// to every server function and SSR render
+#[derive(Clone, Default)]
+pub struct ContextRegistry {
+    entries: HashMap<TypeId, Entry>,
+}
+
+impl ContextRegistry {
+    fn insert<T: Clone + Send + Sync + 'static>(&mut self, value: T, required: bool) -> &mut Self {
+        let entry = Entry {
+            name: type_name::<T>(),
+            value: Arc::new(value),
+            provide: provide_erased::<T>,
+            required,
+        };
+        if let Some(prev) = self.entries.insert(TypeId::of::<T>(), entry) {
+            log::warn!("context {} registered twice, overriding", prev.name);
+        }
+        self
+    }
+
+    /// Register a service every server function and SSR render can expect
+    /// overrides any previously registered value of the same type
+    pub fn register<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> &mut Self {
+        self.insert(value, true)
+    }
+
+    /// Register a service that is only used if present, e.g. a local or fake implementation
+    pub fn register_optional<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> &mut Self {
+        self.insert(value, false)
+    }
+
+    /// Builder style [`Self::register`]
+    pub fn with<T: Clone + Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.register(value);
+        self
+    }
+
+    /// Builder style [`Self::register_optional`]
+    pub fn with_optional<T: Clone + Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.register_optional(value);
+        self
+    }
+
+    /// Contexts registered as required, sorted by name
+    pub fn required(&self) -> Vec<ContextKey> {
+        let mut keys: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.required)
+            .map(|(id, entry)| ContextKey {
+                id: *id,
+                name: entry.name,
+            })
+            .collect();
+        keys.sort_by_key(|key| key.name);
+        keys
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.entries.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn get<T: Clone + 'static>(&self) -> Option<T> {
+        let entry = self.entries.get(&TypeId::of::<T>())?;
+        entry.value.downcast_ref::<T>().cloned()
+    }
+
+    /// Provide every registered service as a context
+    /// must be called inside a reactive owner
+    pub fn provide_all(&self) {
+        for entry in self.entries.values() {
+            (entry.provide)(entry.value.as_ref());
+        }
+    }
+
+    /// Verify that all `required` contexts are registered
+    /// e.g. the contexts another registry requires
+    pub fn ensure_registered(&self, required: &[ContextKey]) -> Result<(), MissingContexts> {
+        let missing: Vec<_> = required
+            .iter()
+            .filter(|key| !self.entries.contains_key(&key.id))
+            .map(|key| key.name)
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingContexts(missing))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct FakeService;
+
+    #[test]
+    fn detects_missing_contexts() {
+        let registry = ContextRegistry::default().with(FakeService);
+        let required = [ContextKey::of::<FakeService>(), ContextKey::of::<String>()];
+
+        let err = registry.ensure_registered(&required).unwrap_err();
+        assert_eq!(err.0, vec![type_name::<String>()]);
+    }
+
+    #[test]
+    fn registered_contexts_are_retrievable() {
+        let registry = ContextRegistry::default().with("value".to_string());
+
+        assert!(registry.contains::<String>());
+        assert!(registry
+            .ensure_registered(&[ContextKey::of::<String>()])
+            .is_ok());
+        assert_eq!(registry.get::<String>().as_deref(), Some("value"));
+    }
+
+    #[test]
+    fn only_required_contexts_are_listed() {
+        let registry = ContextRegistry::default()
+            .with(FakeService)
+            .with_optional("value".to_string());
+
+        assert_eq!(registry.required(), vec![ContextKey::of::<FakeService>()]);
+        assert!(registry.contains::<String>());
+    }
+}
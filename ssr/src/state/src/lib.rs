@@ -6,8 +6,11 @@ pub mod app_state;
 pub mod app_type;
 pub mod audio_state;
 pub mod auth;
+pub mod canister_backend;
 pub mod canisters;
 pub mod content_seed_client;
+#[cfg(feature = "ssr")]
+mod context_registry;
 pub mod local_storage;
 
 #[cfg(not(feature = "ssr"))]
@@ -19,43 +22,49 @@ pub mod server {
 #[cfg(feature = "ssr")]
 pub mod server {
 
-    use auth::server_impl::store::KVStoreImpl;
-    use utils::token::{icpump::ICPumpSearchGrpcChannel, nsfw::ICPumpNSFWGrpcChannel};
-
     use axum::extract::FromRef;
-    use axum_extra::extract::cookie::Key;
     use leptos::prelude::*;
     use leptos_axum::AxumRouteListing;
-    use yral_canisters_common::Canisters;
+
+    pub use super::context_registry::{ContextKey, ContextRegistry, MissingContexts};
 
     // #[cfg(feature = "alloydb")]
     #[derive(Clone)]
     pub struct HonWorkerJwt(pub std::sync::Arc<String>);
 
+    /// Hot or not worker called by server functions, e.g. the `mock-upstreams` worker in tests
+    /// [`consts::HON_WORKER_URL`] if not provided, votes are mocked without it unless `alloydb`
+    /// is enabled
+    #[derive(Clone)]
+    pub struct HonWorkerUrl(pub reqwest::Url);
+
+    impl HonWorkerUrl {
+        pub fn current() -> reqwest::Url {
+            use_context::<Self>().map_or_else(|| consts::HON_WORKER_URL.clone(), |url| url.0)
+        }
+    }
+
     #[derive(FromRef, Clone)]
     pub struct AppState {
         pub leptos_options: LeptosOptions,
-        pub canisters: Canisters<false>,
-        #[cfg(feature = "backend-admin")]
-        pub admin_canisters: super::admin_canisters::AdminCanisters,
-        #[cfg(feature = "cloudflare")]
-        pub cloudflare: gob_cloudflare::CloudflareAuth,
-        pub kv: KVStoreImpl,
         pub routes: Vec<AxumRouteListing>,
-        pub cookie_key: Key,
-        #[cfg(feature = "oauth-ssr")]
-        pub google_oauth_clients: auth::core_clients::CoreClients,
-        #[cfg(feature = "ga4")]
-        pub grpc_offchain_channel: tonic::transport::Channel,
-        #[cfg(feature = "firestore")]
-        pub firestore_db: firestore::FirestoreDb,
-        #[cfg(feature = "qstash")]
-        pub qstash: utils::qstash::QStashClient,
-        pub grpc_icpump_search_channel: ICPumpSearchGrpcChannel,
-        pub grpc_nsfw_channel: ICPumpNSFWGrpcChannel,
-        #[cfg(feature = "alloydb")]
-        pub alloydb: super::alloydb::AlloyDbInstance,
-        #[cfg(feature = "alloydb")]
-        pub hon_worker_jwt: HonWorkerJwt,
+        pub contexts: ContextRegistry,
+    }
+
+    impl AppState {
+        /// Create the app state
+        /// the server's registry is checked for the contexts server functions expect when it is
+        /// built, tests only register the services (or fakes) they need
+        pub fn new(
+            leptos_options: LeptosOptions,
+            routes: Vec<AxumRouteListing>,
+            contexts: ContextRegistry,
+        ) -> Self {
+            Self {
+                leptos_options,
+                routes,
+                contexts,
+            }
+        }
     }
 }
@@ -0,0 +1,87 @@
+//! First party analytics client id, stored in the [`CLIENT_ID_COOKIE`] cookie
+//! issued by the SSR server and rotated every [`CLIENT_ID_MAX_AGE`]
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum_extra::extract::cookie::{Cookie, SameSite};
+
+use consts::analytics::{CLIENT_ID_COOKIE, CLIENT_ID_MAX_AGE};
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientId {
+    pub id: String,
+    /// unix timestamp (seconds)
+    pub issued_at: u64,
+}
+
+impl ClientId {
+    pub fn generate() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            issued_at: now_secs(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        now_secs().saturating_sub(self.issued_at) >= CLIENT_ID_MAX_AGE.as_secs()
+    }
+
+    /// The cookie expires when the id is due for rotation
+    pub fn cookie(&self) -> Cookie<'static> {
+        let remaining = CLIENT_ID_MAX_AGE
+            .as_secs()
+            .saturating_sub(now_secs().saturating_sub(self.issued_at));
+        Cookie::build((CLIENT_ID_COOKIE, self.to_string()))
+            .http_only(true)
+            .secure(true)
+            .path("/")
+            .same_site(SameSite::Lax)
+            .max_age(Duration::from_secs(remaining).try_into().unwrap())
+            .build()
+    }
+
+    /// Cookie which removes the client id from the browser
+    pub fn removal_cookie() -> Cookie<'static> {
+        let mut cookie = Cookie::build((CLIENT_ID_COOKIE, "")).path("/").build();
+        cookie.make_removal();
+        cookie
+    }
+}
+
+/// `<id>.<issued_at>`
+impl Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.id, self.issued_at)
+    }
+}
+
+impl FromStr for ClientId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, issued_at) = s
+            .split_once('.')
+            .ok_or_else(|| format!("invalid client id `{s}`"))?;
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("invalid client id `{s}`"));
+        }
+        let issued_at = issued_at
+            .parse()
+            .map_err(|_| format!("invalid client id timestamp `{issued_at}`"))?;
+
+        Ok(Self {
+            id: id.to_string(),
+            issued_at,
+        })
+    }
+}
@@ -0,0 +1,154 @@
+//! Analytics consent, stored in the [`consts::analytics::CONSENT_COOKIE`] cookie
+//! the server honors it before events reach any sink
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentCategory {
+    /// Product analytics (GA4, Mixpanel) and the analytics client id
+    Analytics,
+    /// Events linked to the user in the warehouse, used to personalize the feed
+    Personalization,
+}
+
+impl ConsentCategory {
+    pub const ALL: [Self; 2] = [Self::Analytics, Self::Personalization];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Analytics => "analytics",
+            Self::Personalization => "personalization",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentStatus {
+    Granted,
+    Denied,
+}
+
+/// Per category consent
+/// users who haven't made a choice yet are treated as granted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsentState {
+    pub analytics: ConsentStatus,
+    pub personalization: ConsentStatus,
+}
+
+impl Default for ConsentState {
+    fn default() -> Self {
+        Self::all(ConsentStatus::Granted)
+    }
+}
+
+impl ConsentState {
+    pub fn all(status: ConsentStatus) -> Self {
+        Self {
+            analytics: status,
+            personalization: status,
+        }
+    }
+
+    pub fn get(&self, category: ConsentCategory) -> ConsentStatus {
+        match category {
+            ConsentCategory::Analytics => self.analytics,
+            ConsentCategory::Personalization => self.personalization,
+        }
+    }
+
+    pub fn set(&mut self, category: ConsentCategory, status: ConsentStatus) {
+        match category {
+            ConsentCategory::Analytics => self.analytics = status,
+            ConsentCategory::Personalization => self.personalization = status,
+        }
+    }
+
+    pub fn is_granted(&self, category: ConsentCategory) -> bool {
+        self.get(category) == ConsentStatus::Granted
+    }
+}
+
+/// Cookie safe encoding, e.g `analytics:granted|personalization:denied`
+impl Display for ConsentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, category) in ConsentCategory::ALL.into_iter().enumerate() {
+            if idx > 0 {
+                f.write_str("|")?;
+            }
+            let status = match self.get(category) {
+                ConsentStatus::Granted => "granted",
+                ConsentStatus::Denied => "denied",
+            };
+            write!(f, "{}:{status}", category.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Unknown categories are ignored, missing ones are denied
+impl FromStr for ConsentState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut state = Self::all(ConsentStatus::Denied);
+        for entry in s.split('|') {
+            let (category, status) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("invalid consent entry `{entry}`"))?;
+            let status = match status {
+                "granted" => ConsentStatus::Granted,
+                "denied" => ConsentStatus::Denied,
+                _ => return Err(format!("invalid consent status `{status}`")),
+            };
+            if let Some(category) = ConsentCategory::ALL
+                .into_iter()
+                .find(|c| c.as_str() == category)
+            {
+                state.set(category, status);
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// Params identifying a user, removed from events without personalization consent
+/// `user_id_viewer` is the user id of login and logout events, `to` the recipient of a transfer
+const USER_IDENTIFIERS: [&str; 4] = ["user_id", "user_id_viewer", "canister_id", "to"];
+
+/// Strip the params linking an event to a user
+pub fn anonymize(params: &mut serde_json::Value) {
+    if let Some(params) = params.as_object_mut() {
+        for key in USER_IDENTIFIERS {
+            params.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consent_roundtrips_through_cookie_value() {
+        let state = ConsentState {
+            analytics: ConsentStatus::Denied,
+            personalization: ConsentStatus::Granted,
+        };
+        let encoded = state.to_string();
+        assert_eq!(encoded, "analytics:denied|personalization:granted");
+        assert_eq!(encoded.parse::<ConsentState>().unwrap(), state);
+
+        assert_eq!(
+            "analytics:granted".parse::<ConsentState>().unwrap(),
+            ConsentState {
+                analytics: ConsentStatus::Granted,
+                personalization: ConsentStatus::Denied,
+            }
+        );
+        assert!("analytics=granted".parse::<ConsentState>().is_err());
+    }
+}
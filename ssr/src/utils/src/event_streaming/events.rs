@@ -12,7 +12,6 @@ use leptos::{ev, prelude::*};
 use leptos_use::storage::use_local_storage;
 use leptos_use::use_cookie;
 use leptos_use::{use_event_listener, use_timeout_fn, UseTimeoutFnReturn};
-use serde_json::json;
 use sns_validation::pbs::sns_pb::SnsInitPayload;
 use wasm_bindgen::JsCast;
 
@@ -112,44 +111,45 @@ impl HistoryCtx {
     }
 }
 
+use super::schema::*;
+use crate::event_streaming::send_event_ssr_spawn;
 #[cfg(feature = "ga4")]
-use crate::event_streaming::{send_event_ssr_spawn, send_event_warehouse_ssr_spawn, send_user_id};
+use crate::event_streaming::send_user_id;
 use crate::token::nsfw::NSFWInfo;
-use crate::user::{user_details_can_store_or_ret, user_details_or_ret};
+use crate::user::{user_details_can_store_or_ret, user_details_or_ret, UserDetails};
 use leptos::html::Video;
 use yral_canisters_common::{
     utils::{posts::PostDetails, profile::ProfileDetails},
     Canisters,
 };
 
-pub enum AnalyticsEvent {
-    VideoWatched(VideoWatched),
-    LikeVideo(LikeVideo),
-    ShareVideo(ShareVideo),
-    VideoUploadInitiated(VideoUploadInitiated),
-    VideoUploadUploadButtonClicked(VideoUploadUploadButtonClicked),
-    VideoUploadVideoSelected(VideoUploadVideoSelected),
-    VideoUploadUnsuccessful(VideoUploadUnsuccessful),
-    VideoUploadSuccessful(VideoUploadSuccessful),
-    Refer(Refer),
-    ReferShareLink(ReferShareLink),
-    LoginSuccessful(LoginSuccessful),
-    LoginMethodSelected(LoginMethodSelected),
-    LoginJoinOverlayViewed(LoginJoinOverlayViewed),
-    LoginCta(LoginCta),
-    LogoutClicked(LogoutClicked),
-    LogoutConfirmation(LogoutConfirmation),
-    ErrorEvent(ErrorEvent),
-    ProfileViewVideo(ProfileViewVideo),
-    TokenCreationStarted(TokenCreationStarted),
-    TokenCreationCompleted(TokenCreationCompleted),
-    TokenCreationFailed(TokenCreationFailed),
-    TokensClaimedFromNeuron(TokensClaimedFromNeuron),
-    TokensTransferred(TokensTransferred),
-    PageVisit(PageVisit),
-    CentsAdded(CentsAdded),
-    CentsWithdrawn(CentsWithdrawn),
-    TokenPumpedDumped(TokenPumpedDumped),
+#[cfg(feature = "hydrate")]
+fn user_props(user: &UserDetails) -> UserProps {
+    UserProps {
+        user_id: user.details.principal,
+        canister_id: user.canister_id,
+    }
+}
+
+#[cfg(feature = "hydrate")]
+fn canisters_user_props(canisters: &Canisters<true>) -> UserProps {
+    UserProps {
+        user_id: canisters.user_principal(),
+        canister_id: canisters.user_canister(),
+    }
+}
+
+/// User from the persisted principal and canister id, `None` if either is unknown
+#[cfg(feature = "hydrate")]
+fn stored_user_props() -> Option<UserProps> {
+    let (canister_id, _, _) =
+        use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
+    let (user_id, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
+
+    Some(UserProps {
+        user_id: user_id.get_untracked()?,
+        canister_id: canister_id.get_untracked()?,
+    })
 }
 
 #[derive(Default)]
@@ -161,7 +161,7 @@ impl VideoWatched {
         vid_details: Signal<Option<PostDetails>>,
         container_ref: NodeRef<Video>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             let (is_connected, _) = account_connected_reader();
 
@@ -174,8 +174,9 @@ impl VideoWatched {
             let post_for_time = vid_details;
             let _ = use_event_listener(container_ref, ev::timeupdate, move |evt| {
                 let user = user_details_can_store_or_ret!(cans_store);
-                let post_o = post_for_time();
-                let post = post_o.as_ref();
+                let Some(post) = post_for_time() else {
+                    return;
+                };
 
                 let Some(target) = evt.target() else {
                     leptos::logging::error!("No target found for video timeupdate event");
@@ -184,41 +185,23 @@ impl VideoWatched {
                 let video = target.unchecked_into::<web_sys::HtmlVideoElement>();
                 let duration = video.duration();
                 let current_time = video.current_time();
-                let nsfw_probability = post.map(|p| p.nsfw_probability);
-                let is_nsfw = nsfw_probability.map(|prob| prob > 0.5);
                 if current_time < 0.95 * duration {
                     set_full_video_watched.set(false);
                 }
 
                 // send bigquery event when video is watched > 95%
                 if current_time >= 0.95 * duration && !full_video_watched.get() {
-                    send_event_warehouse_ssr_spawn(
-                        "video_duration_watched".to_string(),
-                        json!({
-                            "publisher_user_id": post.map(|p| p.poster_principal),
-                            "user_id": user.details.principal,
-                            "is_loggedIn": is_connected(),
-                            "display_name": user.details.display_name.clone(),
-                            "canister_id": user.canister_id,
-                            "video_id": post.map(|p| p.uid.clone()),
-                            "video_category": "NA",
-                            "creator_category": "NApublisher_canister_id(",
-                            "hashtag_count": post.map(|p| p.hastags.len()),
-                            "is_NSFW": is_nsfw,
-                            "is_hotorNot": post.map(|p| p.is_hot_or_not()),
-                            "feed_type": "NA",
-                            "view_count": post.map(|p| p.views),
-                            "like_count": post.map(|p| p.likes),
-                            "share_count": 0,
-                            "percentage_watched": 100.0,
-                            "absolute_watched": duration,
-                            "video_duration": duration,
-                            "post_id": post.map(|p| p.post_id),
-                            "publisher_canister_id": post.map(|p| p.canister_id),
-                            "nsfw_probability": nsfw_probability,
-                        })
-                        .to_string(),
-                    );
+                    send_event_ssr_spawn(AnalyticsEvent::VideoDurationWatched(
+                        VideoDurationWatchedProps {
+                            user: user_props(&user),
+                            video: VideoProps::from(&post),
+                            is_logged_in: is_connected(),
+                            display_name: user.details.display_name.clone(),
+                            percentage_watched: 100.0,
+                            absolute_watched: duration,
+                            video_duration: duration,
+                        },
+                    ));
 
                     set_full_video_watched.set(true);
                 }
@@ -228,30 +211,12 @@ impl VideoWatched {
                 }
 
                 if current_time >= 3.0 {
-                    let _ = send_event_ssr_spawn(
-                        "video_viewed".to_string(),
-                        json!({
-                            "publisher_user_id": post.map(|p| p.poster_principal),
-                            "user_id": user.details.principal,
-                            "is_loggedIn": is_connected(),
-                            "display_name": user.details.display_name,
-                            "canister_id": user.canister_id,
-                            "video_id": post.map(|p| p.uid.clone()),
-                            "video_category": "NA",
-                            "creator_category": "NA",
-                            "hashtag_count": post.map(|p| p.hastags.len()),
-                            "is_NSFW": is_nsfw,
-                            "is_hotorNot": post.map(|p| p.is_hot_or_not()),
-                            "feed_type": "NA",
-                            "view_count": post.map(|p| p.views),
-                            "like_count": post.map(|p| p.likes),
-                            "share_count": 0,
-                            "post_id": post.map(|p| p.post_id),
-                            "publisher_canister_id": post.map(|p| p.canister_id),
-                            "nsfw_probability": nsfw_probability,
-                        })
-                        .to_string(),
-                    );
+                    send_event_ssr_spawn(AnalyticsEvent::VideoViewed(VideoEngagementProps {
+                        user: user_props(&user),
+                        video: VideoProps::from(&post),
+                        is_logged_in: is_connected(),
+                        display_name: user.details.display_name,
+                    }));
                     set_video_watched.set(true);
                 }
             });
@@ -260,10 +225,9 @@ impl VideoWatched {
             let post_for_warehouse = vid_details;
             let _ = use_event_listener(container_ref, ev::pause, move |evt| {
                 let user = user_details_can_store_or_ret!(cans_store);
-                let post_o = post_for_warehouse();
-                let post = post_o.as_ref();
-                let nsfw_probability = post.map(|p| p.nsfw_probability);
-                let is_nsfw = nsfw_probability.map(|prob| prob > 0.5);
+                let Some(post) = post_for_warehouse() else {
+                    return;
+                };
                 let Some(target) = evt.target() else {
                     leptos::logging::error!("No target found for video pause event");
                     return;
@@ -275,35 +239,19 @@ impl VideoWatched {
                     return;
                 }
 
-                let percentage_watched = (current_time / duration) * 100.0;
-
-                send_event_warehouse_ssr_spawn(
-                    "video_duration_watched".to_string(),
-                    json!({
-                        "publisher_user_id": post.map(|p| p.poster_principal),
-                        "user_id": user.details.principal,
-                        "is_loggedIn": is_connected(),
-                        "display_name": user.details.display_name.clone(),
-                        "canister_id": user.canister_id,
-                        "video_id": post.map(|p| p.uid.clone()),
-                        "video_category": "NA",
-                        "creator_category": "NA",
-                        "hashtag_count": post.map(|p| p.hastags.len()),
-                        "is_NSFW": is_nsfw,
-                        "is_hotorNot": post.map(|p| p.is_hot_or_not()),
-                        "feed_type": "NA",
-                        "view_count": post.map(|p| p.views),
-                        "like_count": post.map(|p| p.likes),
-                        "share_count": 0,
-                        "percentage_watched": percentage_watched,
-                        "absolute_watched": current_time,
-                        "video_duration": duration,
-                        "post_id": post.map(|p| p.post_id),
-                        "publisher_canister_id": post.map(|p| p.canister_id),
-                        "nsfw_probability": nsfw_probability,
-                    })
-                    .to_string(),
-                );
+                let percentage_watched = ((current_time / duration) * 100.0).min(100.0);
+
+                send_event_ssr_spawn(AnalyticsEvent::VideoDurationWatched(
+                    VideoDurationWatchedProps {
+                        user: user_props(&user),
+                        video: VideoProps::from(&post),
+                        is_logged_in: is_connected(),
+                        display_name: user.details.display_name,
+                        percentage_watched,
+                        absolute_watched: current_time,
+                        video_duration: duration,
+                    },
+                ));
             });
         }
     }
@@ -319,47 +267,22 @@ impl LikeVideo {
         likes: RwSignal<u64>,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let publisher_user_id = post_details.poster_principal;
-            let video_id = post_details.uid.clone();
-            let hastag_count = post_details.hastags.len();
-            let is_nsfw = post_details.is_nsfw;
-            let is_hotornot = post_details.hot_or_not_feed_ranking_score.is_some();
-            let view_count = post_details.views;
-            let post_id = post_details.post_id;
-            let publisher_canister_id = post_details.canister_id;
-            let nsfw_probability = post_details.nsfw_probability;
-
             let (is_connected, _) = account_connected_reader();
             // like_video - analytics
 
             let user = user_details_can_store_or_ret!(cans_store);
 
-            let _ = send_event_ssr_spawn(
-                "like_video".to_string(),
-                json!({
-                    "publisher_user_id":publisher_user_id,
-                    "user_id": user.details.principal,
-                    "is_loggedIn": is_connected(),
-                    "display_name": user.details.display_name,
-                    "canister_id": user.canister_id,
-                    "video_id": video_id,
-                    "video_category": "NA",
-                    "creator_category": "NA",
-                    "hashtag_count": hastag_count,
-                    "is_NSFW": is_nsfw,
-                    "is_hotorNot": is_hotornot,
-                    "feed_type": "NA",
-                    "view_count": view_count,
-                    "like_count": likes.get(),
-                    "share_count": 0,
-                    "post_id": post_id,
-                    "publisher_canister_id": publisher_canister_id,
-                    "nsfw_probability": nsfw_probability,
-                })
-                .to_string(),
-            );
+            let mut video = VideoProps::from(&post_details);
+            video.like_count = likes.get();
+
+            send_event_ssr_spawn(AnalyticsEvent::LikeVideo(VideoEngagementProps {
+                user: user_props(&user),
+                video,
+                is_logged_in: is_connected(),
+                display_name: user.details.display_name,
+            }));
         }
     }
 }
@@ -373,44 +296,19 @@ impl ShareVideo {
         post_details: PostDetails,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let publisher_user_id = post_details.poster_principal;
-            let video_id = post_details.uid.clone();
-            let hastag_count = post_details.hastags.len();
-            let is_nsfw = post_details.is_nsfw;
-            let is_hotornot = post_details.hot_or_not_feed_ranking_score.is_some();
-            let view_count = post_details.views;
-            let like_count = post_details.likes;
-            let nsfw_probability = post_details.nsfw_probability;
-
             let (is_connected, _) = account_connected_reader();
 
             let user = user_details_can_store_or_ret!(cans_store);
 
             // share_video - analytics
-            let _ = send_event_ssr_spawn(
-                "share_video".to_string(),
-                json!({
-                    "publisher_user_id":publisher_user_id,
-                    "user_id": user.details.principal,
-                    "is_loggedIn": is_connected.get(),
-                    "display_name": user.details.display_name,
-                    "canister_id": user.canister_id,
-                    "video_id": video_id,
-                    "video_category": "NA",
-                    "creator_category": "NA",
-                    "hashtag_count": hastag_count,
-                    "is_NSFW": is_nsfw,
-                    "is_hotorNot": is_hotornot,
-                    "feed_type": "NA",
-                    "view_count": view_count,
-                    "like_count": like_count,
-                    "share_count": 0,
-                    "nsfw_probability": nsfw_probability,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::ShareVideo(VideoEngagementProps {
+                user: user_props(&user),
+                video: VideoProps::from(&post_details),
+                is_logged_in: is_connected.get(),
+                display_name: user.details.display_name,
+            }));
         }
     }
 }
@@ -420,20 +318,15 @@ pub struct VideoUploadInitiated;
 
 impl VideoUploadInitiated {
     pub fn send_event(&self) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // video_upload_initiated - analytics
             let user = user_details_or_ret!();
-            let _ = send_event_ssr_spawn(
-                "video_upload_initiated".to_string(),
-                json!({
-                    "user_id": user.details.principal,
-                    "display_name": user.details.display_name,
-                    "canister_id": user.canister_id,
-                    "creator_category": "NA",
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::VideoUploadInitiated(VideoUploadProps {
+                user: user_props(&user),
+                display_name: user.details.display_name,
+                creator_category: NOT_AVAILABLE.into(),
+            }));
         }
     }
 }
@@ -449,7 +342,7 @@ impl VideoUploadUploadButtonClicked {
         enable_hot_or_not: NodeRef<Input>,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // video_upload_upload_button_clicked - analytics
             let user = user_details_can_store_or_ret!(cans_store);
@@ -467,19 +360,18 @@ impl VideoUploadUploadButtonClicked {
                 .unwrap_or_default();
 
             Effect::new(move |_| {
-                let _ = send_event_ssr_spawn(
-                    "video_upload_upload_button_clicked".to_string(),
-                    json!({
-                        "user_id": user.details.principal,
-                        "display_name": user.details.display_name.clone().unwrap_or_default(),
-                        "canister_id": user.canister_id,
-                        "creator_category": "NA",
-                        "hashtag_count": hashtag_count,
-                        "is_NSFW": is_nsfw_val,
-                        "is_hotorNot": is_hotornot_val,
-                    })
-                    .to_string(),
-                );
+                send_event_ssr_spawn(AnalyticsEvent::VideoUploadUploadButtonClicked(
+                    VideoUploadClickedProps {
+                        user: user_props(&user),
+                        options: VideoUploadOptions {
+                            hashtag_count,
+                            is_nsfw: is_nsfw_val,
+                            is_hot_or_not: is_hotornot_val,
+                        },
+                        display_name: user.details.display_name.clone(),
+                        creator_category: NOT_AVAILABLE.into(),
+                    },
+                ));
             });
         }
     }
@@ -490,21 +382,16 @@ pub struct VideoUploadVideoSelected;
 
 impl VideoUploadVideoSelected {
     pub fn send_event(&self, cans_store: RwSignal<Option<Canisters<true>>>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // video_upload_video_selected - analytics
             let user = user_details_can_store_or_ret!(cans_store);
 
-            let _ = send_event_ssr_spawn(
-                "video_upload_video_selected".to_string(),
-                json!({
-                    "user_id": user.details.principal,
-                    "display_name": user.details.display_name.unwrap_or_default(),
-                    "canister_id": user.canister_id,
-                    "creator_category": "NA",
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::VideoUploadVideoSelected(VideoUploadProps {
+                user: user_props(&user),
+                display_name: user.details.display_name,
+                creator_category: NOT_AVAILABLE.into(),
+            }));
         }
     }
 }
@@ -522,25 +409,24 @@ impl VideoUploadUnsuccessful {
         enable_hot_or_not: bool,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // video_upload_unsuccessful - analytics
             let user = user_details_can_store_or_ret!(cans_store);
 
-            let _ = send_event_ssr_spawn(
-                "video_upload_unsuccessful".to_string(),
-                json!({
-                    "user_id": user.details.principal,
-                    "display_name": user.details.display_name.unwrap_or_default(),
-                    "canister_id": user.canister_id,
-                    "creator_category": "NA",
-                    "hashtag_count": hashtags_len,
-                    "is_NSFW": is_nsfw,
-                    "is_hotorNot": enable_hot_or_not,
-                    "fail_reason": error,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::VideoUploadUnsuccessful(
+                VideoUploadFailedProps {
+                    user: user_props(&user),
+                    options: VideoUploadOptions {
+                        hashtag_count: hashtags_len,
+                        is_nsfw,
+                        is_hot_or_not: enable_hot_or_not,
+                    },
+                    display_name: user.details.display_name,
+                    creator_category: NOT_AVAILABLE.into(),
+                    fail_reason: error,
+                },
+            ));
         }
     }
 }
@@ -558,27 +444,26 @@ impl VideoUploadSuccessful {
         post_id: u64,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // video_upload_successful - analytics
             let user = user_details_can_store_or_ret!(cans_store);
-            let _ = send_event_ssr_spawn(
-                "video_upload_successful".to_string(),
-                json!({
-                    "user_id": user.details.principal,
-                    "publisher_user_id": user.details.principal,
-                    "display_name": user.details.display_name,
-                    "canister_id": user.canister_id,
-                    "creator_category": "NA",
-                    "hashtag_count": hashtags_len,
-                    "is_NSFW": is_nsfw,
-                    "is_hotorNot": enable_hot_or_not,
-                    "is_filter_used": false,
-                    "video_id": video_id,
-                    "post_id": post_id,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::VideoUploadSuccessful(
+                VideoUploadSucceededProps {
+                    user: user_props(&user),
+                    options: VideoUploadOptions {
+                        hashtag_count: hashtags_len,
+                        is_nsfw,
+                        is_hot_or_not: enable_hot_or_not,
+                    },
+                    display_name: user.details.display_name,
+                    creator_category: NOT_AVAILABLE.into(),
+                    publisher_user_id: user.details.principal,
+                    video_id,
+                    post_id,
+                    is_filter_used: false,
+                },
+            ));
         }
     }
 }
@@ -588,31 +473,20 @@ pub struct Refer;
 
 impl Refer {
     pub fn send_event(&self, logged_in: ReadSignal<bool>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            // refer - analytics
-
             let user = user_details_or_ret!();
-            let details = user.details;
-            let user_id = details.principal;
-            let display_name = details.display_name;
-            let canister_id = user.canister_id;
 
             let history_ctx: HistoryCtx = expect_context();
             let prev_site = history_ctx.prev_url_untracked();
 
             // refer - analytics
-            let _ = send_event_ssr_spawn(
-                "refer".to_string(),
-                json!({
-                    "user_id":user_id,
-                    "is_loggedIn": logged_in.get_untracked(),
-                    "display_name": display_name,
-                    "canister_id": canister_id,
-                    "refer_location": prev_site,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::Refer(ReferProps {
+                user: user_props(&user),
+                is_logged_in: logged_in.get_untracked(),
+                display_name: user.details.display_name,
+                refer_location: prev_site,
+            }));
         }
     }
 }
@@ -626,31 +500,20 @@ impl ReferShareLink {
         logged_in: ReadSignal<bool>,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            // refer_share_link - analytics
             let user = user_details_can_store_or_ret!(cans_store);
-            let details = user.details;
-
-            let user_id = details.principal;
-            let display_name = details.display_name;
-            let canister_id = user.canister_id;
 
             let history_ctx: HistoryCtx = expect_context();
             let prev_site = history_ctx.prev_url_untracked();
 
             // refer_share_link - analytics
-            let _ = send_event_ssr_spawn(
-                "refer_share_link".to_string(),
-                json!({
-                    "user_id":user_id,
-                    "is_loggedIn": logged_in.get_untracked(),
-                    "display_name": display_name,
-                    "canister_id": canister_id,
-                    "refer_location": prev_site,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::ReferShareLink(ReferProps {
+                user: user_props(&user),
+                is_logged_in: logged_in.get_untracked(),
+                display_name: user.details.display_name,
+                refer_location: prev_site,
+            }));
         }
     }
 }
@@ -660,7 +523,7 @@ pub struct LoginSuccessful;
 
 impl LoginSuccessful {
     pub fn send_event(&self, canisters: Canisters<true>) -> Result<(), anyhow::Error> {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // login_successful - analytics
 
@@ -670,19 +533,17 @@ impl LoginSuccessful {
             })?;
             let canister_id = canisters.user_canister();
 
+            #[cfg(feature = "ga4")]
             let _ = send_user_id(user_id.to_string());
 
-            // login_successful - analytics
-            let _ = send_event_ssr_spawn(
-                "login_successful".to_string(),
-                json!({
-                    "login_method": "google", // TODO: change this when more providers are added
-                    "user_id": user_id.to_string(),
-                    "canister_id": canister_id.to_string(),
-                    "is_new_user": false,                   // TODO: add this info
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::LoginSuccessful(LoginSuccessfulProps {
+                user: UserProps {
+                    user_id,
+                    canister_id,
+                },
+                login_method: LoginMethod::Google, // TODO: change this when more providers are added
+                is_new_user: false,                // TODO: add this info
+            }));
         }
 
         Ok(())
@@ -694,22 +555,20 @@ pub struct LoginMethodSelected;
 
 impl LoginMethodSelected {
     pub fn send_event(&self, prov: ProviderKind) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // login_method_selected - analytics
-            let _ = send_event_ssr_spawn(
-                "login_method_selected".to_string(),
-                json!({
-                    "login_method": match prov {
+            send_event_ssr_spawn(AnalyticsEvent::LoginMethodSelected(
+                LoginMethodSelectedProps {
+                    login_method: match prov {
                         #[cfg(feature = "local-auth")]
-                        ProviderKind::LocalStorage => "local_storage",
+                        ProviderKind::LocalStorage => LoginMethod::LocalStorage,
                         #[cfg(any(feature = "oauth-ssr", feature = "oauth-hydrate"))]
-                        ProviderKind::Google => "google",
+                        ProviderKind::Google => LoginMethod::Google,
                     },
-                    "attempt_count": 1,
-                })
-                .to_string(),
-            );
+                    attempt_count: 1,
+                },
+            ));
         }
     }
 }
@@ -719,7 +578,7 @@ pub struct LoginJoinOverlayViewed;
 
 impl LoginJoinOverlayViewed {
     pub fn send_event(&self) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // login_join_overlay_viewed - analytics
             let user = user_details_or_ret!();
@@ -727,15 +586,14 @@ impl LoginJoinOverlayViewed {
 
             let user_id = user.details.principal;
 
-            let _ = send_event_ssr_spawn(
-                "login_join_overlay_viewed".to_string(),
-                json!({
-                    "user_id_viewer": user_id,
-                    "previous_event": event_history.event_name.get_untracked(),
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::LoginJoinOverlayViewed(
+                LoginJoinOverlayViewedProps {
+                    user_id,
+                    previous_event: event_history.event_name.get_untracked(),
+                },
+            ));
 
+            #[cfg(feature = "ga4")]
             let _ = send_user_id(user_id.to_string());
         }
     }
@@ -746,20 +604,16 @@ pub struct LoginCta;
 
 impl LoginCta {
     pub fn send_event(&self, cta_location: String) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             // login_cta - analytics
 
             let event_history: EventHistory = expect_context();
 
-            let _ = send_event_ssr_spawn(
-                "login_cta".to_string(),
-                json!({
-                    "previous_event": event_history.event_name.get_untracked(),
-                    "cta_location": cta_location,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::LoginCta(LoginCtaProps {
+                previous_event: event_history.event_name.get_untracked(),
+                cta_location,
+            }));
         }
     }
 }
@@ -769,25 +623,16 @@ pub struct LogoutClicked;
 
 impl LogoutClicked {
     pub fn send_event(&self, cans_store: RwSignal<Option<Canisters<true>>>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             let user = user_details_can_store_or_ret!(cans_store);
-            let details = user.details;
             // logout_clicked - analytics
 
-            let user_id = details.principal;
-            let display_name = details.display_name;
-            let canister_id = user.canister_id;
-
-            let _ = send_event_ssr_spawn(
-                "logout_clicked".to_string(),
-                json!({
-                    "user_id_viewer": user_id,
-                    "display_name": display_name,
-                    "canister_id": canister_id,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::LogoutClicked(LogoutProps {
+                user_id: user.details.principal,
+                canister_id: user.canister_id,
+                display_name: user.details.display_name,
+            }));
         }
     }
 }
@@ -797,25 +642,16 @@ pub struct LogoutConfirmation;
 
 impl LogoutConfirmation {
     pub fn send_event(&self, cans_store: RwSignal<Option<Canisters<true>>>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             let user = user_details_can_store_or_ret!(cans_store);
-            let details = user.details;
-
-            let user_id = details.principal;
-            let display_name = details.display_name;
-            let canister_id = user.canister_id;
             // logout_confirmation - analytics
 
-            let _ = send_event_ssr_spawn(
-                "logout_confirmation".to_string(),
-                json!({
-                    "user_id_viewer": user_id,
-                    "display_name": display_name,
-                    "canister_id": canister_id,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::LogoutConfirmation(LogoutProps {
+                user_id: user.details.principal,
+                canister_id: user.canister_id,
+                display_name: user.details.display_name,
+            }));
         }
     }
 }
@@ -825,26 +661,17 @@ pub struct ErrorEvent;
 
 impl ErrorEvent {
     pub fn send_event(&self, error_str: String, cans_store: RwSignal<Option<Canisters<true>>>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             let event_history: EventHistory = expect_context();
             let user = user_details_can_store_or_ret!(cans_store);
-            let details = user.details;
-
-            let user_id = details.principal;
-            let canister_id = user.canister_id;
 
             // error_event - analytics
-            let _ = send_event_ssr_spawn(
-                "error_event".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "description": error_str,
-                    "previous_event": event_history.event_name.get_untracked(),
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::ErrorEvent(ErrorProps {
+                user: user_props(&user),
+                description: error_str,
+                previous_event: event_history.event_name.get_untracked(),
+            }));
         }
     }
 }
@@ -858,32 +685,32 @@ impl ProfileViewVideo {
         post_details: PostDetails,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let publisher_user_id = post_details.poster_principal;
-            let video_id = post_details.uid.clone();
-
             let (is_connected, _) = account_connected_reader();
 
             let user = user_details_can_store_or_ret!(cans_store);
 
-            let _ = send_event_ssr_spawn(
-                "profile_view_video".to_string(),
-                json!({
-                    "publisher_user_id":publisher_user_id,
-                    "user_id": user.details.principal,
-                    "is_loggedIn": is_connected(),
-                    "display_name": user.details.display_name,
-                    "canister_id": user.canister_id,
-                    "video_id": video_id,
-                    "profile_feed": "main",
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::ProfileViewVideo(ProfileViewVideoProps {
+                user: user_props(&user),
+                is_logged_in: is_connected(),
+                display_name: user.details.display_name,
+                video_id: post_details.uid,
+                publisher_user_id: post_details.poster_principal,
+                profile_feed: "main".into(),
+            }));
         }
     }
 }
 
+fn token_details_props(sns_init_payload: &SnsInitPayload) -> TokenDetailsProps {
+    TokenDetailsProps {
+        token_name: sns_init_payload.token_name.clone(),
+        token_symbol: sns_init_payload.token_symbol.clone(),
+        name: sns_init_payload.name.clone(),
+    }
+}
+
 #[derive(Default)]
 pub struct TokenCreationStarted;
 
@@ -893,26 +720,17 @@ impl TokenCreationStarted {
         sns_init_payload: SnsInitPayload,
         cans_store: RwSignal<Option<Canisters<true>>>,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             let user = user_details_can_store_or_ret!(cans_store);
-            let details = user.details;
-
-            let user_id = details.principal;
-            let canister_id = user.canister_id;
 
             // token_creation_started - analytics
-            let _ = send_event_ssr_spawn(
-                "token_creation_started".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "token_name": sns_init_payload.token_name,
-                    "token_symbol": sns_init_payload.token_symbol,
-                    "name": sns_init_payload.name
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::TokenCreationStarted(
+                TokenCreationStartedProps {
+                    user: user_props(&user),
+                    token: token_details_props(&sns_init_payload),
+                },
+            ));
         }
     }
 }
@@ -929,34 +747,28 @@ impl TokenCreationCompleted {
         canister_id: Principal,
         nsfw_info: NSFWInfo,
     ) {
-        #[cfg(all(feature = "ssr", feature = "ga4"))]
+        #[cfg(feature = "ssr")]
         {
-            use crate::event_streaming::send_event_ssr;
-
-            let user_id = profile_details.principal;
+            use super::{schema::VersionedEvent, send_event_ssr};
 
             let link = format!("/token/info/{token_root}");
 
             // token_creation_completed - analytics
-            let _ = send_event_ssr(
-                "token_creation_completed".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "token_name": sns_init_payload.token_name,
-                    "token_symbol": sns_init_payload.token_symbol,
-                    "name": sns_init_payload.name,
-                    "description": sns_init_payload.description,
-                    "logo": sns_init_payload.logo,
-                    "link": link,
-                    "is_nsfw": nsfw_info.is_nsfw,
-                    "nsfw_ec": nsfw_info.nsfw_ec,
-                    "nsfw_gore": nsfw_info.nsfw_gore,
-                    "csam_detected": nsfw_info.csam_detected,
-                })
-                .to_string(),
-            )
-            .await;
+            let event = AnalyticsEvent::TokenCreationCompleted(TokenCreationCompletedProps {
+                user: UserProps {
+                    user_id: profile_details.principal,
+                    canister_id,
+                },
+                token: token_details_props(&sns_init_payload),
+                description: sns_init_payload.description,
+                logo: sns_init_payload.logo,
+                link,
+                is_nsfw: nsfw_info.is_nsfw,
+                nsfw_ec: nsfw_info.nsfw_ec,
+                nsfw_gore: nsfw_info.nsfw_gore,
+                csam_detected: nsfw_info.csam_detected,
+            });
+            let _ = send_event_ssr(VersionedEvent::new(event, None)).await;
         }
     }
 }
@@ -972,26 +784,21 @@ impl TokenCreationFailed {
         profile_details: ProfileDetails,
         canister_id: Principal,
     ) {
-        #[cfg(all(feature = "ssr", feature = "ga4"))]
+        #[cfg(feature = "ssr")]
         {
-            use crate::event_streaming::send_event_ssr;
-            let user_id = profile_details.principal;
+            use super::{schema::VersionedEvent, send_event_ssr};
 
             // token_creation_failed - analytics
-            let _ = send_event_ssr(
-                "token_creation_failed".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "token_name": sns_init_payload.token_name,
-                    "token_symbol": sns_init_payload.token_symbol,
-                    "name": sns_init_payload.name,
-                    "description": sns_init_payload.description,
-                    "error": error_str
-                })
-                .to_string(),
-            )
-            .await;
+            let event = AnalyticsEvent::TokenCreationFailed(TokenCreationFailedProps {
+                user: UserProps {
+                    user_id: profile_details.principal,
+                    canister_id,
+                },
+                token: token_details_props(&sns_init_payload),
+                description: sns_init_payload.description,
+                error: error_str,
+            });
+            let _ = send_event_ssr(VersionedEvent::new(event, None)).await;
         }
     }
 }
@@ -1001,23 +808,15 @@ pub struct TokensClaimedFromNeuron;
 
 impl TokensClaimedFromNeuron {
     pub fn send_event(&self, amount: u64, cans_store: Canisters<true>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let details = cans_store.profile_details();
-
-            let user_id = details.principal;
-            let canister_id = cans_store.user_canister();
-
             // tokens_claimed_from_neuron - analytics
-            let _ = send_event_ssr_spawn(
-                "tokens_claimed_from_neuron".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "amount": amount
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::TokensClaimedFromNeuron(
+                TokensClaimedFromNeuronProps {
+                    user: canisters_user_props(&cans_store),
+                    amount,
+                },
+            ));
         }
     }
 }
@@ -1027,24 +826,14 @@ pub struct TokensTransferred;
 
 impl TokensTransferred {
     pub fn send_event(&self, amount: String, to: Principal, cans_store: Canisters<true>) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let details = cans_store.profile_details();
-
-            let user_id = details.principal;
-            let canister_id = cans_store.user_canister();
-
             // tokens_transferred - analytics
-            let _ = send_event_ssr_spawn(
-                "tokens_transferred".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "amount": amount,
-                    "to": to
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::TokensTransferred(TokensTransferredProps {
+                user: canisters_user_props(&cans_store),
+                amount,
+                to,
+            }));
         }
     }
 }
@@ -1054,7 +843,7 @@ pub struct PageVisit;
 
 impl PageVisit {
     pub fn send_event(&self, canisters: Canisters<true>, pathname: String) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
             let user_id = canisters.profile_details().principal;
             let (is_connected, _) = account_connected_reader();
@@ -1062,15 +851,11 @@ impl PageVisit {
 
             let UseTimeoutFnReturn { start, .. } = use_timeout_fn(
                 move |_| {
-                    let _ = send_event_ssr_spawn(
-                        "yral_page_visit".to_string(),
-                        json!({
-                            "user_id": user_id,
-                            "is_loggedIn": is_connected,
-                            "pathname": pathname,
-                        })
-                        .to_string(),
-                    );
+                    send_event_ssr_spawn(AnalyticsEvent::PageVisit(PageVisitProps {
+                        user_id,
+                        is_logged_in: is_connected,
+                        pathname: pathname.clone(),
+                    }));
                 },
                 10000.0,
             );
@@ -1085,25 +870,19 @@ pub struct CentsAdded;
 
 impl CentsAdded {
     pub fn send_event(&self, payment_source: String, amount: u64) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let (canister_id, _, _) =
-                use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
-            let (user_id, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
+            let Some(user) = stored_user_props() else {
+                return;
+            };
             let (is_connected, _) = account_connected_reader();
-            let is_connected = is_connected.get_untracked();
 
-            let _ = send_event_ssr_spawn(
-                "cents_added".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "is_loggedin": is_connected,
-                    "amount_added": amount,
-                    "payment_source": payment_source,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::CentsAdded(CentsAddedProps {
+                user,
+                is_logged_in: is_connected.get_untracked(),
+                amount_added: amount,
+                payment_source,
+            }));
         }
     }
 }
@@ -1113,24 +892,18 @@ pub struct CentsWithdrawn;
 
 impl CentsWithdrawn {
     pub fn send_event(&self, amount_withdrawn: f64) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let (canister_id, _, _) =
-                use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
-            let (user_id, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
+            let Some(user) = stored_user_props() else {
+                return;
+            };
             let (is_connected, _) = account_connected_reader();
-            let is_connected = is_connected.get_untracked();
 
-            let _ = send_event_ssr_spawn(
-                "cents_withdrawn".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "is_loggedin": is_connected,
-                    "amount_withdrawn": amount_withdrawn,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::CentsWithdrawn(WithdrawnProps {
+                user,
+                is_logged_in: is_connected.get_untracked(),
+                amount_withdrawn,
+            }));
         }
     }
 }
@@ -1140,24 +913,18 @@ pub struct SatsWithdrawn;
 
 impl SatsWithdrawn {
     pub fn send_event(&self, amount_withdrawn: f64) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let (canister_id, _, _) =
-                use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
-            let (user_id, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
+            let Some(user) = stored_user_props() else {
+                return;
+            };
             let (is_connected, _) = account_connected_reader();
-            let is_connected = is_connected.get_untracked();
 
-            let _ = send_event_ssr_spawn(
-                "sats_withdrawn".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "is_loggedin": is_connected,
-                    "amount_withdrawn": amount_withdrawn,
-                })
-                .to_string(),
-            );
+            send_event_ssr_spawn(AnalyticsEvent::SatsWithdrawn(WithdrawnProps {
+                user,
+                is_logged_in: is_connected.get_untracked(),
+                amount_withdrawn,
+            }));
         }
     }
 }
@@ -1170,30 +937,24 @@ impl TokenPumpedDumped {
         &self,
         token_name: String,
         token_root: Principal,
-        direction: String,
+        direction: PumpOrDump,
         count: u32,
     ) {
-        #[cfg(all(feature = "hydrate", feature = "ga4"))]
+        #[cfg(feature = "hydrate")]
         {
-            let (canister_id, _, _) =
-                use_local_storage::<Option<Principal>, JsonSerdeCodec>(USER_CANISTER_ID_STORE);
-            let (user_id, _) = use_cookie::<Principal, FromToStringCodec>(USER_PRINCIPAL_STORE);
-
-            let is_loggedin = account_connected_reader().0.get_untracked();
-
-            let _ = send_event_ssr_spawn(
-                "token_pumped_dumped".to_string(),
-                json!({
-                    "user_id": user_id,
-                    "canister_id": canister_id,
-                    "token_name": token_name,
-                    "token_root": token_root.to_string(),
-                    "direction": direction,
-                    "count": count,
-                    "is_loggedin": is_loggedin,
-                })
-                .to_string(),
-            );
+            let Some(user) = stored_user_props() else {
+                return;
+            };
+            let is_logged_in = account_connected_reader().0.get_untracked();
+
+            send_event_ssr_spawn(AnalyticsEvent::TokenPumpedDumped(TokenPumpedDumpedProps {
+                user,
+                is_logged_in,
+                token_name,
+                token_root,
+                direction,
+                count,
+            }));
         }
     }
 }
@@ -1,29 +1,21 @@
-use std::env;
-
+#[cfg(feature = "ga4")]
 use gloo_utils::format::JsValueSerdeExt;
 use leptos::prelude::*;
-use serde::Serialize;
+use leptos::server_fn::codec::Json;
 use serde_json::json;
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "ga4")]
 use consts::GTAG_MEASUREMENT_ID;
-#[cfg(all(feature = "ssr", feature = "ga4"))]
-use tracing::instrument;
+use schema::{AnalyticsEvent, VersionedEvent};
 
+#[cfg(feature = "ssr")]
+pub mod client_id;
+pub mod consent;
 pub mod events;
-
-#[derive(Debug, Serialize)]
-struct GA4Event {
-    client_id: String,
-    user_id: Option<String>,
-    events: Vec<Event>,
-}
-
-#[derive(Debug, Serialize)]
-struct Event {
-    name: String,
-    params: serde_json::Value,
-}
+#[cfg(feature = "ssr")]
+pub mod pipeline;
+pub mod schema;
 
 #[cfg(feature = "ssr")]
 pub mod warehouse_events {
@@ -41,62 +33,78 @@ pub struct EventHistory {
     pub event_name: RwSignal<String>,
 }
 
-#[cfg(feature = "ga4")]
-#[server]
-pub async fn send_event_ssr(event_name: String, params: String) -> Result<(), ServerFnError> {
+/// Validate a typed event and queue it for delivery to the configured sinks
+/// honors the [`consent::ConsentState`] of the request
+#[server(endpoint = "send_event", input = Json)]
+pub async fn send_event_ssr(event: VersionedEvent) -> Result<(), ServerFnError> {
     use super::host::get_host;
+    use client_id::ClientId;
+    use consent::{anonymize, ConsentCategory, ConsentState};
 
-    let params = serde_json::from_str::<serde_json::Value>(&params).map_err(|e| {
-        log::error!("Error parsing params: {e:?}");
+    event.validate().map_err(|e| {
+        log::warn!("Rejected analytics event: {e}");
         ServerFnError::new(e.to_string())
     })?;
 
-    let host_str = get_host();
-    let mut params = params.clone();
-    params["host"] = json!(host_str);
-
-    if params["page_location"].is_null() {
-        params["page_location"] = json!(format!("https://{}", host_str));
+    let VersionedEvent {
+        version,
+        page_location,
+        event,
+    } = event;
+    let warehouse_only = event.warehouse_only();
+    let (event_name, mut params) = event.into_parts();
+
+    let consent = use_context::<ConsentState>().unwrap_or_default();
+    if !consent.is_granted(ConsentCategory::Personalization) {
+        anonymize(&mut params);
     }
+    let analytics = consent.is_granted(ConsentCategory::Analytics);
+    let client_id = use_context::<ClientId>()
+        .filter(|_| analytics)
+        .map(|client_id| client_id.id);
 
-    // Warehouse
-    send_event_warehouse(&event_name, &params).await;
-
-    // GA4
-    // get client_id as user_id from params
-    let user_id = params["user_id"].as_str().unwrap_or("0");
-    let res = send_event_ga4(user_id, &event_name, &params).await;
+    let host_str = get_host();
+    params["host"] = json!(host_str);
+    params["page_location"] =
+        json!(page_location.unwrap_or_else(|| format!("https://{}", host_str)));
+    params["schema_version"] = json!(version);
 
-    if let Err(e) = res {
-        log::error!("Error sending event to GA4: {e:?}");
-    }
+    enqueue_event(event_name, params, client_id, |sink| {
+        !sink.is_product_analytics() || (analytics && !warehouse_only)
+    });
 
     Ok(())
 }
 
-#[cfg(feature = "ga4")]
-pub fn send_event_ssr_spawn(event_name: String, params: String) -> Result<(), ServerFnError> {
+#[cfg(feature = "ssr")]
+/// Queue an event on the [`pipeline::EventPipeline`], returns immediately
+/// the event is only delivered to the sinks `accepts` returns true for
+fn enqueue_event(
+    name: String,
+    params: serde_json::Value,
+    client_id: Option<String>,
+    accepts: impl Fn(pipeline::sinks::SinkKind) -> bool,
+) {
+    let Some(queue) = use_context::<pipeline::EventPipeline>() else {
+        log::warn!("analytics pipeline not available, dropping event {name}");
+        return;
+    };
+    queue.enqueue(name, params, client_id, accepts);
+}
+
+pub fn send_event_ssr_spawn(event: AnalyticsEvent) {
     use leptos::task::spawn_local;
 
-    let mut params = serde_json::from_str::<serde_json::Value>(&params).map_err(|e| {
-        log::error!("Error parsing params: {e:?}");
-        ServerFnError::new(e.to_string())
-    })?;
-    params["page_location"] = json!(window().location().href().map_err(|e| {
-        let error_msg = format!("Error getting page location: {e:?}");
-        log::error!("{error_msg}");
-        ServerFnError::new(error_msg)
-    })?);
-    let params = serde_json::to_string(&params).map_err(|e| {
-        log::error!("Error serializing params: {e:?}");
-        ServerFnError::new(e.to_string())
-    })?;
+    let page_location = window()
+        .location()
+        .href()
+        .inspect_err(|e| log::error!("Error getting page location: {e:?}"))
+        .ok();
+    let event = VersionedEvent::new(event, page_location);
 
     spawn_local(async move {
-        let _ = send_event_ssr(event_name, params).await;
+        let _ = send_event_ssr(event).await;
     });
-
-    Ok(())
 }
 
 #[cfg(feature = "ga4")]
@@ -118,138 +126,23 @@ pub fn send_user_id(user_id: String) -> Result<(), ServerFnError> {
     Ok(())
 }
 
-#[cfg(all(feature = "ga4", feature = "ssr"))]
-#[instrument]
-pub async fn send_event_warehouse(event_name: &str, params: &serde_json::Value) {
-    use super::host::get_host;
-
-    let event_name = event_name.to_string();
-
-    let mut params = params.clone();
-    if params["host"].is_null() {
-        let host_str = get_host();
-        params["host"] = json!(host_str);
-    }
-
-    let res = stream_to_offchain_agent(event_name, &params).await;
-    if let Err(e) = res {
-        log::error!("Error sending event to warehouse: {e:?}");
-    }
-}
-
-#[cfg(feature = "ga4")]
-#[server]
-pub async fn send_event_warehouse_ssr(
-    event_name: String,
-    params: String,
-) -> Result<(), ServerFnError> {
-    let params = serde_json::from_str::<serde_json::Value>(&params).map_err(|e| {
-        log::error!("Error parsing params: {e:?}");
-        ServerFnError::new(e.to_string())
-    })?;
-    send_event_warehouse(&event_name, &params).await;
-
-    Ok(())
-}
-
+/// Mirror the analytics consent to gtag's consent mode
 #[cfg(feature = "ga4")]
-pub fn send_event_warehouse_ssr_spawn(event_name: String, params: String) {
-    use leptos::task::spawn_local;
-
-    spawn_local(async move {
-        let _ = send_event_warehouse_ssr(event_name, params).await;
-    });
-}
-
-#[cfg(all(feature = "ga4", feature = "ssr"))]
-#[instrument]
-pub async fn stream_to_offchain_agent(
-    event: String,
-    params: &serde_json::Value,
-) -> Result<(), ServerFnError> {
-    use tonic::metadata::MetadataValue;
-    use tonic::transport::Channel;
-    use tonic::Request;
-
-    let channel: Channel = expect_context();
-
-    let mut off_chain_agent_grpc_auth_token = env::var("GRPC_AUTH_TOKEN").expect("GRPC_AUTH_TOKEN");
-    // removing whitespaces and new lines for proper parsing
-    off_chain_agent_grpc_auth_token.retain(|c| !c.is_whitespace());
-
-    let token: MetadataValue<_> = format!("Bearer {off_chain_agent_grpc_auth_token}").parse()?;
-
-    let mut client =
-        warehouse_events::warehouse_events_client::WarehouseEventsClient::with_interceptor(
-            channel,
-            move |mut req: Request<()>| {
-                req.metadata_mut().insert("authorization", token.clone());
-                Ok(req)
-            },
-        );
-
-    let params = params.to_string();
-    let request = tonic::Request::new(warehouse_events::WarehouseEvent { event, params });
-
-    client.send_event(request).await?;
-
-    Ok(())
-}
-
-fn convert_leaf_values_to_string(value: serde_json::Value) -> serde_json::Value {
-    match value {
-        serde_json::Value::Object(mut obj) => {
-            for (_, val) in obj.iter_mut() {
-                *val = convert_leaf_values_to_string(val.clone());
-            }
-            serde_json::Value::Object(obj)
+pub fn update_gtag_consent(consent: consent::ConsentState) {
+    let storage = |category| {
+        if consent.is_granted(category) {
+            "granted"
+        } else {
+            "denied"
         }
-        serde_json::Value::Array(mut arr) => {
-            for item in arr.iter_mut() {
-                *item = convert_leaf_values_to_string(item.clone());
-            }
-            serde_json::Value::Array(arr)
-        }
-        serde_json::Value::Number(n) => serde_json::Value::String(n.to_string()),
-        serde_json::Value::Null => serde_json::Value::String("".to_string()),
-        serde_json::Value::Bool(value) => serde_json::Value::String(value.to_string()),
-        serde_json::Value::String(value) => serde_json::Value::String(value),
-    }
-}
-
-#[cfg(all(feature = "ga4", feature = "ssr"))]
-#[instrument]
-pub async fn send_event_ga4(
-    user_id: &str,
-    event_name: &str,
-    params: &serde_json::Value,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use reqwest::Client;
-
-    let measurement_id: &str = GTAG_MEASUREMENT_ID.as_ref();
-    let api_secret = env::var("GA4_API_SECRET")?;
-
-    let client = Client::new();
-    let url = format!(
-        "https://www.google-analytics.com/mp/collect?measurement_id={measurement_id}&api_secret={api_secret}"
-    );
-
-    let params = convert_leaf_values_to_string(params.clone());
-
-    let payload = GA4Event {
-        client_id: "12345".to_string(), // Should be some unique id
-        user_id: Some(user_id.to_string()),
-        events: vec![Event {
-            name: event_name.to_string(),
-            params: params.clone(),
-        }],
     };
+    let params = json!({
+        "analytics_storage": storage(consent::ConsentCategory::Analytics),
+        "personalization_storage": storage(consent::ConsentCategory::Personalization),
+    });
 
-    let response = client.post(&url).json(&payload).send().await?;
-
-    if !response.status().is_success() {
-        return Err(format!("GA4 request failed: {:?}", response.status()).into());
+    match JsValue::from_serde(&params) {
+        Ok(params) => gtag("consent", "update", &params),
+        Err(e) => log::error!("Error serializing consent: {e:?}"),
     }
-
-    Ok(())
 }
@@ -0,0 +1,614 @@
+//! Background delivery of analytics events to the configured [`sinks`]
+//! events are queued in memory and flushed in batches by a single worker,
+//! events which still fail after retries are spooled to disk and retried by a separate task
+pub mod sinks;
+mod spool;
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::{future::join_all, stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::{interval, sleep, timeout_at, Instant, MissedTickBehavior},
+};
+
+use sinks::{AnalyticsSink, AnalyticsSinkImpl, SinkKind};
+
+use crate::request_id::RequestId;
+
+pub use spool::EventSpool;
+
+/// Max events held in memory, further events go straight to the spool
+const QUEUE_CAPACITY: usize = 4096;
+/// Max events waiting to be spooled while the queue is full, further events are dropped
+const SPILL_CAPACITY: usize = 1024;
+/// Max events delivered per flush
+const BATCH_SIZE: usize = 100;
+/// Max time an event waits in the queue before being flushed
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Max concurrent requests per sink
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+/// How often spooled events are retried
+const SPOOL_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Max spooled events retried at once
+const SPOOL_RETRY_BATCH: usize = 500;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueuedEvent {
+    /// Unique per event, lets sinks deduplicate retried deliveries
+    pub id: String,
+    pub timestamp_ms: u64,
+    /// First party analytics client id, `None` without analytics consent
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Id of the request the event was sent in, forwarded to the sinks
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// W3C `traceparent` of the request the event was sent in, forwarded to the sinks
+    #[serde(default)]
+    pub traceparent: Option<String>,
+    pub name: String,
+    pub params: serde_json::Value,
+    /// Sinks the event still has to be delivered to
+    pub pending: Vec<SinkKind>,
+}
+
+impl QueuedEvent {
+    /// The `user_id` param, missing for anonymized events
+    fn user_id(&self) -> Option<&str> {
+        self.params["user_id"].as_str()
+    }
+}
+
+/// Request id and `traceparent` shared by every event of `events`, if any
+/// events of a request share its trace id, the batch is sent under the first event's parent
+fn batch_trace<'a>(events: &[&'a QueuedEvent]) -> Option<(&'a str, Option<&'a str>)> {
+    let (first, rest) = events.split_first()?;
+    let id = first.request_id.as_deref()?;
+    rest.iter()
+        .all(|ev| ev.request_id.as_deref() == Some(id))
+        .then_some((id, first.traceparent.as_deref()))
+}
+
+enum Message {
+    Event(QueuedEvent),
+    Flush(oneshot::Sender<()>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+enum Spill {
+    Event(QueuedEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Handle to the analytics pipeline, cheap to clone
+#[derive(Clone)]
+pub struct EventPipeline {
+    tx: mpsc::Sender<Message>,
+    /// events which did not fit in the queue, written to the spool by [`spill`]
+    spill_tx: mpsc::Sender<Spill>,
+    kinds: Arc<[SinkKind]>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventPipeline {
+    /// Spawn the pipeline worker, must be called inside a tokio runtime
+    /// every event is fanned out to all of `sinks`
+    pub fn start(sinks: Vec<AnalyticsSinkImpl>, spool: EventSpool) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        let (spill_tx, spill_rx) = mpsc::channel(SPILL_CAPACITY);
+        let kinds = sinks.iter().map(|sink| sink.kind()).collect();
+        tokio::spawn(spill(spill_rx, spool.clone()));
+        let retry = tokio::spawn(retry_spooled_periodically(sinks.clone(), spool.clone()));
+        let worker = Worker {
+            rx,
+            sinks,
+            spool,
+            retry,
+        };
+        tokio::spawn(worker.run());
+
+        Self {
+            tx,
+            spill_tx,
+            kinds,
+            dropped: Arc::default(),
+        }
+    }
+
+    /// Events dropped since the start because both the queue and the spool backlog were full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queue an event for delivery to the sinks `accepts` returns true for
+    /// never waits on the network, events are spooled if the queue is full
+    /// and dropped if the spool can't keep up either
+    pub fn enqueue(
+        &self,
+        name: String,
+        params: serde_json::Value,
+        client_id: Option<String>,
+        accepts: impl Fn(SinkKind) -> bool,
+    ) {
+        let pending: Vec<_> = self
+            .kinds
+            .iter()
+            .copied()
+            .filter(|&kind| accepts(kind))
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let request_id = RequestId::current();
+        let event = QueuedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp_ms,
+            client_id,
+            request_id: request_id.as_ref().map(RequestId::to_string),
+            traceparent: request_id.as_ref().map(RequestId::traceparent),
+            name,
+            params,
+            pending,
+        };
+
+        let event = match self.tx.try_send(Message::Event(event)) {
+            Ok(()) => return,
+            Err(mpsc::error::TrySendError::Full(Message::Event(event))) => {
+                log::warn!("analytics queue full, spooling {}", event.name);
+                event
+            }
+            Err(mpsc::error::TrySendError::Closed(Message::Event(event))) => {
+                log::warn!("analytics pipeline stopped, spooling {}", event.name);
+                event
+            }
+            Err(_) => unreachable!("only events are sent via enqueue"),
+        };
+
+        if self.spill_tx.try_send(Spill::Event(event)).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            log::error!("analytics spool backlog full, {dropped} events dropped so far");
+        }
+    }
+
+    /// Wait until every event queued so far was delivered (or spooled)
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(Message::Flush(done_tx)).await.is_ok() {
+            _ = done_rx.await;
+        }
+        self.flush_spill().await;
+    }
+
+    async fn flush_spill(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.spill_tx.send(Spill::Flush(done_tx)).await.is_ok() {
+            _ = done_rx.await;
+        }
+    }
+
+    /// Flush the queued events and stop the worker
+    /// undelivered events are kept in the spool and retried on the next start
+    pub async fn shutdown(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(Message::Shutdown(done_tx)).await.is_ok() {
+            _ = done_rx.await;
+        }
+        self.flush_spill().await;
+
+        let dropped = self.dropped();
+        if dropped > 0 {
+            log::warn!("{dropped} analytics events were dropped since the start");
+        }
+    }
+}
+
+/// Write the events which did not fit in the queue to the spool, in batches
+async fn spill(mut rx: mpsc::Receiver<Spill>, spool: EventSpool) {
+    while let Some(msg) = rx.recv().await {
+        let mut events = vec![];
+        let mut done = None;
+        match msg {
+            Spill::Event(ev) => events.push(ev),
+            Spill::Flush(tx) => done = Some(tx),
+        }
+        while done.is_none() && events.len() < BATCH_SIZE {
+            match rx.try_recv() {
+                Ok(Spill::Event(ev)) => events.push(ev),
+                Ok(Spill::Flush(tx)) => done = Some(tx),
+                Err(_) => break,
+            }
+        }
+
+        if let Err(e) = spool.push(events).await {
+            log::error!("failed to spool analytics events: {e}");
+        }
+        if let Some(done) = done {
+            _ = done.send(());
+        }
+    }
+}
+
+/// Retry spooled events every [`SPOOL_RETRY_INTERVAL`], starting right away
+/// runs apart from the worker so slow retries never hold back fresh events
+async fn retry_spooled_periodically(sinks: Vec<AnalyticsSinkImpl>, spool: EventSpool) {
+    let mut ticker = interval(SPOOL_RETRY_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        retry_spooled(&sinks, &spool).await;
+    }
+}
+
+async fn retry_spooled(sinks: &[AnalyticsSinkImpl], spool: &EventSpool) {
+    let spooled = match spool.peek(SPOOL_RETRY_BATCH).await {
+        Ok(spooled) => spooled,
+        Err(e) => {
+            log::error!("failed to read analytics spool: {e}");
+            return;
+        }
+    };
+    if spooled.is_empty() {
+        return;
+    }
+
+    let (seqs, events): (Vec<_>, Vec<_>) = spooled.into_iter().unzip();
+    let failed = deliver(sinks, events).await;
+    // failed events are re-appended so the spool always makes progress
+    if let Err(e) = spool.push(failed).await {
+        log::error!("failed to spool analytics events: {e}");
+        return;
+    }
+    if let Err(e) = spool.remove(seqs).await {
+        log::error!("failed to remove delivered events from the spool: {e}");
+    }
+}
+
+/// Retry `f` with exponential backoff
+async fn with_retry<F, Fut, E>(sink: SinkKind, mut f: F) -> Result<(), E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: Display,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= MAX_ATTEMPTS => {
+                log::warn!("{sink} delivery failed after {attempt} attempts: {e}");
+                return Err(e);
+            }
+            Err(e) => {
+                log::debug!("{sink} delivery attempt {attempt} failed: {e}");
+                sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Deliver the events pending for `sink`
+/// returns the indices of the events which could not be delivered
+async fn deliver_to(sink: &AnalyticsSinkImpl, events: &[QueuedEvent]) -> Vec<usize> {
+    let kind = sink.kind();
+    let mut batches: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, ev) in events
+        .iter()
+        .enumerate()
+        .filter(|(_, ev)| ev.pending.contains(&kind))
+    {
+        batches.entry(sink.batch_key(ev)).or_default().push(idx);
+    }
+    let chunks: Vec<&[usize]> = batches
+        .values()
+        .flat_map(|idxs| idxs.chunks(sink.max_batch_size()))
+        .collect();
+
+    let results: Vec<bool> = stream::iter(&chunks)
+        .map(|idxs| {
+            let batch: Vec<_> = idxs.iter().map(|&idx| &events[idx]).collect();
+            async move { with_retry(kind, || sink.send(&batch)).await.is_err() }
+        })
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .collect()
+        .await;
+
+    chunks
+        .into_iter()
+        .zip(results)
+        .filter(|(_, failed)| *failed)
+        .flat_map(|(idxs, _)| idxs.iter().copied())
+        .collect()
+}
+
+/// Deliver events to their pending sinks
+/// returns the events which could not be delivered, with only the failed sinks pending
+/// sinks which are no longer enabled are dropped from `pending`
+async fn deliver(sinks: &[AnalyticsSinkImpl], events: Vec<QueuedEvent>) -> Vec<QueuedEvent> {
+    let failed = join_all(sinks.iter().map(|sink| deliver_to(sink, &events))).await;
+
+    let mut still_pending = vec![vec![]; events.len()];
+    for (sink, failed) in sinks.iter().zip(failed) {
+        for idx in failed {
+            still_pending[idx].push(sink.kind());
+        }
+    }
+
+    events
+        .into_iter()
+        .zip(still_pending)
+        .filter_map(|(mut ev, pending)| {
+            ev.pending = pending;
+            (!ev.pending.is_empty()).then_some(ev)
+        })
+        .collect()
+}
+
+enum Interrupt {
+    Flush(oneshot::Sender<()>),
+    Shutdown(oneshot::Sender<()>),
+    /// every [`EventPipeline`] handle was dropped
+    Closed,
+}
+
+struct Worker {
+    rx: mpsc::Receiver<Message>,
+    sinks: Vec<AnalyticsSinkImpl>,
+    spool: EventSpool,
+    /// the [`retry_spooled_periodically`] task
+    retry: JoinHandle<()>,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        match self.spool.count().await {
+            Ok(0) => (),
+            Ok(count) => log::info!("{count} spooled analytics events pending"),
+            Err(e) => log::error!("failed to read analytics spool: {e}"),
+        }
+
+        loop {
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            let interrupt = self
+                .fill_batch(&mut batch, Instant::now() + FLUSH_INTERVAL)
+                .await;
+            self.flush(batch).await;
+
+            match interrupt {
+                Some(Interrupt::Flush(done)) => {
+                    _ = done.send(());
+                }
+                Some(Interrupt::Shutdown(done)) => {
+                    self.stop(Some(done)).await;
+                    return;
+                }
+                Some(Interrupt::Closed) => {
+                    self.stop(None).await;
+                    return;
+                }
+                None => (),
+            }
+        }
+    }
+
+    /// Collect events until the batch is full or `deadline` passes
+    async fn fill_batch(
+        &mut self,
+        batch: &mut Vec<QueuedEvent>,
+        deadline: Instant,
+    ) -> Option<Interrupt> {
+        while batch.len() < BATCH_SIZE {
+            match timeout_at(deadline, self.rx.recv()).await {
+                Ok(Some(Message::Event(ev))) => batch.push(ev),
+                Ok(Some(Message::Flush(done))) => return Some(Interrupt::Flush(done)),
+                Ok(Some(Message::Shutdown(done))) => return Some(Interrupt::Shutdown(done)),
+                Ok(None) => return Some(Interrupt::Closed),
+                Err(_) => break,
+            }
+        }
+        None
+    }
+
+    async fn flush(&self, batch: Vec<QueuedEvent>) {
+        if batch.is_empty() {
+            return;
+        }
+        let failed = deliver(&self.sinks, batch).await;
+        if failed.is_empty() {
+            return;
+        }
+        log::warn!("spooling {} undelivered analytics events", failed.len());
+        if let Err(e) = self.spool.push(failed).await {
+            log::error!("failed to spool analytics events: {e}");
+        }
+    }
+
+    /// Events enqueued after the shutdown request are spooled without a delivery attempt
+    /// an in-flight retry may be cut short, its events stay spooled and sinks deduplicate by id
+    async fn stop(mut self, done: Option<oneshot::Sender<()>>) {
+        self.retry.abort();
+        self.rx.close();
+        let mut pending = vec![];
+        while let Some(msg) = self.rx.recv().await {
+            if let Message::Event(ev) = msg {
+                pending.push(ev);
+            }
+        }
+        if let Err(e) = self.spool.push(pending).await {
+            log::error!("failed to spool analytics events: {e}");
+        }
+
+        log::info!("analytics pipeline stopped");
+        if let Some(done) = done {
+            _ = done.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::AtomicU32,
+    };
+
+    use super::{sinks::JsonLinesSink, *};
+
+    /// Unique path in a missing directory, deliveries fail until the directory is created
+    fn events_file() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("analytics-{}", uuid::Uuid::new_v4()))
+            .join("events.jsonl")
+    }
+
+    fn delivered(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| {
+                let line: serde_json::Value = serde_json::from_str(line).unwrap();
+                line["event"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    fn start(path: &Path) -> (EventPipeline, EventSpool) {
+        let spool = EventSpool::new_in_memory().unwrap();
+        let sinks = vec![JsonLinesSink::file(path).into()];
+        (EventPipeline::start(sinks, spool.clone()), spool)
+    }
+
+    fn enqueue(pipeline: &EventPipeline, count: usize) {
+        for i in 0..count {
+            pipeline.enqueue(format!("event_{i}"), serde_json::json!({}), None, |_| true);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batches_are_delivered_in_order() {
+        let path = events_file();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let (pipeline, spool) = start(&path);
+
+        enqueue(&pipeline, BATCH_SIZE + 5);
+        pipeline.flush().await;
+
+        let expected: Vec<_> = (0..BATCH_SIZE + 5).map(|i| format!("event_{i}")).collect();
+        assert_eq!(delivered(&path), expected);
+        assert_eq!(spool.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_with_backoff() {
+        let attempts = &AtomicU32::new(0);
+        let res = with_retry(SinkKind::JsonLines, move || async move {
+            if attempts.fetch_add(1, Ordering::Relaxed) < 2 {
+                Err("unavailable")
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+        assert!(res.is_ok());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+        let attempts = &AtomicU32::new(0);
+        let started = Instant::now();
+        let res = with_retry(SinkKind::JsonLines, move || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>("unavailable")
+        })
+        .await;
+        assert!(res.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), MAX_ATTEMPTS);
+        assert!(started.elapsed() >= INITIAL_BACKOFF * 7);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn undelivered_events_are_spooled_and_replayed() {
+        let path = events_file();
+        let (pipeline, spool) = start(&path);
+
+        enqueue(&pipeline, 3);
+        // stops the periodic retries, so only the replay below delivers
+        pipeline.shutdown().await;
+        assert!(delivered(&path).is_empty());
+        assert_eq!(spool.count().await.unwrap(), 3);
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let sinks: [AnalyticsSinkImpl; 1] = [JsonLinesSink::file(&path).into()];
+        retry_spooled(&sinks, &spool).await;
+
+        assert_eq!(delivered(&path), ["event_0", "event_1", "event_2"]);
+        assert_eq!(spool.count().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn batches_carry_the_shared_trace() {
+        let event = |request_id: Option<&str>, traceparent: Option<&str>| QueuedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp_ms: 0,
+            client_id: None,
+            request_id: request_id.map(str::to_string),
+            traceparent: traceparent.map(str::to_string),
+            name: "event".into(),
+            params: serde_json::json!({}),
+            pending: vec![SinkKind::JsonLines],
+        };
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let first = event(Some("req-1"), Some(traceparent));
+        let second = event(Some("req-1"), None);
+        let other = event(Some("req-2"), Some(traceparent));
+        let spooled = event(None, None);
+
+        assert_eq!(
+            batch_trace(&[&first, &second]),
+            Some(("req-1", Some(traceparent)))
+        );
+        assert_eq!(batch_trace(&[&second, &first]), Some(("req-1", None)));
+        assert_eq!(batch_trace(&[&first, &other]), None);
+        assert_eq!(batch_trace(&[&spooled]), None);
+        assert_eq!(batch_trace(&[]), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn overflow_is_spooled_then_dropped() {
+        let spool = EventSpool::new_in_memory().unwrap();
+        // no worker, the queue and the spill backlog both fill up after one event
+        let (tx, _rx) = mpsc::channel(1);
+        let (spill_tx, spill_rx) = mpsc::channel(1);
+        let pipeline = EventPipeline {
+            tx,
+            spill_tx,
+            kinds: Arc::new([SinkKind::JsonLines]),
+            dropped: Arc::default(),
+        };
+
+        enqueue(&pipeline, 4);
+        assert_eq!(pipeline.dropped(), 2);
+
+        tokio::spawn(spill(spill_rx, spool.clone()));
+        pipeline.flush_spill().await;
+        assert_eq!(spool.count().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use std::env;
+
+use serde::Serialize;
+
+use consts::GTAG_MEASUREMENT_ID;
+
+use super::{propagate_batch_trace, AnalyticsSink, QueuedEvent, SinkError, SinkKind};
+
+#[derive(Debug, Serialize)]
+struct GA4Event {
+    client_id: String,
+    user_id: Option<String>,
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Serialize)]
+struct Event {
+    name: String,
+    params: serde_json::Value,
+}
+
+fn convert_leaf_values_to_string(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(mut obj) => {
+            for (_, val) in obj.iter_mut() {
+                *val = convert_leaf_values_to_string(val.clone());
+            }
+            serde_json::Value::Object(obj)
+        }
+        serde_json::Value::Array(mut arr) => {
+            for item in arr.iter_mut() {
+                *item = convert_leaf_values_to_string(item.clone());
+            }
+            serde_json::Value::Array(arr)
+        }
+        serde_json::Value::Number(n) => serde_json::Value::String(n.to_string()),
+        serde_json::Value::Null => serde_json::Value::String("".to_string()),
+        serde_json::Value::Bool(value) => serde_json::Value::String(value.to_string()),
+        serde_json::Value::String(value) => serde_json::Value::String(value),
+    }
+}
+
+/// GA4 measurement protocol
+#[derive(Clone)]
+pub struct Ga4Sink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl Ga4Sink {
+    pub fn new() -> Self {
+        let measurement_id: &str = GTAG_MEASUREMENT_ID.as_ref();
+        let api_secret = env::var("GA4_API_SECRET").expect("`GA4_API_SECRET` is required!");
+
+        Self {
+            client: reqwest::Client::new(),
+            url: format!(
+                "https://www.google-analytics.com/mp/collect?measurement_id={measurement_id}&api_secret={api_secret}"
+            ),
+        }
+    }
+}
+
+impl AnalyticsSink for Ga4Sink {
+    fn kind(&self) -> SinkKind {
+        SinkKind::Ga4
+    }
+
+    /// GA4 measurement protocol accepts at most 25 events per request
+    fn max_batch_size(&self) -> usize {
+        25
+    }
+
+    /// A request carries the events of a single client and user
+    fn batch_key(&self, event: &QueuedEvent) -> String {
+        format!(
+            "{}/{}",
+            event.client_id.as_deref().unwrap_or_default(),
+            event.user_id().unwrap_or_default()
+        )
+    }
+
+    async fn send(&self, events: &[&QueuedEvent]) -> Result<(), SinkError> {
+        let first = events.first();
+        let payload = GA4Event {
+            // events queued without a client id (e.g spooled by older builds) get a fresh one
+            client_id: first
+                .and_then(|ev| ev.client_id.clone())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()),
+            user_id: first.and_then(|ev| ev.user_id()).map(str::to_string),
+            events: events
+                .iter()
+                .map(|ev| Event {
+                    name: ev.name.clone(),
+                    params: convert_leaf_values_to_string(ev.params.clone()),
+                })
+                .collect(),
+        };
+
+        let req = propagate_batch_trace(self.client.post(&self.url).json(&payload), events);
+        req.send().await?.error_for_status()?;
+
+        Ok(())
+    }
+}
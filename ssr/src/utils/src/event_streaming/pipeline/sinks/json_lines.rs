@@ -0,0 +1,74 @@
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+
+use super::{AnalyticsSink, QueuedEvent, SinkError, SinkKind};
+
+#[derive(Clone)]
+enum Output {
+    Stdout,
+    /// serializes appends from concurrent batches
+    File(Arc<Mutex<PathBuf>>),
+}
+
+/// Writes every event as a line of JSON, meant for local development and tests
+#[derive(Clone)]
+pub struct JsonLinesSink(Output);
+
+impl JsonLinesSink {
+    pub fn stdout() -> Self {
+        Self(Output::Stdout)
+    }
+
+    /// Events are appended to `path`, the file is created if missing
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self(Output::File(Arc::new(Mutex::new(path.into()))))
+    }
+}
+
+impl AnalyticsSink for JsonLinesSink {
+    fn kind(&self) -> SinkKind {
+        SinkKind::JsonLines
+    }
+
+    fn max_batch_size(&self) -> usize {
+        usize::MAX
+    }
+
+    async fn send(&self, events: &[&QueuedEvent]) -> Result<(), SinkError> {
+        let mut lines = String::new();
+        for ev in events {
+            let line = serde_json::json!({
+                "id": ev.id,
+                "timestamp_ms": ev.timestamp_ms,
+                "client_id": ev.client_id,
+                "request_id": ev.request_id,
+                "traceparent": ev.traceparent,
+                "event": ev.name,
+                "params": ev.params,
+            });
+            lines.push_str(&line.to_string());
+            lines.push('\n');
+        }
+
+        match &self.0 {
+            Output::Stdout => {
+                let mut stdout = tokio::io::stdout();
+                stdout.write_all(lines.as_bytes()).await?;
+                stdout.flush().await?;
+            }
+            Output::File(path) => {
+                let path = path.lock().await;
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&*path)
+                    .await?;
+                file.write_all(lines.as_bytes()).await?;
+                file.flush().await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use serde_json::json;
+
+use consts::{MIXPANEL_API_URL, MIXPANEL_PROJECT_TOKEN};
+
+use super::{propagate_batch_trace, AnalyticsSink, QueuedEvent, SinkError, SinkKind};
+
+/// Mixpanel ingestion API
+#[derive(Clone)]
+pub struct MixpanelSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl MixpanelSink {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            // `ip=0` stops mixpanel from geolocating our servers
+            url: format!("{MIXPANEL_API_URL}/track?ip=0"),
+        }
+    }
+}
+
+impl AnalyticsSink for MixpanelSink {
+    fn kind(&self) -> SinkKind {
+        SinkKind::Mixpanel
+    }
+
+    /// Mixpanel accepts at most 50 events per request
+    fn max_batch_size(&self) -> usize {
+        50
+    }
+
+    async fn send(&self, events: &[&QueuedEvent]) -> Result<(), SinkError> {
+        let payload: Vec<_> = events
+            .iter()
+            .map(|ev| {
+                let mut properties = ev.params.clone();
+                properties["token"] = json!(MIXPANEL_PROJECT_TOKEN);
+                // proxied mixpanel events carry their own distinct id
+                if properties["distinct_id"].is_null() {
+                    let distinct_id = ev.user_id().or(ev.client_id.as_deref()).unwrap_or("0");
+                    properties["distinct_id"] = json!(distinct_id);
+                }
+                if let Some(client_id) = &ev.client_id {
+                    properties["$device_id"] = json!(client_id);
+                }
+                properties["time"] = json!(ev.timestamp_ms);
+                // deduplicates retried deliveries
+                properties["$insert_id"] = json!(ev.id);
+                json!({
+                    "event": ev.name,
+                    "properties": properties,
+                })
+            })
+            .collect();
+
+        let req = propagate_batch_trace(self.client.post(&self.url).json(&payload), events);
+        let res = req.send().await?.error_for_status()?;
+        // mixpanel responds with `0` if the batch was rejected
+        let accepted = res.text().await?;
+        if accepted.trim() != "1" {
+            return Err(SinkError::Rejected(format!(
+                "mixpanel rejected the batch: {accepted}"
+            )));
+        }
+
+        Ok(())
+    }
+}
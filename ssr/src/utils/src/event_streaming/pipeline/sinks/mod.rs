@@ -0,0 +1,190 @@
+mod ga4;
+mod json_lines;
+mod mixpanel;
+mod warehouse;
+
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use enum_dispatch::enum_dispatch;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tonic::transport::Channel;
+
+pub use ga4::Ga4Sink;
+pub use json_lines::JsonLinesSink;
+pub use mixpanel::MixpanelSink;
+pub use warehouse::WarehouseSink;
+
+use super::{batch_trace, QueuedEvent};
+use crate::request_id::{REQUEST_ID_HEADER, TRACEPARENT_HEADER};
+
+#[derive(Debug, Error)]
+pub enum SinkError {
+    #[error("grpc: {0}")]
+    Grpc(#[from] tonic::Status),
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// Invalid `ANALYTICS_SINKS`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SinkConfigError {
+    #[error("unknown analytics sink `{0}`")]
+    Unknown(String),
+    #[error("the warehouse sink requires the off-chain agent channel")]
+    WarehouseUnavailable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SinkKind {
+    Warehouse,
+    Ga4,
+    Mixpanel,
+    JsonLines,
+}
+
+impl SinkKind {
+    /// Third party product analytics, subject to analytics consent
+    /// and skipped for warehouse only events
+    pub fn is_product_analytics(self) -> bool {
+        matches!(self, Self::Ga4 | Self::Mixpanel)
+    }
+}
+
+impl Display for SinkKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Warehouse => "warehouse",
+            Self::Ga4 => "GA4",
+            Self::Mixpanel => "mixpanel",
+            Self::JsonLines => "json-lines",
+        })
+    }
+}
+
+/// Forward the request id and trace context shared by the batch
+fn propagate_batch_trace(
+    req: reqwest::RequestBuilder,
+    events: &[&QueuedEvent],
+) -> reqwest::RequestBuilder {
+    let Some((req_id, traceparent)) = batch_trace(events) else {
+        return req;
+    };
+    let req = req.header(REQUEST_ID_HEADER, req_id);
+    match traceparent {
+        Some(traceparent) => req.header(TRACEPARENT_HEADER, traceparent),
+        None => req,
+    }
+}
+
+/// A destination for analytics events
+#[enum_dispatch]
+pub(crate) trait AnalyticsSink {
+    fn kind(&self) -> SinkKind;
+
+    /// Max events per [`AnalyticsSink::send`]
+    fn max_batch_size(&self) -> usize;
+
+    /// Events sent together share the same key
+    fn batch_key(&self, _event: &QueuedEvent) -> String {
+        String::new()
+    }
+
+    /// Deliver all of `events` or fail, failed batches are retried as a whole
+    async fn send(&self, events: &[&QueuedEvent]) -> Result<(), SinkError>;
+}
+
+#[derive(Clone)]
+#[enum_dispatch(AnalyticsSink)]
+pub enum AnalyticsSinkImpl {
+    Warehouse(WarehouseSink),
+    Ga4(Ga4Sink),
+    Mixpanel(MixpanelSink),
+    JsonLines(JsonLinesSink),
+}
+
+/// A sink as listed in `ANALYTICS_SINKS`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SinkSpec {
+    Warehouse,
+    Ga4,
+    Mixpanel,
+    Stdout,
+    /// JSON lines appended to a file
+    File(PathBuf),
+}
+
+impl FromStr for SinkSpec {
+    type Err = SinkConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warehouse" => Ok(Self::Warehouse),
+            "ga4" => Ok(Self::Ga4),
+            "mixpanel" => Ok(Self::Mixpanel),
+            "stdout" => Ok(Self::Stdout),
+            _ => match s.strip_prefix("file:") {
+                Some(path) if !path.is_empty() => Ok(Self::File(path.into())),
+                _ => Err(SinkConfigError::Unknown(s.into())),
+            },
+        }
+    }
+}
+
+impl SinkSpec {
+    /// `warehouse` is the off-chain agent channel, required by [`SinkSpec::Warehouse`]
+    pub fn build(self, warehouse: Option<&Channel>) -> Result<AnalyticsSinkImpl, SinkConfigError> {
+        Ok(match self {
+            Self::Warehouse => {
+                let channel = warehouse.ok_or(SinkConfigError::WarehouseUnavailable)?;
+                WarehouseSink::new(channel.clone()).into()
+            }
+            Self::Ga4 => Ga4Sink::new().into(),
+            Self::Mixpanel => MixpanelSink::new().into(),
+            Self::Stdout => JsonLinesSink::stdout().into(),
+            Self::File(path) => JsonLinesSink::file(path).into(),
+        })
+    }
+}
+
+/// Parse a comma separated list of sinks
+/// e.g `warehouse,ga4` or `stdout,file:./analytics-events.jsonl`
+pub fn parse_sink_specs(specs: &str) -> Result<Vec<SinkSpec>, SinkConfigError> {
+    specs
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(SinkSpec::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sink_specs() {
+        assert_eq!(
+            parse_sink_specs("warehouse, ga4,file:/tmp/events.jsonl,").unwrap(),
+            vec![
+                SinkSpec::Warehouse,
+                SinkSpec::Ga4,
+                SinkSpec::File("/tmp/events.jsonl".into())
+            ]
+        );
+        assert_eq!(
+            parse_sink_specs("stdout,bigquery"),
+            Err(SinkConfigError::Unknown("bigquery".into()))
+        );
+        assert!(parse_sink_specs("file:").is_err());
+        assert!(matches!(
+            SinkSpec::Warehouse.build(None),
+            Err(SinkConfigError::WarehouseUnavailable)
+        ));
+    }
+}
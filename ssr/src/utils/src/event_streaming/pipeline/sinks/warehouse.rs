@@ -0,0 +1,82 @@
+use std::env;
+
+use futures::future::try_join_all;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    transport::Channel,
+};
+
+use crate::event_streaming::warehouse_events::{
+    warehouse_events_client::WarehouseEventsClient, WarehouseEvent,
+};
+use crate::request_id::{REQUEST_ID_HEADER, TRACEPARENT_HEADER};
+
+use super::{AnalyticsSink, QueuedEvent, SinkError, SinkKind};
+
+/// Events per batch, each one a call in flight on the sink's channel
+const BATCH_SIZE: usize = 25;
+
+/// Streams events to the warehouse through the off-chain agent
+#[derive(Clone)]
+pub struct WarehouseSink {
+    channel: Channel,
+    token: MetadataValue<Ascii>,
+}
+
+impl WarehouseSink {
+    /// `channel` must be connected to the off-chain agent
+    pub fn new(channel: Channel) -> Self {
+        let mut grpc_auth_token = env::var("GRPC_AUTH_TOKEN").expect("GRPC_AUTH_TOKEN");
+        // removing whitespaces and new lines for proper parsing
+        grpc_auth_token.retain(|c| !c.is_whitespace());
+        let token = format!("Bearer {grpc_auth_token}")
+            .parse()
+            .expect("Invalid `GRPC_AUTH_TOKEN`");
+
+        Self { channel, token }
+    }
+}
+
+impl AnalyticsSink for WarehouseSink {
+    fn kind(&self) -> SinkKind {
+        SinkKind::Warehouse
+    }
+
+    /// `send_event` takes a single event and the warehouse proto has no batch call,
+    /// so a batch is sent as concurrent calls instead
+    /// a failed call fails the whole batch, the events already delivered are sent again on retry
+    fn max_batch_size(&self) -> usize {
+        BATCH_SIZE
+    }
+
+    async fn send(&self, events: &[&QueuedEvent]) -> Result<(), SinkError> {
+        let token = self.token.clone();
+        let client = WarehouseEventsClient::with_interceptor(
+            self.channel.clone(),
+            move |mut req: tonic::Request<()>| {
+                req.metadata_mut().insert("authorization", token.clone());
+                Ok(req)
+            },
+        );
+
+        try_join_all(events.iter().map(|event| {
+            let mut client = client.clone();
+            let mut request = tonic::Request::new(WarehouseEvent {
+                event: event.name.clone(),
+                params: event.params.to_string(),
+            });
+            if let Some(req_id) = event.request_id.as_deref().and_then(|id| id.parse().ok()) {
+                request.metadata_mut().insert(REQUEST_ID_HEADER, req_id);
+            }
+            if let Some(traceparent) = event.traceparent.as_deref().and_then(|tp| tp.parse().ok()) {
+                request
+                    .metadata_mut()
+                    .insert(TRACEPARENT_HEADER, traceparent);
+            }
+            async move { client.send_event(request).await }
+        }))
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use redb::{backends::InMemoryBackend, Database, ReadableTable, TableDefinition};
+use tokio::task::spawn_blocking;
+
+use super::QueuedEvent;
+
+/// sequence number -> json encoded [`QueuedEvent`]
+const TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("event-spool");
+
+/// Disk backed FIFO of events that could not be delivered
+/// survives restarts, events are retried by the pipeline worker
+#[derive(Clone)]
+pub struct EventSpool(Arc<Database>);
+
+impl EventSpool {
+    #[allow(clippy::result_large_err)]
+    pub fn open(path: &str) -> Result<Self, redb::Error> {
+        let db = Database::create(path)?;
+        Self::init(db)
+    }
+
+    /// Non-persistent spool, used in tests
+    #[allow(clippy::result_large_err)]
+    pub fn new_in_memory() -> Result<Self, redb::Error> {
+        let db = Database::builder().create_with_backend(InMemoryBackend::new())?;
+        Self::init(db)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn init(db: Database) -> Result<Self, redb::Error> {
+        let write_txn = db.begin_write()?;
+        {
+            write_txn.open_table(TABLE)?;
+        }
+        write_txn.commit()?;
+        Ok(Self(Arc::new(db)))
+    }
+
+    async fn blocking<F, R>(&self, f: F) -> Result<R, redb::Error>
+    where
+        F: FnOnce(&Database) -> Result<R, redb::Error> + Send + 'static,
+        R: Send + 'static,
+    {
+        let db = self.0.clone();
+        spawn_blocking(move || f(&db))
+            .await
+            .expect("spool task panicked")
+    }
+
+    /// Append events to the end of the spool
+    pub async fn push(&self, events: Vec<QueuedEvent>) -> Result<(), redb::Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            {
+                let mut table = write_txn.open_table(TABLE)?;
+                let mut seq = table.last()?.map(|(k, _)| k.value() + 1).unwrap_or(0);
+                for event in events {
+                    let raw = serde_json::to_vec(&event).expect("event must be serializable");
+                    table.insert(seq, raw.as_slice())?;
+                    seq += 1;
+                }
+            }
+            write_txn.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Oldest `limit` events along with their sequence numbers
+    /// events stay in the spool until [`Self::remove`] is called
+    pub async fn peek(&self, limit: usize) -> Result<Vec<(u64, QueuedEvent)>, redb::Error> {
+        self.blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            let mut events = Vec::new();
+            {
+                let mut table = write_txn.open_table(TABLE)?;
+                let mut corrupt = Vec::new();
+                for entry in table.iter()?.take(limit) {
+                    let (seq, raw) = entry?;
+                    match serde_json::from_slice(raw.value()) {
+                        Ok(event) => events.push((seq.value(), event)),
+                        Err(e) => {
+                            log::warn!("dropping corrupt spooled event {}: {e}", seq.value());
+                            corrupt.push(seq.value());
+                        }
+                    }
+                }
+                for seq in corrupt {
+                    table.remove(seq)?;
+                }
+            }
+            write_txn.commit()?;
+            Ok(events)
+        })
+        .await
+    }
+
+    pub async fn remove(&self, seqs: Vec<u64>) -> Result<(), redb::Error> {
+        self.blocking(move |db| {
+            let write_txn = db.begin_write()?;
+            {
+                let mut table = write_txn.open_table(TABLE)?;
+                for seq in seqs {
+                    table.remove(seq)?;
+                }
+            }
+            write_txn.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn count(&self) -> Result<u64, redb::Error> {
+        self.blocking(|db| {
+            let read_txn = db.begin_read()?;
+            let table = read_txn.open_table(TABLE)?;
+            Ok(table.len()?)
+        })
+        .await
+    }
+}
@@ -0,0 +1,364 @@
+//! Mixpanel events, sent by the client through the SSR proxy
+//!
+//! every event carries the global user properties, see [`MixpanelEvent::validate`]
+//! event and property names are the ones mixpanel already stores, e.g `NSFW_True`
+use serde::{Deserialize, Serialize};
+use yral_canisters_common::utils::vote::VoteKind;
+
+use super::{SchemaError, SCHEMA_VERSION};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelHomePageViewedProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelSignupSuccessProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub is_referral: bool,
+    pub referrer_user_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelLoginSuccessProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelSatsToBtcConvertedProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub sats_converted: f64,
+    pub updated_sats_wallet_balance: f64,
+    pub updated_token_wallet_balance: f64,
+    pub conversion_ratio: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelNsfwToggleProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub publisher_user_id: String,
+    pub video_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelVideoClickedProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub publisher_user_id: String,
+    pub like_count: u64,
+    pub view_count: u64,
+    pub is_game_enabled: bool,
+    pub video_id: String,
+    pub game_type: MixpanelPostGameType,
+    pub cta_type: MixpanelVideoClickedCTAType,
+    pub is_nsfw: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelReferAndEarnProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub refer_link: String,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixpanelPostGameType {
+    HotOrNot,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixpanelVideoClickedCTAType {
+    Like,
+    Share,
+    ReferAndEarn,
+    Report,
+    NsfwTrue,
+    NsfwFalse,
+    Mute,
+    Unmute,
+    CreatorProfile,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelVideoViewedProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub video_id: String,
+    pub publisher_user_id: String,
+    pub game_type: MixpanelPostGameType,
+    pub like_count: u64,
+    pub view_count: u64,
+    pub is_nsfw: bool,
+    pub is_game_enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelGamePlayedProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub video_id: String,
+    pub publisher_user_id: String,
+    pub game_type: MixpanelPostGameType,
+    pub stake_amount: u64,
+    pub stake_type: StakeType,
+    pub option_chosen: VoteKind,
+    pub like_count: u64,
+    pub view_count: u64,
+    pub is_game_enabled: bool,
+    pub conclusion: GameConclusion,
+    pub won_amount: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameConclusion {
+    Pending,
+    Win,
+    Loss,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeType {
+    SATs,
+    Cents,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelVideoUploadSuccessProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub video_id: String,
+    pub is_game_enabled: bool,
+    pub game_type: MixpanelPostGameType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelCentsToDolrProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub cents_converted: f64,
+    pub updated_cents_wallet_balance: f64,
+    pub conversion_ratio: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MixpanelThirdPartyWalletTransferredProps {
+    pub user_id: Option<String>,
+    pub visitor_id: Option<String>,
+    pub is_logged_in: bool,
+    pub canister_id: String,
+    pub is_nsfw_enabled: bool,
+    pub token_transferred: f64,
+    pub transferred_to: String,
+    pub token_name: String,
+    pub gas_fee: f64,
+}
+
+/// Every mixpanel event, the variant name is the event name
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", content = "params", rename_all = "snake_case")]
+pub enum MixpanelEvent {
+    HomePageViewed(MixpanelHomePageViewedProps),
+    SignupSuccess(MixpanelSignupSuccessProps),
+    LoginSuccess(MixpanelLoginSuccessProps),
+    SatsToBtcConverted(MixpanelSatsToBtcConvertedProps),
+    #[serde(rename = "NSFW_True")]
+    NsfwTrue(MixpanelNsfwToggleProps),
+    #[serde(rename = "NSFW_False")]
+    NsfwFalse(MixpanelNsfwToggleProps),
+    VideoClicked(MixpanelVideoClickedProps),
+    ReferAndEarn(MixpanelReferAndEarnProps),
+    VideoViewed(MixpanelVideoViewedProps),
+    GamePlayed(MixpanelGamePlayedProps),
+    VideoUploadSuccess(MixpanelVideoUploadSuccessProps),
+    #[serde(rename = "cents_to_DOLR")]
+    CentsToDolr(MixpanelCentsToDolrProps),
+    ThirdPartyWalletTransferred(MixpanelThirdPartyWalletTransferredProps),
+}
+
+fn ensure(cond: bool, event: &str, reason: &str) -> Result<(), SchemaError> {
+    if cond {
+        return Ok(());
+    }
+    Err(SchemaError::Invalid {
+        event: event.into(),
+        reason: reason.into(),
+    })
+}
+
+fn non_negative(amount: f64) -> bool {
+    amount.is_finite() && amount >= 0.0
+}
+
+impl MixpanelEvent {
+    /// (event name, properties)
+    pub fn into_parts(self) -> (String, serde_json::Map<String, serde_json::Value>) {
+        let serde_json::Value::Object(mut tagged) =
+            serde_json::to_value(self).expect("mixpanel events must be serializable")
+        else {
+            unreachable!("adjacently tagged enums serialize to an object")
+        };
+        let name = tagged
+            .remove("event")
+            .and_then(|name| name.as_str().map(str::to_string))
+            .expect("event name must be a string");
+        let Some(serde_json::Value::Object(properties)) = tagged.remove("params") else {
+            unreachable!("every mixpanel event has struct properties")
+        };
+        (name, properties)
+    }
+
+    /// Checks the values that the types alone can't enforce
+    /// logged in users are identified by `user_id`, anonymous ones by `visitor_id`
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        let (name, properties) = self.clone().into_parts();
+        let is_set = |key: &str| properties.get(key).is_some_and(|v| !v.is_null());
+        let is_logged_in = properties
+            .get("is_logged_in")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+        ensure(
+            is_set("user_id") == is_logged_in && is_set("visitor_id") != is_logged_in,
+            &name,
+            "exactly one of user_id (logged in) and visitor_id (anonymous) must be set",
+        )?;
+
+        match self {
+            Self::SatsToBtcConverted(p) => ensure(
+                non_negative(p.sats_converted) && p.conversion_ratio.is_finite(),
+                &name,
+                "sats_converted must not be negative",
+            ),
+            Self::CentsToDolr(p) => ensure(
+                non_negative(p.cents_converted) && p.conversion_ratio.is_finite(),
+                &name,
+                "cents_converted must not be negative",
+            ),
+            Self::ThirdPartyWalletTransferred(p) => ensure(
+                non_negative(p.token_transferred) && non_negative(p.gas_fee),
+                &name,
+                "token_transferred and gas_fee must not be negative",
+            ),
+            Self::GamePlayed(p) => ensure(
+                p.won_amount.is_none_or(f64::is_finite),
+                &name,
+                "won_amount must be finite",
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A mixpanel event as sent by the client to the server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersionedMixpanelEvent {
+    pub version: u32,
+    pub event: MixpanelEvent,
+}
+
+impl VersionedMixpanelEvent {
+    pub fn new(event: MixpanelEvent) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            event,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        if self.version != SCHEMA_VERSION {
+            return Err(SchemaError::UnsupportedVersion(self.version));
+        }
+        self.event.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn referral(user_id: Option<&str>, visitor_id: Option<&str>) -> MixpanelEvent {
+        MixpanelEvent::ReferAndEarn(MixpanelReferAndEarnProps {
+            user_id: user_id.map(str::to_string),
+            visitor_id: visitor_id.map(str::to_string),
+            is_logged_in: user_id.is_some(),
+            canister_id: "canister".into(),
+            is_nsfw_enabled: false,
+            refer_link: "https://yral.com/refer".into(),
+        })
+    }
+
+    #[test]
+    fn keeps_mixpanel_event_names() {
+        let (name, properties) = referral(Some("user"), None).into_parts();
+        assert_eq!(name, "refer_and_earn");
+        assert_eq!(properties["user_id"], "user");
+
+        let event = MixpanelEvent::CentsToDolr(MixpanelCentsToDolrProps {
+            user_id: Some("user".into()),
+            visitor_id: None,
+            is_logged_in: true,
+            canister_id: "canister".into(),
+            is_nsfw_enabled: false,
+            cents_converted: 10.0,
+            updated_cents_wallet_balance: 0.0,
+            conversion_ratio: 0.01,
+        });
+        assert!(event.validate().is_ok());
+        assert_eq!(event.into_parts().0, "cents_to_DOLR");
+    }
+
+    #[test]
+    fn rejects_mismatched_identity() {
+        assert!(referral(None, Some("visitor")).validate().is_ok());
+        assert!(referral(Some("user"), Some("visitor")).validate().is_err());
+
+        let mut event = VersionedMixpanelEvent::new(referral(None, None));
+        assert!(event.validate().is_err());
+        event.version = SCHEMA_VERSION + 1;
+        assert_eq!(
+            event.validate().unwrap_err(),
+            SchemaError::UnsupportedVersion(SCHEMA_VERSION + 1)
+        );
+    }
+}
@@ -0,0 +1,589 @@
+//! Typed analytics events
+//!
+//! Every event is a struct, serialized as the `params` of the event by every sink
+//! (warehouse, GA4, mixpanel), property names are part of the schema
+//! and keep the names the sinks already store, e.g `is_loggedIn`
+//!
+//! Renaming, retyping or removing a property requires bumping [`SCHEMA_VERSION`]
+//! adding a new event or an optional property does not
+pub mod mixpanel;
+
+use candid::Principal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use yral_canisters_common::utils::posts::PostDetails;
+
+/// Version of the event schema
+/// sent along with every event as the `schema_version` param
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Videos with a higher nsfw probability are reported as nsfw
+const NSFW_THRESHOLD: f32 = 0.5;
+
+/// Value of the properties the app doesn't track yet
+pub const NOT_AVAILABLE: &str = "NA";
+
+fn not_available() -> String {
+    NOT_AVAILABLE.into()
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SchemaError {
+    #[error("unsupported event schema version {0}, expected {expected}", expected = SCHEMA_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("invalid {event} event: {reason}")]
+    Invalid { event: String, reason: String },
+}
+
+/// The user who triggered the event
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UserProps {
+    pub user_id: Principal,
+    pub canister_id: Principal,
+}
+
+/// The video an event is about
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoProps {
+    pub video_id: String,
+    pub post_id: u64,
+    pub publisher_user_id: Principal,
+    pub publisher_canister_id: Principal,
+    pub hashtag_count: usize,
+    #[serde(rename = "is_NSFW")]
+    pub is_nsfw: bool,
+    pub nsfw_probability: f32,
+    #[serde(rename = "is_hotorNot")]
+    pub is_hot_or_not: bool,
+    pub view_count: u64,
+    pub like_count: u64,
+    /// not tracked yet, always 0
+    #[serde(default)]
+    pub share_count: u64,
+    #[serde(default = "not_available")]
+    pub video_category: String,
+    #[serde(default = "not_available")]
+    pub creator_category: String,
+    #[serde(default = "not_available")]
+    pub feed_type: String,
+}
+
+impl From<&PostDetails> for VideoProps {
+    fn from(post: &PostDetails) -> Self {
+        Self {
+            video_id: post.uid.clone(),
+            post_id: post.post_id,
+            publisher_user_id: post.poster_principal,
+            publisher_canister_id: post.canister_id,
+            hashtag_count: post.hastags.len(),
+            is_nsfw: post.nsfw_probability > NSFW_THRESHOLD,
+            nsfw_probability: post.nsfw_probability,
+            is_hot_or_not: post.is_hot_or_not(),
+            view_count: post.views,
+            like_count: post.likes,
+            share_count: 0,
+            video_category: not_available(),
+            creator_category: not_available(),
+            feed_type: not_available(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoEngagementProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub video: VideoProps,
+    #[serde(rename = "is_loggedIn")]
+    pub is_logged_in: bool,
+    pub display_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoDurationWatchedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub video: VideoProps,
+    #[serde(rename = "is_loggedIn")]
+    pub is_logged_in: bool,
+    pub display_name: Option<String>,
+    pub percentage_watched: f64,
+    /// seconds
+    pub absolute_watched: f64,
+    /// seconds
+    pub video_duration: f64,
+}
+
+/// Options picked on the upload form
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoUploadOptions {
+    pub hashtag_count: usize,
+    #[serde(rename = "is_NSFW")]
+    pub is_nsfw: bool,
+    #[serde(rename = "is_hotorNot")]
+    pub is_hot_or_not: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoUploadProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    pub display_name: Option<String>,
+    #[serde(default = "not_available")]
+    pub creator_category: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoUploadClickedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub options: VideoUploadOptions,
+    pub display_name: Option<String>,
+    #[serde(default = "not_available")]
+    pub creator_category: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoUploadFailedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub options: VideoUploadOptions,
+    pub display_name: Option<String>,
+    #[serde(default = "not_available")]
+    pub creator_category: String,
+    pub fail_reason: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VideoUploadSucceededProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub options: VideoUploadOptions,
+    pub display_name: Option<String>,
+    #[serde(default = "not_available")]
+    pub creator_category: String,
+    /// the uploader
+    pub publisher_user_id: Principal,
+    pub video_id: String,
+    pub post_id: u64,
+    pub is_filter_used: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReferProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(rename = "is_loggedIn")]
+    pub is_logged_in: bool,
+    pub display_name: Option<String>,
+    /// page the user came from
+    pub refer_location: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginMethod {
+    LocalStorage,
+    Google,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LoginSuccessfulProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    pub login_method: LoginMethod,
+    pub is_new_user: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LoginMethodSelectedProps {
+    pub login_method: LoginMethod,
+    pub attempt_count: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LoginJoinOverlayViewedProps {
+    #[serde(rename = "user_id_viewer")]
+    pub user_id: Principal,
+    pub previous_event: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LoginCtaProps {
+    pub previous_event: String,
+    pub cta_location: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LogoutProps {
+    #[serde(rename = "user_id_viewer")]
+    pub user_id: Principal,
+    pub canister_id: Principal,
+    pub display_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ErrorProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    pub description: String,
+    pub previous_event: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProfileViewVideoProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(rename = "is_loggedIn")]
+    pub is_logged_in: bool,
+    pub display_name: Option<String>,
+    pub video_id: String,
+    pub publisher_user_id: Principal,
+    pub profile_feed: String,
+}
+
+/// Token details entered by the creator
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenDetailsProps {
+    pub token_name: Option<String>,
+    pub token_symbol: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenCreationStartedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub token: TokenDetailsProps,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenCreationCompletedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub token: TokenDetailsProps,
+    pub description: Option<String>,
+    pub logo: Option<String>,
+    pub link: String,
+    pub is_nsfw: bool,
+    pub nsfw_ec: String,
+    pub nsfw_gore: String,
+    pub csam_detected: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenCreationFailedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(flatten)]
+    pub token: TokenDetailsProps,
+    pub description: Option<String>,
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokensClaimedFromNeuronProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    pub amount: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokensTransferredProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    pub amount: String,
+    pub to: Principal,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PageVisitProps {
+    pub user_id: Principal,
+    #[serde(rename = "is_loggedIn")]
+    pub is_logged_in: bool,
+    pub pathname: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CentsAddedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(rename = "is_loggedin")]
+    pub is_logged_in: bool,
+    pub amount_added: u64,
+    pub payment_source: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WithdrawnProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(rename = "is_loggedin")]
+    pub is_logged_in: bool,
+    pub amount_withdrawn: f64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PumpOrDump {
+    Pump,
+    Dump,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenPumpedDumpedProps {
+    #[serde(flatten)]
+    pub user: UserProps,
+    #[serde(rename = "is_loggedin")]
+    pub is_logged_in: bool,
+    pub token_name: String,
+    pub token_root: Principal,
+    pub direction: PumpOrDump,
+    pub count: u32,
+}
+
+/// Every analytics event, the variant name is the event name
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", content = "params", rename_all = "snake_case")]
+pub enum AnalyticsEvent {
+    VideoViewed(VideoEngagementProps),
+    /// warehouse only
+    VideoDurationWatched(VideoDurationWatchedProps),
+    LikeVideo(VideoEngagementProps),
+    ShareVideo(VideoEngagementProps),
+    VideoUploadInitiated(VideoUploadProps),
+    VideoUploadUploadButtonClicked(VideoUploadClickedProps),
+    VideoUploadVideoSelected(VideoUploadProps),
+    VideoUploadUnsuccessful(VideoUploadFailedProps),
+    VideoUploadSuccessful(VideoUploadSucceededProps),
+    Refer(ReferProps),
+    ReferShareLink(ReferProps),
+    LoginSuccessful(LoginSuccessfulProps),
+    LoginMethodSelected(LoginMethodSelectedProps),
+    LoginJoinOverlayViewed(LoginJoinOverlayViewedProps),
+    LoginCta(LoginCtaProps),
+    LogoutClicked(LogoutProps),
+    LogoutConfirmation(LogoutProps),
+    ErrorEvent(ErrorProps),
+    ProfileViewVideo(ProfileViewVideoProps),
+    TokenCreationStarted(TokenCreationStartedProps),
+    TokenCreationCompleted(TokenCreationCompletedProps),
+    TokenCreationFailed(TokenCreationFailedProps),
+    TokensClaimedFromNeuron(TokensClaimedFromNeuronProps),
+    TokensTransferred(TokensTransferredProps),
+    #[serde(rename = "yral_page_visit")]
+    PageVisit(PageVisitProps),
+    CentsAdded(CentsAddedProps),
+    CentsWithdrawn(WithdrawnProps),
+    SatsWithdrawn(WithdrawnProps),
+    TokenPumpedDumped(TokenPumpedDumpedProps),
+}
+
+fn ensure(cond: bool, event: &AnalyticsEvent, reason: &str) -> Result<(), SchemaError> {
+    if cond {
+        return Ok(());
+    }
+    Err(SchemaError::Invalid {
+        event: event.name(),
+        reason: reason.into(),
+    })
+}
+
+impl AnalyticsEvent {
+    /// Event name as seen by the sinks
+    pub fn name(&self) -> String {
+        self.clone().into_parts().0
+    }
+
+    /// (event name, params)
+    pub fn into_parts(self) -> (String, serde_json::Value) {
+        let serde_json::Value::Object(mut tagged) =
+            serde_json::to_value(self).expect("analytics events must be serializable")
+        else {
+            unreachable!("adjacently tagged enums serialize to an object")
+        };
+        let name = tagged
+            .remove("event")
+            .and_then(|name| name.as_str().map(str::to_string))
+            .expect("event name must be a string");
+        let params = tagged.remove("params").unwrap_or_default();
+        (name, params)
+    }
+
+    /// Too noisy for product analytics, only kept in the warehouse
+    pub fn warehouse_only(&self) -> bool {
+        matches!(self, Self::VideoDurationWatched(_))
+    }
+
+    /// Checks the values that the types alone can't enforce
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        match self {
+            Self::VideoDurationWatched(p) => {
+                ensure(
+                    p.video_duration.is_finite() && p.video_duration > 0.0,
+                    self,
+                    "video_duration must be positive",
+                )?;
+                ensure(
+                    (0.0..=p.video_duration).contains(&p.absolute_watched),
+                    self,
+                    "absolute_watched must be within the video duration",
+                )?;
+                ensure(
+                    (0.0..=100.0).contains(&p.percentage_watched),
+                    self,
+                    "percentage_watched must be within 0 and 100",
+                )
+            }
+            Self::VideoViewed(p) | Self::LikeVideo(p) | Self::ShareVideo(p) => ensure(
+                (0.0..=1.0).contains(&p.video.nsfw_probability),
+                self,
+                "nsfw_probability must be within 0 and 1",
+            ),
+            Self::LoginMethodSelected(p) => {
+                ensure(p.attempt_count > 0, self, "attempt_count must be positive")
+            }
+            Self::CentsWithdrawn(p) | Self::SatsWithdrawn(p) => ensure(
+                p.amount_withdrawn.is_finite() && p.amount_withdrawn >= 0.0,
+                self,
+                "amount_withdrawn must not be negative",
+            ),
+            Self::TokenPumpedDumped(p) => ensure(p.count > 0, self, "count must be positive"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An event as sent by the client to the server
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VersionedEvent {
+    pub version: u32,
+    /// url of the page the event was triggered on
+    pub page_location: Option<String>,
+    pub event: AnalyticsEvent,
+}
+
+impl VersionedEvent {
+    pub fn new(event: AnalyticsEvent, page_location: Option<String>) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            page_location,
+            event,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        if self.version != SCHEMA_VERSION {
+            return Err(SchemaError::UnsupportedVersion(self.version));
+        }
+        self.event.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user() -> UserProps {
+        UserProps {
+            user_id: Principal::anonymous(),
+            canister_id: Principal::management_canister(),
+        }
+    }
+
+    #[test]
+    fn event_splits_into_name_and_flat_params() {
+        let event = AnalyticsEvent::PageVisit(PageVisitProps {
+            user_id: Principal::anonymous(),
+            is_logged_in: false,
+            pathname: "/wallet".into(),
+        });
+
+        let (name, params) = event.into_parts();
+
+        assert_eq!(name, "yral_page_visit");
+        assert_eq!(params["pathname"], "/wallet");
+        assert_eq!(params["user_id"], Principal::anonymous().to_text());
+    }
+
+    #[test]
+    fn flattened_props_round_trip() {
+        let event = AnalyticsEvent::TokensClaimedFromNeuron(TokensClaimedFromNeuronProps {
+            user: user(),
+            amount: 10,
+        });
+
+        let raw = serde_json::to_string(&VersionedEvent::new(event.clone(), None)).unwrap();
+        let parsed: VersionedEvent = serde_json::from_str(&raw).unwrap();
+
+        assert_eq!(parsed.event, event);
+        assert_eq!(
+            event.into_parts().1["canister_id"],
+            user().canister_id.to_text()
+        );
+    }
+
+    #[test]
+    fn keeps_stored_property_names() {
+        let (_, params) = AnalyticsEvent::LogoutClicked(LogoutProps {
+            user_id: Principal::anonymous(),
+            canister_id: Principal::management_canister(),
+            display_name: None,
+        })
+        .into_parts();
+        assert_eq!(params["user_id_viewer"], Principal::anonymous().to_text());
+
+        let (_, params) = AnalyticsEvent::CentsWithdrawn(WithdrawnProps {
+            user: user(),
+            is_logged_in: true,
+            amount_withdrawn: 1.0,
+        })
+        .into_parts();
+        assert_eq!(params["is_loggedin"], true);
+
+        // sent by clients predating the placeholder properties
+        let event: AnalyticsEvent = serde_json::from_value(serde_json::json!({
+            "event": "video_upload_initiated",
+            "params": {
+                "user_id": Principal::anonymous(),
+                "canister_id": Principal::management_canister(),
+                "display_name": null,
+            },
+        }))
+        .unwrap();
+        assert_eq!(event.into_parts().1["creator_category"], NOT_AVAILABLE);
+    }
+
+    #[test]
+    fn rejects_invalid_events() {
+        let event = AnalyticsEvent::CentsWithdrawn(WithdrawnProps {
+            user: user(),
+            is_logged_in: true,
+            amount_withdrawn: -1.0,
+        });
+        assert!(matches!(
+            VersionedEvent::new(event, None).validate(),
+            Err(SchemaError::Invalid { .. })
+        ));
+
+        let mut versioned = VersionedEvent::new(
+            AnalyticsEvent::LoginCta(LoginCtaProps {
+                previous_event: "".into(),
+                cta_location: "menu".into(),
+            }),
+            None,
+        );
+        versioned.version = SCHEMA_VERSION + 1;
+        assert_eq!(
+            versioned.validate(),
+            Err(SchemaError::UnsupportedVersion(SCHEMA_VERSION + 1))
+        );
+    }
+}
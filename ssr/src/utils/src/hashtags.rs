@@ -0,0 +1,103 @@
+//! Hashtag parsing shared by uploads, descriptions and the hashtag index
+
+/// Longest hashtag accepted, longer ones are ignored
+const MAX_HASHTAG_LEN: usize = 50;
+
+fn is_hashtag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Canonical form of a hashtag, without the `#` and lowercased
+/// `None` if the hashtag contains nothing usable
+pub fn normalize_hashtag(hashtag: &str) -> Option<String> {
+    let hashtag: String = hashtag
+        .trim()
+        .trim_start_matches('#')
+        .chars()
+        .filter(|c| is_hashtag_char(*c))
+        .flat_map(char::to_lowercase)
+        .collect();
+    if hashtag.is_empty() || hashtag.chars().count() > MAX_HASHTAG_LEN {
+        return None;
+    }
+    Some(hashtag)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptionPart<'a> {
+    Text(&'a str),
+    /// a hashtag including the `#`
+    Hashtag(&'a str),
+}
+
+/// Split a post description into text and `#hashtag`s
+/// a `#` only starts a hashtag at the start of the description or after whitespace
+pub fn split_hashtags(description: &str) -> Vec<DescriptionPart<'_>> {
+    let mut parts = vec![];
+    let mut text_start = 0;
+    let mut chars = description.char_indices().peekable();
+    let mut prev = None::<char>;
+
+    while let Some((idx, c)) = chars.next() {
+        let starts_hashtag = c == '#'
+            && prev.is_none_or(char::is_whitespace)
+            && chars.peek().is_some_and(|(_, next)| is_hashtag_char(*next));
+        prev = Some(c);
+        if !starts_hashtag {
+            continue;
+        }
+
+        let mut end = description.len();
+        while let Some((next_idx, next)) = chars.peek().copied() {
+            if !is_hashtag_char(next) {
+                end = next_idx;
+                break;
+            }
+            prev = Some(next);
+            chars.next();
+        }
+
+        if text_start < idx {
+            parts.push(DescriptionPart::Text(&description[text_start..idx]));
+        }
+        parts.push(DescriptionPart::Hashtag(&description[idx..end]));
+        text_start = end;
+    }
+
+    if text_start < description.len() {
+        parts.push(DescriptionPart::Text(&description[text_start..]));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_hashtag, split_hashtags, DescriptionPart};
+
+    #[test]
+    fn normalizes_hashtags() {
+        assert_eq!(
+            normalize_hashtag(" #Funny_Cats "),
+            Some("funny_cats".into())
+        );
+        assert_eq!(normalize_hashtag("#"), None);
+        assert_eq!(normalize_hashtag("a-b"), Some("ab".into()));
+    }
+
+    #[test]
+    fn splits_descriptions() {
+        assert_eq!(
+            split_hashtags("#cats are great, mail@me #dogs_too! ##"),
+            vec![
+                DescriptionPart::Hashtag("#cats"),
+                DescriptionPart::Text(" are great, mail@me "),
+                DescriptionPart::Hashtag("#dogs_too"),
+                DescriptionPart::Text("! ##"),
+            ]
+        );
+        assert_eq!(
+            split_hashtags("no tags"),
+            vec![DescriptionPart::Text("no tags")]
+        );
+    }
+}
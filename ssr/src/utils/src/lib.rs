@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod ab_testing;
 pub mod event_streaming;
+pub mod hashtags;
 pub mod host;
 pub mod icon;
 pub mod mixpanel;
@@ -13,11 +14,28 @@ pub mod profile;
 #[cfg(feature = "qstash")]
 pub mod qstash;
 pub mod report;
+#[cfg(feature = "ssr")]
+pub mod request_id;
+/// Request ids only exist on the server, outgoing requests of the client are left as is
+#[cfg(not(feature = "ssr"))]
+pub mod request_id {
+    pub trait PropagateRequestId {
+        fn propagate_request_id(self) -> Self;
+    }
+
+    impl PropagateRequestId for reqwest::RequestBuilder {
+        fn propagate_request_id(self) -> Self {
+            self
+        }
+    }
+}
 pub mod route;
+pub mod search;
 pub mod time;
 pub mod token;
 pub mod types;
 pub mod user;
+pub mod video_quality;
 pub mod web;
 /// Wrapper for PartialEq that always returns false
 /// this is currently only used for resources
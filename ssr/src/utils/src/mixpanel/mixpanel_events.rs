@@ -1,19 +1,18 @@
 use codee::string::FromToStringCodec;
 use consts::NSFW_TOGGLE_STORE;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
 use leptos_use::storage::use_local_storage;
 use serde::Serialize;
-use serde_wasm_bindgen::to_value;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
-use yral_canisters_common::utils::vote::VoteKind;
 use yral_canisters_common::Canisters;
 
+use super::proxy::track_mixpanel_ssr;
+pub use crate::event_streaming::schema::mixpanel::*;
+
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = mixpanel, catch)]
-    fn track(event_name: &str, properties: JsValue) -> Result<(), JsValue>;
-
     /// mixpanel.identify(user_id)
     #[wasm_bindgen(js_namespace = mixpanel, catch)]
     fn identify(user_id: &str) -> Result<(), JsValue>;
@@ -24,13 +23,19 @@ pub fn identify_user(user_id: &str) {
     let _ = identify(user_id);
 }
 
-/// Generic helper: serializes `props` and calls Mixpanel.track
-pub fn track_event<T>(event_name: &str, props: T)
-where
-    T: Serialize,
-{
-    let js_props = to_value(&props).expect("failed to serialize Mixpanel props");
-    let _ = track(event_name, js_props);
+/// Validate and send the event through [`track_mixpanel_ssr`]
+pub fn track_event(event: MixpanelEvent) {
+    let event = VersionedMixpanelEvent::new(event);
+    if let Err(e) = event.validate() {
+        log::error!("not sending invalid mixpanel event: {e}");
+        return;
+    }
+
+    spawn_local(async move {
+        if let Err(e) = track_mixpanel_ssr(event).await {
+            log::warn!("failed to send mixpanel event: {e}");
+        }
+    });
 }
 
 /// Global properties for Mixpanel events
@@ -68,207 +73,6 @@ impl MixpanelGlobalProps {
     }
 }
 
-#[derive(Serialize)]
-pub struct MixpanelHomePageViewedProps {
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelSignupSuccessProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub is_referral: bool,
-    pub referrer_user_id: Option<String>,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelLoginSuccessProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelSatsToBtcConvertedProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub sats_converted: f64,
-    pub updated_sats_wallet_balance: f64,
-    pub updated_token_wallet_balance: f64,
-    pub conversion_ratio: f64,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelNsfwToggleProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub publisher_user_id: String,
-    pub video_id: String,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelVideoClickedProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub publisher_user_id: String,
-    pub like_count: u64,
-    pub view_count: u64,
-    pub is_game_enabled: bool,
-    pub video_id: String,
-    pub game_type: MixpanelPostGameType,
-    pub cta_type: MixpanelVideoClickedCTAType,
-    pub is_nsfw: bool,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelReferAndEarnProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub refer_link: String,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum MixpanelPostGameType {
-    HotOrNot,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum MixpanelVideoClickedCTAType {
-    Like,
-    Share,
-    ReferAndEarn,
-    Report,
-    NsfwTrue,
-    NsfwFalse,
-    Mute,
-    Unmute,
-    CreatorProfile,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelVideoViewedProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub video_id: String,
-    pub publisher_user_id: String,
-    pub game_type: MixpanelPostGameType,
-    pub like_count: u64,
-    pub view_count: u64,
-    pub is_nsfw: bool,
-    pub is_game_enabled: bool,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelGamePlayedProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub video_id: String,
-    pub publisher_user_id: String,
-    pub game_type: MixpanelPostGameType,
-    pub stake_amount: u64,
-    pub stake_type: StakeType,
-    pub option_chosen: VoteKind,
-    pub like_count: u64,
-    pub view_count: u64,
-    pub is_game_enabled: bool,
-    pub conclusion: GameConclusion,
-    pub won_amount: Option<f64>,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum GameConclusion {
-    Pending,
-    Win,
-    Loss,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum StakeType {
-    SATs,
-    Cents,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelVideoUploadSuccessProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub video_id: String,
-    // pub publisher_user_id: String,
-    pub is_game_enabled: bool,
-    pub game_type: MixpanelPostGameType,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelCentsToDolrProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub cents_converted: f64,
-    pub updated_cents_wallet_balance: f64,
-    pub conversion_ratio: f64,
-}
-
-#[derive(Serialize)]
-pub struct MixpanelThirdPartyWalletTransferredProps {
-    // #[serde(flatten)]
-    pub user_id: Option<String>,
-    pub visitor_id: Option<String>,
-    pub is_logged_in: bool,
-    pub canister_id: String,
-    pub is_nsfw_enabled: bool,
-    pub token_transferred: f64,
-    // pub updated_token_wallet_balance: f64,
-    pub transferred_to: String,
-    pub token_name: String,
-    pub gas_fee: f64,
-}
-
 pub struct MixPanelEvent;
 impl MixPanelEvent {
     /// Call once you know the logged-in user's ID
@@ -276,54 +80,54 @@ impl MixPanelEvent {
         let _ = identify(user_id);
     }
     pub fn track_home_page_viewed(p: MixpanelHomePageViewedProps) {
-        track_event("home_page_viewed", p);
+        track_event(MixpanelEvent::HomePageViewed(p));
     }
 
     pub fn track_signup_success(p: MixpanelSignupSuccessProps) {
-        track_event("signup_success", p);
+        track_event(MixpanelEvent::SignupSuccess(p));
     }
 
     pub fn track_login_success(p: MixpanelLoginSuccessProps) {
-        track_event("login_success", p);
+        track_event(MixpanelEvent::LoginSuccess(p));
     }
 
     pub fn track_sats_to_btc_converted(p: MixpanelSatsToBtcConvertedProps) {
-        track_event("sats_to_btc_converted", p);
+        track_event(MixpanelEvent::SatsToBtcConverted(p));
     }
 
     pub fn track_nsfw_true(p: MixpanelNsfwToggleProps) {
-        track_event("NSFW_True", p);
+        track_event(MixpanelEvent::NsfwTrue(p));
     }
 
     pub fn track_nsfw_false(p: MixpanelNsfwToggleProps) {
-        track_event("NSFW_False", p);
+        track_event(MixpanelEvent::NsfwFalse(p));
     }
 
     pub fn track_video_clicked(p: MixpanelVideoClickedProps) {
-        track_event("video_clicked", p);
+        track_event(MixpanelEvent::VideoClicked(p));
     }
 
     pub fn track_refer_and_earn(p: MixpanelReferAndEarnProps) {
-        track_event("refer_and_earn", p);
+        track_event(MixpanelEvent::ReferAndEarn(p));
     }
 
     pub fn track_video_viewed(p: MixpanelVideoViewedProps) {
-        track_event("video_viewed", p);
+        track_event(MixpanelEvent::VideoViewed(p));
     }
 
     pub fn track_game_played(p: MixpanelGamePlayedProps) {
-        track_event("game_played", p);
+        track_event(MixpanelEvent::GamePlayed(p));
     }
 
     pub fn track_video_upload_success(p: MixpanelVideoUploadSuccessProps) {
-        track_event("video_upload_success", p);
+        track_event(MixpanelEvent::VideoUploadSuccess(p));
     }
 
     pub fn track_cents_to_dolr(p: MixpanelCentsToDolrProps) {
-        track_event("cents_to_DOLR", p);
+        track_event(MixpanelEvent::CentsToDolr(p));
     }
 
     pub fn track_third_party_wallet_transferred(p: MixpanelThirdPartyWalletTransferredProps) {
-        track_event("third_party_wallet_transferred", p);
+        track_event(MixpanelEvent::ThirdPartyWalletTransferred(p));
     }
 }
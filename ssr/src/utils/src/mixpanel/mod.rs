@@ -1 +1,2 @@
 pub mod mixpanel_events;
+pub mod proxy;
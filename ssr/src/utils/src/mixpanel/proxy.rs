@@ -0,0 +1,155 @@
+//! Mixpanel ingestion proxied through the SSR server
+//! events reach mixpanel regardless of ad blockers, and only with an anonymized IP
+use leptos::{prelude::*, server_fn::codec::Json};
+
+use crate::event_streaming::schema::mixpanel::VersionedMixpanelEvent;
+
+/// Validate a typed mixpanel event and queue it on the analytics pipeline
+#[server(endpoint = "mixpanel_track", input = Json)]
+pub async fn track_mixpanel_ssr(event: VersionedMixpanelEvent) -> Result<(), ServerFnError> {
+    event.validate().map_err(|e| {
+        log::warn!("Rejected mixpanel event: {e}");
+        ServerFnError::new(e.to_string())
+    })?;
+    let (event, properties) = event.event.into_parts();
+    server_impl::track(event, properties).await
+}
+
+#[cfg(feature = "ssr")]
+pub use server_impl::enqueue_identity_merge;
+
+#[cfg(feature = "ssr")]
+mod server_impl {
+    use std::net::IpAddr;
+
+    use auth::server_impl::extract_principal_from_cookie;
+    use axum::http::{request::Parts, HeaderMap};
+    use axum_extra::extract::{cookie::Key, SignedCookieJar};
+    use candid::Principal;
+    use leptos::prelude::*;
+    use leptos_axum::extract_with_state;
+    use serde_json::{json, Map, Value};
+
+    use crate::event_streaming::{
+        client_id::ClientId,
+        consent::{ConsentCategory, ConsentState},
+        pipeline::{sinks::SinkKind, EventPipeline},
+    };
+
+    /// Properties set by the server, never taken from the client
+    const RESERVED_PROPERTIES: [&str; 6] =
+        ["token", "ip", "$ip", "time", "$insert_id", "distinct_id"];
+
+    /// The client IP as seen by our load balancer
+    fn client_ip(headers: &HeaderMap) -> Option<IpAddr> {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        header("fly-client-ip")
+            .or_else(|| header("x-forwarded-for").and_then(|v| v.split(',').next()))
+            .and_then(|ip| ip.trim().parse().ok())
+    }
+
+    /// Zero the host part of the address, enough for country / city level geolocation
+    /// the last octet of IPv4 and the last 80 bits of IPv6 addresses
+    pub(super) fn anonymize_ip(ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V4(ip) => {
+                let [a, b, c, _] = ip.octets();
+                IpAddr::from([a, b, c, 0])
+            }
+            IpAddr::V6(ip) => {
+                let mut segments = ip.segments();
+                segments[3..].fill(0);
+                IpAddr::from(segments)
+            }
+        }
+    }
+
+    /// Mixpanel events are only accepted with analytics consent
+    fn enqueue(name: String, properties: Map<String, Value>) {
+        let consent = use_context::<ConsentState>().unwrap_or_default();
+        if !consent.is_granted(ConsentCategory::Analytics) {
+            return;
+        }
+        let Some(queue) = use_context::<EventPipeline>() else {
+            log::warn!("analytics pipeline not available, dropping mixpanel event {name}");
+            return;
+        };
+        let client_id = use_context::<ClientId>().map(|client_id| client_id.id);
+
+        queue.enqueue(name, Value::Object(properties), client_id, |sink| {
+            matches!(sink, SinkKind::Mixpanel | SinkKind::JsonLines)
+        });
+    }
+
+    /// Principal of the request's identity cookie
+    async fn session_principal() -> Result<Option<Principal>, ServerFnError> {
+        let key: Key = expect_context();
+        let jar: SignedCookieJar = extract_with_state(&key).await?;
+        extract_principal_from_cookie(&jar)
+    }
+
+    /// The user is identified by the session, `distinct_id` and the `user_id` / `visitor_id`
+    /// properties sent by the client are replaced by the session's principal
+    pub async fn track(
+        event: String,
+        mut properties: Map<String, Value>,
+    ) -> Result<(), ServerFnError> {
+        for key in RESERVED_PROPERTIES {
+            properties.remove(key);
+        }
+
+        let principal = session_principal()
+            .await?
+            .ok_or_else(|| ServerFnError::new("No identity to track the event for"))?
+            .to_text();
+        let is_logged_in = properties
+            .get("is_logged_in")
+            .and_then(Value::as_bool)
+            .unwrap_or_default();
+        let (identified, unset) = if is_logged_in {
+            ("user_id", "visitor_id")
+        } else {
+            ("visitor_id", "user_id")
+        };
+        properties.insert(identified.into(), json!(principal));
+        properties.insert(unset.into(), Value::Null);
+        properties.insert("distinct_id".into(), json!(principal));
+
+        let ip = use_context::<Parts>()
+            .and_then(|parts| client_ip(&parts.headers))
+            .map(anonymize_ip);
+        if let Some(ip) = ip {
+            properties.insert("ip".into(), json!(ip.to_string()));
+        }
+
+        enqueue(event, properties);
+        Ok(())
+    }
+
+    /// Link the history of `anon_principal` to `user_principal`
+    /// must only be called once `user_principal` is authenticated
+    pub fn enqueue_identity_merge(anon_principal: Principal, user_principal: Principal) {
+        let mut properties = Map::new();
+        properties.insert("distinct_id".into(), json!(user_principal.to_text()));
+        properties.insert("$identified_id".into(), json!(user_principal.to_text()));
+        properties.insert("$anon_id".into(), json!(anon_principal.to_text()));
+
+        enqueue("$identify".into(), properties);
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::server_impl::anonymize_ip;
+
+    #[test]
+    fn anonymizes_ips() {
+        let ip: IpAddr = "203.0.113.77".parse().unwrap();
+        assert_eq!(anonymize_ip(ip).to_string(), "203.0.113.0");
+
+        let ip: IpAddr = "2001:db8:85a3:8d3:1319:8a2e:370:7348".parse().unwrap();
+        assert_eq!(anonymize_ip(ip).to_string(), "2001:db8:85a3::");
+    }
+}
@@ -0,0 +1,146 @@
+//! Engagement signals reported to the ML feed, so recommendations use how posts were watched
+//! and explicit negative feedback
+use candid::Principal;
+use consts::ML_FEED_URL;
+use serde::{Deserialize, Serialize};
+
+use crate::request_id::PropagateRequestId;
+
+/// How the user consumed a single post
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PostInteraction {
+    pub canister_id: Principal,
+    pub post_id: u64,
+    pub video_id: String,
+    /// furthest point reached in the first play, 0-100
+    pub percent_watched: u8,
+    /// times the video restarted after reaching the end
+    pub loops: u32,
+    /// playback position (in seconds) when the user scrolled away without finishing the video
+    pub skipped_at_secs: Option<f64>,
+    pub muted: bool,
+    pub liked: bool,
+    pub shared: bool,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Serialize)]
+struct InteractionsRequest<'a> {
+    canister_id: Principal,
+    interactions: &'a [PostInteraction],
+}
+
+/// Report a batch of interactions of the user with `canister_id`
+pub async fn send_interactions(
+    canister_id: Principal,
+    interactions: &[PostInteraction],
+) -> Result<(), anyhow::Error> {
+    let client = reqwest::Client::new();
+    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/interactions").unwrap();
+
+    let req = InteractionsRequest {
+        canister_id,
+        interactions,
+    };
+    let response = client
+        .post(ml_feed_url)
+        .json(&req)
+        .propagate_request_id()
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Error sending feed interactions: {:?}",
+            response.text().await?
+        ));
+    }
+
+    Ok(())
+}
+
+/// Report a batch of interactions while the page is being hidden or closed
+/// sent as a `keepalive` fetch, so the browser completes it after the page is gone
+/// the response is never read
+pub fn send_interactions_keepalive(
+    canister_id: Principal,
+    interactions: &[PostInteraction],
+) -> Result<(), anyhow::Error> {
+    #[cfg(not(feature = "hydrate"))]
+    {
+        _ = (canister_id, interactions);
+        Ok(())
+    }
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsValue;
+        use web_sys::{Headers, RequestInit};
+
+        let js_err = |e: JsValue| anyhow::anyhow!("{e:?}");
+        let ml_feed_url = ML_FEED_URL.join("api/v1/feed/interactions").unwrap();
+        let body = serde_json::to_string(&InteractionsRequest {
+            canister_id,
+            interactions,
+        })?;
+
+        let headers = Headers::new().map_err(js_err)?;
+        headers
+            .set("content-type", "application/json")
+            .map_err(js_err)?;
+        let init = RequestInit::new();
+        init.set_method("POST");
+        init.set_keepalive(true);
+        init.set_headers(&headers);
+        init.set_body(&JsValue::from_str(&body));
+
+        let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("window not available"))?;
+        _ = window.fetch_with_str_and_init(ml_feed_url.as_str(), &init);
+        Ok(())
+    }
+}
+
+/// "show me less of this"
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NegativeFeedback {
+    NotInterested {
+        canister_id: Principal,
+        post_id: u64,
+    },
+    HideCreator {
+        creator_canister: Principal,
+    },
+}
+
+#[derive(Serialize)]
+struct FeedbackRequest<'a> {
+    canister_id: Principal,
+    feedback: &'a NegativeFeedback,
+}
+
+/// Report negative feedback of the user with `canister_id`
+pub async fn send_negative_feedback(
+    canister_id: Principal,
+    feedback: &NegativeFeedback,
+) -> Result<(), anyhow::Error> {
+    let client = reqwest::Client::new();
+    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/feedback").unwrap();
+
+    let req = FeedbackRequest {
+        canister_id,
+        feedback,
+    };
+    let response = client
+        .post(ml_feed_url)
+        .json(&req)
+        .propagate_request_id()
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Error sending feed feedback: {:?}",
+            response.text().await?
+        ));
+    }
+
+    Ok(())
+}
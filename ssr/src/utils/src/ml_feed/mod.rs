@@ -1,158 +1,73 @@
 use candid::Principal;
 use consts::ML_FEED_URL;
-use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
 use yral_canisters_common::utils::posts::PostDetails;
 use yral_types::post::FeedRequest;
 use yral_types::post::FeedResponse;
 use yral_types::post::PostItem;
 
-// New v2 REST APIs
+use crate::request_id::PropagateRequestId;
 
-pub async fn get_ml_feed_coldstart_clean(
-    canister_id: Principal,
-    num_results: u32,
-    filter_results: Vec<PostDetails>,
-) -> Result<Vec<PostItem>, anyhow::Error> {
-    let client = reqwest::Client::new();
-    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/coldstart/clean").unwrap();
+pub mod interactions;
+pub mod seen_set;
 
-    let req = FeedRequest {
-        canister_id,
-        filter_results: post_details_to_post_item(filter_results),
-        num_results,
-    };
-
-    let response = client.post(ml_feed_url).json(&req).send().await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(format!(
-            "Error fetching ML feed: {:?}",
-            response.text().await?
-        )));
-    }
-    let response = response.json::<FeedResponse>().await?;
+// New v2 REST APIs
 
-    Ok(response.posts)
+/// Content filter applied by the ML feed server
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FeedContent {
+    Clean,
+    Nsfw,
+    Mixed,
 }
 
-pub async fn get_ml_feed_coldstart_nsfw(
-    canister_id: Principal,
-    num_results: u32,
-    filter_results: Vec<PostDetails>,
-) -> Result<Vec<PostItem>, anyhow::Error> {
-    let client = reqwest::Client::new();
-    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/coldstart/nsfw").unwrap();
-
-    let req = FeedRequest {
-        canister_id,
-        filter_results: post_details_to_post_item(filter_results),
-        num_results,
-    };
-
-    let response = client.post(ml_feed_url).json(&req).send().await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(format!(
-            "Error fetching ML feed: {:?}",
-            response.text().await?
-        )));
+impl FeedContent {
+    pub fn from_nsfw_allowed(allow_nsfw: bool) -> Self {
+        if allow_nsfw {
+            Self::Nsfw
+        } else {
+            Self::Clean
+        }
     }
-    let response = response.json::<FeedResponse>().await?;
-
-    Ok(response.posts)
-}
-
-pub async fn get_ml_feed_coldstart_mixed(
-    canister_id: Principal,
-    num_results: u32,
-    filter_results: Vec<PostDetails>,
-) -> Result<Vec<PostItem>, anyhow::Error> {
-    let client = reqwest::Client::new();
-    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/coldstart/mixed").unwrap();
 
-    let req = FeedRequest {
-        canister_id,
-        filter_results: post_details_to_post_item(filter_results),
-        num_results,
-    };
-
-    let response = client.post(ml_feed_url).json(&req).send().await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(format!(
-            "Error fetching ML feed: {:?}",
-            response.text().await?
-        )));
-    }
-    let response = response.json::<FeedResponse>().await?;
-
-    Ok(response.posts)
-}
-
-pub async fn get_ml_feed_clean(
-    canister_id: Principal,
-    num_results: u32,
-    filter_results: Vec<PostDetails>,
-) -> Result<Vec<PostItem>, anyhow::Error> {
-    let client = reqwest::Client::new();
-    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/clean").unwrap();
-
-    let req = FeedRequest {
-        canister_id,
-        filter_results: post_details_to_post_item(filter_results),
-        num_results,
-    };
-
-    let response = client.post(ml_feed_url).json(&req).send().await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(format!(
-            "Error fetching ML feed: {:?}",
-            response.text().await?
-        )));
+    fn as_path(self) -> &'static str {
+        match self {
+            Self::Clean => "clean",
+            Self::Nsfw => "nsfw",
+            Self::Mixed => "mixed",
+        }
     }
-    let response = response.json::<FeedResponse>().await?;
-
-    Ok(response.posts)
 }
 
-pub async fn get_ml_feed_nsfw(
+/// Fetch `num_results` posts from the ML feed, excluding `filter_results`
+/// `coldstart` uses the global feed instead of the personalized one
+pub async fn get_ml_feed(
+    content: FeedContent,
+    coldstart: bool,
     canister_id: Principal,
     num_results: u32,
-    filter_results: Vec<PostDetails>,
+    filter_results: Vec<PostItem>,
 ) -> Result<Vec<PostItem>, anyhow::Error> {
     let client = reqwest::Client::new();
-    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/nsfw").unwrap();
-
-    let req = FeedRequest {
-        canister_id,
-        filter_results: post_details_to_post_item(filter_results),
-        num_results,
+    let path = if coldstart {
+        format!("api/v1/feed/coldstart/{}", content.as_path())
+    } else {
+        format!("api/v1/feed/{}", content.as_path())
     };
-
-    let response = client.post(ml_feed_url).json(&req).send().await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(format!(
-            "Error fetching ML feed: {:?}",
-            response.text().await?
-        )));
-    }
-    let response = response.json::<FeedResponse>().await?;
-
-    Ok(response.posts)
-}
-
-pub async fn get_ml_feed_mixed(
-    canister_id: Principal,
-    num_results: u32,
-    filter_results: Vec<PostDetails>,
-) -> Result<Vec<PostItem>, anyhow::Error> {
-    let client = reqwest::Client::new();
-    let ml_feed_url = ML_FEED_URL.join("api/v1/feed/mixed").unwrap();
+    let ml_feed_url = ML_FEED_URL.join(&path).unwrap();
 
     let req = FeedRequest {
         canister_id,
-        filter_results: post_details_to_post_item(filter_results),
+        filter_results,
         num_results,
     };
 
-    let response = client.post(ml_feed_url).json(&req).send().await?;
+    let response = client
+        .post(ml_feed_url)
+        .json(&req)
+        .propagate_request_id()
+        .send()
+        .await?;
     if !response.status().is_success() {
         return Err(anyhow::anyhow!(format!(
             "Error fetching ML feed: {:?}",
@@ -0,0 +1,149 @@
+//! Compact record of the posts a user has watched, used to deduplicate feed batches
+use candid::Principal;
+use serde::{Deserialize, Serialize};
+
+/// Posts recorded per generation before rotating
+const GENERATION_CAPACITY: u32 = 1000;
+/// Bits per generation, ~1% false positives at capacity
+const GENERATION_BITS: usize = 9600;
+const HASH_COUNT: u64 = 7;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a, stable across targets unlike `DefaultHasher`
+fn fnv1a(seed: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(seed, |hash, b| (hash ^ *b as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    count: u32,
+}
+
+impl Default for BloomFilter {
+    fn default() -> Self {
+        Self {
+            bits: vec![0; GENERATION_BITS / 64],
+            count: 0,
+        }
+    }
+}
+
+impl BloomFilter {
+    /// Double hashing, `h1 + i * h2`
+    fn indices(canister_id: Principal, post_id: u64) -> impl Iterator<Item = usize> {
+        let h1 = fnv1a(
+            fnv1a(FNV_OFFSET, canister_id.as_slice()),
+            &post_id.to_le_bytes(),
+        );
+        let h2 = fnv1a(h1, &post_id.to_be_bytes()) | 1;
+        (0..HASH_COUNT)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % GENERATION_BITS as u64) as usize)
+    }
+
+    fn contains(&self, canister_id: Principal, post_id: u64) -> bool {
+        self.is_valid()
+            && Self::indices(canister_id, post_id)
+                .all(|idx| self.bits[idx / 64] & (1 << (idx % 64)) != 0)
+    }
+
+    fn insert(&mut self, canister_id: Principal, post_id: u64) {
+        if !self.is_valid() {
+            *self = Self::default();
+        }
+        if self.contains(canister_id, post_id) {
+            return;
+        }
+        for idx in Self::indices(canister_id, post_id) {
+            self.bits[idx / 64] |= 1 << (idx % 64);
+        }
+        self.count += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (bits, other) in self.bits.iter_mut().zip(&other.bits) {
+            *bits |= other;
+        }
+        self.count = self.count.max(other.count);
+    }
+
+    fn is_valid(&self) -> bool {
+        self.bits.len() == GENERATION_BITS / 64
+    }
+}
+
+/// Rotating bloom filter over (canister id, post id)
+/// remembers at least the last [`GENERATION_CAPACITY`] posts, older posts are forgotten in batches
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeenSet {
+    current: BloomFilter,
+    previous: BloomFilter,
+}
+
+impl SeenSet {
+    pub fn contains(&self, canister_id: Principal, post_id: u64) -> bool {
+        self.current.contains(canister_id, post_id) || self.previous.contains(canister_id, post_id)
+    }
+
+    pub fn insert(&mut self, canister_id: Principal, post_id: u64) {
+        if self.previous.contains(canister_id, post_id) {
+            return;
+        }
+        self.current.insert(canister_id, post_id);
+        self.rotate_if_full();
+    }
+
+    /// Merge the posts seen on another device
+    pub fn merge(&mut self, other: &Self) {
+        self.current.merge(&other.current);
+        self.previous.merge(&other.previous);
+        self.rotate_if_full();
+    }
+
+    /// Sets built with different parameters are discarded
+    pub fn validated(self) -> Self {
+        if self.current.is_valid() && self.previous.is_valid() {
+            self
+        } else {
+            Self::default()
+        }
+    }
+
+    fn rotate_if_full(&mut self) {
+        if self.current.count < GENERATION_CAPACITY {
+            return;
+        }
+        self.previous = std::mem::take(&mut self.current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+
+    use super::{SeenSet, GENERATION_CAPACITY};
+
+    #[test]
+    fn rotates_generations() {
+        let canister = Principal::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 1, 1]);
+        let mut seen = SeenSet::default();
+        seen.insert(canister, 0);
+        assert!(seen.contains(canister, 0));
+        assert!(!seen.contains(canister, 1));
+
+        for post_id in 1..(3 * GENERATION_CAPACITY as u64) {
+            seen.insert(canister, post_id);
+        }
+        // only the last two generations are kept
+        assert!(!seen.contains(canister, 0));
+        assert!(seen.contains(canister, 3 * GENERATION_CAPACITY as u64 - 1));
+
+        let mut other = SeenSet::default();
+        other.merge(&seen);
+        assert!(other.contains(canister, 3 * GENERATION_CAPACITY as u64 - 1));
+    }
+}
@@ -8,9 +8,9 @@ pub async fn send_principal_and_token_offchain(
     principal_id: String,
 ) -> Result<(), ServerFnError> {
     use crate::off_chain;
+    use crate::request_id::grpc_interceptor;
     use tonic::metadata::MetadataValue;
     use tonic::transport::Channel;
-    use tonic::Request;
 
     let channel: Channel = expect_context();
 
@@ -22,10 +22,7 @@ pub async fn send_principal_and_token_offchain(
 
     let mut client = off_chain::off_chain_client::OffChainClient::with_interceptor(
         channel,
-        move |mut req: Request<()>| {
-            req.metadata_mut().insert("authorization", token.clone());
-            Ok(req)
-        },
+        grpc_interceptor(Some(token)),
     );
 
     let request = tonic::Request::new(off_chain::BindDeviceToPrincipalRequest {
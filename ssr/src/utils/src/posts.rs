@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, PartialEq, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub struct FetchCursor {
     pub start: u64,
     pub limit: u64,
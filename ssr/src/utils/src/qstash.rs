@@ -7,7 +7,9 @@ use http::{
 use reqwest::{Client, Url};
 use yral_qstash_types::{ClaimTokensRequest, ParticipateInSwapRequest};
 
-use consts::{CDAO_SWAP_PRE_READY_TIME_SECS, CDAO_SWAP_TIME_SECS, OFF_CHAIN_AGENT_URL};
+use consts::{CDAO_SWAP_PRE_READY_TIME_SECS, CDAO_SWAP_TIME_SECS, OFF_CHAIN_AGENT_URL, QSTASH_URL};
+
+use crate::request_id::PropagateRequestId;
 
 #[derive(Clone, Debug)]
 pub struct QStashClient {
@@ -28,11 +30,9 @@ impl QStashClient {
             .default_headers(headers)
             .build()
             .expect("Failed to create QStash client");
-        let base_url = Url::parse("https://qstash.upstash.io/v2/").unwrap();
-
         Self {
             client,
-            base_url: Arc::new(base_url),
+            base_url: Arc::new(QSTASH_URL.clone()),
         }
     }
 
@@ -47,6 +47,7 @@ impl QStashClient {
             .json(&req)
             .header(CONTENT_TYPE, "application/json")
             .header("upstash-method", "POST")
+            .propagate_request_id()
             .header("upstash-delay", format!("{CDAO_SWAP_TIME_SECS}s"))
             .send()
             .await?;
@@ -68,6 +69,7 @@ impl QStashClient {
             .json(&req)
             .header(CONTENT_TYPE, "application/json")
             .header("upstash-method", "POST")
+            .propagate_request_id()
             .header("upstash-delay", format!("{CDAO_SWAP_PRE_READY_TIME_SECS}s"))
             .send()
             .await?;
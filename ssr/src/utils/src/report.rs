@@ -11,6 +11,14 @@ pub enum ReportOption {
 }
 
 impl ReportOption {
+    pub const ALL: [ReportOption; 5] = [
+        ReportOption::Nudity,
+        ReportOption::Violence,
+        ReportOption::Offensive,
+        ReportOption::Spam,
+        ReportOption::Other,
+    ];
+
     pub fn as_str(&self) -> impl Display {
         match self {
             ReportOption::Nudity => "Nudity/Porn",
@@ -34,9 +42,9 @@ pub async fn send_report_offchain(
     video_url: String,
 ) -> Result<(), ServerFnError> {
     use crate::off_chain;
+    use crate::request_id::grpc_interceptor;
     use tonic::metadata::MetadataValue;
     use tonic::transport::Channel;
-    use tonic::Request;
 
     let channel: Channel = expect_context();
 
@@ -48,10 +56,7 @@ pub async fn send_report_offchain(
 
     let mut client = off_chain::off_chain_client::OffChainClient::with_interceptor(
         channel,
-        move |mut req: Request<()>| {
-            req.metadata_mut().insert("authorization", token.clone());
-            Ok(req)
-        },
+        grpc_interceptor(Some(token)),
     );
 
     let request = tonic::Request::new(off_chain::ReportPostRequest {
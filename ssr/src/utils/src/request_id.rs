@@ -0,0 +1,211 @@
+use std::{fmt::Display, sync::Arc};
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use leptos::prelude::*;
+use tonic::metadata::{Ascii, MetadataMap, MetadataValue};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Max length of an incoming `x-request-id` we are willing to propagate
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Identifies a single incoming request to the SSR server
+/// and carries the W3C trace context that is forwarded to upstream services
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId {
+    id: Arc<str>,
+    trace_id: [u8; 16],
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+/// Parses the trace-id out of a W3C `traceparent` header
+/// format: `{version}-{trace-id}-{parent-id}-{trace-flags}`
+fn parse_traceparent(traceparent: &str) -> Option<[u8; 16]> {
+    let mut parts = traceparent.split('-');
+    let _version = parts.next()?;
+    let trace_id = parts.next()?;
+    if trace_id.len() != 32 {
+        return None;
+    }
+    let mut res = [0u8; 16];
+    hex::decode_to_slice(trace_id, &mut res).ok()?;
+    // all-zero trace-id is invalid as per spec
+    if res == [0u8; 16] {
+        return None;
+    }
+    Some(res)
+}
+
+impl RequestId {
+    pub fn generate() -> Self {
+        let uuid = uuid::Uuid::new_v4();
+        Self {
+            id: uuid.to_string().into(),
+            trace_id: uuid.into_bytes(),
+        }
+    }
+
+    /// Accepts the `x-request-id` and `traceparent` headers set by the caller (if valid)
+    /// generating new ones otherwise
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let generated = Self::generate();
+
+        let id = headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| is_valid_request_id(id))
+            .map(Arc::from)
+            .unwrap_or(generated.id);
+        let trace_id = headers
+            .get(TRACEPARENT_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_traceparent)
+            .unwrap_or(generated.trace_id);
+
+        Self { id, trace_id }
+    }
+
+    /// Request id for the current request, if any
+    /// only available inside server functions and SSR
+    pub fn current() -> Option<Self> {
+        use axum::http::request::Parts;
+
+        use_context::<Self>().or_else(|| {
+            let parts: Parts = use_context()?;
+            parts.extensions.get::<Self>().cloned()
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    pub fn trace_id(&self) -> String {
+        hex::encode(self.trace_id)
+    }
+
+    /// W3C `traceparent` for an outgoing request
+    /// every call generates a new parent (span) id
+    pub fn traceparent(&self) -> String {
+        let span_id = &uuid::Uuid::new_v4().into_bytes()[..8];
+        format!("00-{}-{}-01", self.trace_id(), hex::encode(span_id))
+    }
+
+    pub fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.id).expect("request id must be a valid header value")
+    }
+
+    /// Inject the request id and trace context into outgoing HTTP headers
+    pub fn inject_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            self.header_value(),
+        );
+        headers.insert(
+            HeaderName::from_static(TRACEPARENT_HEADER),
+            HeaderValue::from_str(&self.traceparent()).unwrap(),
+        );
+    }
+
+    /// Inject the request id and trace context into outgoing gRPC metadata
+    pub fn inject_metadata(&self, metadata: &mut MetadataMap) {
+        let id: MetadataValue<Ascii> = self.id.parse().expect("request id must be valid metadata");
+        metadata.insert(REQUEST_ID_HEADER, id);
+        let traceparent: MetadataValue<Ascii> = self.traceparent().parse().unwrap();
+        metadata.insert(TRACEPARENT_HEADER, traceparent);
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+/// Propagates the current request id to an outgoing reqwest request
+pub trait PropagateRequestId {
+    fn propagate_request_id(self) -> Self;
+}
+
+impl PropagateRequestId for reqwest::RequestBuilder {
+    fn propagate_request_id(self) -> Self {
+        let Some(req_id) = RequestId::current() else {
+            return self;
+        };
+        let mut headers = HeaderMap::new();
+        req_id.inject_headers(&mut headers);
+        self.headers(headers)
+    }
+}
+
+/// Builds a tonic interceptor that attaches `authorization` (if provided)
+/// along with the current request id and trace context
+/// NOTE: must be called inside a leptos reactive context (server function or SSR)
+pub fn grpc_interceptor(
+    authorization: Option<MetadataValue<Ascii>>,
+) -> impl FnMut(tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> + Clone {
+    let req_id = RequestId::current();
+    move |mut req: tonic::Request<()>| {
+        if let Some(token) = authorization.clone() {
+            req.metadata_mut().insert("authorization", token);
+        }
+        if let Some(req_id) = req_id.as_ref() {
+            req_id.inject_metadata(req.metadata_mut());
+        }
+        Ok(req)
+    }
+}
+
+/// Attach the request id to a server function error
+/// so that failures seen by the client can be correlated with server logs
+pub fn attach_to_server_fn_error(err: ServerFnError, req_id: &RequestId) -> ServerFnError {
+    match err {
+        ServerFnError::ServerError(msg) => {
+            ServerFnError::ServerError(format!("{msg} [request-id: {req_id}]"))
+        }
+        e => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_incoming_request_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("abc-123"));
+        let req_id = RequestId::from_headers(&headers);
+        assert_eq!(req_id.as_str(), "abc-123");
+    }
+
+    #[test]
+    fn rejects_invalid_incoming_request_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("abc 123;"));
+        let req_id = RequestId::from_headers(&headers);
+        assert_ne!(req_id.as_str(), "abc 123;");
+    }
+
+    #[test]
+    fn propagates_incoming_trace_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            TRACEPARENT_HEADER,
+            HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        );
+        let req_id = RequestId::from_headers(&headers);
+        assert_eq!(req_id.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert!(req_id
+            .traceparent()
+            .starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Tokenization shared by the search index and search queries
+
+/// Prefixes longer than this are not indexed, longer query terms are looked up by their
+/// first `MAX_PREFIX_LEN` characters and matched against the indexed text
+pub const MAX_PREFIX_LEN: usize = 12;
+
+fn is_term_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Lowercased words of `text`, in order of appearance and without duplicates
+/// `#` and `@` are dropped, so hashtags and usernames match with or without them
+pub fn search_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = vec![];
+    for term in text
+        .split(|c: char| !is_term_char(c))
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+    {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Prefixes of `term` under which it is indexed, shortest first
+pub fn term_prefixes(term: &str) -> impl Iterator<Item = &str> {
+    term.char_indices()
+        .skip(1)
+        .map(|(idx, _)| idx)
+        .chain([term.len()])
+        .take(MAX_PREFIX_LEN)
+        .map(|end| &term[..end])
+}
+
+/// The key `term` is looked up by in the prefix index
+pub fn lookup_prefix(term: &str) -> &str {
+    term_prefixes(term).last().unwrap_or(term)
+}
+
+/// Whether every query term is a prefix of one of the indexed terms
+pub fn matches_terms(indexed: &[String], query: &[String]) -> bool {
+    query
+        .iter()
+        .all(|q| indexed.iter().any(|term| term.starts_with(q.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup_prefix, matches_terms, search_terms, term_prefixes};
+
+    #[test]
+    fn tokenizes_text() {
+        assert_eq!(
+            search_terms("#Cats and @cat_lover, CATS!"),
+            vec!["cats", "and", "cat_lover"]
+        );
+        assert!(search_terms(" #@ ").is_empty());
+    }
+
+    #[test]
+    fn indexes_prefixes() {
+        assert_eq!(
+            term_prefixes("größe").collect::<Vec<_>>(),
+            vec!["g", "gr", "grö", "größ", "größe"]
+        );
+        assert_eq!(lookup_prefix("abcdefghijklmnop"), "abcdefghijkl");
+
+        let indexed = search_terms("funny cats compilation");
+        assert!(matches_terms(&indexed, &search_terms("cat comp")));
+        assert!(!matches_terms(&indexed, &search_terms("cat dogs")));
+    }
+}
@@ -217,12 +217,12 @@ pub struct ICPumpChatInteraction {
 
 #[server]
 pub async fn get_pumpai_results(query: String) -> Result<ICPumpSearchResult, ServerFnError> {
-    use tonic::Request;
+    use crate::request_id::grpc_interceptor;
 
     let channel: ICPumpSearchGrpcChannel = expect_context();
     let mut client = icpump_search::search_service_client::SearchServiceClient::with_interceptor(
         channel.channel,
-        move |req: Request<()>| Ok(req),
+        grpc_interceptor(None),
     );
 
     let request = icpump_search::SearchRequest { input_query: query };
@@ -246,12 +246,12 @@ pub async fn get_pumpai_results_contextual(
     previous_interactions: Vec<ICPumpChatInteraction>,
     rag_data: String,
 ) -> Result<ICPumpSearchResultContexual, ServerFnError> {
-    use tonic::Request;
+    use crate::request_id::grpc_interceptor;
 
     let channel: ICPumpSearchGrpcChannel = expect_context();
     let mut client = icpump_search::search_service_client::SearchServiceClient::with_interceptor(
         channel.channel,
-        move |req: Request<()>| Ok(req),
+        grpc_interceptor(None),
     );
 
     let request = icpump_search::ContextualSearchRequest {
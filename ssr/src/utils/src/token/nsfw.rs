@@ -31,18 +31,15 @@ pub async fn get_nsfw_info(_base64_image: String) -> Result<NSFWInfo, ServerFnEr
 #[cfg(not(feature = "local-bin"))]
 #[server]
 pub async fn get_nsfw_info(base64_image: String) -> Result<NSFWInfo, ServerFnError> {
+    use crate::request_id::grpc_interceptor;
     use tonic::metadata::MetadataValue;
-    use tonic::Request;
 
     let channel: ICPumpNSFWGrpcChannel = expect_context();
     let nsfw_grpc_auth_token = env::var("NSFW_GRPC_TOKEN").expect("NSFW_GRPC_TOKEN");
     let token: MetadataValue<_> = format!("Bearer {}", nsfw_grpc_auth_token).parse()?;
     let mut client = nsfw_detector::nsfw_detector_client::NsfwDetectorClient::with_interceptor(
         channel.channel,
-        move |mut req: Request<()>| {
-            req.metadata_mut().insert("authorization", token.clone());
-            Ok(req)
-        },
+        grpc_interceptor(Some(token)),
     );
 
     let base64_image_without_prefix = base64_image.replace("data:image/png;base64,", "");
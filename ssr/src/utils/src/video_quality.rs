@@ -0,0 +1,222 @@
+//! Playback source selection
+//! adaptive HLS where the browser plays it natively, the single MP4 otherwise
+//! data saver lowers the bandwidth hint of HLS playback and disables preloading
+//! without native HLS there is no lower quality source, data saver only disables preloading
+use std::{fmt::Display, str::FromStr};
+
+use codee::string::FromToStringCodec;
+use consts::DATA_SAVER_STORE;
+use leptos::prelude::*;
+use leptos_use::storage::use_local_storage;
+
+use crate::{mp4_url, stream_url};
+
+/// Bandwidth hint (in Mbps) used for HLS playback in data saver mode
+/// keeps the player on the lowest renditions
+pub const DATA_SAVER_BANDWIDTH_MBPS: f64 = 0.5;
+
+/// Data saver preference, `Auto` follows the connection's hints
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataSaverPref {
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl DataSaverPref {
+    pub fn enabled(self, network: &NetworkHints) -> bool {
+        match self {
+            Self::Auto => network.save_data || network.slow,
+            Self::On => true,
+            Self::Off => false,
+        }
+    }
+}
+
+impl Display for DataSaverPref {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pref = match self {
+            Self::Auto => "auto",
+            Self::On => "on",
+            Self::Off => "off",
+        };
+        f.write_str(pref)
+    }
+}
+
+impl FromStr for DataSaverPref {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "on" => Ok(Self::On),
+            "off" => Ok(Self::Off),
+            _ => Err(format!("unknown data saver preference {s}")),
+        }
+    }
+}
+
+/// Hints of the Network Information API, defaults where it is unsupported
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkHints {
+    /// the user asked the browser to reduce data usage
+    pub save_data: bool,
+    /// effective connection type is 3g or slower
+    pub slow: bool,
+    /// estimated downlink in Mbps
+    pub downlink_mbps: Option<f64>,
+}
+
+impl NetworkHints {
+    #[cfg(feature = "hydrate")]
+    pub fn read() -> Self {
+        use js_sys::Reflect;
+        use wasm_bindgen::JsValue;
+
+        let Ok(connection) = Reflect::get(&window().navigator(), &JsValue::from_str("connection"))
+        else {
+            return Self::default();
+        };
+        if connection.is_undefined() || connection.is_null() {
+            return Self::default();
+        }
+        let field = |name: &str| Reflect::get(&connection, &JsValue::from_str(name)).ok();
+
+        let effective_type = field("effectiveType").and_then(|v| v.as_string());
+        Self {
+            save_data: field("saveData").and_then(|v| v.as_bool()).unwrap_or(false),
+            slow: effective_type.is_some_and(|t| matches!(t.as_str(), "slow-2g" | "2g" | "3g")),
+            downlink_mbps: field("downlink")
+                .and_then(|v| v.as_f64())
+                .filter(|mbps| *mbps > 0.0),
+        }
+    }
+
+    #[cfg(not(feature = "hydrate"))]
+    pub fn read() -> Self {
+        Self::default()
+    }
+}
+
+/// Whether the browser plays HLS natively
+#[cfg(feature = "hydrate")]
+pub fn hls_supported() -> bool {
+    use wasm_bindgen::JsCast;
+
+    document()
+        .create_element("video")
+        .ok()
+        .and_then(|el| el.dyn_into::<web_sys::HtmlMediaElement>().ok())
+        .is_some_and(|video| {
+            !video
+                .can_play_type("application/vnd.apple.mpegurl")
+                .is_empty()
+        })
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub fn hls_supported() -> bool {
+    false
+}
+
+/// How videos are played on this client
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlaybackPrefs {
+    pub hls: bool,
+    pub data_saver: bool,
+    pub downlink_mbps: Option<f64>,
+}
+
+impl PlaybackPrefs {
+    /// Source of the video `uid`
+    /// HLS starts at the rendition matching the bandwidth hint and adapts from there
+    pub fn video_url(&self, uid: impl Display) -> String {
+        if !self.hls {
+            return mp4_url(uid);
+        }
+        let hint = if self.data_saver {
+            Some(
+                self.downlink_mbps
+                    .unwrap_or(DATA_SAVER_BANDWIDTH_MBPS)
+                    .min(DATA_SAVER_BANDWIDTH_MBPS),
+            )
+        } else {
+            self.downlink_mbps
+        };
+        match hint {
+            Some(mbps) => format!("{}?clientBandwidthHint={mbps:.1}", stream_url(uid)),
+            None => stream_url(uid),
+        }
+    }
+
+    /// `preload` attribute of the video element
+    pub fn preload(&self) -> &'static str {
+        if self.data_saver {
+            "none"
+        } else {
+            "auto"
+        }
+    }
+}
+
+/// Data saver preference of the user, persisted in local storage
+pub fn use_data_saver_pref() -> (Signal<DataSaverPref>, WriteSignal<DataSaverPref>) {
+    let (pref, set_pref, _) =
+        use_local_storage::<DataSaverPref, FromToStringCodec>(DATA_SAVER_STORE);
+    (pref, set_pref)
+}
+
+/// Playback preferences of this client
+/// browser capabilities are only read once mounted, SSR and hydration use the defaults
+pub fn use_playback_prefs() -> Signal<PlaybackPrefs> {
+    let (pref, _) = use_data_saver_pref();
+    let client = RwSignal::new(None::<(bool, NetworkHints)>);
+    Effect::new(move || client.set(Some((hls_supported(), NetworkHints::read()))));
+
+    Signal::derive(move || {
+        let (hls, network) = client.get().unwrap_or_default();
+        PlaybackPrefs {
+            hls,
+            data_saver: pref.get().enabled(&network),
+            downlink_mbps: network.downlink_mbps,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_source_from_prefs() {
+        let mut prefs = PlaybackPrefs::default();
+        assert!(prefs
+            .video_url("uid")
+            .ends_with("/uid/downloads/default.mp4"));
+
+        prefs.hls = true;
+        assert!(prefs.video_url("uid").ends_with("/uid/manifest/video.m3u8"));
+        prefs.downlink_mbps = Some(4.0);
+        assert!(prefs
+            .video_url("uid")
+            .ends_with("/uid/manifest/video.m3u8?clientBandwidthHint=4.0"));
+
+        prefs.data_saver = true;
+        assert!(prefs.video_url("uid").ends_with("?clientBandwidthHint=0.5"));
+        assert_eq!(prefs.preload(), "none");
+    }
+
+    #[test]
+    fn data_saver_follows_network_on_auto() {
+        let slow = NetworkHints {
+            slow: true,
+            ..Default::default()
+        };
+        assert!(DataSaverPref::Auto.enabled(&slow));
+        assert!(!DataSaverPref::Auto.enabled(&NetworkHints::default()));
+        assert!(!DataSaverPref::Off.enabled(&slow));
+        assert_eq!("on".parse(), Ok(DataSaverPref::On));
+    }
+}
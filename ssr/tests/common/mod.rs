@@ -0,0 +1,207 @@
+//! In-process test harness
+//! builds the application router with an [`AppState`] assembled from fakes
+//! requests are served without binding to a port
+
+use std::{future::IntoFuture, path::PathBuf};
+
+use auth::server_impl::store::{redb_kv::ReDBKV, KVStoreImpl};
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderMap, Request, StatusCode},
+    Router,
+};
+use axum_extra::extract::cookie::Key;
+use hot_or_not_web_leptos_ssr::{app::App, router::app_router};
+use leptos::prelude::*;
+use leptos_axum::generate_route_list;
+use page::post_view::{bet::stake::StakeConfig, coldstart::ColdstartCache};
+use state::{
+    canister_backend::FakeCanisters,
+    server::{AppState, ContextRegistry, HonWorkerUrl},
+};
+use tower::ServiceExt;
+use utils::event_streaming::pipeline::{sinks::JsonLinesSink, EventPipeline, EventSpool};
+use yral_canisters_common::Canisters;
+use yral_types::post::PostItem;
+
+/// Max body size read by the harness
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// Users and posts of the `mock-upstreams` fixtures, served by [`FakeCanisters`]
+pub const FIXTURES: &str = include_str!("../../src/mock_upstreams/fixtures/default.json");
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl TestResponse {
+    /// `name=value` pairs of all the cookies set by the response
+    pub fn set_cookies(&self) -> Vec<String> {
+        self.headers
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| v.split(';').next())
+            .map(|v| v.trim().to_string())
+            .collect()
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> T {
+        serde_json::from_str(&self.body)
+            .unwrap_or_else(|e| panic!("invalid json response {}: {e}", self.body))
+    }
+}
+
+/// Posts of [`FIXTURES`], the feed the harness renders instead of the ML feed
+pub fn fixture_posts() -> Vec<PostItem> {
+    let mut fixtures: serde_json::Value = serde_json::from_str(FIXTURES).expect("invalid fixtures");
+    serde_json::from_value(fixtures["posts"].take()).expect("invalid fixture posts")
+}
+
+/// File the analytics events emitted by a [`TestApp`] are written to
+#[derive(Clone)]
+pub struct EmittedEvents(PathBuf);
+
+pub struct TestApp {
+    router: Router,
+    pub contexts: ContextRegistry,
+    pub routes: Vec<String>,
+}
+
+impl TestApp {
+    /// Services which don't require network access
+    /// [`Canisters`] is anonymous and only reaches the network if a canister is called
+    /// directly, pages using the canister backend are served by [`FakeCanisters`] from [`FIXTURES`]
+    /// the coldstart feed is [`fixture_posts`], analytics events are only written to [`EmittedEvents`]
+    pub fn fake_contexts() -> ContextRegistry {
+        let events_path =
+            std::env::temp_dir().join(format!("analytics-events-{}.jsonl", uuid::Uuid::new_v4()));
+        let event_pipeline = EventPipeline::start(
+            vec![JsonLinesSink::file(&events_path).into()],
+            EventSpool::new_in_memory().expect("failed to create in-memory spool"),
+        );
+
+        ContextRegistry::default()
+            .with(Canisters::<false>::default())
+            .with_optional(FakeCanisters::from_fixtures(FIXTURES).expect("invalid fixtures"))
+            .with(KVStoreImpl::ReDB(
+                ReDBKV::new_in_memory().expect("failed to create in-memory kv"),
+            ))
+            .with(Key::generate())
+            .with(event_pipeline)
+            .with_optional(EmittedEvents(events_path))
+            .with(ColdstartCache::fixed(fixture_posts()))
+            .with(StakeConfig::default())
+    }
+
+    pub fn new() -> Self {
+        Self::with_contexts(Self::fake_contexts())
+    }
+
+    /// Serve the `mock-upstreams` hot or not worker on a free port
+    /// and point the server functions of `contexts` at it
+    pub async fn with_mock_hon_worker(contexts: ContextRegistry) -> ContextRegistry {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind the mock hon worker");
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(axum::serve(listener, mock_upstreams::hon_worker::router()).into_future());
+
+        contexts.with_optional(HonWorkerUrl(url.parse().unwrap()))
+    }
+
+    pub fn with_contexts(contexts: ContextRegistry) -> Self {
+        let leptos_options = LeptosOptions::builder()
+            .output_name("hot-or-not-web-leptos-ssr")
+            .build();
+        let routes = generate_route_list(App);
+        let route_paths = routes.iter().map(|r| r.path().to_string()).collect();
+        let app_state = AppState::new(leptos_options, routes, contexts.clone());
+
+        Self {
+            router: app_router(app_state),
+            contexts,
+            routes: route_paths,
+        }
+    }
+
+    pub async fn call(&self, req: Request<Body>) -> TestResponse {
+        let res = self
+            .router
+            .clone()
+            .oneshot(req)
+            .await
+            .expect("router is infallible");
+        let (parts, body) = res.into_parts();
+        let body = to_bytes(body, MAX_BODY_SIZE)
+            .await
+            .expect("failed to read response body");
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }
+    }
+
+    /// POST a JSON encoded server function
+    pub async fn post_json(
+        &self,
+        path: &str,
+        body: serde_json::Value,
+        cookies: &[String],
+    ) -> TestResponse {
+        let req = Request::post(path)
+            .header(header::HOST, "yral.com")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "application/json")
+            .header(header::COOKIE, cookies.join("; "))
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        self.call(req).await
+    }
+
+    /// GET a server function (or SSR route)
+    pub async fn get(&self, path_and_query: &str, cookies: &[String]) -> TestResponse {
+        self.get_with_host("yral.com", path_and_query, cookies)
+            .await
+    }
+
+    /// GET as seen by a specific host, e.g. to render for a different [`state::app_type::AppType`]
+    pub async fn get_with_host(
+        &self,
+        host: &str,
+        path_and_query: &str,
+        cookies: &[String],
+    ) -> TestResponse {
+        let req = Request::get(path_and_query)
+            .header(header::HOST, host)
+            .header(header::COOKIE, cookies.join("; "))
+            .body(Body::empty())
+            .unwrap();
+        self.call(req).await
+    }
+
+    /// Analytics events emitted so far, as written by the JSON lines sink
+    pub async fn emitted_events(&self) -> Vec<serde_json::Value> {
+        let pipeline: EventPipeline = self
+            .contexts
+            .get()
+            .expect("analytics pipeline not registered");
+        let EmittedEvents(path) = self
+            .contexts
+            .get()
+            .expect("emitted events are only recorded with the fake contexts");
+        pipeline.flush().await;
+
+        let Ok(lines) = std::fs::read_to_string(path) else {
+            return vec![];
+        };
+        lines
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("sink wrote invalid json"))
+            .collect()
+    }
+}
@@ -0,0 +1,1080 @@
+#![cfg(feature = "ssr")]
+#[allow(dead_code)]
+mod common;
+
+use auth::server_impl::store::KVStoreImpl;
+use axum::body::Body;
+use candid::Principal;
+use common::TestApp;
+use consts::{
+    analytics::{CLIENT_ID_COOKIE, CONSENT_COOKIE},
+    auth::{PREVIOUS_IDENTITY_COOKIE, REFRESH_TOKEN_COOKIE},
+};
+use futures::future::join_all;
+use hon_worker_common::{sign_vote_request, GameResult, HotOrNot, VoteRequest, VoteRes};
+use http::{header, Request, StatusCode};
+use ic_agent::{identity::Secp256k1Identity, Identity};
+use page::comments::Comment;
+use page::hashtag::server_impl::index_post;
+use page::post_view::bet::stake::{StakeConfig, StakeLadder};
+use page::search::{server_impl::index_fixtures, SearchResults, SearchSuggestion};
+use serde_json::json;
+use state::app_type::AppType;
+use utils::{
+    event_streaming::schema::{
+        mixpanel::{MixpanelEvent, MixpanelHomePageViewedProps, VersionedMixpanelEvent},
+        AnalyticsEvent, LoginMethod, LoginMethodSelectedProps, PageVisitProps, VersionedEvent,
+        SCHEMA_VERSION,
+    },
+    ml_feed::seen_set::SeenSet,
+    request_id::REQUEST_ID_HEADER,
+};
+use web_time::Duration;
+use yral_canisters_common::utils::posts::PostDetails;
+use yral_types::{delegated_identity::DelegatedIdentityWire, post::PostItem};
+
+fn test_secret_key() -> k256::SecretKey {
+    k256::SecretKey::from_slice(&[7u8; 32]).unwrap()
+}
+
+/// Sets the refresh token cookie for `test_secret_key` and returns the cookies
+async fn login_anonymous(app: &TestApp) -> Vec<String> {
+    login_as(app, test_secret_key()).await
+}
+
+/// Sets the refresh token cookie for `key` and returns the cookies
+async fn login_as(app: &TestApp, key: k256::SecretKey) -> Vec<String> {
+    let res = app
+        .post_json(
+            "/api/set_anonymous_identity_cookie",
+            json!({ "anonymous_identity": key.to_jwk() }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let cookies = res.set_cookies();
+    assert!(
+        cookies
+            .iter()
+            .any(|c| c.starts_with(&format!("{REFRESH_TOKEN_COOKIE}="))),
+        "refresh token cookie not set: {cookies:?}"
+    );
+    cookies
+}
+
+#[tokio::test]
+async fn extract_identity_without_cookie_returns_none() {
+    let app = TestApp::new();
+
+    let res = app.post_json("/api/extract_identity", json!({}), &[]).await;
+
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert!(res.headers.contains_key(REQUEST_ID_HEADER));
+    assert!(res.json::<Option<DelegatedIdentityWire>>().is_none());
+}
+
+#[tokio::test]
+async fn extract_identity_with_refresh_token() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+
+    let res = app
+        .post_json("/api/extract_identity", json!({}), &cookies)
+        .await;
+
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let identity: DelegatedIdentityWire = res
+        .json::<Option<_>>()
+        .expect("identity must be extracted from the refresh token");
+    let expected = Secp256k1Identity::from_private_key(test_secret_key());
+    assert_eq!(identity.from_key, expected.public_key().unwrap());
+}
+
+#[tokio::test]
+async fn extract_identity_rejects_tampered_cookie() {
+    let app = TestApp::new();
+    let cookies: Vec<_> = login_anonymous(&app)
+        .await
+        .into_iter()
+        .map(|c| format!("{c}tampered"))
+        .collect();
+
+    let res = app
+        .post_json("/api/extract_identity", json!({}), &cookies)
+        .await;
+
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert!(res.json::<Option<DelegatedIdentityWire>>().is_none());
+}
+
+/// Signed vote on a post of the first fixture user
+fn fixture_vote(post_id: u64, direction: HotOrNot) -> serde_json::Value {
+    let identity = Secp256k1Identity::from_private_key(test_secret_key());
+    let req = VoteRequest {
+        post_canister: Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap(),
+        post_id,
+        vote_amount: 10,
+        direction,
+    };
+    let sig = sign_vote_request(&identity, req.clone()).unwrap();
+    json!({
+        "sender": identity.sender().unwrap(),
+        "req": req,
+        "sig": sig,
+    })
+}
+
+#[tokio::test]
+async fn vote_returns_game_result_of_worker() {
+    let contexts = TestApp::with_mock_hon_worker(TestApp::fake_contexts()).await;
+    let app = TestApp::with_contexts(contexts);
+
+    // without the sentiment db every video is hot, the mock worker decides on that
+    let res = app
+        .post_json("/api/vote", fixture_vote(1, HotOrNot::Hot), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let vote_res: VoteRes = res.json();
+    let GameResult::Win { win_amt } = vote_res.game_result else {
+        panic!("hot vote on a hot video must win: {}", res.body);
+    };
+    assert_eq!(win_amt, 10u32.into());
+
+    let res = app
+        .post_json("/api/vote", fixture_vote(1, HotOrNot::Not), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let vote_res: VoteRes = res.json();
+    let GameResult::Loss { lose_amt } = vote_res.game_result else {
+        panic!("not vote on a hot video must lose: {}", res.body);
+    };
+    assert_eq!(lose_amt, 10u32.into());
+}
+
+#[tokio::test]
+async fn vote_without_worker_is_mocked() {
+    // no worker is registered, so the vote must not reach the production worker
+    let app = TestApp::new();
+    let res = app
+        .post_json("/api/vote", fixture_vote(1, HotOrNot::Not), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let vote_res: VoteRes = res.json();
+    assert!(
+        matches!(vote_res.game_result, GameResult::Win { ref win_amt } if *win_amt == 0u32.into()),
+        "{}",
+        res.body
+    );
+}
+
+#[tokio::test]
+async fn vote_on_unknown_post_fails() {
+    let contexts = TestApp::with_mock_hon_worker(TestApp::fake_contexts()).await;
+    let app = TestApp::with_contexts(contexts);
+    let res = app
+        .post_json("/api/vote", fixture_vote(999, HotOrNot::Hot), &[])
+        .await;
+    assert!(res.status.is_server_error(), "{}", res.body);
+    assert!(res.body.contains("post not found"), "{}", res.body);
+}
+
+#[tokio::test]
+async fn vote_stake_is_checked_against_ladder() {
+    let config = StakeConfig::new([(
+        AppType::YRAL,
+        StakeLadder::new(vec![25, 50], Some(60)).unwrap(),
+    )]);
+    let app = TestApp::with_contexts(TestApp::fake_contexts().with(config));
+
+    let res = app.post_json("/api/stake_ladder", json!({}), &[]).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let ladder: StakeLadder = res.json();
+    assert_eq!(ladder.options, vec![25, 50]);
+    assert_eq!(ladder.max, 60);
+
+    let identity = Secp256k1Identity::from_private_key(test_secret_key());
+    let req = VoteRequest {
+        post_canister: Principal::anonymous(),
+        post_id: 0,
+        vote_amount: 100,
+        direction: HotOrNot::Hot,
+    };
+    let sig = sign_vote_request(&identity, req.clone()).unwrap();
+    let res = app
+        .post_json(
+            "/api/vote",
+            json!({
+                "sender": identity.sender().unwrap(),
+                "req": req,
+                "sig": sig,
+            }),
+            &[],
+        )
+        .await;
+
+    assert!(res.status.is_server_error(), "{}", res.body);
+    assert!(res.body.contains("Stake can be at most 60"), "{}", res.body);
+}
+
+#[tokio::test]
+async fn server_fn_error_carries_request_id() {
+    let app = TestApp::new();
+    // structurally valid JWK with an invalid (zero) secret scalar
+    let invalid_jwk = json!({
+        "kty": "EC",
+        "crv": "secp256k1",
+        "x": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+        "y": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+        "d": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+    });
+
+    let res = app
+        .post_json(
+            "/api/set_anonymous_identity_cookie",
+            json!({ "anonymous_identity": invalid_jwk }),
+            &[],
+        )
+        .await;
+
+    assert!(res.status.is_server_error(), "{}", res.body);
+    assert!(res.set_cookies().is_empty());
+    let req_id = res.headers[REQUEST_ID_HEADER].to_str().unwrap();
+    assert!(
+        res.body.contains(&format!("[request-id: {req_id}]")),
+        "{}",
+        res.body
+    );
+}
+
+fn login_method_selected(attempt_count: u32) -> VersionedEvent {
+    VersionedEvent::new(
+        AnalyticsEvent::LoginMethodSelected(LoginMethodSelectedProps {
+            login_method: LoginMethod::Google,
+            attempt_count,
+        }),
+        None,
+    )
+}
+
+#[tokio::test]
+async fn send_event_emits_to_sinks() {
+    let app = TestApp::new();
+
+    let res = app
+        .post_json(
+            "/api/send_event",
+            json!({ "event": login_method_selected(1) }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let events = app.emitted_events().await;
+    assert_eq!(events.len(), 1, "{events:?}");
+    let event = &events[0];
+    assert_eq!(event["event"], "login_method_selected");
+    assert_eq!(event["params"]["attempt_count"], 1);
+    assert_eq!(event["params"]["host"], "yral.com");
+    assert_eq!(event["params"]["page_location"], "https://yral.com");
+    assert_eq!(event["params"]["schema_version"], SCHEMA_VERSION);
+
+    // the client id is issued with the response and attached to the event
+    let client_id = res
+        .set_cookies()
+        .into_iter()
+        .find_map(|c| {
+            c.strip_prefix(&format!("{CLIENT_ID_COOKIE}="))
+                .map(str::to_string)
+        })
+        .expect("client id cookie not issued");
+    let (id, _) = client_id.split_once('.').unwrap();
+    assert_eq!(event["client_id"], id);
+
+    // and reused by later requests
+    let res = app
+        .post_json(
+            "/api/send_event",
+            json!({ "event": login_method_selected(2) }),
+            &[format!("{CLIENT_ID_COOKIE}={client_id}")],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert!(res.set_cookies().is_empty());
+    assert_eq!(app.emitted_events().await[1]["client_id"], id);
+}
+
+#[tokio::test]
+async fn send_event_honors_denied_consent() {
+    let app = TestApp::new();
+    let event = VersionedEvent::new(
+        AnalyticsEvent::PageVisit(PageVisitProps {
+            user_id: Principal::anonymous(),
+            is_logged_in: false,
+            pathname: "/".into(),
+        }),
+        None,
+    );
+
+    let res = app
+        .post_json(
+            "/api/send_event",
+            json!({ "event": event }),
+            &[
+                format!("{CONSENT_COOKIE}=analytics:denied|personalization:denied"),
+                format!("{CLIENT_ID_COOKIE}=abc.1"),
+            ],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    // the existing client id is removed
+    assert_eq!(res.set_cookies(), vec![format!("{CLIENT_ID_COOKIE}=")]);
+
+    let events = app.emitted_events().await;
+    assert_eq!(events.len(), 1, "{events:?}");
+    let event = &events[0];
+    assert_eq!(event["event"], "yral_page_visit");
+    assert!(event["client_id"].is_null());
+    assert!(event["params"].get("user_id").is_none(), "{event}");
+    assert_eq!(event["params"]["pathname"], "/");
+    assert_eq!(event["params"]["is_loggedIn"], false);
+}
+
+#[tokio::test]
+async fn send_event_rejects_invalid_events() {
+    let app = TestApp::new();
+
+    let res = app
+        .post_json(
+            "/api/send_event",
+            json!({ "event": login_method_selected(0) }),
+            &[],
+        )
+        .await;
+
+    assert!(res.status.is_server_error(), "{}", res.body);
+    assert!(app.emitted_events().await.is_empty());
+}
+
+#[tokio::test]
+async fn identity_change_remembers_previous_principal() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let previous = Secp256k1Identity::from_private_key(test_secret_key())
+        .sender()
+        .unwrap();
+
+    let other_key = k256::SecretKey::from_slice(&[9u8; 32]).unwrap();
+    let res = app
+        .post_json(
+            "/api/set_anonymous_identity_cookie",
+            json!({ "anonymous_identity": other_key.to_jwk() }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let previous_cookie = res
+        .set_cookies()
+        .into_iter()
+        .find(|c| c.starts_with(&format!("{PREVIOUS_IDENTITY_COOKIE}=")))
+        .expect("previous identity cookie not set");
+    // signed, the principal is a suffix of the value
+    assert!(
+        previous_cookie.ends_with(&previous.to_text()),
+        "{previous_cookie}"
+    );
+}
+
+#[tokio::test]
+async fn mixpanel_proxy_anonymizes_ip() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let principal = Secp256k1Identity::from_private_key(test_secret_key())
+        .sender()
+        .unwrap();
+    let event =
+        VersionedMixpanelEvent::new(MixpanelEvent::HomePageViewed(MixpanelHomePageViewedProps {
+            user_id: None,
+            // replaced by the session's principal
+            visitor_id: Some("2vxsx-fae".into()),
+            is_logged_in: false,
+            canister_id: "zfbzf-gaaaa-aaaac-aaaia-cai".into(),
+            is_nsfw_enabled: false,
+        }));
+    let mut body = json!({ "event": event });
+    // unknown properties are not forwarded
+    body["event"]["event"]["params"]["token"] = json!("spoofed");
+    let req = Request::post("/api/mixpanel_track")
+        .header(header::HOST, "yral.com")
+        .header(header::COOKIE, cookies.join("; "))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json")
+        .header("x-forwarded-for", "203.0.113.77, 10.0.0.1")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let res = app.call(req).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let events = app.emitted_events().await;
+    assert_eq!(events.len(), 1, "{events:?}");
+    let params = &events[0]["params"];
+    assert_eq!(events[0]["event"], "home_page_viewed");
+    assert_eq!(params["ip"], "203.0.113.0");
+    assert_eq!(params["distinct_id"], principal.to_text());
+    assert_eq!(params["visitor_id"], principal.to_text());
+    assert!(params.get("token").is_none(), "{params}");
+}
+
+#[tokio::test]
+async fn sync_seen_posts_merges_devices() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let canister = Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap();
+
+    let mut first_device = SeenSet::default();
+    first_device.insert(canister, 1);
+    let res = app
+        .post_json(
+            "/api/sync_seen_posts",
+            json!({ "local": first_device }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let mut second_device = SeenSet::default();
+    second_device.insert(canister, 2);
+    let res = app
+        .post_json(
+            "/api/sync_seen_posts",
+            json!({ "local": second_device }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let merged: SeenSet = res.json();
+    assert!(merged.contains(canister, 1));
+    assert!(merged.contains(canister, 2));
+}
+
+#[tokio::test]
+async fn concurrent_seen_post_syncs_are_kept() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let canister = Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap();
+
+    let syncs = (0..3).map(|post_id| {
+        let mut device = SeenSet::default();
+        device.insert(canister, post_id);
+        app.post_json("/api/sync_seen_posts", json!({ "local": device }), &cookies)
+    });
+    for res in join_all(syncs).await {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+
+    let res = app
+        .post_json(
+            "/api/sync_seen_posts",
+            json!({ "local": SeenSet::default() }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let merged: SeenSet = res.json();
+    for post_id in 0..3 {
+        assert!(merged.contains(canister, post_id), "post {post_id} lost");
+    }
+}
+
+#[tokio::test]
+async fn hidden_creators_are_stored_per_user() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let creator = Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap();
+
+    let res = app
+        .post_json(
+            "/api/feed_hide_creator",
+            json!({ "creator_canister": creator }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(res.json::<Vec<Principal>>(), vec![creator]);
+
+    let res = app
+        .post_json("/api/feed_hidden_creators", json!({}), &cookies)
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(res.json::<Vec<Principal>>(), vec![creator]);
+
+    let res = app
+        .post_json("/api/feed_hidden_creators", json!({}), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert!(res.json::<Vec<Principal>>().is_empty());
+
+    let res = app
+        .post_json(
+            "/api/feed_unhide_creator",
+            json!({ "creator_canister": creator }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert!(res.json::<Vec<Principal>>().is_empty());
+}
+
+#[tokio::test]
+async fn concurrent_hides_are_kept() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let creators: Vec<_> = (0..10u8)
+        .map(|i| Principal::from_slice(&[i + 1; 10]))
+        .collect();
+    let set_hidden = |creator: Principal, hide: bool| {
+        let path = if hide {
+            "/api/feed_hide_creator"
+        } else {
+            "/api/feed_unhide_creator"
+        };
+        app.post_json(path, json!({ "creator_canister": creator }), &cookies)
+    };
+
+    for res in join_all(creators.iter().map(|creator| set_hidden(*creator, true))).await {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+    // half are unhidden while the others are hidden again
+    let updates = creators
+        .iter()
+        .enumerate()
+        .map(|(i, creator)| set_hidden(*creator, i >= 5));
+    for res in join_all(updates).await {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+
+    let res = app
+        .post_json("/api/feed_hidden_creators", json!({}), &cookies)
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let mut hidden = res.json::<Vec<Principal>>();
+    hidden.sort();
+    let mut expected = creators[5..].to_vec();
+    expected.sort();
+    assert_eq!(hidden, expected);
+}
+
+#[tokio::test]
+async fn hashtag_index_starts_empty() {
+    let app = TestApp::new();
+
+    let res = app
+        .post_json(
+            "/api/hashtag_posts",
+            json!({ "hashtag": "#Cats", "start": 0, "limit": 10 }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(res.body, "[]");
+
+    let res = app
+        .post_json("/api/trending_hashtags", json!({ "window_hours": 24 }), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(res.body, "[]");
+}
+
+fn tagged_post(post_id: u64, hashtags: &[&str]) -> PostDetails {
+    let creator = Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap();
+    PostDetails {
+        canister_id: creator,
+        post_id,
+        uid: format!("uid-{post_id}"),
+        description: String::new(),
+        views: 0,
+        likes: 0,
+        display_name: String::new(),
+        propic_url: String::new(),
+        liked_by_user: None,
+        poster_principal: Principal::anonymous(),
+        hastags: hashtags.iter().map(|hashtag| hashtag.to_string()).collect(),
+        is_nsfw: false,
+        hot_or_not_feed_ranking_score: None,
+        created_at: Duration::from_secs(1_700_000_000 + post_id),
+        nsfw_probability: 0.0,
+    }
+}
+
+#[tokio::test]
+async fn hashtag_index_counts_concurrent_posts() {
+    let app = TestApp::new();
+    let kv: KVStoreImpl = app.contexts.get().unwrap();
+
+    let posts: Vec<_> = (0..20)
+        .map(|post_id| tagged_post(post_id, &["Cats", "#cats", "dogs"]))
+        .collect();
+    for res in join_all(posts.iter().map(|post| index_post(&kv, post))).await {
+        res.unwrap();
+    }
+    // indexing a post again is a no-op
+    index_post(&kv, &posts[0]).await.unwrap();
+
+    let res = app
+        .post_json(
+            "/api/hashtag_posts",
+            json!({ "hashtag": "#Cats", "start": 5, "limit": 3 }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let ids: Vec<_> = res
+        .json::<Vec<PostItem>>()
+        .into_iter()
+        .map(|post| post.post_id)
+        .collect();
+    assert_eq!(ids, vec![14, 13, 12]);
+
+    let res = app
+        .post_json(
+            "/api/trending_hashtags",
+            json!({ "window_hours": 24 * 30 * 12 * 100 }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    // the posts are older than the retained history
+    assert_eq!(res.body, "[]");
+
+    let recent: Vec<_> = (100..120)
+        .map(|post_id| PostDetails {
+            created_at: Duration::ZERO,
+            ..tagged_post(post_id, &["cats"])
+        })
+        .collect();
+    for res in join_all(recent.iter().map(|post| index_post(&kv, post))).await {
+        res.unwrap();
+    }
+    // short windows are read from the hourly counts, longer ones from the daily counts
+    for window_hours in [24, 24 * 7] {
+        let res = app
+            .post_json(
+                "/api/trending_hashtags",
+                json!({ "window_hours": window_hours }),
+                &[],
+            )
+            .await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        assert_eq!(
+            res.json::<serde_json::Value>(),
+            json!([{ "hashtag": "cats", "posts": 20 }])
+        );
+    }
+}
+
+#[tokio::test]
+async fn follow_requires_known_creator() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let creator = Principal::from_text("zfbzf-gaaaa-aaaac-aaaia-cai").unwrap();
+
+    // the creator has no canister in the fake backend
+    let res = app
+        .post_json(
+            "/api/follow_creator",
+            json!({ "creator_principal": creator }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let res = app
+        .post_json(
+            "/api/follow_stats",
+            json!({ "user_principal": creator }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(
+        res.json::<serde_json::Value>(),
+        json!({ "followers": 0, "following": 0, "followed_by_caller": false })
+    );
+}
+
+#[tokio::test]
+async fn concurrent_follows_are_counted() {
+    let app = TestApp::new();
+    // fixture user with a canister in the fake backend
+    let creator =
+        Principal::from_text("3tuwa-t777e-uswqq-bmf55-ytjqk-p6oaj-zuczv-gqpl5-qwfh6-x2zwb-zqe")
+            .unwrap();
+    let mut users = vec![];
+    for i in 0..10u8 {
+        let key = k256::SecretKey::from_slice(&[i + 10; 32]).unwrap();
+        users.push(login_as(&app, key).await);
+    }
+    let set_following = |cookies: &Vec<String>, follow: bool| {
+        let path = if follow {
+            "/api/follow_creator"
+        } else {
+            "/api/unfollow_creator"
+        };
+        app.post_json(path, json!({ "creator_principal": creator }), cookies)
+    };
+
+    for res in join_all(users.iter().map(|cookies| set_following(cookies, true))).await {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+    // half unfollow while the others follow again
+    let updates = users
+        .iter()
+        .enumerate()
+        .map(|(i, cookies)| set_following(cookies, i >= 5));
+    for res in join_all(updates).await {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+
+    let stats = |cookies: &Vec<String>| {
+        app.post_json(
+            "/api/follow_stats",
+            json!({ "user_principal": creator }),
+            cookies,
+        )
+    };
+    let res = stats(&users[9]).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(
+        res.json::<serde_json::Value>(),
+        json!({ "followers": 5, "following": 0, "followed_by_caller": true })
+    );
+    let res = stats(&users[0]).await;
+    assert_eq!(res.json::<serde_json::Value>()["followed_by_caller"], false);
+
+    let followers_page = |start: u64| {
+        app.post_json(
+            "/api/followers",
+            json!({ "user_principal": creator, "start": start, "limit": 3 }),
+            &[],
+        )
+    };
+    let mut followers = followers_page(0).await.json::<Vec<Principal>>();
+    assert_eq!(followers.len(), 3);
+    followers.extend(followers_page(3).await.json::<Vec<Principal>>());
+    followers.sort();
+    followers.dedup();
+    assert_eq!(followers.len(), 5);
+
+    let res = app
+        .post_json(
+            "/api/followed_creators",
+            json!({ "start": 0, "limit": 10 }),
+            &users[9],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(
+        res.json::<serde_json::Value>(),
+        json!([{ "principal": creator, "canister_id": "zfbzf-gaaaa-aaaac-aaaia-cai" }])
+    );
+}
+
+#[tokio::test]
+async fn search_finds_indexed_fixtures() {
+    let app = TestApp::new();
+
+    let res = app
+        .post_json(
+            "/api/search",
+            json!({ "query": "cat", "allow_nsfw": false }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(res.json::<SearchResults>(), SearchResults::default());
+
+    // principals are looked up without being indexed
+    let cat_videos = "2syes-usqll-dfpna-aiuxj-milj3-czoql-2wr3g-oy5nk-f6fvk-wvej2-mae";
+    let res = app
+        .post_json(
+            "/api/search",
+            json!({ "query": cat_videos, "allow_nsfw": false }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let principals: Vec<_> = res
+        .json::<SearchResults>()
+        .users
+        .iter()
+        .map(|u| u.principal.to_text())
+        .collect();
+    assert_eq!(principals, vec![cat_videos]);
+    let res = app
+        .post_json("/api/search_suggestions", json!({ "prefix": "@cat" }), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert!(res.json::<Vec<SearchSuggestion>>().is_empty());
+
+    let kv: KVStoreImpl = app.contexts.get().unwrap();
+    index_fixtures(&kv, common::FIXTURES).await.unwrap();
+
+    let res = app
+        .post_json(
+            "/api/search",
+            json!({ "query": "Cat", "allow_nsfw": false }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let results: SearchResults = res.json();
+    let usernames: Vec<_> = results.users.iter().map(|u| u.username.as_str()).collect();
+    assert_eq!(usernames, vec!["cat_videos"]);
+    let posts: Vec<_> = results.posts.iter().map(|p| p.post.post_id).collect();
+    assert_eq!(posts, vec![5, 2]);
+
+    let res = app
+        .post_json("/api/search_suggestions", json!({ "prefix": "#fo" }), &[])
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    assert_eq!(
+        res.json::<Vec<SearchSuggestion>>(),
+        vec![SearchSuggestion::Hashtag("food".into())]
+    );
+}
+
+#[tokio::test]
+async fn comments_thread_and_delete() {
+    let app = TestApp::new();
+    let cookies = login_anonymous(&app).await;
+    let post = json!({ "post_canister": Principal::anonymous(), "post_id": 1 });
+
+    let res = app
+        .post_json(
+            "/api/add_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 1, "parent_id": null, "text": "first!" }),
+            &[],
+        )
+        .await;
+    assert_eq!(
+        res.status,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "{}",
+        res.body
+    );
+
+    let res = app
+        .post_json(
+            "/api/add_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 1, "parent_id": null, "text": " first! " }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let comment: Comment = res.json();
+    assert_eq!(comment.text, "first!");
+
+    let res = app
+        .post_json(
+            "/api/add_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 1, "parent_id": comment.id, "text": "reply" }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let reply: Comment = res.json();
+    assert_eq!(reply.parent_id, Some(comment.id));
+
+    let res = app
+        .post_json(
+            "/api/post_comments",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 1, "parent_id": null, "start": 0, "limit": 10 }),
+            &[],
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    let top_level: Vec<Comment> = res.json();
+    assert_eq!(top_level.len(), 1);
+    assert_eq!(top_level[0].reply_count, 1);
+
+    let res = app.post_json("/api/comment_count", post.clone(), &[]).await;
+    assert_eq!(res.json::<u64>(), 2);
+
+    // only the post's creator can pin
+    let res = app
+        .post_json(
+            "/api/pin_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 1, "comment_id": comment.id }),
+            &cookies,
+        )
+        .await;
+    assert_ne!(res.status, StatusCode::OK, "{}", res.body);
+
+    let res = app
+        .post_json(
+            "/api/delete_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 1, "comment_id": comment.id }),
+            &cookies,
+        )
+        .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+
+    let res = app.post_json("/api/comment_count", post, &[]).await;
+    assert_eq!(res.json::<u64>(), 0);
+}
+
+#[tokio::test]
+async fn concurrent_comments_are_kept() {
+    let app = TestApp::new();
+    let post = json!({ "post_canister": Principal::anonymous(), "post_id": 2 });
+    let mut users = vec![];
+    for i in 0..6u8 {
+        let key = k256::SecretKey::from_slice(&[i + 30; 32]).unwrap();
+        users.push(login_as(&app, key).await);
+    }
+    let add = |cookies: &Vec<String>, parent_id: Option<u64>, text: String| {
+        app.post_json(
+            "/api/add_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 2, "parent_id": parent_id, "text": text }),
+            cookies,
+        )
+    };
+
+    let comments = join_all(
+        users
+            .iter()
+            .enumerate()
+            .map(|(i, cookies)| add(cookies, None, format!("comment {i}"))),
+    )
+    .await;
+    let mut ids = vec![];
+    for res in comments {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        ids.push(res.json::<Comment>().id);
+    }
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), users.len());
+
+    let replies = join_all(
+        users
+            .iter()
+            .map(|cookies| add(cookies, Some(ids[0]), "reply".into())),
+    )
+    .await;
+    for res in replies {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+    let res = app.post_json("/api/comment_count", post.clone(), &[]).await;
+    assert_eq!(res.json::<u64>(), 12);
+    let res = app
+        .post_json(
+            "/api/post_comments",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 2, "parent_id": null, "start": 0, "limit": 10 }),
+            &[],
+        )
+        .await;
+    let top_level: Vec<Comment> = res.json();
+    assert_eq!(top_level.len(), 6);
+    let first = top_level.iter().find(|c| c.id == ids[0]).unwrap();
+    assert_eq!(first.reply_count, 6);
+
+    // the rate limit holds for concurrent requests of one user
+    let key = k256::SecretKey::from_slice(&[40; 32]).unwrap();
+    let spammer = login_as(&app, key).await;
+    let burst = join_all((0..8).map(|i| add(&spammer, Some(ids[1]), format!("burst {i}")))).await;
+    let accepted = burst
+        .iter()
+        .filter(|res| res.status == StatusCode::OK)
+        .count();
+    assert_eq!(accepted, 5);
+
+    // concurrent reports by distinct users remove the comment and its replies
+    let reports = users[1..4].iter().map(|cookies| {
+        app.post_json(
+            "/api/report_comment",
+            json!({ "post_canister": Principal::anonymous(), "post_id": 2, "comment_id": ids[0], "reason": "Spam/Ad" }),
+            cookies,
+        )
+    });
+    for res in join_all(reports).await {
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+    }
+    let res = app.post_json("/api/comment_count", post, &[]).await;
+    assert_eq!(res.json::<u64>(), 10);
+}
+
+#[cfg(feature = "oauth-ssr")]
+mod google {
+    use auth::core_clients::CoreClients;
+    use openidconnect::{
+        core::{CoreClient, CoreJsonWebKeySet},
+        AuthUrl, ClientId, ClientSecret, IssuerUrl, TokenUrl,
+    };
+
+    use super::*;
+
+    const FAKE_AUTH_URL: &str = "https://accounts.example.com/auth";
+
+    fn fake_core_clients() -> CoreClients {
+        let client = CoreClient::new(
+            ClientId::new("test-client".into()),
+            Some(ClientSecret::new("test-secret".into())),
+            IssuerUrl::new("https://accounts.example.com".into()).unwrap(),
+            AuthUrl::new(FAKE_AUTH_URL.into()).unwrap(),
+            Some(TokenUrl::new("https://accounts.example.com/token".into()).unwrap()),
+            None,
+            CoreJsonWebKeySet::new(vec![]),
+        );
+        CoreClients {
+            google_oauth: client.clone(),
+            hotornot_google_oauth: client.clone(),
+            icpump_google_oauth: client.clone(),
+            pumpdump_google_oauth: client,
+        }
+    }
+
+    fn app() -> TestApp {
+        TestApp::with_contexts(TestApp::fake_contexts().with(fake_core_clients()))
+    }
+
+    #[tokio::test]
+    async fn google_auth_url_sets_oauth_cookies() {
+        let res = app()
+            .get(
+                "/api/google_auth_url?client_redirect_uri=https://yral.com/auth/google_redirect",
+                &[],
+            )
+            .await;
+
+        assert_eq!(res.status, StatusCode::OK, "{}", res.body);
+        let url: String = res.json();
+        assert!(url.starts_with(FAKE_AUTH_URL), "{url}");
+        let cookies = res.set_cookies();
+        assert!(cookies
+            .iter()
+            .any(|c| c.starts_with("google-pkce-verifier=")));
+        assert!(cookies.iter().any(|c| c.starts_with("google-csrf-token=")));
+    }
+
+    #[tokio::test]
+    async fn google_auth_url_rejects_foreign_redirect_uri() {
+        let res = app()
+            .get(
+                "/api/google_auth_url?client_redirect_uri=https://evil.example.com/steal",
+                &[],
+            )
+            .await;
+
+        assert!(res.status.is_server_error());
+        assert!(
+            res.body.contains("Invalid client redirect uri"),
+            "{}",
+            res.body
+        );
+        assert!(res.set_cookies().is_empty());
+    }
+}
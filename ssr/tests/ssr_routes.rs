@@ -0,0 +1,224 @@
+//! Renders every route registered in `app.rs` for each `AppType` host
+//! and compares the head + body structure against the snapshots in `tests/snapshots`
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to (re)generate the snapshots after an intended change
+#![cfg(feature = "ssr")]
+#[allow(dead_code)]
+mod common;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use common::{TestApp, TestResponse};
+use http::header;
+use regex::Regex;
+
+const HOSTS: [&str; 4] = ["yral.com", "hotornot.wtf", "icpump.fun", "pumpdump.wtf"];
+
+/// Values substituted for route params
+const ROUTE_PARAMS: [(&str, &str); 7] = [
+    (":canister_id", "zfbzf-gaaaa-aaaac-aaaia-cai"),
+    (":token_root", "zfbzf-gaaaa-aaaac-aaaia-cai"),
+    (":key_principal", "6xq5c-hyaaa-aaaac-baaiq-cai"),
+    (":id", "6xq5c-hyaaa-aaaac-baaiq-cai"),
+    (":post_id", "1"),
+    (":tab", "posts"),
+    ("*any", ""),
+];
+
+/// Elements which never have children
+const VOID_ELEMENTS: [&str; 8] = ["meta", "link", "img", "input", "br", "hr", "source", "path"];
+
+static SCRIPT_OR_STYLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<(script|style)\b([^>]*)>.*?</(script|style)>").unwrap());
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)([a-zA-Z][\w-]*)([^>]*?)(/?)>").unwrap());
+static ATTR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"([\w-]+)="([^"]*)""#).unwrap());
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<title[^>]*>(.*?)</title>").unwrap());
+
+fn concrete_path(route: &str) -> String {
+    ROUTE_PARAMS
+        .iter()
+        .fold(route.to_string(), |path, (param, value)| {
+            path.replace(param, value)
+        })
+}
+
+fn snapshot_path(host: &str, route: &str) -> PathBuf {
+    let name = route.trim_matches('/').replace(['/', ':', '*'], "_");
+    let name = if name.is_empty() {
+        "index".into()
+    } else {
+        name
+    };
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(host)
+        .join(format!("{name}.snap"))
+}
+
+fn attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    ATTR.captures_iter(attrs)
+        .find(|c| &c[1] == name)
+        .map(|c| c.get(2).unwrap().as_str())
+}
+
+/// Title, meta and link tags of the document head
+fn head_summary(head: &str, out: &mut String) {
+    if let Some(title) = TITLE.captures(head) {
+        out.push_str(&format!("  title: {}\n", title[1].trim()));
+    }
+    for tag in TAG.captures_iter(head) {
+        let attrs = &tag[3];
+        let line = match &tag[2] {
+            "meta" => {
+                let Some(name) = attr(attrs, "name").or_else(|| attr(attrs, "property")) else {
+                    continue;
+                };
+                format!("meta {name}={}", attr(attrs, "content").unwrap_or_default())
+            }
+            "link" => format!(
+                "link {}={}",
+                attr(attrs, "rel").unwrap_or_default(),
+                attr(attrs, "href").unwrap_or_default()
+            ),
+            _ => continue,
+        };
+        out.push_str(&format!("  {line}\n"));
+    }
+}
+
+/// Nested element names of the document body
+fn body_structure(body: &str, out: &mut String) {
+    let mut depth = 1usize;
+    for tag in TAG.captures_iter(body) {
+        let name = tag[2].to_lowercase();
+        let closing = !tag[1].is_empty();
+        let self_closing = !tag[4].is_empty() || VOID_ELEMENTS.contains(&name.as_str());
+        if closing {
+            depth = depth.saturating_sub(1).max(1);
+            continue;
+        }
+        out.push_str(&format!("{}{name}\n", "  ".repeat(depth)));
+        if !self_closing {
+            depth += 1;
+        }
+    }
+}
+
+fn normalize(res: &TestResponse) -> String {
+    let mut out = format!("status: {}\n", res.status.as_u16());
+    if let Some(location) = res.headers.get(header::LOCATION) {
+        out.push_str(&format!("location: {}\n", location.to_str().unwrap()));
+    }
+
+    let html = SCRIPT_OR_STYLE.replace_all(&res.body, "<$1$2></$1>");
+    let html = COMMENT.replace_all(&html, "");
+    let (head, body) = match (html.find("<head"), html.find("<body")) {
+        (Some(h), Some(b)) if h < b => (&html[h..b], &html[b..]),
+        _ => ("", html.as_ref()),
+    };
+
+    out.push_str("head:\n");
+    head_summary(head, &mut out);
+    out.push_str("body:\n");
+    body_structure(body, &mut out);
+    out
+}
+
+async fn check_host(host: &str) {
+    let app = TestApp::new();
+    let update = env::var("UPDATE_SNAPSHOTS").is_ok();
+
+    let mut routes = app.routes.clone();
+    routes.sort();
+    routes.dedup();
+
+    let mut mismatches = vec![];
+    for route in routes {
+        let path = concrete_path(&route);
+        let res = app.get_with_host(host, &path, &[]).await;
+        let actual = normalize(&res);
+
+        let snapshot = snapshot_path(host, &route);
+        match fs::read_to_string(&snapshot) {
+            Ok(expected) if expected == actual => (),
+            _ if update => {
+                fs::create_dir_all(snapshot.parent().unwrap()).unwrap();
+                fs::write(&snapshot, &actual).unwrap();
+            }
+            Ok(expected) => mismatches.push(format!(
+                "{host}{path} does not match {}\n--- expected\n{expected}\n+++ actual\n{actual}",
+                snapshot.display()
+            )),
+            Err(_) => mismatches.push(format!(
+                "{host}{path} has no snapshot at {}, run with UPDATE_SNAPSHOTS=1 to create it",
+                snapshot.display()
+            )),
+        }
+    }
+
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n\n"));
+}
+
+/// Every route renders a full document, or redirects, with the contexts of the test registry
+/// a context a page expects but nobody registered fails the render
+#[tokio::test]
+async fn routes_render_against_registry() {
+    let app = TestApp::new();
+    let required: Vec<_> = app
+        .contexts
+        .required()
+        .iter()
+        .map(|key| key.name())
+        .collect();
+
+    let mut failures = vec![];
+    for host in HOSTS {
+        for route in &app.routes {
+            let path = concrete_path(route);
+            let res = app.get_with_host(host, &path, &[]).await;
+            if res.status.is_server_error() {
+                failures.push(format!("{host}{path}: {}", res.status));
+            } else if res.status.is_redirection() {
+                if !res.headers.contains_key(header::LOCATION) {
+                    failures.push(format!("{host}{path}: redirect without a location"));
+                }
+            } else if !(res.body.contains("<head") && res.body.contains("<body")) {
+                failures.push(format!("{host}{path}: {} without a document", res.status));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "routes failed to render with contexts [{}]:\n{}",
+        required.join(", "),
+        failures.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn yral_routes_match_snapshots() {
+    check_host(HOSTS[0]).await;
+}
+
+#[tokio::test]
+async fn hotornot_routes_match_snapshots() {
+    check_host(HOSTS[1]).await;
+}
+
+#[tokio::test]
+async fn icpump_routes_match_snapshots() {
+    check_host(HOSTS[2]).await;
+}
+
+#[tokio::test]
+async fn pumpdump_routes_match_snapshots() {
+    check_host(HOSTS[3]).await;
+}